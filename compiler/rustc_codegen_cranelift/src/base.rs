@@ -417,6 +417,19 @@ fn codegen_fn_body(fx: &mut FunctionCx<'_, '_, '_>, start_block: Block) {
                             Some(source_info.span),
                         );
                     }
+                    AssertKind::InvalidBool(ref found)
+                    | AssertKind::InvalidChar(ref found)
+                    | AssertKind::InvalidEnumDiscriminant(ref found) => {
+                        let found = codegen_operand(fx, found).load_scalar(fx);
+                        let location = fx.get_caller_location(source_info).load_scalar(fx);
+
+                        codegen_panic_inner(
+                            fx,
+                            msg.panic_function(),
+                            &[found, location],
+                            Some(source_info.span),
+                        );
+                    }
                     _ => {
                         let location = fx.get_caller_location(source_info).load_scalar(fx);
 
@@ -539,4 +539,20 @@ fn add_used_global(&self, global: &'ll Value) {
     fn add_compiler_used_global(&self, global: &'ll Value) {
         self.compiler_used_statics.borrow_mut().push(global);
     }
+
+    fn static_addr_of_local_symbol(&self, cv: &'ll Value, align: Align, name: &str) -> &'ll Value {
+        let gv = self.define_private_global(self.val_ty(cv));
+        // Unlike private globals, internal ones keep their symbol in the object file. LLVM makes
+        // the name unique if another global already has it.
+        llvm::set_value_name(gv, name.as_bytes());
+        llvm::set_linkage(gv, llvm::Linkage::InternalLinkage);
+        unsafe {
+            llvm::LLVMSetInitializer(gv, cv);
+            llvm::LLVMSetGlobalConstant(gv, True);
+        }
+        set_global_alignment(self, gv, align);
+        // The global is not `unnamed_addr`, so that LLVM does not merge it with identical
+        // constants, whose bytes would then be attributed to `name`.
+        gv
+    }
 }
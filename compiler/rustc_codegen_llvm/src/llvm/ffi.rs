@@ -999,6 +999,15 @@ pub fn LLVMConstInBoundsGEP2<'a>(
     pub fn LLVMSetDLLStorageClass(V: &Value, C: DLLStorageClass);
     pub fn LLVMGlobalGetValueType(Global: &Value) -> &Type;
 
+    // Operations on global aliases
+    pub fn LLVMAddAlias2<'a>(
+        M: &'a Module,
+        ValueTy: &'a Type,
+        AddrSpace: c_uint,
+        Aliasee: &'a Value,
+        Name: *const c_char,
+    ) -> &'a Value;
+
     // Operations on global variables
     pub fn LLVMIsAGlobalVariable(GlobalVar: &Value) -> Option<&Value>;
     pub fn LLVMAddGlobal<'a>(M: &'a Module, Ty: &'a Type, Name: *const c_char) -> &'a Value;
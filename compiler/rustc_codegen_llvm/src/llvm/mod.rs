@@ -220,6 +220,16 @@ pub fn add_global<'a>(llmod: &'a Module, ty: &'a Type, name_cstr: &CStr) -> &'a
     unsafe { LLVMAddGlobal(llmod, ty, name_cstr.as_ptr()) }
 }
 
+pub fn add_alias<'a>(
+    llmod: &'a Module,
+    ty: &'a Type,
+    address_space: c_uint,
+    aliasee: &'a Value,
+    name_cstr: &CStr,
+) -> &'a Value {
+    unsafe { LLVMAddAlias2(llmod, ty, address_space, aliasee, name_cstr.as_ptr()) }
+}
+
 pub fn set_initializer(llglobal: &Value, constant_val: &Value) {
     unsafe {
         LLVMSetInitializer(llglobal, constant_val);
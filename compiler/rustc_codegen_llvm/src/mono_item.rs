@@ -1,8 +1,10 @@
+use rustc_abi::HasDataLayout;
 use rustc_codegen_ssa::traits::*;
+use rustc_data_structures::small_c_str::SmallCStr;
 use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{DefId, LOCAL_CRATE};
 use rustc_middle::bug;
-use rustc_middle::mir::mono::{Linkage, Visibility};
+use rustc_middle::mir::mono::{Linkage, MonoItem, Visibility};
 use rustc_middle::ty::layout::{FnAbiOf, HasTypingEnv, LayoutOf};
 use rustc_middle::ty::{self, Instance, TypeVisitableExt};
 use rustc_session::config::CrateType;
@@ -55,6 +57,10 @@ fn predefine_fn(
         symbol_name: &str,
     ) {
         assert!(!instance.args.has_infer());
+        if self.instances.borrow().contains_key(&instance) {
+            // Already predefined as the aliasee of a function that precedes it.
+            return;
+        }
 
         let fn_abi = self.fn_abi_of_instance(instance, ty::List::empty());
         let lldecl = self.declare_fn(symbol_name, fn_abi, Some(instance));
@@ -88,6 +94,49 @@ fn predefine_fn(
 
         self.instances.borrow_mut().insert(instance, lldecl);
     }
+
+    fn supports_fn_aliases(&self) -> bool {
+        true
+    }
+
+    fn predefine_fn_alias(
+        &self,
+        instance: Instance<'tcx>,
+        aliasee: Instance<'tcx>,
+        linkage: Linkage,
+        visibility: Visibility,
+        symbol_name: &str,
+    ) {
+        let aliasee_data = self.codegen_unit.items()[&MonoItem::Fn(aliasee)];
+        self.predefine_fn(
+            aliasee,
+            aliasee_data.linkage,
+            aliasee_data.visibility,
+            self.tcx.symbol_name(aliasee).name,
+        );
+        let llaliasee = self.instances.borrow()[&aliasee];
+
+        if self.get_declared_value(symbol_name).is_some() {
+            self.sess().dcx().emit_fatal(SymbolAlreadyDefined {
+                span: self.tcx.def_span(instance.def_id()),
+                symbol_name,
+            })
+        }
+        let llty = unsafe { llvm::LLVMGlobalGetValueType(llaliasee) };
+        let address_space = self.data_layout().instruction_address_space;
+        let name = SmallCStr::new(symbol_name);
+        let llalias = llvm::add_alias(self.llmod, llty, address_space.0, llaliasee, &name);
+        llvm::set_linkage(llalias, base::linkage_to_llvm(linkage));
+        llvm::set_visibility(llalias, base::visibility_to_llvm(visibility));
+
+        debug!("predefine_fn_alias: instance = {:?}, aliasee = {:?}", instance, aliasee);
+
+        if self.should_assume_dso_local(llalias, false) {
+            unsafe { llvm::LLVMRustSetDSOLocal(llalias, true) };
+        }
+
+        self.instances.borrow_mut().insert(instance, llalias);
+    }
 }
 
 impl CodegenCx<'_, '_> {
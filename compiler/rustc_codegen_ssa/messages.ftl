@@ -174,6 +174,10 @@ codegen_ssa_lib_def_write_failure = failed to write lib.def file: {$error}
 
 codegen_ssa_link_exe_unexpected_error = `link.exe` returned an unexpected error
 
+codegen_ssa_link_repro_failed = failed to write a reproducer of the linker invocation to {$path}: {$error}
+
+codegen_ssa_link_repro_written = a reproducer of the linker invocation was written to {$path}
+
 codegen_ssa_link_script_unavailable = can only use link script when linking with GNU-like linker
 
 codegen_ssa_link_script_write_failure = failed to write link script to {$path}: {$error}
@@ -247,6 +251,9 @@ codegen_ssa_shuffle_indices_evaluation = could not evaluate shuffle_indices at c
 
 codegen_ssa_specify_libraries_to_link = use the `-l` flag to specify native libraries to link
 
+codegen_ssa_stack_usage_no_stack_sizes = no stack sizes were found in the object files, so the stack usage of the entry points is unknown
+    .note = stack sizes are only emitted by the LLVM backend, for ELF targets
+
 codegen_ssa_static_library_native_artifacts = Link against the following native artifacts when linking against this static library. The order and any duplication can be significant on some platforms.
 
 codegen_ssa_static_library_native_artifacts_to_file = Native artifacts to link against have been written to {$path}. The order and any duplication can be significant on some platforms.
@@ -256,6 +263,11 @@ codegen_ssa_stripping_debug_info_failed = stripping debug info with `{$util}` fa
 
 codegen_ssa_symbol_file_write_failure = failed to write symbols file: {$error}
 
+codegen_ssa_symbol_size_report_no_symbols = no symbols were found in {$path}, so the symbol size report only has the sizes of its sections
+    .note = symbols are removed by `-C strip=symbols`
+
+codegen_ssa_symbol_size_report_unreadable = cannot read {$path} for the symbol size report: {$error}
+
 codegen_ssa_target_feature_disable_or_enable =
     the target features {$features} must all be either enabled or disabled together
 
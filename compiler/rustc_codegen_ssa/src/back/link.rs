@@ -50,7 +50,7 @@
 use super::linker::{self, Linker};
 use super::metadata::{MetadataPosition, create_wrapper_file};
 use super::rpath::{self, RPathConfig};
-use super::{apple, versioned_llvm_target};
+use super::{apple, link_repro, symbol_size, versioned_llvm_target};
 use crate::{
     CodegenResults, CompiledModule, CrateInfo, NativeLib, common, errors,
     looks_like_rust_object_file,
@@ -75,6 +75,7 @@ pub fn link_binary(
     let _timer = sess.timer("link_binary");
     let output_metadata = sess.opts.output_types.contains_key(&OutputType::Metadata);
     let mut tempfiles_for_stdout_output: Vec<PathBuf> = Vec::new();
+    let mut linked_artifacts: Vec<PathBuf> = Vec::new();
     for &crate_type in &codegen_results.crate_info.crate_types {
         // Ignore executable crates if we have -Z no-codegen, as they will error.
         if (sess.opts.unstable_opts.no_codegen || !sess.opts.output_types.should_codegen())
@@ -140,6 +141,7 @@ pub fn link_binary(
                         &codegen_results,
                         path.as_ref(),
                     );
+                    linked_artifacts.push(out_filename.clone());
                 }
             }
             if sess.opts.json_artifact_notifications {
@@ -172,6 +174,12 @@ pub fn link_binary(
         }
     }
 
+    if let Some(path) = &sess.opts.unstable_opts.symbol_size_report {
+        sess.time("write_symbol_size_report", || {
+            symbol_size::write_report(sess, &linked_artifacts, path)
+        });
+    }
+
     // Remove the temporary object file and metadata if we aren't saving temps.
     sess.time("link_binary_remove_temps", || {
         // If the user requests that temporaries are saved, don't delete any.
@@ -980,6 +988,14 @@ fn is_illegal_instruction(_status: &ExitStatus) -> bool {
         }
     }
 
+    let repro_dir = sess.opts.unstable_opts.link_repro.as_deref().and_then(|dir| {
+        let failed = !matches!(prog, Ok(ref output) if output.status.success());
+        if !failed && sess.opts.unstable_opts.link_repro_on_failure {
+            return None;
+        }
+        link_repro::write_reproducer(sess, dir, &cmd, flavor, temp_filename, tmpdir, &prog)
+    });
+
     match prog {
         Ok(prog) => {
             if !prog.status.success() {
@@ -994,6 +1010,9 @@ fn is_illegal_instruction(_status: &ExitStatus) -> bool {
                     verbose: sess.opts.verbose,
                 };
                 sess.dcx().emit_err(err);
+                if let Some(path) = repro_dir {
+                    sess.dcx().emit_note(errors::LinkReproWritten { path });
+                }
                 // If MSVC's `link.exe` was expected but the return code
                 // is not a Microsoft LNK error then suggest a way to fix or
                 // install the Visual Studio build tools.
@@ -1054,6 +1073,9 @@ fn is_illegal_instruction(_status: &ExitStatus) -> bool {
                 sess.dcx().emit_note(errors::CheckInstalledVisualStudio);
                 sess.dcx().emit_note(errors::InsufficientVSCodeProduct);
             }
+            if let Some(path) = repro_dir {
+                sess.dcx().emit_note(errors::LinkReproWritten { path });
+            }
             FatalError.raise();
         }
     }
@@ -1692,31 +1714,8 @@ fn exec_linker(
 
     info!("falling back to passing arguments to linker via an @-file");
     let mut cmd2 = cmd.clone();
-    let mut args = String::new();
-    for arg in cmd2.take_args() {
-        args.push_str(
-            &Escape {
-                arg: arg.to_str().unwrap(),
-                // LLD also uses MSVC-like parsing for @-files by default when running on windows hosts
-                is_like_msvc: sess.target.is_like_msvc || (cfg!(windows) && flavor.uses_lld()),
-            }
-            .to_string(),
-        );
-        args.push('\n');
-    }
+    let bytes = linker_response_file(sess, flavor, &cmd2.take_args());
     let file = tmpdir.join("linker-arguments");
-    let bytes = if sess.target.is_like_msvc {
-        let mut out = Vec::with_capacity((1 + args.len()) * 2);
-        // start the stream with a UTF-16 BOM
-        for c in std::iter::once(0xFEFF).chain(args.encode_utf16()) {
-            // encode in little endian
-            out.push(c as u8);
-            out.push((c >> 8) as u8);
-        }
-        out
-    } else {
-        args.into_bytes()
-    };
     fs::write(&file, &bytes)?;
     cmd2.arg(format!("@{}", file.display()));
     info!("invoking linker {:?}", cmd2);
@@ -1768,50 +1767,83 @@ fn command_line_too_big(err: &io::Error) -> bool {
     fn command_line_too_big(_: &io::Error) -> bool {
         false
     }
+}
 
-    struct Escape<'a> {
-        arg: &'a str,
-        is_like_msvc: bool,
-    }
-
-    impl<'a> fmt::Display for Escape<'a> {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            if self.is_like_msvc {
-                // This is "documented" at
-                // https://docs.microsoft.com/en-us/cpp/build/reference/at-specify-a-linker-response-file
-                //
-                // Unfortunately there's not a great specification of the
-                // syntax I could find online (at least) but some local
-                // testing showed that this seemed sufficient-ish to catch
-                // at least a few edge cases.
-                write!(f, "\"")?;
-                for c in self.arg.chars() {
-                    match c {
-                        '"' => write!(f, "\\{c}")?,
-                        c => write!(f, "{c}")?,
-                    }
+/// An argument of a linker response file, escaped for the syntax of the linker.
+struct Escape<'a> {
+    arg: &'a str,
+    is_like_msvc: bool,
+}
+
+impl<'a> fmt::Display for Escape<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_like_msvc {
+            // This is "documented" at
+            // https://docs.microsoft.com/en-us/cpp/build/reference/at-specify-a-linker-response-file
+            //
+            // Unfortunately there's not a great specification of the
+            // syntax I could find online (at least) but some local
+            // testing showed that this seemed sufficient-ish to catch
+            // at least a few edge cases.
+            write!(f, "\"")?;
+            for c in self.arg.chars() {
+                match c {
+                    '"' => write!(f, "\\{c}")?,
+                    c => write!(f, "{c}")?,
                 }
-                write!(f, "\"")?;
-            } else {
-                // This is documented at https://linux.die.net/man/1/ld, namely:
-                //
-                // > Options in file are separated by whitespace. A whitespace
-                // > character may be included in an option by surrounding the
-                // > entire option in either single or double quotes. Any
-                // > character (including a backslash) may be included by
-                // > prefixing the character to be included with a backslash.
-                //
-                // We put an argument on each line, so all we need to do is
-                // ensure the line is interpreted as one whole argument.
-                for c in self.arg.chars() {
-                    match c {
-                        '\\' | ' ' => write!(f, "\\{c}")?,
-                        c => write!(f, "{c}")?,
-                    }
+            }
+            write!(f, "\"")?;
+        } else {
+            // This is documented at https://linux.die.net/man/1/ld, namely:
+            //
+            // > Options in file are separated by whitespace. A whitespace
+            // > character may be included in an option by surrounding the
+            // > entire option in either single or double quotes. Any
+            // > character (including a backslash) may be included by
+            // > prefixing the character to be included with a backslash.
+            //
+            // We put an argument on each line, so all we need to do is
+            // ensure the line is interpreted as one whole argument.
+            for c in self.arg.chars() {
+                match c {
+                    '\\' | ' ' => write!(f, "\\{c}")?,
+                    c => write!(f, "{c}")?,
                 }
             }
-            Ok(())
         }
+        Ok(())
+    }
+}
+
+/// Returns the contents of a response file passing `args` to the linker.
+pub(super) fn linker_response_file(
+    sess: &Session,
+    flavor: LinkerFlavor,
+    args: &[OsString],
+) -> Vec<u8> {
+    let mut out = String::new();
+    for arg in args {
+        out.push_str(
+            &Escape {
+                arg: arg.to_str().unwrap(),
+                // LLD also uses MSVC-like parsing for @-files by default when running on windows hosts
+                is_like_msvc: sess.target.is_like_msvc || (cfg!(windows) && flavor.uses_lld()),
+            }
+            .to_string(),
+        );
+        out.push('\n');
+    }
+    if sess.target.is_like_msvc {
+        let mut bytes = Vec::with_capacity((1 + out.len()) * 2);
+        // start the stream with a UTF-16 BOM
+        for c in std::iter::once(0xFEFF).chain(out.encode_utf16()) {
+            // encode in little endian
+            bytes.push(c as u8);
+            bytes.push((c >> 8) as u8);
+        }
+        bytes
+    } else {
+        out.into_bytes()
     }
 }
 
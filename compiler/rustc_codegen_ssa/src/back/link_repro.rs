@@ -0,0 +1,363 @@
+//! The reproducers of linker invocations of `-Z link-repro`.
+//!
+//! A reproducer is a directory with everything needed to invoke the linker
+//! again the way it was invoked by rustc, once the temporary directory of the
+//! compilation is gone or on another machine:
+//!
+//! - `inputs/` has copies of the files passed to the linker, like the objects,
+//!   rlibs, archives and linker scripts, and `search-paths/` has a directory
+//!   for each library search path with copies of the libraries linked with
+//!   `-l` that were found there. The libraries found in the default search
+//!   paths of the linker, like the C library, are not copied.
+//! - `linker-arguments` is a response file with the arguments of the linker,
+//!   whose paths are rewritten to refer to the copies, and `linker-command` is
+//!   the original command.
+//! - `linker-output` has the exit status and the output of the linker.
+//! - `replay.sh`, or `replay.bat` on Windows, sets the environment of the
+//!   linker and invokes it with the response file, writing the linked
+//!   artifact to `output/`.
+
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::{env, fs, io};
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_session::Session;
+use rustc_target::spec::LinkerFlavor;
+
+use super::command::Command;
+use super::link::linker_response_file;
+use crate::errors;
+
+/// The variables of the environment of rustc that affect the linker and are
+/// recorded in the replay script. The rest of the environment isn't recorded,
+/// as it might contain secrets.
+const LINKER_ENV_VARS: &[&str] = &[
+    // tidy-alphabetical-start
+    "COMPILER_PATH",
+    "GCC_EXEC_PREFIX",
+    "IPHONEOS_DEPLOYMENT_TARGET",
+    "LD_LIBRARY_PATH",
+    "LD_RUN_PATH",
+    "LIB",
+    "LIBPATH",
+    "LIBRARY_PATH",
+    "LINK",
+    "MACOSX_DEPLOYMENT_TARGET",
+    "PATH",
+    "SDKROOT",
+    "TVOS_DEPLOYMENT_TARGET",
+    "VCINSTALLDIR",
+    "WATCHOS_DEPLOYMENT_TARGET",
+    "XROS_DEPLOYMENT_TARGET",
+    "_LINK_",
+    // tidy-alphabetical-end
+];
+
+/// Writes a reproducer of the invocation of the linker by `cmd` to a
+/// subdirectory of `dir` named after the linked artifact, and returns the path
+/// of the reproducer. Failing to write it is only a warning, as it mustn't
+/// fail the compilation.
+pub(super) fn write_reproducer(
+    sess: &Session,
+    dir: &Path,
+    cmd: &Command,
+    flavor: LinkerFlavor,
+    out_filename: &Path,
+    tmpdir: &Path,
+    output: &io::Result<Output>,
+) -> Option<PathBuf> {
+    let repro_dir = dir.join(out_filename.file_name().unwrap());
+    let result: io::Result<()> = try {
+        if repro_dir.is_dir() {
+            fs::remove_dir_all(&repro_dir)?;
+        }
+        fs::create_dir_all(repro_dir.join("inputs"))?;
+        fs::create_dir_all(repro_dir.join("output"))?;
+
+        let mut bundle = Bundle::new(&repro_dir, out_filename, tmpdir, cmd.get_args())?;
+        let args = bundle.rewrite_args(cmd.get_args())?;
+        fs::write(repro_dir.join("linker-arguments"), linker_response_file(sess, flavor, &args))?;
+        fs::write(repro_dir.join("linker-command"), format!("{cmd:?}\n"))?;
+
+        let linker_output = match output {
+            Ok(output) => {
+                let mut contents = format!("{}\n", output.status).into_bytes();
+                contents.extend_from_slice(&output.stderr);
+                contents.extend_from_slice(&output.stdout);
+                contents
+            }
+            Err(error) => format!("could not run the linker: {error}\n").into_bytes(),
+        };
+        fs::write(repro_dir.join("linker-output"), linker_output)?;
+
+        write_replay_script(&repro_dir, cmd)?;
+    };
+    match result {
+        Ok(()) => Some(repro_dir),
+        Err(error) => {
+            sess.dcx().emit_warn(errors::LinkReproFailed { path: repro_dir, error });
+            None
+        }
+    }
+}
+
+/// The files copied to a reproducer, and the search paths of the libraries.
+struct Bundle<'a> {
+    dir: &'a Path,
+    out_filename: &'a Path,
+    tmpdir: &'a Path,
+    /// The relative paths of the copies of the files copied to `inputs/`.
+    inputs: FxHashMap<PathBuf, PathBuf>,
+    /// The names of the files copied to `inputs/`.
+    input_names: FxHashSet<OsString>,
+    /// The library search paths, with the relative paths of the directories
+    /// the libraries found there are copied to.
+    search_paths: Vec<(PathBuf, PathBuf)>,
+}
+
+impl<'a> Bundle<'a> {
+    fn new(
+        dir: &'a Path,
+        out_filename: &'a Path,
+        tmpdir: &'a Path,
+        args: &[OsString],
+    ) -> io::Result<Bundle<'a>> {
+        let mut bundle = Bundle {
+            dir,
+            out_filename,
+            tmpdir,
+            inputs: Default::default(),
+            input_names: Default::default(),
+            search_paths: Vec::new(),
+        };
+        // The linker looks up all the libraries in all the search paths,
+        // whatever the order of the arguments, so collect them first.
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let path = if arg == "-L" {
+                let Some(path) = args.next() else { break };
+                path.clone()
+            } else if let Some(path) = search_path_arg(arg) {
+                path.into()
+            } else {
+                continue;
+            };
+            let path = PathBuf::from(path);
+            if bundle.search_paths.iter().all(|(p, _)| *p != path) {
+                let copy = Path::new("search-paths").join(bundle.search_paths.len().to_string());
+                fs::create_dir_all(dir.join(&copy))?;
+                bundle.search_paths.push((path, copy));
+            }
+        }
+        Ok(bundle)
+    }
+
+    /// Copies the files the arguments refer to into the reproducer, and
+    /// returns the arguments referring to the copies.
+    fn rewrite_args(&mut self, args: &[OsString]) -> io::Result<Vec<OsString>> {
+        let mut rewritten = Vec::with_capacity(args.len());
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "-L" || arg == "-l" {
+                rewritten.push(arg.clone());
+                let Some(value) = args.next() else { break };
+                let value = value.to_string_lossy();
+                if arg == "-L" {
+                    rewritten.push(self.search_path_copy(Path::new(&*value)).into());
+                } else {
+                    self.copy_library(&value)?;
+                    rewritten.push(value.into_owned().into());
+                }
+                continue;
+            }
+
+            let arg = arg.to_string_lossy();
+            if let Some(path) = search_path_arg(OsStr::new(&*arg)) {
+                let prefix = &arg[..arg.len() - path.len()];
+                let copy = self.search_path_copy(Path::new(path));
+                rewritten.push(format!("{prefix}{}", copy.display()).into());
+            } else if let Some(name) = arg.strip_prefix("-l") {
+                self.copy_library(name)?;
+                rewritten.push(arg.into_owned().into());
+            } else {
+                rewritten.push(self.rewrite_path_arg(&arg)?.into());
+            }
+        }
+        Ok(rewritten)
+    }
+
+    /// Rewrites an argument that is, or ends with, the path of a file read or
+    /// written by the linker, like `foo.o`, `-Wl,--version-script=list` or
+    /// `/OUT:foo.exe`.
+    fn rewrite_path_arg(&mut self, arg: &str) -> io::Result<String> {
+        let starts = std::iter::once(0).chain(
+            arg.char_indices()
+                .filter(|&(_, c)| matches!(c, '=' | ':' | ',' | '@'))
+                .map(|(i, c)| i + c.len_utf8()),
+        );
+        for start in starts {
+            let (prefix, path) = arg.split_at(start);
+            if path.is_empty() {
+                continue;
+            }
+            let path = Path::new(path);
+            let rewritten = if path == self.out_filename {
+                Path::new("output").join(path.file_name().unwrap())
+            } else if path.is_file() {
+                self.copy_input(path)?
+            } else if !path.exists()
+                && path.file_name().is_some()
+                && path.parent().is_some_and(|parent| {
+                    parent == self.tmpdir || Some(parent) == self.out_filename.parent()
+                })
+            {
+                // Other files written by the linker, like import libraries
+                // and PDB files, are written next to the linked artifact.
+                Path::new("output").join(path.file_name().unwrap())
+            } else {
+                continue;
+            };
+            return Ok(format!("{prefix}{}", rewritten.display()));
+        }
+
+        // Libraries looked up in the search paths, like `kernel32.lib`.
+        if Path::new(arg).extension().is_some_and(|ext| ext == "lib") && !arg.contains(['/', '\\'])
+        {
+            self.copy_from_search_paths(&[arg.to_owned()])?;
+        }
+        Ok(arg.to_owned())
+    }
+
+    /// Copies a file to `inputs/`, keeping its name, and returns the relative
+    /// path of the copy.
+    fn copy_input(&mut self, path: &Path) -> io::Result<PathBuf> {
+        let key = path.canonicalize()?;
+        if let Some(copy) = self.inputs.get(&key) {
+            return Ok(copy.clone());
+        }
+        let name = path.file_name().unwrap();
+        let mut copy = Path::new("inputs").join(name);
+        if !self.input_names.insert(name.to_owned()) {
+            // Files with the same name are copied to subdirectories, so that
+            // the linker sees the same file names.
+            let subdir = Path::new("inputs").join(self.inputs.len().to_string());
+            fs::create_dir_all(self.dir.join(&subdir))?;
+            copy = subdir.join(name);
+        }
+        fs::copy(path, self.dir.join(&copy))?;
+        self.inputs.insert(key, copy.clone());
+        Ok(copy)
+    }
+
+    /// Copies the library linked with `-l<name>` from the first search path
+    /// where it is found.
+    fn copy_library(&mut self, name: &str) -> io::Result<()> {
+        let file_names = if let Some(file_name) = name.strip_prefix(':') {
+            vec![file_name.to_owned()]
+        } else {
+            ["lib{}.a", "lib{}.so", "lib{}.dylib", "lib{}.tbd", "lib{}.dll.a", "{}.lib"]
+                .iter()
+                .map(|pattern| pattern.replace("{}", name))
+                .collect()
+        };
+        self.copy_from_search_paths(&file_names)
+    }
+
+    /// Copies all the given files found in the first search path where any of
+    /// them is found, as the linker picks one of them depending on its options.
+    fn copy_from_search_paths(&mut self, file_names: &[String]) -> io::Result<()> {
+        for (path, copy) in &self.search_paths {
+            let found: Vec<_> =
+                file_names.iter().filter(|file_name| path.join(file_name).is_file()).collect();
+            for file_name in &found {
+                fs::copy(path.join(file_name), self.dir.join(copy).join(file_name))?;
+            }
+            if !found.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn search_path_copy(&self, path: &Path) -> PathBuf {
+        self.search_paths.iter().find(|(p, _)| p == path).unwrap().1.clone()
+    }
+}
+
+/// Returns the path of an argument adding a library search path, other than
+/// `-L` followed by the path in the next argument.
+fn search_path_arg(arg: &OsStr) -> Option<&str> {
+    let arg = arg.to_str()?;
+    let path = if let Some(path) = arg.strip_prefix("-L") {
+        path
+    } else if arg.len() > 9 && arg[..9].eq_ignore_ascii_case("/LIBPATH:") {
+        &arg[9..]
+    } else {
+        return None;
+    };
+    (!path.is_empty()).then_some(path)
+}
+
+/// Writes a script setting the environment of `cmd` and invoking its program
+/// with the response file of the reproducer.
+fn write_replay_script(dir: &Path, cmd: &Command) -> io::Result<()> {
+    let mut program = cmd.clone();
+    program.take_args();
+    let program = program.command();
+
+    let mut env: Vec<(OsString, Option<OsString>)> = LINKER_ENV_VARS
+        .iter()
+        .filter_map(|&var| Some((OsString::from(var), Some(env::var_os(var)?))))
+        .filter(|(var, _)| program.get_envs().all(|(k, _)| k != var.as_os_str()))
+        .collect();
+    env.extend(program.get_envs().map(|(k, v)| (k.to_owned(), v.map(|v| v.to_owned()))));
+
+    let mut invocation = vec![program.get_program()];
+    invocation.extend(program.get_args());
+
+    let mut script = String::new();
+    if cfg!(windows) {
+        let quote = |s: &OsStr| format!("\"{}\"", s.to_string_lossy().replace('%', "%%"));
+        script.push_str("@echo off\r\n");
+        script.push_str("rem Invokes the linker the way rustc did, writing to `output`.\r\n");
+        script.push_str("cd /d \"%~dp0\"\r\n");
+        for (var, value) in &env {
+            let var = var.to_string_lossy();
+            match value {
+                Some(value) => {
+                    let value = value.to_string_lossy().replace('%', "%%");
+                    write!(script, "set \"{var}={value}\"\r\n").unwrap();
+                }
+                None => write!(script, "set {var}=\r\n").unwrap(),
+            }
+        }
+        let invocation: Vec<_> = invocation.into_iter().map(quote).collect();
+        write!(script, "{} @linker-arguments\r\n", invocation.join(" ")).unwrap();
+        fs::write(dir.join("replay.bat"), script)?;
+    } else {
+        let quote = |s: &OsStr| format!("'{}'", s.to_string_lossy().replace('\'', r"'\''"));
+        script.push_str("#!/bin/sh\n");
+        script.push_str("# Invokes the linker the way rustc did, writing to `output`.\n");
+        script.push_str("cd \"$(dirname \"$0\")\" || exit 1\n");
+        for (var, value) in &env {
+            let var = var.to_string_lossy();
+            match value {
+                Some(value) => writeln!(script, "export {var}={}", quote(value)).unwrap(),
+                None => writeln!(script, "unset {var}").unwrap(),
+            }
+        }
+        let invocation: Vec<_> = invocation.into_iter().map(quote).collect();
+        writeln!(script, "exec {} @linker-arguments", invocation.join(" ")).unwrap();
+        let path = dir.join("replay.sh");
+        fs::write(&path, script)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+    }
+    Ok(())
+}
@@ -1739,7 +1739,7 @@ fn add_no_exec(&mut self) {}
     fn add_as_needed(&mut self) {}
 }
 
-fn for_each_exported_symbols_include_dep<'tcx>(
+pub(crate) fn for_each_exported_symbols_include_dep<'tcx>(
     tcx: TyCtxt<'tcx>,
     crate_type: CrateType,
     mut callback: impl FnMut(ExportedSymbol<'tcx>, SymbolExportInfo, CrateNum),
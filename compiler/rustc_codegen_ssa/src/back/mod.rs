@@ -6,11 +6,14 @@
 pub mod archive;
 pub(crate) mod command;
 pub mod link;
+pub(crate) mod link_repro;
 pub(crate) mod linker;
 pub mod lto;
 pub mod metadata;
 pub(crate) mod rpath;
+pub(crate) mod stack_usage;
 pub mod symbol_export;
+pub(crate) mod symbol_size;
 pub mod write;
 
 /// The target triple depends on the deployment target, and is required to
@@ -0,0 +1,322 @@
+//! The worst-case stack usage report of `-Z stack-usage-report`.
+//!
+//! The stack usage of an entry point is the largest sum of the stack frames of
+//! the functions along a call chain starting at the entry point. The call
+//! graph is gathered during the collection of mono items, and the stack frame
+//! sizes are read from the `.stack_sizes` sections that LLVM emits in the
+//! object files of the codegen units.
+//!
+//! A function that is not defined in any of the object files was inlined into
+//! all its callers by LLVM, so its frame is part of the frames of its callers.
+//! The stack usage is not bounded when a call chain is recursive, calls a
+//! function pointer or a trait object, calls a function of another crate,
+//! whose stack usage is unknown, or calls a function that is defined without a
+//! stack size.
+
+use std::fs;
+use std::path::Path;
+
+use object::{Object, ObjectSection, ObjectSymbol, RelocationTarget, SectionIndex, SymbolKind};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexSet};
+use rustc_data_structures::stack::ensure_sufficient_stack;
+use rustc_session::Session;
+use rustc_session::stack_usage::StackUsageFn;
+use serde::Serialize;
+use tracing::debug;
+
+use crate::CompiledModule;
+use crate::errors::{FailedToWrite, StackUsageNoStackSizes};
+
+#[derive(Serialize)]
+struct Report<'a> {
+    entry_points: Vec<EntryPoint<'a>>,
+}
+
+#[derive(Serialize)]
+struct EntryPoint<'a> {
+    name: &'a str,
+    symbol: &'a str,
+    /// The stack usage of the deepest call chain, in bytes.
+    stack_size: u64,
+    /// Whether `stack_size` is an upper bound of the stack usage of the entry
+    /// point, that is, whether `unbounded` is empty.
+    bounded: bool,
+    /// The functions of the deepest call chain, starting with the entry point.
+    call_chain: Vec<&'a str>,
+    unbounded: Vec<Unbounded<'a>>,
+}
+
+/// The reasons why the stack usage of an entry point is not bounded.
+#[derive(Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+enum Unbounded<'a> {
+    Recursion { function: &'a str },
+    IndirectCall { function: &'a str },
+    ExternalCall { function: &'a str, callee: &'a str },
+    UnknownFrameSize { function: &'a str },
+}
+
+pub(crate) fn write_report<'a>(
+    sess: &Session,
+    modules: impl Iterator<Item = &'a CompiledModule>,
+    path: &Path,
+) {
+    let mut frame_sizes = FxHashMap::default();
+    let mut defined = FxHashSet::default();
+    // Whether every object file was read, so that a function that is not
+    // defined in any of them is known to have been inlined.
+    let mut complete = true;
+    for module in modules {
+        let Some(object) = &module.object else {
+            complete = false;
+            continue;
+        };
+        match fs::read(object) {
+            Ok(data) => complete &= read_stack_sizes(&data, &mut frame_sizes, &mut defined),
+            Err(error) => {
+                debug!("cannot read {}: {error}", object.display());
+                complete = false;
+            }
+        }
+    }
+    if frame_sizes.is_empty() {
+        sess.dcx().emit_warn(StackUsageNoStackSizes);
+    }
+
+    let fns = sess.stack_usage_call_graph.fns();
+    let frame_sizes = fns
+        .iter()
+        .map(|function| match frame_sizes.get(&function.symbol) {
+            Some(&size) => Some(size),
+            None if complete && !defined.contains(&function.symbol) => Some(0),
+            None => None,
+        })
+        .collect();
+    let mut graph = CallGraph::new(&fns, frame_sizes);
+    for index in 0..fns.len() {
+        if let State::Unvisited = graph.states[index] {
+            graph.visit(index);
+        }
+    }
+
+    let entry_points = fns
+        .iter()
+        .enumerate()
+        .filter(|(_, function)| function.is_entry_point)
+        .map(|(index, function)| graph.entry_point(index, function))
+        .collect();
+    let report = serde_json::to_string_pretty(&Report { entry_points }).unwrap();
+    if let Err(error) = fs::write(path, report) {
+        sess.dcx().emit_err(FailedToWrite { path: path.to_path_buf(), error });
+    }
+}
+
+/// Reads the size of the stack frames of the functions of an object file, from
+/// the `.stack_sizes` sections emitted by LLVM. Each entry of these sections is
+/// the address of a function, which is relocated against its symbol, followed
+/// by the size of its stack frame as an ULEB128.
+///
+/// Also records the functions defined in the object file in `defined`. Returns
+/// `false` if the object file cannot be parsed.
+fn read_stack_sizes(
+    data: &[u8],
+    frame_sizes: &mut FxHashMap<String, u64>,
+    defined: &mut FxHashSet<String>,
+) -> bool {
+    let Ok(file) = object::File::parse(data) else { return false };
+    let address_size = if file.is_64() { 8 } else { 4 };
+    // The lowest bit of the address of Thumb functions is set.
+    let address_mask = if file.architecture() == object::Architecture::Arm { !1 } else { !0 };
+
+    // Relocations against local functions may target the symbol of their section.
+    let mut functions: FxHashMap<(SectionIndex, u64), &str> = FxHashMap::default();
+    for symbol in file.symbols() {
+        if symbol.kind() == SymbolKind::Text
+            && let Some(section) = symbol.section_index()
+            && let Ok(name) = symbol.name()
+        {
+            functions.insert((section, symbol.address() & address_mask), name);
+            defined.insert(name.to_owned());
+        }
+    }
+
+    for section in file.sections() {
+        if section.name().ok() != Some(".stack_sizes") {
+            continue;
+        }
+        let Ok(data) = section.data() else { continue };
+        let relocations: FxHashMap<_, _> = section.relocations().collect();
+        let mut offset = 0;
+        while offset + address_size <= data.len() {
+            let Some(relocation) = relocations.get(&(offset as u64)) else { break };
+            let addend = if relocation.has_implicit_addend() {
+                let bytes = &data[offset..offset + address_size];
+                let mut value = 0;
+                for (i, &byte) in bytes.iter().enumerate() {
+                    let shift = if file.is_little_endian() { i } else { address_size - 1 - i };
+                    value |= u64::from(byte) << (8 * shift);
+                }
+                value
+            } else {
+                relocation.addend() as u64
+            };
+            let name = match relocation.target() {
+                RelocationTarget::Symbol(index) => {
+                    file.symbol_by_index(index).ok().and_then(|symbol| {
+                        if symbol.kind() == SymbolKind::Section {
+                            let section = symbol.section_index()?;
+                            functions.get(&(section, addend & address_mask)).copied()
+                        } else {
+                            symbol.name().ok()
+                        }
+                    })
+                }
+                _ => None,
+            };
+            offset += address_size;
+            let Some(size) = read_uleb128(data, &mut offset) else { break };
+            if let Some(name) = name {
+                // Functions that are inlined in several codegen units have one
+                // stack frame size per copy.
+                let frame_size = frame_sizes.entry(name.to_owned()).or_default();
+                *frame_size = (*frame_size).max(size);
+            }
+        }
+    }
+    true
+}
+
+fn read_uleb128(data: &[u8], offset: &mut usize) -> Option<u64> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*offset)?;
+        *offset += 1;
+        value |= u64::from(byte & 0x7f).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+        shift += 7;
+    }
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Unvisited,
+    InProgress,
+    Done,
+}
+
+struct CallGraph<'a> {
+    fns: &'a [StackUsageFn],
+    callees: Vec<Vec<usize>>,
+    /// The size of the stack frame of each function, unless it is unknown.
+    frame_sizes: Vec<Option<u64>>,
+    states: Vec<State>,
+    /// The stack usage of the deepest call chain starting at each function.
+    depths: Vec<u64>,
+    /// The next function of the deepest call chain starting at each function.
+    deepest_callees: Vec<Option<usize>>,
+    /// Whether each function is part of a cycle of the call graph.
+    recursive: Vec<bool>,
+    /// The functions whose visit is in progress, in call order.
+    stack: Vec<usize>,
+}
+
+impl<'a> CallGraph<'a> {
+    fn new(fns: &'a [StackUsageFn], frame_sizes: Vec<Option<u64>>) -> CallGraph<'a> {
+        let indices: FxHashMap<&str, usize> =
+            fns.iter().enumerate().map(|(index, function)| (&*function.symbol, index)).collect();
+        CallGraph {
+            fns,
+            callees: fns
+                .iter()
+                .map(|function| {
+                    function
+                        .callees
+                        .iter()
+                        .filter_map(|callee| indices.get(&**callee).copied())
+                        .collect()
+                })
+                .collect(),
+            frame_sizes,
+            states: vec![State::Unvisited; fns.len()],
+            depths: vec![0; fns.len()],
+            deepest_callees: vec![None; fns.len()],
+            recursive: vec![false; fns.len()],
+            stack: vec![],
+        }
+    }
+
+    fn visit(&mut self, index: usize) {
+        self.states[index] = State::InProgress;
+        self.stack.push(index);
+        let mut depth = 0;
+        let mut deepest_callee = None;
+        for i in 0..self.callees[index].len() {
+            let callee = self.callees[index][i];
+            match self.states[callee] {
+                State::Unvisited => ensure_sufficient_stack(|| self.visit(callee)),
+                State::InProgress => {
+                    // Every function between the callee and this function on
+                    // the stack is part of the cycle.
+                    let start = self.stack.iter().rposition(|&f| f == callee).unwrap();
+                    for &f in &self.stack[start..] {
+                        self.recursive[f] = true;
+                    }
+                    continue;
+                }
+                State::Done => {}
+            }
+            if deepest_callee.is_none() || self.depths[callee] > depth {
+                depth = self.depths[callee];
+                deepest_callee = Some(callee);
+            }
+        }
+        self.depths[index] = self.frame_sizes[index].unwrap_or(0) + depth;
+        self.deepest_callees[index] = deepest_callee;
+        self.states[index] = State::Done;
+        self.stack.pop();
+    }
+
+    fn entry_point(&self, index: usize, function: &'a StackUsageFn) -> EntryPoint<'a> {
+        let fns = self.fns;
+        let mut call_chain = vec![];
+        let mut next = Some(index);
+        while let Some(i) = next {
+            call_chain.push(&*fns[i].name);
+            next = self.deepest_callees[i];
+        }
+
+        let mut unbounded = vec![];
+        let mut reachable = FxIndexSet::default();
+        reachable.insert(index);
+        let mut i = 0;
+        while let Some(&reached) = reachable.get_index(i) {
+            let function = &fns[reached];
+            if self.recursive[reached] {
+                unbounded.push(Unbounded::Recursion { function: &function.name });
+            }
+            if self.frame_sizes[reached].is_none() {
+                unbounded.push(Unbounded::UnknownFrameSize { function: &function.name });
+            }
+            if function.has_indirect_calls {
+                unbounded.push(Unbounded::IndirectCall { function: &function.name });
+            }
+            for callee in &function.external_callees {
+                unbounded.push(Unbounded::ExternalCall { function: &function.name, callee });
+            }
+            reachable.extend(self.callees[reached].iter().copied());
+            i += 1;
+        }
+
+        EntryPoint {
+            name: &function.name,
+            symbol: &function.symbol,
+            stack_size: self.depths[index],
+            bounded: unbounded.is_empty(),
+            call_chain,
+            unbounded,
+        }
+    }
+}
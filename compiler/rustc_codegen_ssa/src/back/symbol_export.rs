@@ -2,6 +2,7 @@
 
 use rustc_ast::expand::allocator::{ALLOCATOR_METHODS, NO_ALLOC_SHIM_IS_UNSTABLE};
 use rustc_data_structures::unord::UnordMap;
+use rustc_hir::LangItem;
 use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{CrateNum, DefId, DefIdMap, LOCAL_CRATE, LocalDefId};
 use rustc_middle::bug;
@@ -10,12 +11,15 @@
     ExportedSymbol, SymbolExportInfo, SymbolExportKind, SymbolExportLevel, metadata_symbol_name,
 };
 use rustc_middle::query::LocalCrate;
+use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::{self, GenericArgKind, GenericArgsRef, Instance, SymbolName, TyCtxt};
 use rustc_middle::util::Providers;
-use rustc_session::config::{CrateType, OomStrategy};
+use rustc_session::config::{CrateType, OomStrategy, PrintKind, PrintRequest};
 use rustc_target::spec::{SanitizerSet, TlsModel};
+use serde::Serialize;
 use tracing::debug;
 
+use super::linker;
 use crate::base::allocator_kind_for_codegen;
 
 fn threshold(tcx: TyCtxt<'_>) -> SymbolExportLevel {
@@ -684,3 +688,127 @@ fn wasm_import_module_map(tcx: TyCtxt<'_>, cnum: CrateNum) -> DefIdMap<String> {
 
     ret
 }
+
+/// An exported symbol as printed by `--print exported-symbols`.
+#[derive(Serialize)]
+struct PrintedSymbol {
+    crate_type: String,
+    symbol: String,
+    /// The export level and kind, which are unknown for the symbols exported
+    /// by proc-macro crates and for the symbols overridden by the target.
+    level: Option<&'static str>,
+    kind: Option<&'static str>,
+    /// The crate in which the symbol is defined.
+    #[serde(rename = "crate")]
+    krate: Option<String>,
+    /// The item the symbol was generated for, if any.
+    item: Option<String>,
+    generic_args: Vec<String>,
+    shim: Option<&'static str>,
+}
+
+/// Prints the symbols exported by each artifact of the crate, together with
+/// the instances they were generated for.
+pub fn print_exported_symbols(tcx: TyCtxt<'_>, print: &PrintRequest) {
+    let mut symbols = vec![];
+    for &crate_type in tcx.crate_types() {
+        let start = symbols.len();
+        if tcx.sess.target.override_export_symbols.is_some() || crate_type == CrateType::ProcMacro {
+            symbols.extend(linker::exported_symbols(tcx, crate_type).into_iter().map(|symbol| {
+                PrintedSymbol {
+                    crate_type: crate_type.to_string(),
+                    symbol,
+                    level: None,
+                    kind: None,
+                    krate: None,
+                    item: None,
+                    generic_args: vec![],
+                    shim: None,
+                }
+            }));
+        } else {
+            let export_threshold = crates_export_threshold(&[crate_type]);
+            linker::for_each_exported_symbols_include_dep(tcx, crate_type, |symbol, info, cnum| {
+                if !info.level.is_below_threshold(export_threshold) {
+                    return;
+                }
+                let (item, generic_args, shim) = exported_symbol_instance(tcx, symbol);
+                symbols.push(PrintedSymbol {
+                    crate_type: crate_type.to_string(),
+                    symbol: exporting_symbol_name_for_instance_in_crate(tcx, symbol, cnum),
+                    level: Some(match info.level {
+                        SymbolExportLevel::C => "C",
+                        SymbolExportLevel::Rust => "Rust",
+                    }),
+                    kind: Some(match info.kind {
+                        SymbolExportKind::Text => "text",
+                        SymbolExportKind::Data => "data",
+                        SymbolExportKind::Tls => "tls",
+                    }),
+                    krate: Some(tcx.crate_name(cnum).to_string()),
+                    item,
+                    generic_args,
+                    shim,
+                });
+            });
+        }
+        symbols[start..].sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    }
+
+    let output = if print.kind == PrintKind::ExportedSymbolsJson {
+        serde_json::to_string_pretty(&symbols).unwrap() + "\n"
+    } else {
+        let mut output = String::new();
+        for symbol in &symbols {
+            let mut instance = symbol.item.clone().unwrap_or_else(|| "-".to_owned());
+            if !symbol.generic_args.is_empty() {
+                instance = format!("{instance}::<{}>", symbol.generic_args.join(", "));
+            }
+            if let Some(shim) = symbol.shim {
+                instance = format!("{instance} ({shim})");
+            }
+            output += &format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                symbol.crate_type,
+                symbol.level.unwrap_or("-"),
+                symbol.kind.unwrap_or("-"),
+                symbol.symbol,
+                instance,
+            );
+        }
+        output
+    };
+    print.out.overwrite(&output, tcx.sess);
+}
+
+/// Returns the path of the item an exported symbol was generated for, its
+/// generic arguments and the kind of shim the symbol is for, if any.
+fn exported_symbol_instance<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    symbol: ExportedSymbol<'tcx>,
+) -> (Option<String>, Vec<String>, Option<&'static str>) {
+    let (def_id, args, shim) = match symbol {
+        ExportedSymbol::NonGeneric(def_id) => (def_id, ty::GenericArgs::empty(), None),
+        ExportedSymbol::Generic(def_id, args) => (def_id, args, None),
+        ExportedSymbol::DropGlue(ty) => {
+            let def_id = tcx.require_lang_item(LangItem::DropInPlace, None);
+            (def_id, tcx.mk_args(&[ty.into()]), Some("drop glue"))
+        }
+        ExportedSymbol::AsyncDropGlueCtorShim(ty) => {
+            let def_id = tcx.require_lang_item(LangItem::AsyncDropInPlace, None);
+            (def_id, tcx.mk_args(&[ty.into()]), Some("async drop glue"))
+        }
+        ExportedSymbol::ThreadLocalShim(def_id) => {
+            (def_id, ty::GenericArgs::empty(), Some("thread-local shim"))
+        }
+        ExportedSymbol::NoDefId(_) => return (None, vec![], None),
+    };
+    with_no_trimmed_paths!({
+        let generic_args = args
+            .iter()
+            .filter(|arg| !matches!(arg.unpack(), GenericArgKind::Lifetime(_)))
+            .map(|arg| arg.to_string())
+            .collect();
+        (Some(tcx.def_path_str(def_id)), generic_args, shim)
+    })
+}
@@ -0,0 +1,372 @@
+//! The binary size report of `-Z symbol-size-report`.
+//!
+//! The report attributes the bytes of the symbols of the linked artifacts to
+//! the items defining them, grouped by crate, module and item. The owners of
+//! the symbols code generated in this crate, including the instantiations of
+//! the generic items of other crates, are recorded during partitioning, so
+//! that they are known for the codegen units reused by incremental compilation
+//! too. The symbols of the code of other crates, which is linked from
+//! their rlibs, are attributed by demangling their names, and symbols which
+//! are not Rust symbols are attributed to the `[native]` crate.
+//!
+//! Bytes that no symbol covers, like the string literals merged by the
+//! linker, are only counted in the size of their section.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use object::{Object, ObjectSection, ObjectSymbol, SectionIndex, SectionKind, SymbolKind};
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
+use rustc_session::Session;
+use rustc_session::symbol_size::SymbolOwner;
+use rustc_symbol_mangling::demangle_symbol;
+use serde::Serialize;
+
+use crate::errors::{FailedToWrite, SymbolSizeReportNoSymbols, SymbolSizeReportUnreadable};
+
+#[derive(Serialize)]
+struct Report {
+    artifacts: Vec<Artifact>,
+}
+
+#[derive(Serialize)]
+struct Artifact {
+    path: PathBuf,
+    /// The sum of the sizes of the sections of the artifact which are loaded
+    /// in memory.
+    size: u64,
+    sections: Vec<Section>,
+    crates: Vec<Group<Group<Group<Symbol>>>>,
+}
+
+#[derive(Serialize)]
+struct Section {
+    name: String,
+    kind: &'static str,
+    size: u64,
+    /// The number of bytes of the section covered by symbols.
+    symbols_size: u64,
+}
+
+/// A crate, module or item, with the size of all the symbols it contains.
+#[derive(Serialize)]
+struct Group<T> {
+    name: String,
+    size: u64,
+    #[serde(rename = "contents")]
+    children: Vec<T>,
+}
+
+#[derive(Serialize)]
+struct Symbol {
+    symbol: String,
+    /// The item with its generic arguments, or the symbol itself for a symbol
+    /// which is not a Rust symbol.
+    name: String,
+    kind: &'static str,
+    section: String,
+    size: u64,
+}
+
+pub(crate) fn write_report(sess: &Session, artifacts: &[PathBuf], path: &Path) {
+    let owners = sess.symbol_owners.owners();
+    let artifacts = artifacts
+        .iter()
+        .filter_map(|artifact| {
+            let data = match fs::read(artifact) {
+                Ok(data) => data,
+                Err(error) => {
+                    sess.dcx().emit_warn(SymbolSizeReportUnreadable {
+                        path: artifact.clone(),
+                        error: error.to_string(),
+                    });
+                    return None;
+                }
+            };
+            match object::File::parse(&*data) {
+                Ok(file) => Some(read_artifact(sess, artifact, &file, &owners)),
+                Err(error) => {
+                    sess.dcx().emit_warn(SymbolSizeReportUnreadable {
+                        path: artifact.clone(),
+                        error: error.to_string(),
+                    });
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let report = serde_json::to_string_pretty(&Report { artifacts }).unwrap();
+    if let Err(error) = fs::write(path, report) {
+        sess.dcx().emit_err(FailedToWrite { path: path.to_path_buf(), error });
+    }
+}
+
+fn read_artifact(
+    sess: &Session,
+    path: &Path,
+    file: &object::File<'_>,
+    owners: &FxHashMap<String, SymbolOwner>,
+) -> Artifact {
+    let mut sections = FxIndexMap::default();
+    for section in file.sections() {
+        let Some(kind) = section_kind(section.kind()) else { continue };
+        if section.size() == 0 {
+            continue;
+        }
+        let name = section.name().unwrap_or("").to_owned();
+        sections
+            .insert(section.index(), Section { name, kind, size: section.size(), symbols_size: 0 });
+    }
+
+    // Executables usually keep their full symbol table, shared libraries
+    // which were stripped still have their dynamic symbols.
+    let mut symbols: Vec<_> = file.symbols().filter_map(|symbol| sized_symbol(&symbol)).collect();
+    if symbols.is_empty() {
+        symbols = file.dynamic_symbols().filter_map(|symbol| sized_symbol(&symbol)).collect();
+    }
+    if symbols.is_empty() {
+        sess.dcx().emit_warn(SymbolSizeReportNoSymbols { path: path.to_path_buf() });
+    }
+    symbols.retain(|symbol| sections.contains_key(&symbol.section));
+
+    // Aliases share the bytes of a single symbol. Symbols without a size, as
+    // in Mach-O files, span until the next symbol or the end of their section.
+    symbols.sort_by_key(|symbol| (symbol.section, symbol.address, symbol.size == 0));
+    symbols.dedup_by_key(|symbol| (symbol.section, symbol.address));
+    for i in 0..symbols.len() {
+        if symbols[i].size != 0 {
+            continue;
+        }
+        let end = match symbols.get(i + 1) {
+            Some(next) if next.section == symbols[i].section => next.address,
+            _ => {
+                let section = file.section_by_index(symbols[i].section).unwrap();
+                section.address() + section.size()
+            }
+        };
+        symbols[i].size = end.saturating_sub(symbols[i].address);
+    }
+
+    let mut crates: FxIndexMap<String, FxIndexMap<String, FxIndexMap<String, Vec<Symbol>>>> =
+        FxIndexMap::default();
+    for symbol in symbols {
+        let section = &mut sections[&symbol.section];
+        section.symbols_size += symbol.size;
+        let owner = symbol_owner(file, &symbol, owners);
+        crates
+            .entry(owner.krate)
+            .or_default()
+            .entry(owner.module)
+            .or_default()
+            .entry(owner.item)
+            .or_default()
+            .push(Symbol {
+                symbol: symbol.name.to_owned(),
+                name: owner.instance,
+                kind: owner.kind,
+                section: section.name.clone(),
+                size: symbol.size,
+            });
+    }
+
+    let crates = group(crates, |modules| {
+        group(modules, |items| group(items, |symbols| sort_by_size(symbols, |symbol| symbol.size)))
+    });
+    Artifact {
+        path: path.to_path_buf(),
+        size: sections.values().map(|section| section.size).sum(),
+        sections: sections.into_values().collect(),
+        crates,
+    }
+}
+
+struct SizedSymbol<'data> {
+    name: &'data str,
+    kind: SymbolKind,
+    section: SectionIndex,
+    address: u64,
+    size: u64,
+}
+
+fn sized_symbol<'data>(symbol: &object::Symbol<'data, '_>) -> Option<SizedSymbol<'data>> {
+    if !matches!(symbol.kind(), SymbolKind::Text | SymbolKind::Data | SymbolKind::Tls)
+        || !symbol.is_definition()
+    {
+        return None;
+    }
+    let name = symbol.name().ok().filter(|name| !name.is_empty())?;
+    Some(SizedSymbol {
+        name,
+        kind: symbol.kind(),
+        section: symbol.section_index()?,
+        address: symbol.address(),
+        size: symbol.size(),
+    })
+}
+
+/// The kinds of the sections which are loaded in memory.
+fn section_kind(kind: SectionKind) -> Option<&'static str> {
+    Some(match kind {
+        SectionKind::Text => "text",
+        SectionKind::Data => "data",
+        SectionKind::ReadOnlyData
+        | SectionKind::ReadOnlyDataWithRel
+        | SectionKind::ReadOnlyString => "read-only data",
+        SectionKind::UninitializedData => "uninitialized data",
+        SectionKind::Tls | SectionKind::UninitializedTls => "thread-local data",
+        _ => return None,
+    })
+}
+
+fn symbol_owner(
+    file: &object::File<'_>,
+    symbol: &SizedSymbol<'_>,
+    owners: &FxHashMap<String, SymbolOwner>,
+) -> SymbolOwner {
+    // Mach-O prefixes symbols with an underscore, and ThinLTO may add a
+    // suffix to the symbols it promotes.
+    let mut name = symbol.name;
+    if file.format() == object::BinaryFormat::MachO {
+        name = name.strip_prefix('_').unwrap_or(name);
+    }
+    if let Some((prefix, _)) = name.split_once(".llvm.") {
+        name = prefix;
+    }
+    if let Some(owner) = owners.get(name) {
+        return owner.clone();
+    }
+
+    let kind = if symbol.kind == SymbolKind::Text { "function" } else { "static" };
+    match demangle_symbol(name) {
+        Some(path) => {
+            let item = strip_generic_args(&path);
+            let module = demangled_module(&item);
+            let krate = module.split("::").next().unwrap().to_owned();
+            SymbolOwner { kind, krate, module, item, instance: path }
+        }
+        None => SymbolOwner {
+            kind,
+            krate: "[native]".to_owned(),
+            module: "[native]".to_owned(),
+            item: name.to_owned(),
+            instance: name.to_owned(),
+        },
+    }
+}
+
+/// Removes the generic argument lists `::<...>` from a demangled path, so
+/// that all the instantiations of a generic item are grouped together.
+fn strip_generic_args(path: &str) -> String {
+    let mut item = String::with_capacity(path.len());
+    let mut depth = 0;
+    let mut rest = path;
+    while let Some(c) = rest.chars().next() {
+        if depth == 0 && rest.starts_with("::<") {
+            depth = 1;
+            rest = &rest[3..];
+            continue;
+        }
+        if depth > 0 {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {}
+            }
+        } else {
+            item.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    item
+}
+
+/// Returns the module of the item with the demangled path `item`. The items
+/// of `<Type as Trait>` and `<Type>` impls belong to the module of `Type`, or
+/// of `Trait` if `Type` is not a path, e.g. a reference.
+fn demangled_module(item: &str) -> String {
+    let mut segments = split_path(item);
+    if let Some(qualified) = segments.first().and_then(|first| first.strip_prefix('<')) {
+        let qualified = qualified.strip_suffix('>').unwrap_or(qualified);
+        let (self_ty, trait_path) = match qualified.split_once(" as ") {
+            Some((self_ty, trait_path)) => (self_ty, Some(trait_path)),
+            None => (qualified, None),
+        };
+        let is_path = |path: &str| path.starts_with(|c: char| c.is_alphabetic() || c == '_');
+        segments = match trait_path {
+            Some(trait_path) if !is_path(self_ty) || !self_ty.contains("::") => {
+                split_path(trait_path)
+            }
+            _ => split_path(self_ty),
+        };
+    }
+    // Closures and shims belong to the module of their parent item.
+    while segments.len() > 1 && segments.last().is_some_and(|segment| segment.starts_with('{')) {
+        segments.pop();
+    }
+    if segments.len() > 1 {
+        segments.pop();
+    }
+    segments.join("::")
+}
+
+/// Splits a path at the `::` which are not nested in angle brackets.
+fn split_path(path: &str) -> Vec<&str> {
+    let mut segments = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    let bytes = path.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'<' => depth += 1,
+            b'>' if depth > 0 => depth -= 1,
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => {
+                segments.push(&path[start..i]);
+                i += 2;
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    segments.push(&path[start..]);
+    segments
+}
+
+/// Turns groups of symbols into a list of groups, largest first.
+fn group<T, U: HasSize>(
+    groups: FxIndexMap<String, T>,
+    mut children: impl FnMut(T) -> Vec<U>,
+) -> Vec<Group<U>> {
+    let groups = groups
+        .into_iter()
+        .map(|(name, contents)| {
+            let children = children(contents);
+            Group { size: children.iter().map(HasSize::size).sum(), name, children }
+        })
+        .collect();
+    sort_by_size(groups, |group| group.size)
+}
+
+fn sort_by_size<T>(mut items: Vec<T>, size: impl Fn(&T) -> u64) -> Vec<T> {
+    items.sort_by_key(|item| std::cmp::Reverse(size(item)));
+    items
+}
+
+trait HasSize {
+    fn size(&self) -> u64;
+}
+
+impl<T> HasSize for Group<T> {
+    fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl HasSize for Symbol {
+    fn size(&self) -> u64 {
+        self.size
+    }
+}
@@ -39,6 +39,7 @@
 
 use super::link::{self, ensure_removed};
 use super::lto::{self, SerializedModule};
+use super::stack_usage;
 use super::symbol_export::symbol_name_for_instance_in_crate;
 use crate::errors::ErrorCreatingRemarkDir;
 use crate::traits::*;
@@ -2039,6 +2040,13 @@ pub fn join(self, sess: &Session) -> (CodegenResults, FxIndexMap<WorkProductId,
 
         let work_products =
             copy_all_cgu_workproducts_to_incr_comp_cache_dir(sess, &compiled_modules);
+        // The report reads the objects of the codegen units, which may be
+        // removed once the final artifacts are produced.
+        if let Some(path) = &sess.opts.unstable_opts.stack_usage_report {
+            sess.time("write_stack_usage_report", || {
+                stack_usage::write_report(sess, compiled_modules.modules.iter(), path)
+            });
+        }
         produce_final_output_artifacts(sess, &compiled_modules, &self.output_filenames);
 
         // FIXME: time_llvm_passes support - does this use a global context or
@@ -505,6 +505,25 @@ pub(crate) struct UnableToRunDsymutil {
     pub error: Error,
 }
 
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_usage_no_stack_sizes)]
+#[note]
+pub(crate) struct StackUsageNoStackSizes;
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_symbol_size_report_no_symbols)]
+#[note]
+pub(crate) struct SymbolSizeReportNoSymbols {
+    pub path: PathBuf,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_symbol_size_report_unreadable)]
+pub(crate) struct SymbolSizeReportUnreadable {
+    pub path: PathBuf,
+    pub error: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(codegen_ssa_stripping_debug_info_failed)]
 #[note]
@@ -546,6 +565,19 @@ pub(crate) struct LinkScriptWriteFailure {
     pub error: Error,
 }
 
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_link_repro_failed)]
+pub(crate) struct LinkReproFailed {
+    pub path: PathBuf,
+    pub error: Error,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_link_repro_written)]
+pub(crate) struct LinkReproWritten {
+    pub path: PathBuf,
+}
+
 #[derive(Diagnostic)]
 #[diag(codegen_ssa_failed_to_write)]
 pub(crate) struct FailedToWrite {
@@ -1,4 +1,5 @@
 use rustc_middle::bug;
+use rustc_middle::mir::mono::vtable_symbol_name;
 use rustc_middle::ty::{self, GenericArgKind, Ty};
 use rustc_session::config::Lto;
 use rustc_symbol_mangling::typeid_for_trait_ref;
@@ -109,7 +110,13 @@ pub(crate) fn get_vtable<'tcx, Cx: CodegenMethods<'tcx>>(
     let vtable_allocation = tcx.global_alloc(vtable_alloc_id).unwrap_memory();
     let vtable_const = cx.const_data_from_alloc(vtable_allocation);
     let align = cx.data_layout().pointer_align.abi;
-    let vtable = cx.static_addr_of(vtable_const, align, Some("vtable"));
+    let vtable = if tcx.sess.opts.unstable_opts.symbol_size_report.is_some() {
+        // The vtable gets a global of its own, as the one of `static_addr_of` may be shared with
+        // identical constants. The owner of its symbol is recorded during partitioning.
+        cx.static_addr_of_local_symbol(vtable_const, align, &vtable_symbol_name(tcx, ty, trait_ref))
+    } else {
+        cx.static_addr_of(vtable_const, align, Some("vtable"))
+    };
 
     cx.apply_vcall_visibility_metadata(ty, trait_ref, vtable);
     cx.create_vtable_debuginfo(ty, trait_ref, vtable);
@@ -713,6 +713,14 @@ fn codegen_assert_terminator(
                 // and `#[track_caller]` adds an implicit third argument.
                 (LangItem::PanicMisalignedPointerDereference, vec![required, found, location])
             }
+            AssertKind::InvalidBool(ref found)
+            | AssertKind::InvalidChar(ref found)
+            | AssertKind::InvalidEnumDiscriminant(ref found) => {
+                let found = self.codegen_operand(bx, found).immediate();
+                // It's `fn panic_invalid_...(found: ...)`,
+                // and `#[track_caller]` adds an implicit second argument.
+                (msg.panic_function(), vec![found, location])
+            }
             _ => {
                 // It's `pub fn panic_...()` and `#[track_caller]` adds an implicit argument.
                 (msg.panic_function(), vec![location])
@@ -102,7 +102,10 @@ fn define<Bx: BuilderMethods<'a, 'tcx>>(&self, cx: &'a Bx::CodegenCx) {
                 }
             }
             MonoItem::Fn(instance) => {
-                base::codegen_instance::<Bx>(cx, instance);
+                // Functions defined as aliases by `predefine` have no body of their own.
+                if cx.codegen_unit().fn_alias(instance).is_none() || !cx.supports_fn_aliases() {
+                    base::codegen_instance::<Bx>(cx, instance);
+                }
             }
         }
 
@@ -140,6 +143,10 @@ fn predefine<Bx: BuilderMethods<'a, 'tcx>>(
 
                 if attrs.flags.contains(CodegenFnAttrFlags::NAKED) {
                     // do not define this function; it will become a global assembly block
+                } else if let Some(aliasee) = cx.codegen_unit().fn_alias(instance)
+                    && cx.supports_fn_aliases()
+                {
+                    cx.predefine_fn_alias(instance, aliasee, linkage, visibility, symbol_name);
                 } else {
                     cx.predefine_fn(instance, linkage, visibility, symbol_name);
                 };
@@ -1,4 +1,5 @@
 use rustc_hir::def_id::DefId;
+use rustc_middle::bug;
 use rustc_middle::mir::mono::{Linkage, Visibility};
 use rustc_middle::ty::Instance;
 
@@ -17,4 +18,25 @@ fn predefine_fn(
         visibility: Visibility,
         symbol_name: &str,
     );
+
+    /// Whether the backend can define a function as an alias of another one,
+    /// see [`PreDefineCodegenMethods::predefine_fn_alias`]. Otherwise, the
+    /// functions that the codegen unit defines as aliases are code generated
+    /// like any other function.
+    fn supports_fn_aliases(&self) -> bool {
+        false
+    }
+
+    /// Defines `instance` as an alias of `aliasee`, which is a function of the
+    /// same codegen unit with an identical body.
+    fn predefine_fn_alias(
+        &self,
+        instance: Instance<'tcx>,
+        aliasee: Instance<'tcx>,
+        _linkage: Linkage,
+        _visibility: Visibility,
+        _symbol_name: &str,
+    ) {
+        bug!("cannot define {instance} as an alias of {aliasee}")
+    }
 }
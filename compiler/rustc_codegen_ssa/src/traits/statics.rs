@@ -18,6 +18,19 @@ pub trait StaticCodegenMethods: BackendTypes {
     /// on some targets (non-ELF), we may use `add_used_global` for `#[used]` statics
     /// instead.
     fn add_compiler_used_global(&self, global: Self::Value);
+
+    /// Like `static_addr_of`, but defines a constant global of its own, which is not shared with
+    /// identical constants, and lists it as a local symbol named after `name` in the symbol table
+    /// of the object file, so that tools inspecting the linked artifact can attribute its bytes.
+    /// Backends which cannot do so fall back to `static_addr_of`.
+    fn static_addr_of_local_symbol(
+        &self,
+        cv: Self::Value,
+        align: Align,
+        _name: &str,
+    ) -> Self::Value {
+        self.static_addr_of(cv, align, None)
+    }
 }
 
 pub trait StaticBuilderMethods: BackendTypes {
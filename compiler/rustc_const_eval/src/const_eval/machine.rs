@@ -509,6 +509,10 @@ fn assert_panic(
                     found: eval_to_int(found)?,
                 }
             }
+            NullPointerDereference => NullPointerDereference,
+            InvalidBool(found) => InvalidBool(eval_to_int(found)?),
+            InvalidChar(found) => InvalidChar(eval_to_int(found)?),
+            InvalidEnumDiscriminant(found) => InvalidEnumDiscriminant(eval_to_int(found)?),
         };
         Err(ConstEvalErrKind::AssertFailure(err)).into()
     }
@@ -1,3 +1,6 @@
+driver_impl_api_diff_crate_not_loaded = no crate was loaded for `-Zapi-diff` crate `{$name}`
+    .help = pass the crate with `--extern {$name}=path/to/lib{$name}.rmeta`
+
 driver_impl_ice = the compiler unexpectedly panicked. this is a bug.
 driver_impl_ice_bug_report = we would appreciate a bug report: {$bug_report_url}
 driver_impl_ice_bug_report_internal_feature = using internal features is not supported and expected to cause internal compiler errors when used incorrectly
@@ -24,4 +27,6 @@ driver_impl_rlink_unable_to_read = failed to read rlink file: `{$err}`
 
 driver_impl_rlink_wrong_file_type = The input does not look like a .rlink file
 
+driver_impl_stable_mir_export = cannot write the stable MIR of the crate to `{$path}`: {$error}
+
 driver_impl_unstable_feature_usage = cannot dump feature usage metrics: {$error}
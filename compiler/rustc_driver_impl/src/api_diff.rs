@@ -0,0 +1,485 @@
+//! Implementation of `-Zapi-diff`, which compares the public API of two versions of the same
+//! crate and classifies the differences according to semver.
+//!
+//! Both versions are loaded as ordinary dependencies of the crate being compiled
+//! (`--extern old=libfoo-1.rmeta --extern new=libfoo-2.rmeta`). Their public surface is
+//! reconstructed from the reexport tree stored in metadata, which is the cross-crate
+//! counterpart of the effective visibilities computed by `rustc_privacy`: an item appears in
+//! it exactly when it is nameable from outside of its crate. Effective visibilities themselves
+//! are not encoded in metadata, so the items reached through that tree are recorded and used in
+//! their place wherever reachability matters, e.g. for the traits and types of trait impls.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def::{CtorKind, DefKind, Res};
+use rustc_hir::def_id::{CrateNum, DefId};
+use rustc_middle::ty::print::{with_no_trimmed_paths, with_no_visible_paths};
+use rustc_middle::ty::{self, TyCtxt, TypingMode};
+use rustc_session::config::{ExternLocation, OutFileName};
+use rustc_span::sym;
+use rustc_trait_selection::infer::TyCtxtInferExt;
+use rustc_trait_selection::traits::{ObligationCause, ObligationCtxt};
+
+use crate::session_diagnostics::ApiDiffCrateNotLoaded;
+
+/// Whether a change requires a new major version or only a new minor version.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Severity {
+    Major,
+    Minor,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Major => "major",
+            Severity::Minor => "minor",
+        }
+    }
+}
+
+struct Change {
+    severity: Severity,
+    path: String,
+    description: String,
+}
+
+/// The parts of a public item that are relevant for semver compatibility.
+#[derive(Debug)]
+struct ApiItem {
+    kind: &'static str,
+    /// The printed signature: the type of fns, consts, statics, fields and aliases, followed by
+    /// the generic parameters and where clauses of the item.
+    signature: String,
+    /// Set for structs, variants and enums marked `#[non_exhaustive]`.
+    non_exhaustive: bool,
+    /// Set for structs and variants with fields that are not visible outside of the crate,
+    /// meaning they cannot be constructed or exhaustively destructured by dependents.
+    private_fields: bool,
+    /// Set for associated items of a trait that implementors have to provide.
+    required: bool,
+    /// The auto traits implemented by ADTs, with the bounds their generic parameters need for
+    /// it beyond the where clauses of the ADT.
+    auto_traits: BTreeMap<&'static str, Vec<String>>,
+}
+
+impl ApiItem {
+    fn new(kind: &'static str, signature: String) -> ApiItem {
+        ApiItem {
+            kind,
+            signature,
+            non_exhaustive: false,
+            private_fields: false,
+            required: false,
+            auto_traits: BTreeMap::new(),
+        }
+    }
+}
+
+struct ApiCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    krate: CrateNum,
+    items: BTreeMap<String, ApiItem>,
+    visited: FxHashSet<DefId>,
+    /// Every item of `krate` reached through the reexport tree.
+    reachable: FxHashSet<DefId>,
+}
+
+impl<'tcx> ApiCollector<'tcx> {
+    fn collect(tcx: TyCtxt<'tcx>, krate: CrateNum) -> BTreeMap<String, ApiItem> {
+        let mut collector = ApiCollector {
+            tcx,
+            krate,
+            items: BTreeMap::new(),
+            visited: FxHashSet::default(),
+            reachable: FxHashSet::default(),
+        };
+        let root = krate.as_def_id();
+        collector.visited.insert(root);
+        collector.visit_children(root, tcx.crate_name(krate).as_str());
+        collector.visit_trait_impls();
+        collector.items
+    }
+
+    fn visit_children(&mut self, parent: DefId, prefix: &str) {
+        let tcx = self.tcx;
+        for child in tcx.module_children(parent) {
+            if !child.vis.is_public() {
+                continue;
+            }
+            let Res::Def(kind, def_id) = child.res else { continue };
+            // Constructors share their name with the type they construct; whether they are
+            // usable is covered by the fields of that type.
+            if let DefKind::Ctor(..) = kind {
+                continue;
+            }
+            let path = format!("{prefix}::{}", child.ident);
+            if def_id.krate == self.krate {
+                self.reachable.insert(def_id);
+            }
+            self.visit_item(&path, kind, def_id);
+            // Items reexported from other crates are not part of this crate's API surface
+            // beyond their name, so only descend into our own modules, enums and traits.
+            if matches!(kind, DefKind::Mod | DefKind::Enum | DefKind::Trait)
+                && def_id.krate == self.krate
+                && self.visited.insert(def_id)
+            {
+                self.visit_children(def_id, &path);
+            }
+            if matches!(kind, DefKind::Struct | DefKind::Enum | DefKind::Union)
+                && def_id.krate == self.krate
+            {
+                self.visit_inherent_impls(def_id, &path);
+            }
+        }
+    }
+
+    /// Inherent impls are not part of the module tree either, so their associated items are
+    /// keyed by the path of the type they are implemented on.
+    fn visit_inherent_impls(&mut self, ty_def_id: DefId, prefix: &str) {
+        let tcx = self.tcx;
+        for &impl_def_id in tcx.inherent_impls(ty_def_id) {
+            for &def_id in tcx.associated_item_def_ids(impl_def_id) {
+                if !tcx.visibility(def_id).is_public() {
+                    continue;
+                }
+                let path = format!("{prefix}::{}", tcx.item_name(def_id));
+                self.visit_item(&path, tcx.def_kind(def_id), def_id);
+            }
+        }
+    }
+
+    /// Whether `def_id` can be named from outside of the crate that defines it. Items of other
+    /// crates are only checked for being `pub`, as their reexport trees are not walked.
+    fn is_reachable(&self, def_id: DefId) -> bool {
+        if def_id.krate == self.krate {
+            self.reachable.contains(&def_id)
+        } else {
+            self.tcx.visibility(def_id).is_public()
+        }
+    }
+
+    fn visit_item(&mut self, path: &str, kind: DefKind, def_id: DefId) {
+        let tcx = self.tcx;
+        let descr = kind.descr(def_id);
+        let mut item = match kind {
+            DefKind::Fn | DefKind::AssocFn => {
+                let sig = tcx.fn_sig(def_id).instantiate_identity();
+                ApiItem::new(descr, format!("{sig}{}", self.generics(def_id)))
+            }
+            DefKind::Const | DefKind::AssocConst | DefKind::Static { .. } | DefKind::TyAlias => {
+                let ty = tcx.type_of(def_id).instantiate_identity();
+                ApiItem::new(descr, format!("{ty}{}", self.generics(def_id)))
+            }
+            DefKind::Struct | DefKind::Union => {
+                let adt = tcx.adt_def(def_id);
+                let mut item = ApiItem::new(descr, self.generics(def_id));
+                self.visit_fields(path, adt.non_enum_variant(), &mut item);
+                item.auto_traits = self.auto_traits(def_id);
+                item
+            }
+            DefKind::Enum => {
+                let adt = tcx.adt_def(def_id);
+                let mut item = ApiItem::new(descr, self.generics(def_id));
+                item.non_exhaustive = adt.is_variant_list_non_exhaustive();
+                item.auto_traits = self.auto_traits(def_id);
+                item
+            }
+            DefKind::Variant => {
+                let variant = tcx.adt_def(tcx.parent(def_id)).variant_with_id(def_id);
+                let mut item = ApiItem::new(descr, String::new());
+                self.visit_fields(path, variant, &mut item);
+                item
+            }
+            DefKind::Trait | DefKind::TraitAlias => {
+                let mut signature = self.generics(def_id);
+                if tcx.trait_is_auto(def_id) {
+                    signature.insert_str(0, "auto");
+                }
+                ApiItem::new(descr, signature)
+            }
+            _ => ApiItem::new(descr, String::new()),
+        };
+        if matches!(kind, DefKind::AssocFn | DefKind::AssocConst | DefKind::AssocTy)
+            && tcx.def_kind(tcx.parent(def_id)) == DefKind::Trait
+        {
+            item.required = !tcx.defaultness(def_id).has_value();
+        }
+        self.items.insert(path.to_string(), item);
+    }
+
+    fn visit_fields(&mut self, path: &str, variant: &ty::VariantDef, item: &mut ApiItem) {
+        let tcx = self.tcx;
+        item.non_exhaustive = variant.is_field_list_non_exhaustive();
+        let kind = match variant.ctor_kind() {
+            Some(CtorKind::Fn) => "tuple",
+            Some(CtorKind::Const) => "unit",
+            None => "struct",
+        };
+        item.signature.insert_str(0, kind);
+        for field in &variant.fields {
+            if !field.vis.is_public() {
+                item.private_fields = true;
+                continue;
+            }
+            let ty = tcx.type_of(field.did).instantiate_identity();
+            let field_item = ApiItem::new("field", ty.to_string());
+            self.items.insert(format!("{path}::{}", field.name), field_item);
+        }
+    }
+
+    /// Trait impls are not reachable through the module tree, so they are keyed by the
+    /// implemented trait reference instead of a path.
+    fn visit_trait_impls(&mut self) {
+        let tcx = self.tcx;
+        for &impl_def_id in tcx.trait_impls_in_crate(self.krate) {
+            let Some(header) = tcx.impl_trait_header(impl_def_id) else { continue };
+            let trait_ref = header.trait_ref.instantiate_identity();
+            if !self.is_reachable(trait_ref.def_id) {
+                continue;
+            }
+            if let Some(adt) = trait_ref.self_ty().ty_adt_def()
+                && !self.is_reachable(adt.did())
+            {
+                continue;
+            }
+            let polarity = match header.polarity {
+                ty::ImplPolarity::Positive | ty::ImplPolarity::Reservation => "",
+                ty::ImplPolarity::Negative => "!",
+            };
+            let path = format!("impl {polarity}{trait_ref}");
+            self.items.insert(path, ApiItem::new("impl", self.generics(impl_def_id)));
+        }
+    }
+
+    /// Prints the own generic parameters and where clauses of an item in a stable order.
+    fn generics(&self, def_id: DefId) -> String {
+        let tcx = self.tcx;
+        let params: Vec<_> =
+            tcx.generics_of(def_id).own_params.iter().map(|param| param.name.to_string()).collect();
+        let mut predicates: Vec<_> = tcx
+            .predicates_of(def_id)
+            .predicates
+            .iter()
+            .map(|(clause, _)| clause.to_string())
+            .collect();
+        predicates.sort();
+        let mut out = String::new();
+        if !params.is_empty() {
+            write!(out, "<{}>", params.join(", ")).unwrap();
+        }
+        if !predicates.is_empty() {
+            write!(out, " where {}", predicates.join(", ")).unwrap();
+        }
+        out
+    }
+
+    /// Returns the auto traits implemented by an ADT, each with the bounds its generic parameters
+    /// need for it.
+    ///
+    /// The auto traits are evaluated without the where clauses of the ADT, so that the bounds
+    /// its fields put on the generic parameters are left over rather than assumed. Those that are
+    /// where clauses of the ADT hold for every instantiation of it and are not listed. An ADT
+    /// that needs bounds on anything but its generic parameters does not implement the trait.
+    fn auto_traits(&self, def_id: DefId) -> BTreeMap<&'static str, Vec<String>> {
+        let tcx = self.tcx;
+        let auto_traits = [
+            ("Send", tcx.get_diagnostic_item(sym::Send)),
+            ("Sync", tcx.lang_items().sync_trait()),
+            ("Unpin", tcx.lang_items().unpin_trait()),
+            ("UnwindSafe", tcx.get_diagnostic_item(sym::unwind_safe_trait)),
+            ("RefUnwindSafe", tcx.get_diagnostic_item(sym::ref_unwind_safe_trait)),
+        ];
+        let ty = tcx.type_of(def_id).instantiate_identity();
+        let where_clauses: Vec<_> = tcx
+            .predicates_of(def_id)
+            .instantiate_identity(tcx)
+            .predicates
+            .into_iter()
+            .map(|clause| clause.as_predicate())
+            .collect();
+        auto_traits
+            .into_iter()
+            .filter_map(|(name, trait_def_id)| {
+                let trait_def_id = trait_def_id?;
+                let infcx = tcx.infer_ctxt().build(TypingMode::non_body_analysis());
+                let ocx = ObligationCtxt::new_with_diagnostics(&infcx);
+                let cause = ObligationCause::dummy();
+                ocx.register_bound(cause, ty::ParamEnv::empty(), ty, trait_def_id);
+                let mut bounds = Vec::new();
+                for error in ocx.select_all_or_error() {
+                    let predicate = infcx.resolve_vars_if_possible(error.obligation.predicate);
+                    if where_clauses.contains(&predicate) {
+                        continue;
+                    }
+                    let on_param = predicate.as_trait_clause().is_some_and(|predicate| {
+                        let self_ty = predicate.self_ty().skip_binder();
+                        matches!(self_ty.kind(), ty::Param(_) | ty::Alias(..))
+                    });
+                    if !on_param {
+                        return None;
+                    }
+                    bounds.push(predicate.to_string());
+                }
+                bounds.sort();
+                bounds.dedup();
+                Some((name, bounds))
+            })
+            .collect()
+    }
+}
+
+fn diff(old: &BTreeMap<String, ApiItem>, new: &BTreeMap<String, ApiItem>) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut change = |severity, path: &str, description: String| {
+        changes.push(Change { severity, path: path.to_string(), description })
+    };
+
+    for (path, old_item) in old {
+        let Some(new_item) = new.get(path) else {
+            change(Severity::Major, path, format!("{} removed", old_item.kind));
+            continue;
+        };
+        if old_item.kind != new_item.kind {
+            change(
+                Severity::Major,
+                path,
+                format!("changed from {} to {}", old_item.kind, new_item.kind),
+            );
+            continue;
+        }
+        if old_item.signature != new_item.signature {
+            change(
+                Severity::Major,
+                path,
+                format!(
+                    "{} changed from `{}` to `{}`",
+                    old_item.kind, old_item.signature, new_item.signature
+                ),
+            );
+        }
+        match (old_item.non_exhaustive, new_item.non_exhaustive) {
+            (false, true) => change(Severity::Major, path, "became `#[non_exhaustive]`".into()),
+            (true, false) => {
+                change(Severity::Minor, path, "is no longer `#[non_exhaustive]`".into())
+            }
+            _ => {}
+        }
+        match (old_item.private_fields, new_item.private_fields) {
+            (false, true) => change(Severity::Major, path, "gained private fields".into()),
+            (true, false) => change(Severity::Minor, path, "no longer has private fields".into()),
+            _ => {}
+        }
+        if !old_item.required && new_item.required {
+            change(Severity::Major, path, "no longer has a default".into());
+        }
+        for (auto_trait, old_bounds) in &old_item.auto_traits {
+            let Some(new_bounds) = new_item.auto_traits.get(auto_trait) else {
+                change(Severity::Major, path, format!("no longer implements `{auto_trait}`"));
+                continue;
+            };
+            if old_bounds != new_bounds {
+                // Only additional bounds exclude instantiations that implemented the trait.
+                let severity = if new_bounds.iter().all(|bound| old_bounds.contains(bound)) {
+                    Severity::Minor
+                } else {
+                    Severity::Major
+                };
+                let description = format!(
+                    "implements `{auto_trait}` {} instead of {}",
+                    describe_bounds(new_bounds),
+                    describe_bounds(old_bounds)
+                );
+                change(severity, path, description);
+            }
+        }
+        for (auto_trait, new_bounds) in &new_item.auto_traits {
+            if !old_item.auto_traits.contains_key(auto_trait) {
+                let bounds = describe_bounds(new_bounds);
+                change(Severity::Minor, path, format!("now implements `{auto_trait}` {bounds}"));
+            }
+        }
+    }
+
+    for (path, new_item) in new {
+        if old.contains_key(path) {
+            continue;
+        }
+        // Additions are only breaking if dependents could previously rely on the parent being
+        // complete: exhaustive matches on enums, struct expressions and trait impls.
+        let parent = path.rsplit_once("::").and_then(|(parent, _)| old.get(parent));
+        let breaks_parent = parent.is_some_and(|parent| match new_item.kind {
+            "field" => !parent.non_exhaustive && !parent.private_fields,
+            "variant" => !parent.non_exhaustive,
+            _ => parent.kind == "trait" && new_item.required,
+        });
+        let severity = if breaks_parent { Severity::Major } else { Severity::Minor };
+        change(severity, path, format!("{} added", new_item.kind));
+    }
+
+    changes.sort_by(|a, b| (a.severity, &a.path).cmp(&(b.severity, &b.path)));
+    changes
+}
+
+/// Describes the bounds under which an auto trait is implemented.
+fn describe_bounds(bounds: &[String]) -> String {
+    if bounds.is_empty() {
+        "unconditionally".to_string()
+    } else {
+        format!("where `{}`", bounds.join(", "))
+    }
+}
+
+/// Finds the crate that was loaded for the `--extern` entry called `name`.
+fn extern_crate_num(tcx: TyCtxt<'_>, name: &str) -> Option<CrateNum> {
+    let entry = tcx.sess.opts.externs.get(name)?;
+    let ExternLocation::ExactPaths(paths) = &entry.location else { return None };
+    tcx.crates(()).iter().copied().find(|&cnum| {
+        tcx.used_crate_source(cnum).paths().any(|source| {
+            let canonical = std::fs::canonicalize(source).ok();
+            paths.iter().any(|path| {
+                path.original() == source || Some(path.canonicalized()) == canonical.as_ref()
+            })
+        })
+    })
+}
+
+pub(crate) fn print(tcx: TyCtxt<'_>) {
+    let Some((old_name, new_name)) = &tcx.sess.opts.unstable_opts.api_diff else { return };
+    let (Some(old_cnum), Some(new_cnum)) =
+        (extern_crate_num(tcx, old_name), extern_crate_num(tcx, new_name))
+    else {
+        for name in [old_name, new_name] {
+            if extern_crate_num(tcx, name).is_none() {
+                tcx.dcx().emit_err(ApiDiffCrateNotLoaded { name });
+            }
+        }
+        return;
+    };
+
+    let changes = with_no_trimmed_paths!(with_no_visible_paths!({
+        let old = ApiCollector::collect(tcx, old_cnum);
+        let new = ApiCollector::collect(tcx, new_cnum);
+        diff(&old, &new)
+    }));
+
+    let mut out = String::new();
+    for change in &changes {
+        writeln!(out, "{}: `{}`: {}", change.severity.as_str(), change.path, change.description)
+            .unwrap();
+    }
+    let major = changes.iter().filter(|change| change.severity == Severity::Major).count();
+    let minor = changes.len() - major;
+    let bump = if major > 0 {
+        "major"
+    } else if minor > 0 {
+        "minor"
+    } else {
+        "patch"
+    };
+    writeln!(out, "{major} major and {minor} minor changes; a {bump} version bump is required")
+        .unwrap();
+    tcx.sess.io.output_file.as_ref().unwrap_or(&OutFileName::Stdout).overwrite(&out, tcx.sess);
+}
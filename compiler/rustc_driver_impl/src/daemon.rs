@@ -0,0 +1,528 @@
+//! The resident compiler of `-Zdaemon`, and the client side of it in every compiler invocation.
+//!
+//! A compiler started with `-Zdaemon=SOCKET` listens on the Unix socket `SOCKET`. A compiler
+//! invocation that finds `RUSTC_DAEMON=SOCKET` in its environment sends its arguments,
+//! environment and working directory to the daemon along with its standard streams, and exits
+//! with the exit code of the compilation done by the daemon. If the daemon cannot be reached or
+//! refuses the request, the invocation compiles in its own process as usual.
+//!
+//! The daemon runs each request like a separate compiler process would, but the metadata of
+//! loaded crates and the dep graphs of incremental sessions stay in memory between requests, see
+//! `rustc_metadata::enable_metadata_cache` and `rustc_incremental::enable_dep_graph_cache`.
+//!
+//! The environment of the daemon process is never changed. The variables Cargo sets for each
+//! crate are passed to the compilation as its logical environment (`--env-set`), which is what
+//! `env!` and `option_env!` consult, and all other variables of a request must have the same
+//! values as in the daemon, as the compiler reads them from its process. Requests that differ are
+//! refused and compiled by the client itself.
+//!
+//! This includes the variables naming a jobserver, such as `CARGO_MAKEFLAGS`, as the jobserver
+//! client of the process is created once from the environment of the daemon and cannot be
+//! replaced. A daemon only serves the requests of the build that started it, or of builds without
+//! a jobserver if it was started outside of one.
+//!
+//! The path of ICE report files is that of the daemon process, not of the requests, and the
+//! daemon stops serving after an ICE, as the state of the process cannot be trusted anymore.
+//!
+//! Requests are compiled one at a time, since the working directory and standard streams of the
+//! process are set for each of them.
+//!
+//! The socket is only accessible to the user running the daemon, and the daemon additionally
+//! refuses connections from processes of other users.
+//!
+//! A request is a message with the standard streams of the client attached as `SCM_RIGHTS`,
+//! made of the length of the rest of the request as a `u64`, followed by the arguments, the
+//! environment variables and the working directory. The arguments and the environment are
+//! encoded as a `u64` count followed by the strings, and strings as a `u64` length followed by
+//! their bytes. The response is a status byte, followed by the exit code as an `i32` if the
+//! request was compiled.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use rustc_session::EarlyDiagCtxt;
+
+/// Sends the work of this compiler invocation to the daemon named by `RUSTC_DAEMON`, if any, and
+/// exits with the exit code of the compilation if the daemon did it.
+pub(crate) fn forward_if_requested() {
+    #[cfg(unix)]
+    if !is_serving()
+        && let Some(socket) = std::env::var_os(unix::CLIENT_ENV)
+        && let Ok(Some(exit_code)) = unix::forward(Path::new(&socket))
+    {
+        std::process::exit(exit_code);
+    }
+}
+
+/// Whether this process is a daemon serving requests.
+pub(crate) fn is_serving() -> bool {
+    #[cfg(unix)]
+    return unix::SERVING.load(std::sync::atomic::Ordering::Relaxed);
+    #[cfg(not(unix))]
+    false
+}
+
+/// Serves the requests sent to `socket` until the daemon is out of date or fails.
+pub(crate) fn serve(
+    early_dcx: &EarlyDiagCtxt,
+    socket: &Path,
+    using_internal_features: Arc<AtomicBool>,
+) {
+    #[cfg(unix)]
+    unix::serve(early_dcx, socket, using_internal_features);
+    #[cfg(not(unix))]
+    {
+        let _ = (socket, using_internal_features);
+        early_dcx.early_fatal("`-Zdaemon` is only supported on Unix");
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::ffi::{OsStr, OsString};
+    use std::io::{self, Read, Write};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::panic::{self, AssertUnwindSafe};
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::SystemTime;
+    use std::{env, fs, mem, ptr};
+
+    use rustc_session::EarlyDiagCtxt;
+
+    use crate::{Callbacks, RunCompiler, TimePassesCallbacks, catch_with_exit_code};
+
+    /// The environment variable that makes compiler invocations send their work to a daemon.
+    pub(super) const CLIENT_ENV: &str = "RUSTC_DAEMON";
+
+    /// The request was compiled, and the exit code follows.
+    const STATUS_COMPILED: u8 = 0;
+    /// The daemon is out of date, so the client has to compile the request itself.
+    const STATUS_STALE: u8 = 1;
+    /// The daemon cannot compile this request, so the client has to compile it itself.
+    const STATUS_REFUSED: u8 = 2;
+
+    struct Request {
+        args: Vec<OsString>,
+        env: Vec<(OsString, OsString)>,
+        cwd: OsString,
+    }
+
+    /// Whether this process is serving requests, which must not start daemons themselves nor be
+    /// forwarded to a daemon again.
+    pub(super) static SERVING: AtomicBool = AtomicBool::new(false);
+
+    /// The exit code of a compiler process that panicked.
+    const ICE_EXIT_CODE: i32 = 101;
+
+    pub(super) fn forward(socket: &Path) -> io::Result<Option<i32>> {
+        let mut stream = UnixStream::connect(socket)?;
+        let request = Request {
+            args: env::args_os().collect(),
+            env: env::vars_os().filter(|(key, _)| key != CLIENT_ENV).collect(),
+            cwd: env::current_dir()?.into_os_string(),
+        };
+
+        let mut payload = Vec::new();
+        write_strings(&mut payload, &request.args);
+        write_u64(&mut payload, request.env.len() as u64);
+        for (key, value) in &request.env {
+            write_bytes(&mut payload, key.as_bytes());
+            write_bytes(&mut payload, value.as_bytes());
+        }
+        write_bytes(&mut payload, request.cwd.as_bytes());
+
+        let stdio = [io::stdin().as_raw_fd(), io::stdout().as_raw_fd(), io::stderr().as_raw_fd()];
+        send_with_fds(&stream, &(payload.len() as u64).to_le_bytes(), &stdio)?;
+        stream.write_all(&payload)?;
+
+        let mut status = [0];
+        stream.read_exact(&mut status)?;
+        if status[0] != STATUS_COMPILED {
+            return Ok(None);
+        }
+        let mut exit_code = [0; 4];
+        stream.read_exact(&mut exit_code)?;
+        Ok(Some(i32::from_le_bytes(exit_code)))
+    }
+
+    pub(super) fn serve(
+        early_dcx: &EarlyDiagCtxt,
+        socket: &Path,
+        using_internal_features: Arc<AtomicBool>,
+    ) {
+        if SERVING.swap(true, Ordering::Relaxed) {
+            early_dcx.early_fatal("`-Zdaemon` cannot be used in a request to a compiler daemon");
+        }
+
+        rustc_metadata::enable_metadata_cache();
+        rustc_incremental::enable_dep_graph_cache();
+
+        // A socket left behind by a previous daemon would make binding fail.
+        let _ = fs::remove_file(socket);
+        let listener = bind_private(socket).unwrap_or_else(|err| {
+            early_dcx.early_fatal(format!("failed to listen on {}: {err}", socket.display()))
+        });
+        let compiler = CompilerStamp::current();
+        let daemon_env: Vec<_> = env::vars_os().filter(|(key, _)| key != CLIENT_ENV).collect();
+        // SAFETY: `geteuid` is always successful.
+        let uid = unsafe { libc::geteuid() };
+
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            match peer_uid(&stream) {
+                Ok(peer) if peer == uid => {}
+                Ok(peer) => {
+                    early_dcx.early_warn(format!("refused a connection from user {peer}"));
+                    continue;
+                }
+                Err(err) => {
+                    early_dcx.early_warn(format!("failed to identify a client: {err}"));
+                    continue;
+                }
+            }
+            // A daemon started from a compiler that was updated or rebuilt since must not
+            // compile with the old one, so let the clients compile with the new one instead.
+            if compiler.is_none() || CompilerStamp::current() != compiler {
+                let _ = stream.write_all(&[STATUS_STALE]);
+                break;
+            }
+            match serve_request(&mut stream, &daemon_env, &using_internal_features) {
+                Ok(ServeResult::Continue) => {}
+                // The state of the process cannot be trusted after an ICE.
+                Ok(ServeResult::Stop) => break,
+                Err(err) => early_dcx.early_warn(format!("failed to serve a request: {err}")),
+            }
+            drop(stream);
+            rustc_incremental::prefetch_dep_graphs();
+        }
+
+        let _ = fs::remove_file(socket);
+    }
+
+    enum ServeResult {
+        Continue,
+        Stop,
+    }
+
+    fn serve_request(
+        stream: &mut UnixStream,
+        daemon_env: &[(OsString, OsString)],
+        using_internal_features: &Arc<AtomicBool>,
+    ) -> io::Result<ServeResult> {
+        let mut len = [0; 8];
+        let fds = recv_with_fds(stream, &mut len)?;
+        let [stdin, stdout, stderr] = <[OwnedFd; 3]>::try_from(fds).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "expected the three standard streams")
+        })?;
+        let mut payload = vec![0; u64::from_le_bytes(len) as usize];
+        stream.read_exact(&mut payload)?;
+        let request = decode_request(&payload)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed request"))?;
+
+        let Some(logical_env) = logical_env(daemon_env, request.env) else {
+            stream.write_all(&[STATUS_REFUSED])?;
+            return Ok(ServeResult::Continue);
+        };
+
+        // Set up the process like a compiler invocation of the client.
+        env::set_current_dir(&request.cwd)?;
+        let redirect = Redirect::new([stdin, stdout, stderr])?;
+
+        let args: Vec<String> = request
+            .args
+            .into_iter()
+            .map(|arg| arg.into_string())
+            .collect::<Result<_, _>>()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "non-UTF-8 argument"))?;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut callbacks = RequestCallbacks { logical_env, inner: Default::default() };
+            catch_with_exit_code(|| {
+                RunCompiler::new(&args, &mut callbacks)
+                    .set_using_internal_features(Arc::clone(using_internal_features))
+                    .run();
+                Ok(())
+            })
+        }));
+        drop(redirect);
+
+        // The panic was reported to the client by the ICE hook already.
+        let (exit_code, serve_result) = match result {
+            Ok(exit_code) => (exit_code, ServeResult::Continue),
+            Err(_) => (ICE_EXIT_CODE, ServeResult::Stop),
+        };
+        stream.write_all(&[STATUS_COMPILED])?;
+        stream.write_all(&exit_code.to_le_bytes())?;
+        Ok(serve_result)
+    }
+
+    /// The environment variables that name the jobserver of a build.
+    const JOBSERVER_VARS: &[&str] = &["CARGO_MAKEFLAGS", "MAKEFLAGS", "MFLAGS"];
+
+    /// Whether Cargo sets the environment variable `key` for each crate it compiles.
+    ///
+    /// Cargo also sets `CARGO_MAKEFLAGS`, but it names the jobserver of the whole build, which
+    /// must be that of the daemon.
+    fn is_per_crate_var(key: &OsStr) -> bool {
+        (key.as_bytes().starts_with(b"CARGO_") && !JOBSERVER_VARS.iter().any(|var| key == *var))
+            || key == "OUT_DIR"
+    }
+
+    /// Returns the variables Cargo set for the crate of a request, or `None` if the other
+    /// variables of the request differ from those of the daemon.
+    fn logical_env(
+        daemon_env: &[(OsString, OsString)],
+        request_env: Vec<(OsString, OsString)>,
+    ) -> Option<Vec<(String, String)>> {
+        let (per_crate, mut shared): (Vec<_>, Vec<_>) = request_env
+            .into_iter()
+            .filter(|(key, _)| key != CLIENT_ENV)
+            .partition(|(key, _)| is_per_crate_var(key));
+        let mut daemon_shared: Vec<_> =
+            daemon_env.iter().filter(|(key, _)| !is_per_crate_var(key)).cloned().collect();
+        shared.sort();
+        daemon_shared.sort();
+        // A variable of the daemon that the request does not override would be visible to
+        // `env!` in place of the unset variable.
+        let overrides_daemon = daemon_env
+            .iter()
+            .filter(|(key, _)| is_per_crate_var(key))
+            .all(|(key, _)| per_crate.iter().any(|(k, _)| k == key));
+        if shared != daemon_shared || !overrides_daemon {
+            return None;
+        }
+        per_crate
+            .into_iter()
+            .map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+            .collect()
+    }
+
+    /// The callbacks of a request, which set its logical environment.
+    struct RequestCallbacks {
+        logical_env: Vec<(String, String)>,
+        inner: TimePassesCallbacks,
+    }
+
+    impl Callbacks for RequestCallbacks {
+        // JUSTIFICATION: the session doesn't exist at this point.
+        #[allow(rustc::bad_opt_access)]
+        fn config(&mut self, config: &mut rustc_interface::interface::Config) {
+            self.inner.config(config);
+            // Variables given with `--env-set` in the request take precedence.
+            for (key, value) in self.logical_env.drain(..) {
+                config.opts.logical_env.entry(key).or_insert(value);
+            }
+        }
+    }
+
+    /// Binds a socket that only the current user can connect to.
+    fn bind_private(socket: &Path) -> io::Result<UnixListener> {
+        let listener = UnixListener::bind(socket)?;
+        fs::set_permissions(socket, fs::Permissions::from_mode(0o600))?;
+        Ok(listener)
+    }
+
+    /// The user of the process at the other end of `stream`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn peer_uid(stream: &UnixStream) -> io::Result<libc::uid_t> {
+        // SAFETY: `cred` is large enough for the option, as `len` tells the kernel.
+        unsafe {
+            let mut cred: libc::ucred = mem::zeroed();
+            let mut len = mem::size_of::<libc::ucred>() as libc::socklen_t;
+            let ret = libc::getsockopt(
+                stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                ptr::addr_of_mut!(cred).cast(),
+                &mut len,
+            );
+            if ret < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(cred.uid)
+        }
+    }
+
+    /// The user of the process at the other end of `stream`.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn peer_uid(stream: &UnixStream) -> io::Result<libc::uid_t> {
+        let (mut uid, mut gid) = (0, 0);
+        // SAFETY: `getpeereid` only writes to the two integers.
+        if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(uid)
+    }
+
+    /// Makes the standard streams of this process those of the client until dropped.
+    struct Redirect {
+        saved: [OwnedFd; 3],
+    }
+
+    impl Redirect {
+        fn new(streams: [OwnedFd; 3]) -> io::Result<Redirect> {
+            let saved = [0, 1, 2].map(|fd| unsafe { libc::dup(fd) });
+            if saved.iter().any(|&fd| fd < 0) {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: The file descriptors were just created by `dup`.
+            let saved = saved.map(|fd| unsafe { OwnedFd::from_raw_fd(fd) });
+            for (target, stream) in streams.iter().enumerate() {
+                // SAFETY: Replacing a standard stream does not affect memory safety.
+                if unsafe { libc::dup2(stream.as_raw_fd(), target as RawFd) } < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(Redirect { saved })
+        }
+    }
+
+    impl Drop for Redirect {
+        fn drop(&mut self) {
+            let _ = io::stdout().flush();
+            let _ = io::stderr().flush();
+            for (target, saved) in self.saved.iter().enumerate() {
+                // SAFETY: Replacing a standard stream does not affect memory safety.
+                unsafe { libc::dup2(saved.as_raw_fd(), target as RawFd) };
+            }
+        }
+    }
+
+    /// What identifies the version of the running compiler.
+    #[derive(PartialEq)]
+    struct CompilerStamp {
+        path: PathBuf,
+        len: u64,
+        modified: SystemTime,
+    }
+
+    impl CompilerStamp {
+        fn current() -> Option<CompilerStamp> {
+            let path = env::current_exe().ok()?;
+            let metadata = fs::metadata(&path).ok()?;
+            Some(CompilerStamp { len: metadata.len(), modified: metadata.modified().ok()?, path })
+        }
+    }
+
+    fn decode_request(mut payload: &[u8]) -> Option<Request> {
+        let args = read_strings(&mut payload)?;
+        let env_len = read_u64(&mut payload)?;
+        let env = (0..env_len)
+            .map(|_| Some((read_string(&mut payload)?, read_string(&mut payload)?)))
+            .collect::<Option<_>>()?;
+        let cwd = read_string(&mut payload)?;
+        payload.is_empty().then_some(Request { args, env, cwd })
+    }
+
+    fn write_u64(buf: &mut Vec<u8>, value: u64) {
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+        write_u64(buf, bytes.len() as u64);
+        buf.extend_from_slice(bytes);
+    }
+
+    fn write_strings(buf: &mut Vec<u8>, strings: &[OsString]) {
+        write_u64(buf, strings.len() as u64);
+        for string in strings {
+            write_bytes(buf, string.as_bytes());
+        }
+    }
+
+    fn read_u64(payload: &mut &[u8]) -> Option<u64> {
+        let (value, rest) = payload.split_first_chunk()?;
+        *payload = rest;
+        Some(u64::from_le_bytes(*value))
+    }
+
+    fn read_string(payload: &mut &[u8]) -> Option<OsString> {
+        let len = usize::try_from(read_u64(payload)?).ok()?;
+        let (bytes, rest) = payload.split_at_checked(len)?;
+        *payload = rest;
+        Some(OsString::from_vec(bytes.to_vec()))
+    }
+
+    fn read_strings(payload: &mut &[u8]) -> Option<Vec<OsString>> {
+        let len = read_u64(payload)?;
+        (0..len).map(|_| read_string(payload)).collect()
+    }
+
+    const MAX_FDS: usize = 3;
+
+    /// Sends `bytes` with the file descriptors `fds` attached.
+    fn send_with_fds(mut stream: &UnixStream, bytes: &[u8], fds: &[RawFd]) -> io::Result<()> {
+        assert!(fds.len() <= MAX_FDS);
+        let fds_size = mem::size_of_val(fds) as u32;
+        let mut control = [0u8; 64];
+        // SAFETY: The control buffer is large enough for `MAX_FDS` file descriptors, and the
+        // message only points to buffers that outlive the call.
+        unsafe {
+            assert!(libc::CMSG_SPACE(fds_size) as usize <= control.len());
+            let mut iov = libc::iovec { iov_base: bytes.as_ptr() as *mut _, iov_len: bytes.len() };
+            let mut msg: libc::msghdr = mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr().cast();
+            msg.msg_controllen = libc::CMSG_SPACE(fds_size) as _;
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(fds_size) as _;
+            ptr::copy_nonoverlapping(fds.as_ptr(), libc::CMSG_DATA(cmsg).cast(), fds.len());
+
+            let sent = libc::sendmsg(stream.as_raw_fd(), &msg, 0);
+            if sent < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // The file descriptors are only attached to the first part of the message.
+            stream.write_all(&bytes[sent as usize..])
+        }
+    }
+
+    /// Fills `buf`, returning the file descriptors attached to it.
+    fn recv_with_fds(mut stream: &UnixStream, buf: &mut [u8]) -> io::Result<Vec<OwnedFd>> {
+        let mut control = [0u8; 64];
+        let mut fds = Vec::new();
+        // SAFETY: The message only points to buffers that outlive the call, and the kernel only
+        // writes as much control data as they can hold.
+        let received = unsafe {
+            let mut iov = libc::iovec { iov_base: buf.as_mut_ptr().cast(), iov_len: buf.len() };
+            let mut msg: libc::msghdr = mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr().cast();
+            msg.msg_controllen = control.len() as _;
+            let received = libc::recvmsg(stream.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC);
+            if received < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                    let data = libc::CMSG_DATA(cmsg).cast::<RawFd>();
+                    let len = ((*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize)
+                        / mem::size_of::<RawFd>();
+                    for i in 0..len {
+                        fds.push(OwnedFd::from_raw_fd(data.add(i).read_unaligned()));
+                    }
+                }
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
+            received as usize
+        };
+        if received == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        stream.read_exact(&mut buf[received..])?;
+        Ok(fds)
+    }
+}
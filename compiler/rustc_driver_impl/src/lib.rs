@@ -34,7 +34,7 @@
 use std::{env, str};
 
 use rustc_ast as ast;
-use rustc_codegen_ssa::back::apple;
+use rustc_codegen_ssa::back::{apple, symbol_export};
 use rustc_codegen_ssa::traits::CodegenBackend;
 use rustc_codegen_ssa::{CodegenErrors, CodegenResults};
 use rustc_data_structures::profiling::{
@@ -52,13 +52,14 @@
 use rustc_middle::ty::TyCtxt;
 use rustc_parse::{new_parser_from_file, new_parser_from_source_str, unwrap_or_emit_fatal};
 use rustc_session::config::{
-    CG_OPTIONS, ErrorOutputType, Input, OutFileName, OutputType, UnstableOptions, Z_OPTIONS,
-    nightly_options,
+    CG_OPTIONS, ErrorOutputType, Input, OutFileName, OutputType, PrintKind, StableMirExportFormat,
+    UnstableOptions, Z_OPTIONS, nightly_options,
 };
 use rustc_session::getopts::{self, Matches};
 use rustc_session::lint::{Lint, LintId};
 use rustc_session::output::collect_crate_types;
 use rustc_session::{EarlyDiagCtxt, Session, config, filesearch};
+use rustc_smir::rustc_internal::export;
 use rustc_span::FileName;
 use rustc_span::source_map::FileLoader;
 use rustc_target::json::ToJson;
@@ -85,10 +86,14 @@
 #[allow(unused_imports)]
 use {do_not_use_print as print, do_not_use_print as println};
 
+mod api_diff;
 pub mod args;
+mod daemon;
 pub mod pretty;
 #[macro_use]
 mod print;
+mod proc_macro_helper;
+mod reproducibility;
 mod session_diagnostics;
 #[cfg(all(unix, any(target_env = "gnu", target_os = "macos")))]
 mod signal_handler;
@@ -102,7 +107,8 @@ pub(super) fn install() {}
 
 use crate::session_diagnostics::{
     RLinkEmptyVersionNumber, RLinkEncodingVersionMismatch, RLinkRustcVersionMismatch,
-    RLinkWrongFileType, RlinkCorruptFile, RlinkNotAFile, RlinkUnableToRead, UnstableFeatureUsage,
+    RLinkWrongFileType, RlinkCorruptFile, RlinkNotAFile, RlinkUnableToRead, StableMirExport,
+    UnstableFeatureUsage,
 };
 
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
@@ -286,6 +292,8 @@ fn run_compiler(
     >,
     using_internal_features: Arc<std::sync::atomic::AtomicBool>,
 ) {
+    daemon::forward_if_requested();
+
     let mut default_early_dcx = EarlyDiagCtxt::new(ErrorOutputType::default());
 
     // Throw away the first argument, the name of the binary.
@@ -296,6 +304,7 @@ fn run_compiler(
     // This situation was possible before due to arg_expand_all being
     // called before removing the argument, enabling a crash by calling
     // the compiler with @empty_file as argv[0] and no more arguments.
+    let compiler_args = at_args;
     let at_args = at_args.get(1..).unwrap_or_default();
 
     let args = args::arg_expand_all(&default_early_dcx, at_args);
@@ -305,14 +314,37 @@ fn run_compiler(
     };
 
     let sopts = config::build_session_options(&mut default_early_dcx, &matches);
-    // fully initialize ice path static once unstable options are available as context
-    let ice_file = ice_path_with_config(Some(&sopts.unstable_opts)).clone();
+
+    // fully initialize ice path static once unstable options are available as context, which
+    // the daemon does for all of its requests
+    let ice_file = if daemon::is_serving() {
+        ice_path().clone()
+    } else {
+        ice_path_with_config(Some(&sopts.unstable_opts)).clone()
+    };
+
+    if let Some(ref socket) = sopts.unstable_opts.daemon {
+        daemon::serve(&default_early_dcx, socket, using_internal_features);
+        return;
+    }
 
     if let Some(ref code) = matches.opt_str("explain") {
         handle_explain(&default_early_dcx, diagnostics_registry(), code, sopts.color);
         return;
     }
 
+    if sopts.unstable_opts.verify_reproducibility && !reproducibility::is_verifying() {
+        reproducibility::verify(
+            &default_early_dcx,
+            compiler_args,
+            callbacks,
+            file_loader,
+            make_codegen_backend,
+            using_internal_features,
+        );
+        return;
+    }
+
     let (odir, ofile) = make_output(&matches);
     let mut config = interface::Config {
         opts: sopts,
@@ -427,6 +459,11 @@ fn run_compiler(
                 dump_feature_usage_metrics(tcx, metrics_dir);
             }
 
+            if sess.opts.unstable_opts.api_diff.is_some() {
+                api_diff::print(tcx);
+                return early_exit();
+            }
+
             if callbacks.after_expansion(compiler, tcx) == Compilation::Stop {
                 return early_exit();
             }
@@ -445,10 +482,20 @@ fn run_compiler(
 
             tcx.ensure().analysis(());
 
+            if let Some(format) = sess.opts.unstable_opts.export_stable_mir
+                && sess.dcx().has_errors().is_none()
+            {
+                export_stable_mir(tcx, format);
+            }
+
             if callbacks.after_analysis(compiler, tcx) == Compilation::Stop {
                 return early_exit();
             }
 
+            if sess.dcx().has_errors().is_none() {
+                print_exported_symbols(tcx);
+            }
+
             Some(Linker::codegen_and_build_linker(tcx, &*compiler.codegen_backend))
         });
 
@@ -460,6 +507,16 @@ fn run_compiler(
     })
 }
 
+/// Prints the symbols requested with `--print exported-symbols[-json]`, which needs the analysis
+/// of the crate and is therefore not handled by `print_crate_info`.
+fn print_exported_symbols(tcx: TyCtxt<'_>) {
+    for print in &tcx.sess.opts.prints {
+        if let PrintKind::ExportedSymbols | PrintKind::ExportedSymbolsJson = print.kind {
+            symbol_export::print_exported_symbols(tcx, print);
+        }
+    }
+}
+
 fn dump_feature_usage_metrics(tcxt: TyCtxt<'_>, metrics_dir: &Path) {
     let output_filenames = tcxt.output_filenames(());
     let mut metrics_file_name = std::ffi::OsString::from("unstable_feature_usage_metrics-");
@@ -477,6 +534,15 @@ fn dump_feature_usage_metrics(tcxt: TyCtxt<'_>, metrics_dir: &Path) {
     }
 }
 
+fn export_stable_mir(tcx: TyCtxt<'_>, format: StableMirExportFormat) {
+    let path = tcx.output_filenames(()).with_extension(format.extension());
+    let result = File::create(&path)
+        .and_then(|file| export::write_crate_mir(tcx, format, io::BufWriter::new(file)));
+    if let Err(error) = result {
+        tcx.dcx().emit_err(StableMirExport { path, error });
+    }
+}
+
 // Extract output directory and file from matches.
 fn make_output(matches: &getopts::Matches) -> (Option<PathBuf>, Option<OutFileName>) {
     let odir = matches.opt_str("out-dir").map(|o| PathBuf::from(&o));
@@ -693,9 +759,11 @@ fn print_crate_info(
     #[allow(unused_imports)]
     use {do_not_use_safe_print as safe_print, do_not_use_safe_print as safe_println};
 
-    // NativeStaticLibs and LinkArgs are special - printed during linking
-    // (empty iterator returns true)
-    if sess.opts.prints.iter().all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs) {
+    // NativeStaticLibs, LinkArgs and ExportedSymbols are special - printed after analysis
+    // and during linking (empty iterator returns true)
+    if sess.opts.prints.iter().all(|p| {
+        matches!(p.kind, NativeStaticLibs | LinkArgs | ExportedSymbols | ExportedSymbolsJson)
+    }) {
         return Compilation::Continue;
     }
 
@@ -730,6 +798,10 @@ fn print_crate_info(
             TargetSpec => {
                 println_info!("{}", serde_json::to_string_pretty(&sess.target.to_json()).unwrap());
             }
+            TargetSpecJsonSchema => {
+                let schema = Target::json_schema();
+                println_info!("{}", serde_json::to_string_pretty(&schema).unwrap());
+            }
             AllTargetSpecs => {
                 let mut targets = BTreeMap::new();
                 for name in rustc_target::spec::TARGETS {
@@ -840,6 +912,7 @@ fn print_crate_info(
             // Any output here interferes with Cargo's parsing of other printed output
             NativeStaticLibs => {}
             LinkArgs => {}
+            ExportedSymbols | ExportedSymbolsJson => {}
             SplitDebuginfo => {
                 use rustc_target::spec::SplitDebuginfo::{Off, Packed, Unpacked};
 
@@ -1581,6 +1654,8 @@ pub fn install_ctrlc_handler() {
 }
 
 pub fn main() -> ! {
+    proc_macro_helper::run_if_requested();
+
     let start_time = Instant::now();
     let start_rss = get_resident_set_size();
 
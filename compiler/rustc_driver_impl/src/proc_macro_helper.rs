@@ -0,0 +1,50 @@
+//! The helper process of `-Zproc-macro-execution-strategy=out-of-process`.
+
+use std::{env, io, process};
+
+use rustc_expand::proc_macro_process::{HELPER_ENV, ProcMacroDecls, run_helper};
+use rustc_metadata::{DylibError, load_symbol_from_dylib};
+
+/// Runs the proc-macro helper instead of the compiler if this process was started as one.
+///
+/// Only the `rustc` binary is started as a helper, so this is called from [`main`](crate::main)
+/// rather than for every compiler session, which tools embedding the driver also run.
+pub(crate) fn run_if_requested() {
+    if env::var_os(HELPER_ENV).is_none() {
+        return;
+    }
+    // Processes started by the macros must not become helpers themselves.
+    env::remove_var(HELPER_ENV);
+
+    let result = run_helper(io::stdin(), protocol_output(), |path, decls_symbol| {
+        match unsafe { load_symbol_from_dylib::<*const ProcMacroDecls>(path, decls_symbol) } {
+            Ok(decls) => Ok(unsafe { *decls }),
+            Err(DylibError::DlOpen(path, err) | DylibError::DlSym(path, err)) => {
+                Err(format!("{path}{err}"))
+            }
+        }
+    });
+    process::exit(if result.is_ok() { 0 } else { 1 })
+}
+
+/// Takes over stdout for the messages to the compiler, so that anything the macros print to
+/// stdout goes to stderr instead of corrupting them.
+#[cfg(unix)]
+fn protocol_output() -> std::fs::File {
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: Duplicating the standard streams does not affect memory safety, and the new file
+    // descriptor is owned by the returned file.
+    unsafe {
+        let fd = libc::dup(libc::STDOUT_FILENO);
+        assert!(fd >= 0, "failed to duplicate stdout: {}", io::Error::last_os_error());
+        libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO);
+        std::fs::File::from_raw_fd(fd)
+    }
+}
+
+/// Macros printing to stdout corrupt the messages to the compiler on these platforms.
+#[cfg(not(unix))]
+fn protocol_output() -> io::Stdout {
+    io::stdout()
+}
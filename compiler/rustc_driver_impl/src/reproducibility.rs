@@ -0,0 +1,638 @@
+//! The reproducible build verification of `-Zverify-reproducibility`.
+//!
+//! The crate is compiled a second time in the same process, with conditions that must not affect
+//! the outputs changed: the working directory recorded by the compiler, the output directory, the
+//! directory of temporary files and the number of threads are different. The process keeps its
+//! working directory, so that the relative paths of the command line still resolve to the same
+//! files. The outputs of both compilations are then compared. Archives are compared member by
+//! member and object files section by section, the differences in crate metadata are attributed
+//! to the table entry and `DefPath` they are in, and the differences in text files to a line.
+//!
+//! When the part of an output which differs contains the perturbed paths, they are pointed out,
+//! along with whether the working directory is remapped with `--remap-path-prefix`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{env, fs, io, process, str, thread};
+
+use object::read::archive::ArchiveFile;
+use object::{Object, ObjectSection};
+use rustc_ast as ast;
+use rustc_codegen_ssa::traits::CodegenBackend;
+use rustc_data_structures::sync::Lrc;
+use rustc_errors::{ErrorGuaranteed, FatalError};
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_interface::interface;
+use rustc_metadata::{METADATA_HEADER, describe_metadata_position};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::{self, Input, OutFileName, OutputType, OutputTypes};
+use rustc_session::output::out_filename;
+use rustc_session::{EarlyDiagCtxt, lint};
+use rustc_span::RealFileName;
+use rustc_span::source_map::FileLoader;
+
+use crate::{Callbacks, Compilation, catch_fatal_errors, run_compiler};
+
+/// Whether the crate is being compiled for the verification, which must not start another one.
+static VERIFYING: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn is_verifying() -> bool {
+    VERIFYING.load(Ordering::Relaxed)
+}
+
+/// Resets `VERIFYING` when the verification ends, even by a fatal error.
+struct Verifying;
+
+impl Drop for Verifying {
+    fn drop(&mut self) {
+        VERIFYING.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Compiles the crate of `at_args` twice and reports where the outputs differ.
+pub(crate) fn verify(
+    early_dcx: &EarlyDiagCtxt,
+    at_args: &[String],
+    callbacks: &mut (dyn Callbacks + Send),
+    file_loader: Option<Box<dyn FileLoader + Send + Sync>>,
+    make_codegen_backend: Option<
+        Box<dyn FnOnce(&config::Options) -> Box<dyn CodegenBackend> + Send>,
+    >,
+    using_internal_features: Arc<AtomicBool>,
+) {
+    VERIFYING.store(true, Ordering::Relaxed);
+    let _verifying = Verifying;
+
+    // The codegen backend made by the driver can only be made once, and compiling the crate again
+    // with another backend would not tell whether its outputs are reproducible.
+    if make_codegen_backend.is_some() {
+        early_dcx.early_warn(
+            "`-Zverify-reproducibility` is ignored, as the driver provides the codegen backend",
+        );
+        run_compiler(
+            at_args,
+            callbacks,
+            file_loader,
+            make_codegen_backend,
+            using_internal_features,
+        );
+        return;
+    }
+
+    let file_loader = file_loader.map(|file_loader| SharedFileLoader(Arc::from(file_loader)));
+    let mut original = Recorder { callbacks: &mut *callbacks, perturbation: None, outputs: None };
+    run_compiler(
+        at_args,
+        &mut original,
+        file_loader.as_ref().map(SharedFileLoader::boxed),
+        None,
+        using_internal_features.clone(),
+    );
+    // Nothing was compiled, e.g. because only `--print` was requested.
+    let Some(original) = original.outputs.take().filter(|outputs| !outputs.files.is_empty()) else {
+        return;
+    };
+
+    let root = env::temp_dir().join(format!("rustc-verify-reproducibility-{}", process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let perturbation = Perturbation::new(&root, &original);
+    if let Err(err) = fs::create_dir_all(&perturbation.working_dir)
+        .and_then(|()| fs::create_dir_all(&perturbation.out_dir))
+        .and_then(|()| fs::create_dir_all(&perturbation.temps_dir))
+    {
+        early_dcx.early_fatal(format!("failed to create {}: {err}", root.display()));
+    }
+
+    let mut perturbed = Recorder { callbacks, perturbation: Some(&perturbation), outputs: None };
+    let file_loader = file_loader.as_ref().map(SharedFileLoader::boxed);
+    let result = catch_fatal_errors(|| {
+        run_compiler(at_args, &mut perturbed, file_loader, None, using_internal_features)
+    });
+    let guar = match (result, perturbed.outputs) {
+        (Ok(()), Some(perturbed)) => compare(early_dcx, &original, &perturbed),
+        _ => Some(early_dcx.early_err(
+            "the crate failed to compile again with a different working directory, output \
+            directory and number of threads",
+        )),
+    };
+    let _ = fs::remove_dir_all(&root);
+
+    if guar.is_some() {
+        FatalError.raise();
+    }
+}
+
+/// The file loader of the driver, used by both compilations.
+#[derive(Clone)]
+struct SharedFileLoader(Arc<dyn FileLoader + Send + Sync>);
+
+impl SharedFileLoader {
+    fn boxed(&self) -> Box<dyn FileLoader + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+impl FileLoader for SharedFileLoader {
+    fn file_exists(&self, path: &Path) -> bool {
+        self.0.file_exists(path)
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        self.0.read_file(path)
+    }
+
+    fn read_binary_file(&self, path: &Path) -> io::Result<Lrc<[u8]>> {
+        self.0.read_binary_file(path)
+    }
+}
+
+/// The conditions of the second compilation, which must not change its outputs.
+struct Perturbation {
+    input: Input,
+    original_working_dir: PathBuf,
+    working_dir: PathBuf,
+    out_dir: PathBuf,
+    temps_dir: PathBuf,
+    incremental_dir: PathBuf,
+    threads: usize,
+}
+
+impl Perturbation {
+    fn new(root: &Path, original: &Outputs) -> Perturbation {
+        let threads = if original.threads == 1 {
+            thread::available_parallelism().map_or(2, |threads| threads.get().max(2))
+        } else {
+            1
+        };
+        Perturbation {
+            // The standard input was consumed by the first compilation.
+            input: original.input.clone(),
+            original_working_dir: original.working_dir.clone(),
+            working_dir: root.join("cwd"),
+            out_dir: root.join("out"),
+            temps_dir: root.join("temps"),
+            incremental_dir: root.join("incremental"),
+            threads,
+        }
+    }
+
+    fn out_file(&self, file: &OutFileName, output_type: OutputType) -> OutFileName {
+        let file_name = match file {
+            OutFileName::Real(path) => path.file_name().map(PathBuf::from),
+            OutFileName::Stdout => {
+                Some(PathBuf::from(format!("stdout.{}", output_type.extension())))
+            }
+        };
+        OutFileName::Real(self.out_dir.join(file_name.unwrap_or_default()))
+    }
+
+    // JUSTIFICATION: the session doesn't exist at this point.
+    #[allow(rustc::bad_opt_access)]
+    fn apply(&self, config: &mut interface::Config) {
+        config.input = self.input.clone();
+
+        // Paths under the working directory are seen under the new one, remapped like before.
+        let remapped_to_new_working_dir: Vec<_> = config
+            .opts
+            .remap_path_prefix
+            .iter()
+            .filter_map(|(from, to)| {
+                let rest = from.strip_prefix(&self.original_working_dir).ok()?;
+                Some((self.working_dir.join(rest), to.clone()))
+            })
+            .collect();
+        config.opts.remap_path_prefix.extend(remapped_to_new_working_dir);
+        config.opts.working_dir =
+            config.opts.file_path_mapping().to_real_filename(self.working_dir.as_path());
+
+        config.output_dir = Some(self.out_dir.clone());
+        config.output_file =
+            config.output_file.as_ref().map(|file| self.out_file(file, OutputType::Exe));
+        let output_types: Vec<_> = config
+            .opts
+            .output_types
+            .iter()
+            .map(|(&output_type, file)| {
+                (output_type, file.as_ref().map(|file| self.out_file(file, output_type)))
+            })
+            .collect();
+        config.opts.output_types = OutputTypes::new(&output_types);
+        config.opts.unstable_opts.temps_dir = Some(self.temps_dir.to_string_lossy().into_owned());
+        if config.opts.incremental.is_some() {
+            config.opts.incremental = Some(self.incremental_dir.clone());
+        }
+        config.opts.unstable_opts.threads = self.threads;
+
+        // Only the first compilation prints, lints and writes reports.
+        config.opts.prints.clear();
+        config.opts.lint_cap = Some(lint::Level::Allow);
+        let unstable_opts = &mut config.opts.unstable_opts;
+        unstable_opts.api_diff = None;
+        unstable_opts.apply_suggestions = None;
+        unstable_opts.dump_failed_proof_trees = None;
+        unstable_opts.export_stable_mir = None;
+        unstable_opts.link_repro = None;
+        unstable_opts.metrics_dir = None;
+        unstable_opts.mir_remarks = None;
+        unstable_opts.stack_usage_report = None;
+        unstable_opts.symbol_size_report = None;
+    }
+}
+
+/// The conditions of a compilation and the outputs it wrote.
+struct Outputs {
+    input: Input,
+    working_dir: PathBuf,
+    working_dir_remapped: bool,
+    /// The output directory, as given on the command line.
+    out_dir: PathBuf,
+    temps_dir: Option<PathBuf>,
+    threads: usize,
+    /// The outputs, with the crate type or the `--emit` type they were written for.
+    files: Vec<(String, OutputType, PathBuf)>,
+}
+
+impl Outputs {
+    fn file(&self, kind: &str) -> Option<&Path> {
+        self.files.iter().find(|(file_kind, ..)| file_kind == kind).map(|(.., path)| &**path)
+    }
+
+    /// The output directory, as an absolute path.
+    fn absolute_out_dir(&self) -> PathBuf {
+        env::current_dir().unwrap_or_default().join(&self.out_dir)
+    }
+}
+
+/// The callbacks of both compilations, which record their conditions and outputs.
+struct Recorder<'a> {
+    callbacks: &'a mut (dyn Callbacks + Send),
+    perturbation: Option<&'a Perturbation>,
+    outputs: Option<Outputs>,
+}
+
+impl Callbacks for Recorder<'_> {
+    // JUSTIFICATION: the session doesn't exist at this point.
+    #[allow(rustc::bad_opt_access)]
+    fn config(&mut self, config: &mut interface::Config) {
+        self.callbacks.config(config);
+        if let Some(perturbation) = self.perturbation {
+            perturbation.apply(config);
+        }
+
+        let (working_dir, working_dir_remapped) = match &config.opts.working_dir {
+            RealFileName::LocalPath(path) => (path.clone(), false),
+            RealFileName::Remapped { local_path, .. } => {
+                (local_path.clone().unwrap_or_default(), true)
+            }
+        };
+        let out_dir = match (&config.output_file, &config.output_dir) {
+            (Some(OutFileName::Real(file)), _) => file.parent().map(Path::to_path_buf),
+            (_, dir) => dir.clone(),
+        };
+        self.outputs = Some(Outputs {
+            input: config.input.clone(),
+            working_dir,
+            working_dir_remapped,
+            out_dir: out_dir.unwrap_or_default(),
+            temps_dir: config.opts.unstable_opts.temps_dir.as_ref().map(PathBuf::from),
+            threads: config.opts.unstable_opts.threads,
+            files: Vec::new(),
+        });
+    }
+
+    fn after_crate_root_parsing(
+        &mut self,
+        compiler: &interface::Compiler,
+        krate: &ast::Crate,
+    ) -> Compilation {
+        self.callbacks.after_crate_root_parsing(compiler, krate)
+    }
+
+    fn after_expansion<'tcx>(
+        &mut self,
+        compiler: &interface::Compiler,
+        tcx: TyCtxt<'tcx>,
+    ) -> Compilation {
+        if let Some(outputs) = &mut self.outputs {
+            let sess = tcx.sess;
+            let output_filenames = tcx.output_filenames(());
+            for (&output_type, _) in sess.opts.output_types.iter() {
+                if output_type == OutputType::Exe {
+                    for &crate_type in tcx.crate_types() {
+                        let file = out_filename(
+                            sess,
+                            crate_type,
+                            output_filenames,
+                            tcx.crate_name(LOCAL_CRATE),
+                        );
+                        if let OutFileName::Real(path) = file {
+                            outputs.files.push((crate_type.to_string(), output_type, path));
+                        }
+                    }
+                } else if let OutFileName::Real(path) = output_filenames.path(output_type) {
+                    outputs.files.push((output_type.shorthand().to_owned(), output_type, path));
+                }
+            }
+        }
+        self.callbacks.after_expansion(compiler, tcx)
+    }
+
+    fn after_analysis<'tcx>(
+        &mut self,
+        compiler: &interface::Compiler,
+        tcx: TyCtxt<'tcx>,
+    ) -> Compilation {
+        self.callbacks.after_analysis(compiler, tcx)
+    }
+}
+
+/// Reports the differences between the outputs of both compilations.
+fn compare(
+    early_dcx: &EarlyDiagCtxt,
+    original: &Outputs,
+    perturbed: &Outputs,
+) -> Option<ErrorGuaranteed> {
+    let mut guar = None;
+    for (kind, output_type, path) in &original.files {
+        let original_data = fs::read(path);
+        let perturbed_data = perturbed.file(kind).map(fs::read);
+        let (original_data, perturbed_data) = match (original_data, perturbed_data) {
+            (Ok(original_data), Some(Ok(perturbed_data))) => (original_data, perturbed_data),
+            // Not every requested output is written, e.g. object files of several codegen units.
+            (Err(_), None | Some(Err(_))) => continue,
+            _ => {
+                guar = Some(early_dcx.early_err(format!(
+                    "`{}` is not reproducible: it was only written by one of the compilations",
+                    path.display()
+                )));
+                continue;
+            }
+        };
+        let perturbed_data = if *output_type == OutputType::DepInfo {
+            normalize_dep_info(original, perturbed, perturbed_data)
+        } else {
+            perturbed_data
+        };
+        let Some(difference) = diff(&original_data, &perturbed_data) else {
+            continue;
+        };
+
+        guar = Some(early_dcx.early_err(format!(
+            "`{}` is not reproducible: it differs {} when the crate is compiled again",
+            path.display(),
+            difference.location
+        )));
+        if let Some(details) = &difference.details {
+            early_dcx.early_note(details.clone());
+        }
+        report_paths(early_dcx, original, perturbed, &difference);
+    }
+
+    if guar.is_some() {
+        let threads = match perturbed.threads {
+            1 => "1 thread".to_owned(),
+            threads => format!("{threads} threads"),
+        };
+        early_dcx.early_note(format!(
+            "the crate was compiled again with the working directory `{}`, the output directory \
+            `{}` and {threads}",
+            perturbed.working_dir.display(),
+            perturbed.out_dir.display()
+        ));
+    }
+    guar
+}
+
+/// Dep-info files name the outputs of their compilation, so the output directory of the second
+/// compilation is replaced by the one of the first.
+fn normalize_dep_info(original: &Outputs, perturbed: &Outputs, data: Vec<u8>) -> Vec<u8> {
+    let Ok(text) = String::from_utf8(data) else {
+        return Vec::new();
+    };
+    let separator = std::path::MAIN_SEPARATOR_STR;
+    let original_prefix = match original.out_dir.to_str() {
+        Some("") | None => String::new(),
+        Some(dir) => format!("{}{separator}", dir.trim_end_matches(separator)),
+    };
+    let mut text =
+        text.replace(&format!("{}{separator}", perturbed.out_dir.display()), &original_prefix);
+    if let (Some(original_temps), Some(perturbed_temps)) =
+        (&original.temps_dir, &perturbed.temps_dir)
+    {
+        text = text
+            .replace(&perturbed_temps.to_string_lossy()[..], &original_temps.to_string_lossy()[..]);
+    }
+    text.into_bytes()
+}
+
+/// Where the outputs of both compilations differ.
+struct Difference<'a> {
+    /// Like "at offset 0x40 of section `.text` of member `lib.o`".
+    location: String,
+    /// What is at the location, when it is known.
+    details: Option<String>,
+    /// The smallest part of each output which contains the difference and whose boundaries are
+    /// known, like a section or an archive member.
+    original: &'a [u8],
+    perturbed: &'a [u8],
+}
+
+fn first_difference(a: &[u8], b: &[u8]) -> Option<usize> {
+    a.iter()
+        .zip(b)
+        .position(|(a, b)| a != b)
+        .or_else(|| (a.len() != b.len()).then(|| a.len().min(b.len())))
+}
+
+fn diff<'a>(original: &'a [u8], perturbed: &'a [u8]) -> Option<Difference<'a>> {
+    let offset = first_difference(original, perturbed)?;
+
+    if let (Ok(original_archive), Ok(perturbed_archive)) =
+        (ArchiveFile::parse(original), ArchiveFile::parse(perturbed))
+        && let Some(difference) =
+            diff_archives(original, &original_archive, perturbed, &perturbed_archive)
+    {
+        return Some(difference);
+    }
+    if let (Ok(original_object), Ok(perturbed_object)) =
+        (object::File::parse(original), object::File::parse(perturbed))
+        && let Some(difference) = diff_objects(&original_object, &perturbed_object)
+    {
+        return Some(difference);
+    }
+
+    let location = format!("at offset {offset:#x}");
+    let details = if original.starts_with(METADATA_HEADER) {
+        describe_metadata_position(original, offset)
+            .map(|position| format!("the difference is in {position} in the crate metadata"))
+    } else if let (Ok(original_text), Ok(perturbed_text)) =
+        (str::from_utf8(original), str::from_utf8(perturbed))
+    {
+        // The outputs are the same up to `offset`, so their lines start at the same positions.
+        let before = &original[..offset];
+        let start = before.iter().rposition(|&byte| byte == b'\n').map_or(0, |newline| newline + 1);
+        let line_of = |text: &str| text[start..].lines().next().unwrap_or_default().to_owned();
+        Some(format!(
+            "line {} is `{}` in the first compilation and `{}` in the second one",
+            before.iter().filter(|&&byte| byte == b'\n').count() + 1,
+            line_of(original_text),
+            line_of(perturbed_text)
+        ))
+    } else {
+        None
+    };
+    Some(Difference { location, details, original, perturbed })
+}
+
+fn diff_archives<'a>(
+    original_data: &'a [u8],
+    original: &ArchiveFile<'a>,
+    perturbed_data: &'a [u8],
+    perturbed: &ArchiveFile<'a>,
+) -> Option<Difference<'a>> {
+    let members =
+        |archive: &ArchiveFile<'a>, data: &'a [u8]| -> Option<Vec<(&'a [u8], &'a [u8])>> {
+            archive
+                .members()
+                .map(|member| {
+                    let member = member.ok()?;
+                    Some((member.name(), member.data(data).ok()?))
+                })
+                .collect()
+        };
+    let original_members = members(original, original_data)?;
+    let perturbed_members = members(perturbed, perturbed_data)?;
+
+    for (&(original_name, original), &(perturbed_name, perturbed)) in
+        original_members.iter().zip(&perturbed_members)
+    {
+        let name = String::from_utf8_lossy(original_name);
+        if original_name != perturbed_name {
+            return Some(Difference {
+                location: format!(
+                    "in the name of member `{name}`, which is `{}` in the second compilation",
+                    String::from_utf8_lossy(perturbed_name)
+                ),
+                details: None,
+                original,
+                perturbed,
+            });
+        }
+        if let Some(difference) = diff(original, perturbed) {
+            return Some(Difference {
+                location: format!("{} of member `{name}`", difference.location),
+                ..difference
+            });
+        }
+    }
+    if original_members.len() != perturbed_members.len() {
+        return Some(Difference {
+            location: format!(
+                "in its number of members, {} in the first compilation and {} in the second one",
+                original_members.len(),
+                perturbed_members.len()
+            ),
+            details: None,
+            original: original_data,
+            perturbed: perturbed_data,
+        });
+    }
+    None
+}
+
+fn diff_objects<'a>(
+    original: &object::File<'a>,
+    perturbed: &object::File<'a>,
+) -> Option<Difference<'a>> {
+    let sections = |object: &object::File<'a>| -> Vec<(String, &'a [u8])> {
+        object
+            .sections()
+            .map(|section| {
+                let name = section.name().unwrap_or("<unnamed>").to_owned();
+                (name, section.data().unwrap_or_default())
+            })
+            .collect()
+    };
+    let original_sections = sections(original);
+    let perturbed_sections = sections(perturbed);
+
+    for ((name, original), (perturbed_name, perturbed)) in
+        original_sections.into_iter().zip(perturbed_sections)
+    {
+        if name != perturbed_name {
+            return Some(Difference {
+                location: format!(
+                    "in the name of section `{name}`, which is `{perturbed_name}` in the second \
+                    compilation"
+                ),
+                details: None,
+                original,
+                perturbed,
+            });
+        }
+        if let Some(difference) = diff(original, perturbed) {
+            return Some(Difference {
+                location: format!("{} of section `{name}`", difference.location),
+                ..difference
+            });
+        }
+    }
+    // The sections are the same, so the difference is in the headers.
+    None
+}
+
+/// Points out the perturbed paths found in the part of the outputs which differs.
+fn report_paths(
+    early_dcx: &EarlyDiagCtxt,
+    original: &Outputs,
+    perturbed: &Outputs,
+    difference: &Difference<'_>,
+) {
+    let contains = |data: &[u8], path: &Path| {
+        let path = path.to_string_lossy();
+        !path.is_empty() && data.windows(path.len()).any(|window| window == path.as_bytes())
+    };
+    let found = |original_path: &Path, perturbed_path: &Path| {
+        contains(difference.original, original_path)
+            && contains(difference.perturbed, perturbed_path)
+    };
+
+    if found(&original.working_dir, &perturbed.working_dir) {
+        if original.working_dir_remapped {
+            early_dcx.early_note(format!(
+                "it contains the working directory `{}`, even though it is remapped",
+                original.working_dir.display()
+            ));
+        } else {
+            early_dcx.early_note(format!(
+                "it contains the working directory `{}`, which is not remapped",
+                original.working_dir.display()
+            ));
+            early_dcx.early_help(format!(
+                "use `--remap-path-prefix={}=<path>` to replace it by a path which does not \
+                depend on where the crate is built",
+                original.working_dir.display()
+            ));
+        }
+    }
+    let original_out_dir = original.absolute_out_dir();
+    if original_out_dir != original.working_dir
+        && found(&original_out_dir, &perturbed.absolute_out_dir())
+    {
+        early_dcx.early_note(format!(
+            "it contains the output directory `{}`",
+            original_out_dir.display()
+        ));
+    }
+    if let (Some(original_temps), Some(perturbed_temps)) =
+        (&original.temps_dir, &perturbed.temps_dir)
+        && let original_temps = env::current_dir().unwrap_or_default().join(original_temps)
+        && found(&original_temps, perturbed_temps)
+    {
+        early_dcx.early_note(format!(
+            "it contains the directory of temporary files `{}`",
+            original_temps.display()
+        ));
+    }
+}
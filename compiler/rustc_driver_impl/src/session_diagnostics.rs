@@ -40,6 +40,13 @@ pub(crate) struct RlinkCorruptFile<'a> {
     pub file: &'a std::path::Path,
 }
 
+#[derive(Diagnostic)]
+#[diag(driver_impl_api_diff_crate_not_loaded)]
+#[help]
+pub(crate) struct ApiDiffCrateNotLoaded<'a> {
+    pub name: &'a str,
+}
+
 #[derive(Diagnostic)]
 #[diag(driver_impl_ice)]
 pub(crate) struct Ice;
@@ -96,6 +103,13 @@ pub(crate) struct IceFlags {
 #[diag(driver_impl_ice_exclude_cargo_defaults)]
 pub(crate) struct IceExcludeCargoDefaults;
 
+#[derive(Diagnostic)]
+#[diag(driver_impl_stable_mir_export)]
+pub(crate) struct StableMirExport {
+    pub path: std::path::PathBuf,
+    pub error: std::io::Error,
+}
+
 #[derive(Diagnostic)]
 #[diag(driver_impl_unstable_feature_usage)]
 pub(crate) struct UnstableFeatureUsage {
@@ -1,3 +1,21 @@
+errors_apply_suggestions_applied =
+    applied {$count} {$count ->
+        [1] suggestion
+        *[other] suggestions
+    } to `{$path}`
+
+errors_apply_suggestions_failed =
+    failed to apply suggestions to `{$path}`: {$err}
+
+errors_apply_suggestions_file_changed =
+    failed to apply suggestions to `{$path}`: file changed on disk
+
+errors_apply_suggestions_would_apply =
+    would apply {$count} {$count ->
+        [1] suggestion
+        *[other] suggestions
+    } to `{$path}`
+
 errors_delayed_at_with_newline =
     delayed at {$emitted_at}
     {$note}
@@ -0,0 +1,350 @@
+//! Applying machine-applicable suggestions directly from the compiler (`-Zapply-suggestions`).
+//!
+//! Suggestions are collected as diagnostics are emitted. At the end of the session they are
+//! resolved to byte ranges in local source files, overlapping suggestions are dropped in a
+//! deterministic order, and the remaining edits are either written back, emitted as a unified
+//! diff, or only counted.
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_macros::Diagnostic;
+use rustc_span::source_map::SourceMap;
+use rustc_span::{BytePos, SourceFile};
+
+use crate::{Applicability, DiagInner, SubstitutionPart, Suggestions};
+
+#[cfg(test)]
+mod tests;
+
+/// What to do with the machine-applicable suggestions collected during a session.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ApplySuggestionsMode {
+    /// Rewrite the affected source files in place.
+    Write,
+    /// Emit a unified diff of the changes for each affected file.
+    Diff,
+    /// Only report how many suggestions would be applied to each file.
+    DryRun,
+}
+
+#[derive(Diagnostic)]
+#[diag(errors_apply_suggestions_applied)]
+pub(crate) struct Applied<'a> {
+    pub(crate) path: &'a Path,
+    pub(crate) count: usize,
+}
+
+#[derive(Diagnostic)]
+#[diag(errors_apply_suggestions_would_apply)]
+pub(crate) struct WouldApply<'a> {
+    pub(crate) path: &'a Path,
+    pub(crate) count: usize,
+}
+
+#[derive(Diagnostic)]
+#[diag(errors_apply_suggestions_failed)]
+pub(crate) struct FailedToApply<'a> {
+    pub(crate) path: &'a Path,
+    pub(crate) err: io::Error,
+}
+
+#[derive(Diagnostic)]
+#[diag(errors_apply_suggestions_file_changed)]
+pub(crate) struct FileChanged<'a> {
+    pub(crate) path: &'a Path,
+}
+
+/// A replacement of the bytes `lo..hi` of a source file.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) struct Edit {
+    pub(crate) lo: usize,
+    pub(crate) hi: usize,
+    pub(crate) snippet: String,
+}
+
+impl Edit {
+    /// Two edits conflict if they touch the same bytes, or if they insert at the same position,
+    /// in which case the order of the insertions would be ambiguous.
+    fn conflicts_with(&self, other: &Edit) -> bool {
+        (self.lo < other.hi && other.lo < self.hi)
+            || (self.lo == other.lo && (self.lo == self.hi || other.lo == other.hi))
+    }
+}
+
+/// The edits that will be made to a single source file.
+pub(crate) struct FileFix {
+    pub(crate) path: PathBuf,
+    pub(crate) file: Lrc<SourceFile>,
+    /// The normalized source the edit positions refer to.
+    pub(crate) src: Lrc<String>,
+    /// Sorted by position and never conflicting with each other.
+    pub(crate) edits: Vec<Edit>,
+    /// The number of suggestions that contributed edits.
+    pub(crate) suggestions: usize,
+}
+
+pub(crate) struct SuggestionCollector {
+    pub(crate) mode: ApplySuggestionsMode,
+    /// Only suggestions of these lints are collected, or all of them if empty.
+    lints: Vec<String>,
+    suggestions: Vec<Vec<SubstitutionPart>>,
+}
+
+impl SuggestionCollector {
+    pub(crate) fn new(mode: ApplySuggestionsMode, lints: Vec<String>) -> SuggestionCollector {
+        let lints = lints.into_iter().map(|lint| lint.replace('-', "_").to_lowercase()).collect();
+        SuggestionCollector { mode, lints, suggestions: Vec::new() }
+    }
+
+    pub(crate) fn collect(&mut self, diag: &DiagInner) {
+        if !self.lints.is_empty() {
+            let Some(lint) = &diag.is_lint else { return };
+            if !self.lints.contains(&lint.name) {
+                return;
+            }
+        }
+        let suggestions = match &diag.suggestions {
+            Suggestions::Enabled(suggestions) => &suggestions[..],
+            Suggestions::Sealed(suggestions) => &suggestions[..],
+            Suggestions::Disabled => return,
+        };
+        for suggestion in suggestions {
+            if suggestion.applicability != Applicability::MachineApplicable {
+                continue;
+            }
+            // With several alternatives there is no single right answer to apply.
+            let [substitution] = &suggestion.substitutions[..] else { continue };
+            if substitution.parts.iter().any(|part| part.span.from_expansion()) {
+                continue;
+            }
+            self.suggestions.push(substitution.parts.clone());
+        }
+    }
+
+    /// Resolves the collected suggestions to edits of local source files.
+    ///
+    /// Suggestions are considered in order of their position in the source rather than in
+    /// emission order, so the result does not depend on the order in which the compiler
+    /// happened to report them. A suggestion is dropped entirely if any of its parts conflicts
+    /// with a suggestion that was accepted before it.
+    pub(crate) fn resolve(&self, sm: &SourceMap) -> Vec<FileFix> {
+        let mut files: FxIndexMap<PathBuf, Lrc<SourceFile>> = Default::default();
+        let mut suggestions: Vec<Vec<(PathBuf, Edit)>> = self
+            .suggestions
+            .iter()
+            .filter_map(|parts| {
+                parts
+                    .iter()
+                    .map(|part| {
+                        let lo = sm.lookup_byte_offset(part.span.lo());
+                        let hi = sm.lookup_byte_offset(part.span.hi());
+                        if !Lrc::ptr_eq(&lo.sf, &hi.sf) || lo.sf.src.is_none() {
+                            return None;
+                        }
+                        let path = lo.sf.name.clone().into_local_path()?;
+                        files.entry(path.clone()).or_insert_with(|| Lrc::clone(&lo.sf));
+                        let edit = Edit {
+                            lo: lo.pos.0 as usize,
+                            hi: hi.pos.0 as usize,
+                            snippet: part.snippet.clone(),
+                        };
+                        Some((path, edit))
+                    })
+                    .collect::<Option<Vec<_>>>()
+            })
+            .collect();
+        for suggestion in &mut suggestions {
+            suggestion.sort();
+        }
+        suggestions.sort();
+        suggestions.dedup();
+
+        let mut fixes: FxIndexMap<PathBuf, FileFix> = Default::default();
+        for suggestion in suggestions {
+            let conflicts = suggestion.iter().any(|(path, edit)| {
+                fixes.get(path).is_some_and(|fix| fix.edits.iter().any(|e| e.conflicts_with(edit)))
+            });
+            // Parts of a single suggestion may also conflict with each other.
+            let conflicts_with_itself = suggestion.iter().enumerate().any(|(i, (path, edit))| {
+                suggestion[i + 1..].iter().any(|(p, e)| p == path && e.conflicts_with(edit))
+            });
+            if conflicts || conflicts_with_itself {
+                continue;
+            }
+            let mut touched = Vec::new();
+            for (path, edit) in suggestion {
+                let fix = fixes.entry(path.clone()).or_insert_with(|| FileFix {
+                    file: Lrc::clone(&files[&path]),
+                    src: Lrc::clone(files[&path].src.as_ref().unwrap()),
+                    path: path.clone(),
+                    edits: Vec::new(),
+                    suggestions: 0,
+                });
+                fix.edits.push(edit);
+                if !touched.contains(&path) {
+                    touched.push(path);
+                }
+            }
+            for path in touched {
+                fixes[&path].suggestions += 1;
+            }
+        }
+
+        let mut fixes: Vec<_> = fixes.into_values().collect();
+        for fix in &mut fixes {
+            fix.edits.sort();
+        }
+        fixes.sort_by(|a, b| a.path.cmp(&b.path));
+        fixes
+    }
+}
+
+/// Applies sorted, non-conflicting edits to `src`.
+pub(crate) fn apply_edits(src: &str, edits: &[Edit]) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut pos = 0;
+    for edit in edits {
+        out.push_str(&src[pos..edit.lo]);
+        out.push_str(&edit.snippet);
+        pos = edit.hi;
+    }
+    out.push_str(&src[pos..]);
+    out
+}
+
+/// Maps edits of the normalized source of `file` to edits of its `original` contents on disk.
+///
+/// The compiler strips the byte order mark and turns `\r\n` into `\n` when loading a file, so
+/// positions are translated back through the recorded normalizations, and newlines in the
+/// snippets are written as `\r\n` in files that use them. Returns `None` if the file changed on
+/// disk since it was loaded.
+pub(crate) fn original_edits(
+    file: &SourceFile,
+    original: &str,
+    edits: &[Edit],
+) -> Option<Vec<Edit>> {
+    if !file.src_hash.matches(original) {
+        return None;
+    }
+    let crlf = original.contains("\r\n");
+    let original_pos =
+        |pos: usize| file.original_relative_byte_pos(file.start_pos + BytePos::from_usize(pos));
+    let edits = edits
+        .iter()
+        .map(|edit| Edit {
+            lo: original_pos(edit.lo).to_usize(),
+            hi: original_pos(edit.hi).to_usize(),
+            snippet: if crlf {
+                edit.snippet.replace("\r\n", "\n").replace('\n', "\r\n")
+            } else {
+                edit.snippet.clone()
+            },
+        })
+        .collect();
+    Some(edits)
+}
+
+/// The number of unchanged lines shown around each change in a unified diff.
+const CONTEXT_LINES: usize = 3;
+
+/// Renders sorted, non-conflicting edits of `src` as a unified diff.
+///
+/// Hunks are derived from the edits themselves, so only the few lines around each edit are
+/// compared line by line.
+pub(crate) fn unified_diff(path: &str, src: &str, edits: &[Edit]) -> String {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(src.match_indices('\n').map(|(i, _)| i + 1).filter(|&i| i < src.len()))
+        .collect();
+    let line_of = |pos: usize| line_starts.partition_point(|&start| start <= pos).max(1) - 1;
+    let last_line = line_starts.len() - 1;
+
+    // Group the edits whose context lines would touch or overlap into hunks.
+    let mut hunks: Vec<(usize, usize, Vec<&Edit>)> = Vec::new();
+    for edit in edits {
+        let first = line_of(edit.lo);
+        let last = if edit.hi > edit.lo { line_of(edit.hi - 1) } else { first };
+        let start = first.saturating_sub(CONTEXT_LINES);
+        let end = (last + CONTEXT_LINES).min(last_line);
+        match hunks.last_mut() {
+            Some((_, hunk_end, hunk_edits)) if start <= *hunk_end + 1 => {
+                *hunk_end = end.max(*hunk_end);
+                hunk_edits.push(edit);
+            }
+            _ => hunks.push((start, end, vec![edit])),
+        }
+    }
+
+    let mut out = format!("--- a/{path}\n+++ b/{path}\n");
+    let mut line_delta: isize = 0;
+    for (start, end, hunk_edits) in hunks {
+        let base = line_starts[start];
+        let old_end = line_starts.get(end + 1).copied().unwrap_or(src.len());
+        let old = &src[base..old_end];
+        let relative: Vec<Edit> = hunk_edits
+            .iter()
+            .map(|edit| Edit {
+                lo: edit.lo - base,
+                hi: edit.hi - base,
+                snippet: edit.snippet.clone(),
+            })
+            .collect();
+        let new = apply_edits(old, &relative);
+        let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+        let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+
+        let header_start = |start: usize, len: usize| if len == 0 { start } else { start + 1 };
+        let new_start = (start as isize + line_delta) as usize;
+        writeln!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            header_start(start, old_lines.len()),
+            old_lines.len(),
+            header_start(new_start, new_lines.len()),
+            new_lines.len(),
+        )
+        .unwrap();
+        for (prefix, line) in diff_lines(&old_lines, &new_lines) {
+            out.push(prefix);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        line_delta += new_lines.len() as isize - old_lines.len() as isize;
+    }
+    out
+}
+
+/// Computes a line diff of two short sequences from their longest common subsequence.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(char, &'a str)> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
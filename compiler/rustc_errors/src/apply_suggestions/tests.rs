@@ -0,0 +1,69 @@
+use super::*;
+
+fn edit(lo: usize, hi: usize, snippet: &str) -> Edit {
+    Edit { lo, hi, snippet: snippet.to_string() }
+}
+
+#[test]
+fn conflicts() {
+    // Overlapping replacements.
+    assert!(edit(0, 4, "").conflicts_with(&edit(2, 6, "")));
+    // Adjacent replacements and an insertion at the end of a replacement are fine.
+    assert!(!edit(0, 4, "").conflicts_with(&edit(4, 6, "")));
+    assert!(!edit(0, 4, "").conflicts_with(&edit(4, 4, "x")));
+    // Two insertions at the same position have no defined order.
+    assert!(edit(3, 3, "a").conflicts_with(&edit(3, 3, "b")));
+    assert!(edit(3, 3, "a").conflicts_with(&edit(3, 5, "")));
+}
+
+#[test]
+fn apply() {
+    let src = "use std::io::{self, Read};\n";
+    let edits = [edit(13, 14, ""), edit(18, 24, ""), edit(24, 25, "")];
+    assert_eq!(apply_edits(src, &edits), "use std::io::self;\n");
+}
+
+#[test]
+fn diff_single_hunk() {
+    let src = "fn main() {\n    let mut x = 1;\n    println!(\"{x}\");\n}\n";
+    let edits = [edit(20, 24, "")];
+    assert_eq!(
+        unified_diff("main.rs", src, &edits),
+        "--- a/main.rs\n+++ b/main.rs\n@@ -1,4 +1,4 @@\n fn main() {\n-    let mut x = 1;\n+    \
+         let x = 1;\n     println!(\"{x}\");\n }\n"
+    );
+}
+
+#[test]
+fn diff_separate_hunks() {
+    let src: String = (0..20).map(|i| format!("line {i}\n")).collect();
+    let second = src.find("line 2\n").unwrap();
+    let eighteenth = src.find("line 18\n").unwrap();
+    let edits =
+        [edit(second, second + "line 2\n".len(), ""), edit(eighteenth, eighteenth, "inserted\n")];
+    let diff = unified_diff("lines.txt", &src, &edits);
+    assert!(diff.contains("@@ -1,6 +1,5 @@\n line 0\n line 1\n-line 2\n line 3\n"));
+    assert!(diff.contains("@@ -16,5 +15,6 @@\n line 15\n line 16\n line 17\n+inserted\n line 18\n"));
+}
+
+#[test]
+fn original_line_endings() {
+    let original = "\u{feff}fn main() {\r\n    let mut x = 1;\r\n}\r\n";
+    let file = SourceFile::new(
+        rustc_span::FileName::Custom("crlf.rs".to_string()),
+        original.to_string(),
+        rustc_span::SourceFileHashAlgorithm::Md5,
+        None,
+    )
+    .unwrap();
+    let src = file.src.as_ref().unwrap();
+    let mut_pos = src.find("mut ").unwrap();
+    let brace_pos = src.rfind('}').unwrap();
+    let edits = [edit(mut_pos, mut_pos + 4, ""), edit(brace_pos, brace_pos, "x;\n")];
+    let edits = original_edits(&file, original, &edits).unwrap();
+    assert_eq!(
+        apply_edits(original, &edits),
+        "\u{feff}fn main() {\r\n    let x = 1;\r\nx;\r\n}\r\n"
+    );
+    assert!(original_edits(&file, "fn main() {}\n", &edits).is_none());
+}
@@ -192,6 +192,10 @@ fn emit_artifact_notification(&mut self, _path: &Path, _artifact_type: &str) {}
     /// Currently only supported for the JSON format.
     fn emit_future_breakage_report(&mut self, _diags: Vec<DiagInner>, _registry: &Registry) {}
 
+    /// Emit a unified diff of the suggestions `-Zapply-suggestions=diff` would apply to the file at
+    /// `path`.
+    fn emit_suggestion_diff(&mut self, _path: &Path, _diff: &str) {}
+
     /// Emit list of unused externs.
     /// Currently only supported for the JSON format.
     fn emit_unused_externs(
@@ -526,6 +530,12 @@ fn emit_diagnostic(&mut self, mut diag: DiagInner, _registry: &Registry) {
         );
     }
 
+    fn emit_suggestion_diff(&mut self, _path: &Path, diff: &str) {
+        if let Err(e) = self.dst.write_all(diff.as_bytes()).and_then(|()| self.dst.flush()) {
+            panic!("failed to emit suggestion diff: {e}")
+        }
+    }
+
     fn should_show_explain(&self) -> bool {
         !self.short_message
     }
@@ -106,6 +106,7 @@ enum EmitTyped<'a> {
     Artifact(ArtifactNotification<'a>),
     FutureIncompat(FutureIncompatReport<'a>),
     UnusedExtern(UnusedExterns<'a>),
+    SuggestionDiff(SuggestionDiff<'a>),
 }
 
 impl Translate for JsonEmitter {
@@ -170,6 +171,14 @@ fn emit_unused_externs(&mut self, lint_level: rustc_lint_defs::Level, unused_ext
         }
     }
 
+    fn emit_suggestion_diff(&mut self, path: &Path, diff: &str) {
+        let data = SuggestionDiff { file: path, diff };
+        let result = self.emit(EmitTyped::SuggestionDiff(data));
+        if let Err(e) = result {
+            panic!("failed to print suggestion diff: {e:?}");
+        }
+    }
+
     fn source_map(&self) -> Option<&SourceMap> {
         Some(&self.sm)
     }
@@ -263,6 +272,14 @@ struct ArtifactNotification<'a> {
     emit: &'a str,
 }
 
+#[derive(Serialize)]
+struct SuggestionDiff<'a> {
+    /// The path of the file the suggestions apply to.
+    file: &'a Path,
+    /// The changes to the file as a unified diff.
+    diff: &'a str,
+}
+
 #[derive(Serialize)]
 struct FutureBreakageItem<'a> {
     // Always EmitTyped::Diagnostic, but we want to make sure it gets serialized
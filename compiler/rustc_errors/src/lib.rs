@@ -43,6 +43,7 @@
 use std::{fmt, panic};
 
 use Level::*;
+pub use apply_suggestions::ApplySuggestionsMode;
 pub use codes::*;
 pub use diagnostic::{
     BugAbort, Diag, DiagArg, DiagArgMap, DiagArgName, DiagArgValue, DiagInner, DiagStyledString,
@@ -76,9 +77,11 @@
 pub use termcolor::{Color, ColorSpec, WriteColor};
 use tracing::debug;
 
+use crate::apply_suggestions::SuggestionCollector;
 use crate::registry::Registry;
 
 pub mod annotate_snippet_emitter_writer;
+mod apply_suggestions;
 pub mod codes;
 mod diagnostic;
 mod diagnostic_impls;
@@ -550,6 +553,9 @@ struct DiagCtxtInner {
     /// The file where the ICE information is stored. This allows delayed_span_bug backtraces to be
     /// stored along side the main panic backtrace.
     ice_file: Option<PathBuf>,
+
+    /// Collects machine-applicable suggestions when `-Zapply-suggestions` is used.
+    suggestion_collector: Option<SuggestionCollector>,
 }
 
 /// A key denoting where from a diagnostic was stashed.
@@ -668,6 +674,17 @@ pub fn with_ice_file(mut self, ice_file: PathBuf) -> Self {
         self
     }
 
+    /// Collects the machine-applicable suggestions of emitted diagnostics, optionally only
+    /// those of the given lints, so they can be applied by [`DiagCtxtHandle::apply_suggestions`].
+    pub fn with_suggestion_collection(
+        mut self,
+        mode: ApplySuggestionsMode,
+        lints: Vec<String>,
+    ) -> Self {
+        self.inner.get_mut().suggestion_collector = Some(SuggestionCollector::new(mode, lints));
+        self
+    }
+
     pub fn with_registry(mut self, registry: Registry) -> Self {
         self.inner.get_mut().registry = registry;
         self
@@ -785,6 +802,7 @@ pub fn reset_err_count(&self) {
             future_breakage_diagnostics,
             fulfilled_expectations,
             ice_file: _,
+            suggestion_collector: _,
         } = inner.deref_mut();
 
         // For the `Vec`s and `HashMap`s, we overwrite with an empty container to free the
@@ -1092,6 +1110,59 @@ pub fn emit_future_breakage_report(&self) {
         }
     }
 
+    /// Applies the suggestions collected because of [`DiagCtxt::with_suggestion_collection`],
+    /// and reports which files were (or would be) changed.
+    pub fn apply_suggestions(&self) {
+        let (mode, fixes) = {
+            let mut inner = self.inner.borrow_mut();
+            let Some(collector) = inner.suggestion_collector.take() else { return };
+            let Some(sm) = inner.emitter.source_map() else { return };
+            (collector.mode, collector.resolve(sm))
+        };
+
+        for fix in fixes {
+            let path = &fix.path;
+            let count = fix.suggestions;
+            match mode {
+                ApplySuggestionsMode::Write => {
+                    // The source map only holds the normalized source, so the edits are applied
+                    // to the contents on disk to preserve the byte order mark and line endings.
+                    let original = match std::fs::read_to_string(path) {
+                        Ok(original) => original,
+                        Err(err) => {
+                            self.emit_err(apply_suggestions::FailedToApply { path, err });
+                            continue;
+                        }
+                    };
+                    let Some(edits) =
+                        apply_suggestions::original_edits(&fix.file, &original, &fix.edits)
+                    else {
+                        self.emit_err(apply_suggestions::FileChanged { path });
+                        continue;
+                    };
+                    let fixed = apply_suggestions::apply_edits(&original, &edits);
+                    match std::fs::write(path, fixed) {
+                        Ok(()) => self.emit_note(apply_suggestions::Applied { path, count }),
+                        Err(err) => {
+                            self.emit_err(apply_suggestions::FailedToApply { path, err });
+                        }
+                    }
+                }
+                ApplySuggestionsMode::Diff => {
+                    let diff = apply_suggestions::unified_diff(
+                        &path.display().to_string(),
+                        &fix.src,
+                        &fix.edits,
+                    );
+                    self.inner.borrow_mut().emitter.emit_suggestion_diff(path, &diff);
+                }
+                ApplySuggestionsMode::DryRun => {
+                    self.emit_note(apply_suggestions::WouldApply { path, count })
+                }
+            }
+        }
+    }
+
     pub fn emit_unused_externs(
         &self,
         lint_level: rustc_lint_defs::Level,
@@ -1436,6 +1507,7 @@ fn new(emitter: Box<DynEmitter>) -> Self {
             future_breakage_diagnostics: Vec::new(),
             fulfilled_expectations: Default::default(),
             ice_file: None,
+            suggestion_collector: None,
         }
     }
 
@@ -1593,6 +1665,10 @@ fn emit_diagnostic(
                 }
                 self.has_printed = true;
 
+                if let Some(collector) = &mut self.suggestion_collector {
+                    collector.collect(&diagnostic);
+                }
+
                 self.emitter.emit_diagnostic(diagnostic, &self.registry);
             }
 
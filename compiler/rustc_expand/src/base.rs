@@ -33,6 +33,8 @@
 use crate::errors;
 use crate::expand::{self, AstFragment, Invocation};
 use crate::module::DirOwnership;
+use crate::proc_macro_cache::ProcMacroCache;
+use crate::proc_macro_process::HelperPool;
 
 // When adding new variants, make sure to
 // adjust the `visit_*` / `flat_map_*` calls in `InvocationCollector`
@@ -1147,6 +1149,10 @@ pub struct ExtCtxt<'a> {
     /// in the AST, but insert it here so that we know
     /// not to expand it again.
     pub(super) expanded_inert_attrs: MarkedAttrs,
+    /// Expansions of deterministic proc macros cached across incremental sessions.
+    pub proc_macro_cache: Option<ProcMacroCache>,
+    /// Helper processes for running proc macros out of process.
+    pub(crate) proc_macro_helpers: Rc<HelperPool>,
 }
 
 impl<'a> ExtCtxt<'a> {
@@ -1176,6 +1182,8 @@ pub fn new(
             expansions: FxIndexMap::default(),
             expanded_inert_attrs: MarkedAttrs::new(),
             buffered_early_lint: vec![],
+            proc_macro_cache: ProcMacroCache::load(sess),
+            proc_macro_helpers: Rc::new(HelperPool::new(sess)),
         }
     }
 
@@ -1270,6 +1278,12 @@ pub fn def_site_path(&self, components: &[Symbol]) -> Vec<Ident> {
     pub fn check_unused_macros(&mut self) {
         self.resolver.check_unused_macros();
     }
+
+    pub fn save_proc_macro_cache(&self) {
+        if let Some(cache) = &self.proc_macro_cache {
+            cache.save(self.sess);
+        }
+    }
 }
 
 /// Resolves a `path` mentioned inside Rust code, returning an absolute path.
@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::path::Path;
 
 use rustc_ast::ast;
 use rustc_errors::codes::*;
@@ -406,6 +407,13 @@ pub(crate) struct ProcMacroDeriveTokens {
     pub span: Span,
 }
 
+#[derive(Diagnostic)]
+#[diag(expand_proc_macro_cache_write)]
+pub(crate) struct ProcMacroCacheWrite<'a> {
+    pub path: &'a Path,
+    pub err: std::io::Error,
+}
+
 #[derive(Diagnostic)]
 #[diag(expand_duplicate_matcher_binding)]
 pub(crate) struct DuplicateMatcherBinding {
@@ -34,5 +34,7 @@
 // FIXME(Nilstrieb) Translate proc_macro diagnostics
 #[allow(rustc::untranslatable_diagnostic)]
 pub mod proc_macro;
+pub mod proc_macro_cache;
+pub mod proc_macro_process;
 
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
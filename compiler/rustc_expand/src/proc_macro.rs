@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use rustc_ast as ast;
 use rustc_ast::ptr::P;
 use rustc_ast::tokenstream::TokenStream;
@@ -8,6 +10,8 @@
 use rustc_span::profiling::SpannedEventArgRecorder;
 
 use crate::base::{self, *};
+use crate::proc_macro_cache::{CacheLookup, CacheableProcMacro, ProcMacroCache};
+use crate::proc_macro_process::{OutOfProcess, ProcMacroLocation};
 use crate::{errors, proc_macro_server};
 
 struct MessagePipe<T> {
@@ -31,15 +35,49 @@ fn recv(&mut self) -> Option<T> {
     }
 }
 
-fn exec_strategy(ecx: &ExtCtxt<'_>) -> impl pm::bridge::server::ExecutionStrategy {
-    pm::bridge::server::MaybeCrossThread::<MessagePipe<_>>::new(
-        ecx.sess.opts.unstable_opts.proc_macro_execution_strategy
-            == ProcMacroExecutionStrategy::CrossThread,
-    )
+enum Strategy<'a> {
+    InProcess(pm::bridge::server::MaybeCrossThread<MessagePipe<pm::bridge::Buffer>>),
+    OutOfProcess(OutOfProcess<'a>),
+}
+
+impl pm::bridge::server::ExecutionStrategy for Strategy<'_> {
+    fn run_bridge_and_client(
+        &self,
+        dispatcher: &mut impl pm::bridge::server::DispatcherTrait,
+        input: pm::bridge::Buffer,
+        run_client: extern "C" fn(pm::bridge::BridgeConfig<'_>) -> pm::bridge::Buffer,
+        force_show_panics: bool,
+    ) -> pm::bridge::Buffer {
+        match self {
+            Strategy::InProcess(strategy) => {
+                strategy.run_bridge_and_client(dispatcher, input, run_client, force_show_panics)
+            }
+            Strategy::OutOfProcess(strategy) => {
+                strategy.run_bridge_and_client(dispatcher, input, run_client, force_show_panics)
+            }
+        }
+    }
+}
+
+fn exec_strategy<'a>(ecx: &ExtCtxt<'_>, location: Option<&'a ProcMacroLocation>) -> Strategy<'a> {
+    let strategy = ecx.sess.opts.unstable_opts.proc_macro_execution_strategy;
+    // Macros that are not loaded from a dylib, like the built-in `quote!`, always run in the
+    // compiler itself.
+    if strategy == ProcMacroExecutionStrategy::OutOfProcess
+        && let Some(location) = location
+    {
+        let pool = Rc::clone(&ecx.proc_macro_helpers);
+        return Strategy::OutOfProcess(OutOfProcess { pool, location });
+    }
+    Strategy::InProcess(pm::bridge::server::MaybeCrossThread::new(
+        strategy == ProcMacroExecutionStrategy::CrossThread,
+    ))
 }
 
 pub struct BangProcMacro {
     pub client: pm::bridge::client::Client<pm::TokenStream, pm::TokenStream>,
+    pub cacheable: Option<CacheableProcMacro>,
+    pub location: Option<ProcMacroLocation>,
 }
 
 impl base::BangProcMacro for BangProcMacro {
@@ -54,22 +92,35 @@ fn expand(
                 recorder.record_arg_with_span(ecx.sess.source_map(), ecx.expansion_descr(), span);
             });
 
+        let pending = match ProcMacroCache::lookup(ecx, self.cacheable, "bang", &[&input]) {
+            CacheLookup::Hit(stream) => return Ok(stream),
+            CacheLookup::Miss(pending) => Some(pending),
+            CacheLookup::Uncacheable => None,
+        };
+
         let proc_macro_backtrace = ecx.ecfg.proc_macro_backtrace;
-        let strategy = exec_strategy(ecx);
+        let strategy = exec_strategy(ecx, self.location.as_ref());
         let server = proc_macro_server::Rustc::new(ecx);
-        self.client.run(&strategy, server, input, proc_macro_backtrace).map_err(|e| {
-            ecx.dcx().emit_err(errors::ProcMacroPanicked {
-                span,
-                message: e
-                    .as_str()
-                    .map(|message| errors::ProcMacroPanickedHelp { message: message.into() }),
-            })
-        })
+        let stream =
+            self.client.run(&strategy, server, input, proc_macro_backtrace).map_err(|e| {
+                ecx.dcx().emit_err(errors::ProcMacroPanicked {
+                    span,
+                    message: e
+                        .as_str()
+                        .map(|message| errors::ProcMacroPanickedHelp { message: message.into() }),
+                })
+            })?;
+        if let Some(pending) = pending {
+            ProcMacroCache::store(ecx, pending, &stream);
+        }
+        Ok(stream)
     }
 }
 
 pub struct AttrProcMacro {
     pub client: pm::bridge::client::Client<(pm::TokenStream, pm::TokenStream), pm::TokenStream>,
+    pub cacheable: Option<CacheableProcMacro>,
+    pub location: Option<ProcMacroLocation>,
 }
 
 impl base::AttrProcMacro for AttrProcMacro {
@@ -85,24 +136,38 @@ fn expand(
                 recorder.record_arg_with_span(ecx.sess.source_map(), ecx.expansion_descr(), span);
             });
 
+        let pending =
+            match ProcMacroCache::lookup(ecx, self.cacheable, "attr", &[&annotation, &annotated]) {
+                CacheLookup::Hit(stream) => return Ok(stream),
+                CacheLookup::Miss(pending) => Some(pending),
+                CacheLookup::Uncacheable => None,
+            };
+
         let proc_macro_backtrace = ecx.ecfg.proc_macro_backtrace;
-        let strategy = exec_strategy(ecx);
+        let strategy = exec_strategy(ecx, self.location.as_ref());
         let server = proc_macro_server::Rustc::new(ecx);
-        self.client.run(&strategy, server, annotation, annotated, proc_macro_backtrace).map_err(
-            |e| {
+        let stream = self
+            .client
+            .run(&strategy, server, annotation, annotated, proc_macro_backtrace)
+            .map_err(|e| {
                 ecx.dcx().emit_err(errors::CustomAttributePanicked {
                     span,
                     message: e.as_str().map(|message| errors::CustomAttributePanickedHelp {
                         message: message.into(),
                     }),
                 })
-            },
-        )
+            })?;
+        if let Some(pending) = pending {
+            ProcMacroCache::store(ecx, pending, &stream);
+        }
+        Ok(stream)
     }
 }
 
 pub struct DeriveProcMacro {
     pub client: pm::bridge::client::Client<pm::TokenStream, pm::TokenStream>,
+    pub cacheable: Option<CacheableProcMacro>,
+    pub location: Option<ProcMacroLocation>,
 }
 
 impl MultiItemModifier for DeriveProcMacro {
@@ -124,6 +189,11 @@ fn expand(
         // altogether. See #73345.
         crate::base::ann_pretty_printing_compatibility_hack(&item, &ecx.sess);
         let input = item.to_tokens();
+        let pending = match ProcMacroCache::lookup(ecx, self.cacheable, "derive", &[&input]) {
+            CacheLookup::Hit(stream) => return parse_derive_output(ecx, span, is_stmt, stream),
+            CacheLookup::Miss(pending) => Some(pending),
+            CacheLookup::Uncacheable => None,
+        };
         let stream = {
             let _timer =
                 ecx.sess.prof.generic_activity_with_arg_recorder("expand_proc_macro", |recorder| {
@@ -134,7 +204,7 @@ fn expand(
                     );
                 });
             let proc_macro_backtrace = ecx.ecfg.proc_macro_backtrace;
-            let strategy = exec_strategy(ecx);
+            let strategy = exec_strategy(ecx, self.location.as_ref());
             let server = proc_macro_server::Rustc::new(ecx);
             match self.client.run(&strategy, server, input, proc_macro_backtrace) {
                 Ok(stream) => stream,
@@ -151,33 +221,45 @@ fn expand(
                 }
             }
         };
+        if let Some(pending) = pending {
+            ProcMacroCache::store(ecx, pending, &stream);
+        }
 
-        let error_count_before = ecx.dcx().err_count();
-        let mut parser = Parser::new(&ecx.sess.psess, stream, Some("proc-macro derive"));
-        let mut items = vec![];
-
-        loop {
-            match parser.parse_item(ForceCollect::No) {
-                Ok(None) => break,
-                Ok(Some(item)) => {
-                    if is_stmt {
-                        items.push(Annotatable::Stmt(P(ecx.stmt_item(span, item))));
-                    } else {
-                        items.push(Annotatable::Item(item));
-                    }
-                }
-                Err(err) => {
-                    err.emit();
-                    break;
+        parse_derive_output(ecx, span, is_stmt, stream)
+    }
+}
+
+fn parse_derive_output(
+    ecx: &mut ExtCtxt<'_>,
+    span: Span,
+    is_stmt: bool,
+    stream: TokenStream,
+) -> ExpandResult<Vec<Annotatable>, Annotatable> {
+    let error_count_before = ecx.dcx().err_count();
+    let mut parser = Parser::new(&ecx.sess.psess, stream, Some("proc-macro derive"));
+    let mut items = vec![];
+
+    loop {
+        match parser.parse_item(ForceCollect::No) {
+            Ok(None) => break,
+            Ok(Some(item)) => {
+                if is_stmt {
+                    items.push(Annotatable::Stmt(P(ecx.stmt_item(span, item))));
+                } else {
+                    items.push(Annotatable::Item(item));
                 }
             }
+            Err(err) => {
+                err.emit();
+                break;
+            }
         }
+    }
 
-        // fail if there have been errors emitted
-        if ecx.dcx().err_count() > error_count_before {
-            ecx.dcx().emit_err(errors::ProcMacroDeriveTokens { span });
-        }
-
-        ExpandResult::Ready(items)
+    // fail if there have been errors emitted
+    if ecx.dcx().err_count() > error_count_before {
+        ecx.dcx().emit_err(errors::ProcMacroDeriveTokens { span });
     }
+
+    ExpandResult::Ready(items)
 }
@@ -0,0 +1,463 @@
+//! Caching of proc-macro expansions across incremental sessions (`-Zcache-proc-macros`).
+//!
+//! Only macros whose authors marked them `#[proc_macro_deterministic]` are cached. Such a macro
+//! promises that its output depends on nothing but its input tokens, so an expansion from a
+//! previous session can be replayed as long as the macro crate and the input tokens are
+//! unchanged.
+//!
+//! Spans cannot be stored as they are, since the input may have moved in the meantime. Every
+//! output span is instead recorded as the span of one of the input tokens or as one of the
+//! call-site, def-site and mixed-site spans of the expansion. Outputs containing any other span
+//! are not cached.
+
+use std::fs;
+use std::hash::Hash;
+use std::path::PathBuf;
+
+use rustc_ast::token::{Delimiter, Token, TokenKind};
+use rustc_ast::tokenstream::{DelimSpacing, DelimSpan, Spacing, TokenStream, TokenTree};
+use rustc_ast_pretty::pprust;
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_data_structures::svh::Svh;
+use rustc_macros::{Decodable, Encodable};
+use rustc_serialize::leb128;
+use rustc_serialize::opaque::{IntEncodedWithFixedSize, MAGIC_END_BYTES, MemDecoder};
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use rustc_session::Session;
+use rustc_span::def_id::{CrateNum, DefId, DefIndex};
+use rustc_span::hygiene::{ExpnId, SyntaxContext};
+use rustc_span::{DUMMY_SP, Span, SpanEncoder, Symbol};
+use tracing::debug;
+
+use crate::base::ExtCtxt;
+use crate::errors;
+
+const CACHE_FILE_NAME: &str = "proc-macro-cache.bin";
+const FILE_MAGIC: &[u8] = b"RSPM";
+/// The fingerprint and length of the encoded entries, written after them.
+const FOOTER_LEN: usize = 16 + IntEncodedWithFixedSize::ENCODED_SIZE;
+
+/// Encodes the entries of the cache into memory, so that their fingerprint can be computed
+/// before they are written. The encoding is that of `FileEncoder`, which `MemDecoder` reads.
+#[derive(Default)]
+struct CacheEncoder {
+    data: Vec<u8>,
+}
+
+macro_rules! write_leb128 {
+    ($this_fn:ident, $int_ty:ty, $write_leb_fn:ident) => {
+        fn $this_fn(&mut self, v: $int_ty) {
+            let mut buf = [0; leb128::max_leb128_len::<$int_ty>()];
+            let len = leb128::$write_leb_fn(&mut buf, v);
+            self.data.extend_from_slice(&buf[..len]);
+        }
+    };
+}
+
+impl Encoder for CacheEncoder {
+    write_leb128!(emit_usize, usize, write_usize_leb128);
+    write_leb128!(emit_u128, u128, write_u128_leb128);
+    write_leb128!(emit_u64, u64, write_u64_leb128);
+    write_leb128!(emit_u32, u32, write_u32_leb128);
+
+    fn emit_u16(&mut self, v: u16) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn emit_u8(&mut self, v: u8) {
+        self.data.push(v);
+    }
+
+    write_leb128!(emit_isize, isize, write_isize_leb128);
+    write_leb128!(emit_i128, i128, write_i128_leb128);
+    write_leb128!(emit_i64, i64, write_i64_leb128);
+    write_leb128!(emit_i32, i32, write_i32_leb128);
+
+    fn emit_i16(&mut self, v: i16) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn emit_raw_bytes(&mut self, s: &[u8]) {
+        self.data.extend_from_slice(s);
+    }
+}
+
+/// Cached expansions only contain spans as [`CachedSpan`]s and no interpolated tokens, so
+/// symbols are all that is encoded besides plain data.
+impl SpanEncoder for CacheEncoder {
+    fn encode_span(&mut self, _span: Span) {
+        panic!("cannot encode `Span` in the proc-macro cache");
+    }
+
+    fn encode_symbol(&mut self, symbol: Symbol) {
+        self.emit_str(symbol.as_str());
+    }
+
+    fn encode_expn_id(&mut self, _expn_id: ExpnId) {
+        panic!("cannot encode `ExpnId` in the proc-macro cache");
+    }
+
+    fn encode_syntax_context(&mut self, _syntax_context: SyntaxContext) {
+        panic!("cannot encode `SyntaxContext` in the proc-macro cache");
+    }
+
+    fn encode_crate_num(&mut self, _crate_num: CrateNum) {
+        panic!("cannot encode `CrateNum` in the proc-macro cache");
+    }
+
+    fn encode_def_index(&mut self, _def_index: DefIndex) {
+        panic!("cannot encode `DefIndex` in the proc-macro cache");
+    }
+
+    fn encode_def_id(&mut self, _def_id: DefId) {
+        panic!("cannot encode `DefId` in the proc-macro cache");
+    }
+}
+
+/// Identifies a proc macro whose expansions may be cached.
+#[derive(Clone, Copy, Debug)]
+pub struct CacheableProcMacro {
+    /// The hash of the crate defining the macro, so that changes to it invalidate the cache.
+    pub crate_hash: Svh,
+    pub name: Symbol,
+}
+
+#[derive(Clone, Copy, Encodable, Decodable)]
+enum CachedSpan {
+    /// The span of the input token with this index.
+    Input(u32),
+    CallSite,
+    DefSite,
+    MixedSite,
+}
+
+#[derive(Encodable, Decodable)]
+enum CachedTokenTree {
+    Token(TokenKind, CachedSpan, Spacing),
+    Delimited(CachedSpan, CachedSpan, DelimSpacing, Delimiter, Vec<CachedTokenTree>),
+}
+
+/// The spans an expansion may refer to besides those of its input.
+struct SiteSpans {
+    call_site: Span,
+    def_site: Span,
+    mixed_site: Span,
+}
+
+impl SiteSpans {
+    fn new(ecx: &ExtCtxt<'_>) -> SiteSpans {
+        let expn_data = ecx.current_expansion.id.expn_data();
+        SiteSpans {
+            call_site: ecx.with_call_site_ctxt(expn_data.call_site),
+            def_site: ecx.with_def_site_ctxt(expn_data.def_site),
+            mixed_site: ecx.with_mixed_site_ctxt(expn_data.call_site),
+        }
+    }
+}
+
+pub(crate) enum CacheLookup {
+    Hit(TokenStream),
+    Miss(PendingExpansion),
+    Uncacheable,
+}
+
+/// An expansion that was not found in the cache and may be stored once the macro has run.
+pub(crate) struct PendingExpansion {
+    key: Fingerprint,
+    /// The index of the first input token with each span.
+    input_spans: FxHashMap<Span, u32>,
+    sites: SiteSpans,
+    err_count: usize,
+    depinfo_len: (usize, usize),
+}
+
+pub struct ProcMacroCache {
+    path: PathBuf,
+    /// Expansions loaded from the previous session that have not been used yet.
+    previous: FxHashMap<Fingerprint, Vec<CachedTokenTree>>,
+    /// Expansions used in this session, which are the only ones saved.
+    current: FxIndexMap<Fingerprint, Vec<CachedTokenTree>>,
+}
+
+impl ProcMacroCache {
+    /// Loads the cache of the previous session, if caching is enabled.
+    pub fn load(sess: &Session) -> Option<ProcMacroCache> {
+        if !sess.opts.unstable_opts.cache_proc_macros {
+            return None;
+        }
+        let path = sess.incr_comp_session_dir_opt()?.join(CACHE_FILE_NAME);
+        let previous = match fs::read(&path) {
+            Ok(data) => decode_cache(&data, sess.cfg_version).unwrap_or_else(|| {
+                debug!("ignoring corrupt or foreign proc-macro cache {}", path.display());
+                Default::default()
+            }),
+            Err(_) => Default::default(),
+        };
+        Some(ProcMacroCache { path, previous, current: Default::default() })
+    }
+
+    /// Writes the expansions used in this session. The file starts with a header made of
+    /// [`FILE_MAGIC`] and the version of the compiler, and the entries are followed by their
+    /// fingerprint and length, so that [`decode_cache`] can reject foreign or corrupt files
+    /// before decoding anything, as `MemDecoder` panics on malformed input.
+    pub fn save(&self, sess: &Session) {
+        let mut encoder = CacheEncoder::default();
+        encoder.emit_usize(self.current.len());
+        for (key, output) in &self.current {
+            key.encode(&mut encoder);
+            output.encode(&mut encoder);
+        }
+        let payload = encoder.data;
+
+        let mut data = Vec::with_capacity(payload.len() + 64);
+        data.extend_from_slice(FILE_MAGIC);
+        data.extend_from_slice(&(sess.cfg_version.len() as u64).to_le_bytes());
+        data.extend_from_slice(sess.cfg_version.as_bytes());
+        data.extend_from_slice(&payload);
+        data.extend_from_slice(&payload_fingerprint(&payload).to_le_bytes());
+        data.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        data.extend_from_slice(MAGIC_END_BYTES);
+
+        // The file may be a hard link to the one of the previous session, which must not be
+        // modified, so it has to be removed rather than truncated.
+        if let Err(err) = fs::remove_file(&self.path)
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            sess.dcx().emit_warn(errors::ProcMacroCacheWrite { path: &self.path, err });
+            return;
+        }
+        if let Err(err) = fs::write(&self.path, &data) {
+            sess.dcx().emit_warn(errors::ProcMacroCacheWrite { path: &self.path, err });
+        }
+    }
+
+    pub(crate) fn lookup(
+        ecx: &mut ExtCtxt<'_>,
+        cacheable: Option<CacheableProcMacro>,
+        kind: &str,
+        inputs: &[&TokenStream],
+    ) -> CacheLookup {
+        let Some(cacheable) = cacheable else { return CacheLookup::Uncacheable };
+        if ecx.proc_macro_cache.is_none() {
+            return CacheLookup::Uncacheable;
+        }
+
+        let mut hasher = StableHasher::new();
+        cacheable.crate_hash.as_u128().hash(&mut hasher);
+        cacheable.name.as_str().hash(&mut hasher);
+        kind.hash(&mut hasher);
+        let mut input_spans = FxHashMap::default();
+        for input in inputs {
+            if !hash_stream(input, &mut hasher, &mut input_spans) {
+                return CacheLookup::Uncacheable;
+            }
+        }
+        let key: Fingerprint = hasher.finish();
+
+        let sites = SiteSpans::new(ecx);
+        let cache = ecx.proc_macro_cache.as_mut().unwrap();
+        if let Some(output) = cache.previous.remove(&key) {
+            cache.current.insert(key, output);
+        }
+        if let Some(output) = cache.current.get(&key) {
+            let mut spans = vec![DUMMY_SP; input_spans.len()];
+            for (span, index) in input_spans {
+                spans[index as usize] = span;
+            }
+            debug!("replaying cached expansion of `{}`", cacheable.name);
+            return CacheLookup::Hit(replay(output, &spans, &sites));
+        }
+
+        let psess = &ecx.sess.psess;
+        let depinfo_len = (psess.env_depinfo.borrow().len(), psess.file_depinfo.borrow().len());
+        CacheLookup::Miss(PendingExpansion {
+            key,
+            input_spans,
+            sites,
+            err_count: ecx.dcx().err_count(),
+            depinfo_len,
+        })
+    }
+
+    /// Stores the output of an expansion that was not found in the cache.
+    pub(crate) fn store(ecx: &mut ExtCtxt<'_>, pending: PendingExpansion, output: &TokenStream) {
+        // An expansion that reported errors or tracked environment variables or files did more
+        // than map its input to its output, whatever the macro promised.
+        let psess = &ecx.sess.psess;
+        let depinfo_len = (psess.env_depinfo.borrow().len(), psess.file_depinfo.borrow().len());
+        if ecx.dcx().err_count() != pending.err_count || depinfo_len != pending.depinfo_len {
+            return;
+        }
+        let Some(output) = record(output, &pending.input_spans, &pending.sites) else {
+            debug!("not caching an expansion with spans unrelated to its input");
+            return;
+        };
+        if let Some(cache) = &mut ecx.proc_macro_cache {
+            cache.current.insert(pending.key, output);
+        }
+    }
+}
+
+/// Decodes the cache file written by [`ProcMacroCache::save`]. Returns `None` if the file was
+/// written by another compiler, or if it is truncated or corrupt.
+fn decode_cache(
+    data: &[u8],
+    cfg_version: &str,
+) -> Option<FxHashMap<Fingerprint, Vec<CachedTokenTree>>> {
+    let data = data.strip_prefix(FILE_MAGIC)?;
+    let (version_len, data) = data.split_first_chunk()?;
+    let version_len = usize::try_from(u64::from_le_bytes(*version_len)).ok()?;
+    let (version, data) = data.split_at_checked(version_len)?;
+    if version != cfg_version.as_bytes() {
+        return None;
+    }
+    let body = data.strip_suffix(MAGIC_END_BYTES)?;
+    let (payload, footer) = body.split_at_checked(body.len().checked_sub(FOOTER_LEN)?)?;
+    let (fingerprint, len) = footer.split_at(16);
+    if u64::from_le_bytes(len.try_into().unwrap()) != payload.len() as u64
+        || Fingerprint::from_le_bytes(fingerprint.try_into().unwrap())
+            != payload_fingerprint(payload)
+    {
+        return None;
+    }
+    // Only entries that were written as they are now are decoded, so decoding cannot fail.
+    let mut decoder = MemDecoder::new(data, 0).ok()?;
+    let len = decoder.read_usize();
+    Some(
+        (0..len)
+            .map(|_| {
+                (Fingerprint::decode(&mut decoder), Vec::<CachedTokenTree>::decode(&mut decoder))
+            })
+            .collect(),
+    )
+}
+
+fn payload_fingerprint(payload: &[u8]) -> Fingerprint {
+    let mut hasher = StableHasher::new();
+    payload.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hashes the tokens of `stream` while numbering their spans. Returns `false` if the stream
+/// contains tokens that cannot be hashed independently of the current session.
+fn hash_stream(
+    stream: &TokenStream,
+    hasher: &mut StableHasher,
+    spans: &mut FxHashMap<Span, u32>,
+) -> bool {
+    for tree in stream.iter() {
+        match tree {
+            TokenTree::Token(Token { kind, span }, spacing) => {
+                if matches!(
+                    kind,
+                    TokenKind::Interpolated(..)
+                        | TokenKind::NtIdent(..)
+                        | TokenKind::NtLifetime(..)
+                ) {
+                    return false;
+                }
+                0u8.hash(hasher);
+                pprust::token_kind_to_string(kind).hash(hasher);
+                (*spacing as u8).hash(hasher);
+                number_span(spans, *span);
+            }
+            TokenTree::Delimited(dspan, spacing, delim, inner) => {
+                if let Delimiter::Invisible(_) = delim {
+                    return false;
+                }
+                1u8.hash(hasher);
+                pprust::token_kind_to_string(&TokenKind::OpenDelim(*delim)).hash(hasher);
+                (spacing.open as u8, spacing.close as u8).hash(hasher);
+                number_span(spans, dspan.open);
+                number_span(spans, dspan.close);
+                if !hash_stream(inner, hasher, spans) {
+                    return false;
+                }
+                2u8.hash(hasher);
+            }
+        }
+    }
+    true
+}
+
+fn number_span(spans: &mut FxHashMap<Span, u32>, span: Span) {
+    let next = spans.len() as u32;
+    spans.entry(span).or_insert(next);
+}
+
+fn record(
+    stream: &TokenStream,
+    input_spans: &FxHashMap<Span, u32>,
+    sites: &SiteSpans,
+) -> Option<Vec<CachedTokenTree>> {
+    let cached_span = |span: Span| {
+        if let Some(&index) = input_spans.get(&span) {
+            Some(CachedSpan::Input(index))
+        } else if span == sites.call_site {
+            Some(CachedSpan::CallSite)
+        } else if span == sites.def_site {
+            Some(CachedSpan::DefSite)
+        } else if span == sites.mixed_site {
+            Some(CachedSpan::MixedSite)
+        } else {
+            None
+        }
+    };
+    stream
+        .iter()
+        .map(|tree| match tree {
+            TokenTree::Token(Token { kind, span }, spacing) => {
+                if matches!(
+                    kind,
+                    TokenKind::Interpolated(..)
+                        | TokenKind::NtIdent(..)
+                        | TokenKind::NtLifetime(..)
+                ) {
+                    return None;
+                }
+                Some(CachedTokenTree::Token(kind.clone(), cached_span(*span)?, *spacing))
+            }
+            TokenTree::Delimited(dspan, spacing, delim, inner) => {
+                if let Delimiter::Invisible(_) = delim {
+                    return None;
+                }
+                Some(CachedTokenTree::Delimited(
+                    cached_span(dspan.open)?,
+                    cached_span(dspan.close)?,
+                    *spacing,
+                    *delim,
+                    record(inner, input_spans, sites)?,
+                ))
+            }
+        })
+        .collect()
+}
+
+fn replay(output: &[CachedTokenTree], input_spans: &[Span], sites: &SiteSpans) -> TokenStream {
+    let span = |cached: CachedSpan| match cached {
+        CachedSpan::Input(index) => input_spans[index as usize],
+        CachedSpan::CallSite => sites.call_site,
+        CachedSpan::DefSite => sites.def_site,
+        CachedSpan::MixedSite => sites.mixed_site,
+    };
+    TokenStream::new(
+        output
+            .iter()
+            .map(|tree| match tree {
+                CachedTokenTree::Token(kind, cached, spacing) => {
+                    TokenTree::Token(Token::new(kind.clone(), span(*cached)), *spacing)
+                }
+                CachedTokenTree::Delimited(open, close, spacing, delim, inner) => {
+                    TokenTree::Delimited(
+                        DelimSpan::from_pair(span(*open), span(*close)),
+                        *spacing,
+                        *delim,
+                        replay(inner, input_spans, sites),
+                    )
+                }
+            })
+            .collect(),
+    )
+}
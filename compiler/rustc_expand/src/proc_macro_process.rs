@@ -0,0 +1,584 @@
+//! Running proc macros in a helper process (`-Zproc-macro-execution-strategy=out-of-process`).
+//!
+//! The helper is the `rustc` executable of the sysroot the compiler libraries were loaded from,
+//! started with [`HELPER_ENV`] set, which `rustc_driver::main` checks before anything else. So
+//! other drivers, such as rustdoc or clippy, start the same helper as rustc. It loads proc-macro
+//! dylibs on request and runs macros with the usual `proc_macro::bridge` RPC, except
+//! that the buffers exchanged with the server are sent over its stdin and stdout. A macro that
+//! crashes or hangs thus only takes down the helper, and is reported like a panicking macro.
+//!
+//! The compiler never loads the dylibs itself: the names and kinds of the macros are obtained
+//! from a helper with [`load_remote_proc_macros`], so that not even the static initializers of a
+//! proc-macro crate run in the compiler process.
+//!
+//! Paths are sent as their bytes on Unix, and as UTF-8 strings elsewhere.
+//!
+//! Helpers are kept around between invocations and started on demand, so that nested macro
+//! invocations (through `TokenStream::expand_expr`) run in a helper of their own.
+
+use std::cell::RefCell;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use std::{env, thread};
+
+use pm::bridge::Buffer;
+use pm::bridge::client::ProcMacro;
+use pm::bridge::server::{DispatcherTrait, ExecutionStrategy, client_failure, dispatch_failure};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_session::{Session, filesearch};
+use rustc_span::Symbol;
+use tracing::debug;
+
+/// The environment variable that makes the compiler act as a proc-macro helper process.
+pub const HELPER_ENV: &str = "RUSTC_PROC_MACRO_HELPER";
+
+/// The proc-macro declarations of a dylib.
+pub type ProcMacroDecls = &'static [ProcMacro];
+
+/// The proc macros of a proc-macro crate, in the order of their declarations.
+pub enum ProcMacros {
+    /// The declarations of a dylib loaded into the compiler.
+    Loaded(ProcMacroDecls),
+    /// The macros of a dylib that is only loaded in helper processes.
+    Remote(Vec<RemoteProcMacro>),
+}
+
+impl ProcMacros {
+    /// Returns the declaration of the macro with `index`, along with its name and the names of
+    /// its helper attributes. Only the kind and the client of the declaration are meaningful, and
+    /// the client must only run in this process if the macro was loaded into it.
+    pub fn get(&self, index: usize) -> (&ProcMacro, Symbol, Vec<Symbol>) {
+        match self {
+            ProcMacros::Loaded(decls) => {
+                let decl = &decls[index];
+                let (name, helper_attrs) = match *decl {
+                    ProcMacro::CustomDerive { trait_name, attributes, .. } => {
+                        (trait_name, attributes)
+                    }
+                    ProcMacro::Attr { name, .. } | ProcMacro::Bang { name, .. } => (name, &[][..]),
+                };
+                let helper_attrs = helper_attrs.iter().map(|&attr| Symbol::intern(attr)).collect();
+                (decl, Symbol::intern(name), helper_attrs)
+            }
+            ProcMacros::Remote(macros) => {
+                let remote = &macros[index];
+                (&remote.decl, remote.name, remote.helper_attrs.clone())
+            }
+        }
+    }
+}
+
+/// A proc macro of a dylib that is only loaded in helper processes.
+pub struct RemoteProcMacro {
+    /// A declaration of the same kind, whose names are empty and whose client must never run.
+    decl: ProcMacro,
+    name: Symbol,
+    helper_attrs: Vec<Symbol>,
+}
+
+/// The largest buffer accepted from the other process, so that a corrupt length cannot make the
+/// reader allocate arbitrary amounts of memory.
+const MAX_BUFFER_LEN: u64 = 1 << 30;
+
+/// What the compiler needs to know about a proc macro that is only loaded in a helper process.
+enum RemoteDecl {
+    CustomDerive { trait_name: String, attributes: Vec<String> },
+    Attr { name: String },
+    Bang { name: String },
+}
+
+impl RemoteDecl {
+    fn new(decl: &ProcMacro) -> RemoteDecl {
+        match *decl {
+            ProcMacro::CustomDerive { trait_name, attributes, .. } => RemoteDecl::CustomDerive {
+                trait_name: trait_name.to_owned(),
+                attributes: attributes.iter().map(|&attr| attr.to_owned()).collect(),
+            },
+            ProcMacro::Attr { name, .. } => RemoteDecl::Attr { name: name.to_owned() },
+            ProcMacro::Bang { name, .. } => RemoteDecl::Bang { name: name.to_owned() },
+        }
+    }
+
+    /// Interns the names of the macro, whose client must never run, as it only exists in a
+    /// helper.
+    fn into_remote(self) -> RemoteProcMacro {
+        fn remote_only(_: pm::TokenStream) -> pm::TokenStream {
+            unreachable!("proc macros loaded in a helper process only run there")
+        }
+        fn remote_only2(_: pm::TokenStream, _: pm::TokenStream) -> pm::TokenStream {
+            unreachable!("proc macros loaded in a helper process only run there")
+        }
+        match self {
+            RemoteDecl::CustomDerive { trait_name, attributes } => RemoteProcMacro {
+                decl: ProcMacro::custom_derive("", &[], remote_only),
+                name: Symbol::intern(&trait_name),
+                helper_attrs: attributes.iter().map(|attr| Symbol::intern(attr)).collect(),
+            },
+            RemoteDecl::Attr { name } => RemoteProcMacro {
+                decl: ProcMacro::attr("", remote_only2),
+                name: Symbol::intern(&name),
+                helper_attrs: Vec::new(),
+            },
+            RemoteDecl::Bang { name } => RemoteProcMacro {
+                decl: ProcMacro::bang("", remote_only),
+                name: Symbol::intern(&name),
+                helper_attrs: Vec::new(),
+            },
+        }
+    }
+}
+
+/// A proc-macro dylib, and the symbol of the proc-macro declarations in it.
+#[derive(Debug)]
+pub struct ProcMacroDylib {
+    pub path: PathBuf,
+    pub decls_symbol: String,
+}
+
+/// Where a helper process finds a proc macro.
+#[derive(Clone, Debug)]
+pub struct ProcMacroLocation {
+    pub dylib: Lrc<ProcMacroDylib>,
+    /// The index of the macro in the declarations of the dylib.
+    pub index: usize,
+}
+
+enum Message {
+    /// Asks the helper to run a macro on the given input buffer.
+    Run {
+        dylib: PathBuf,
+        decls_symbol: String,
+        index: usize,
+        force_show_panics: bool,
+        input: Vec<u8>,
+    },
+    /// The reply of the server to a `Dispatch`.
+    Reply(Vec<u8>),
+    /// A request of the running macro to the server.
+    Dispatch(Vec<u8>),
+    /// The output buffer of the macro.
+    Done(Vec<u8>),
+    /// The dylib or its proc-macro declarations could not be loaded.
+    LoadError(String),
+    /// Asks the helper for the proc-macro declarations of a dylib.
+    Describe { dylib: PathBuf, decls_symbol: String },
+    /// The proc-macro declarations of a dylib, in order.
+    Decls(Vec<RemoteDecl>),
+}
+
+impl Message {
+    fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        fn bytes(w: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+            w.write_all(&(bytes.len() as u64).to_le_bytes())?;
+            w.write_all(bytes)
+        }
+        #[cfg(unix)]
+        fn path(w: &mut impl Write, path: &Path) -> io::Result<()> {
+            use std::os::unix::ffi::OsStrExt;
+            bytes(w, path.as_os_str().as_bytes())
+        }
+        #[cfg(not(unix))]
+        fn path(w: &mut impl Write, path: &Path) -> io::Result<()> {
+            let path = path.to_str().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "paths must be valid UTF-8")
+            })?;
+            bytes(w, path.as_bytes())
+        }
+        match self {
+            Message::Run { dylib, decls_symbol, index, force_show_panics, input } => {
+                w.write_all(&[0])?;
+                path(w, dylib)?;
+                bytes(w, decls_symbol.as_bytes())?;
+                w.write_all(&(*index as u64).to_le_bytes())?;
+                w.write_all(&[*force_show_panics as u8])?;
+                bytes(w, input)?;
+            }
+            Message::Reply(buf) => {
+                w.write_all(&[1])?;
+                bytes(w, buf)?;
+            }
+            Message::Dispatch(buf) => {
+                w.write_all(&[2])?;
+                bytes(w, buf)?;
+            }
+            Message::Done(buf) => {
+                w.write_all(&[3])?;
+                bytes(w, buf)?;
+            }
+            Message::LoadError(message) => {
+                w.write_all(&[4])?;
+                bytes(w, message.as_bytes())?;
+            }
+            Message::Describe { dylib, decls_symbol } => {
+                w.write_all(&[5])?;
+                path(w, dylib)?;
+                bytes(w, decls_symbol.as_bytes())?;
+            }
+            Message::Decls(decls) => {
+                w.write_all(&[6])?;
+                w.write_all(&(decls.len() as u64).to_le_bytes())?;
+                for decl in decls {
+                    match decl {
+                        RemoteDecl::CustomDerive { trait_name, attributes } => {
+                            w.write_all(&[0])?;
+                            bytes(w, trait_name.as_bytes())?;
+                            w.write_all(&(attributes.len() as u64).to_le_bytes())?;
+                            for attr in attributes {
+                                bytes(w, attr.as_bytes())?;
+                            }
+                        }
+                        RemoteDecl::Attr { name } => {
+                            w.write_all(&[1])?;
+                            bytes(w, name.as_bytes())?;
+                        }
+                        RemoteDecl::Bang { name } => {
+                            w.write_all(&[2])?;
+                            bytes(w, name.as_bytes())?;
+                        }
+                    }
+                }
+            }
+        }
+        w.flush()
+    }
+
+    /// Reads the next message, or returns `None` if the other end has closed the pipe.
+    fn read(r: &mut impl Read) -> io::Result<Option<Message>> {
+        fn u64(r: &mut impl Read) -> io::Result<u64> {
+            let mut buf = [0; 8];
+            r.read_exact(&mut buf)?;
+            Ok(u64::from_le_bytes(buf))
+        }
+        fn u8(r: &mut impl Read) -> io::Result<u8> {
+            let mut buf = [0];
+            r.read_exact(&mut buf)?;
+            Ok(buf[0])
+        }
+        fn len(r: &mut impl Read) -> io::Result<usize> {
+            let len = u64(r)?;
+            if len > MAX_BUFFER_LEN {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "message too large"));
+            }
+            Ok(len as usize)
+        }
+        fn bytes(r: &mut impl Read) -> io::Result<Vec<u8>> {
+            let mut buf = vec![0; len(r)?];
+            r.read_exact(&mut buf)?;
+            Ok(buf)
+        }
+        #[cfg(unix)]
+        fn path(r: &mut impl Read) -> io::Result<PathBuf> {
+            use std::os::unix::ffi::OsStringExt;
+            Ok(PathBuf::from(std::ffi::OsString::from_vec(bytes(r)?)))
+        }
+        #[cfg(not(unix))]
+        fn path(r: &mut impl Read) -> io::Result<PathBuf> {
+            Ok(PathBuf::from(string(r)?))
+        }
+        fn string(r: &mut impl Read) -> io::Result<String> {
+            String::from_utf8(bytes(r)?)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+        }
+
+        let tag = match u8(r) {
+            Ok(tag) => tag,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let message = match tag {
+            0 => Message::Run {
+                dylib: path(r)?,
+                decls_symbol: string(r)?,
+                index: u64(r)? as usize,
+                force_show_panics: u8(r)? != 0,
+                input: bytes(r)?,
+            },
+            1 => Message::Reply(bytes(r)?),
+            2 => Message::Dispatch(bytes(r)?),
+            3 => Message::Done(bytes(r)?),
+            4 => Message::LoadError(string(r)?),
+            5 => Message::Describe { dylib: path(r)?, decls_symbol: string(r)? },
+            6 => {
+                let mut decls = Vec::new();
+                for _ in 0..len(r)? {
+                    let decl = match u8(r)? {
+                        0 => {
+                            let trait_name = string(r)?;
+                            let attributes =
+                                (0..len(r)?).map(|_| string(r)).collect::<Result<_, _>>()?;
+                            RemoteDecl::CustomDerive { trait_name, attributes }
+                        }
+                        1 => RemoteDecl::Attr { name: string(r)? },
+                        2 => RemoteDecl::Bang { name: string(r)? },
+                        _ => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                "unknown macro kind",
+                            ));
+                        }
+                    };
+                    decls.push(decl);
+                }
+                Message::Decls(decls)
+            }
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "unknown message")),
+        };
+        Ok(Some(message))
+    }
+}
+
+/// Runs the helper process, serving requests from `input` and answering on `output` until the
+/// compiler closes `input`. If the compiler does not answer a request of a running macro, the
+/// macro panics, which ends the helper if the compiler went away.
+///
+/// `load` loads the proc-macro declarations of a dylib, given its path and the symbol of the
+/// declarations.
+pub fn run_helper(
+    input: impl Read,
+    output: impl Write,
+    load: impl Fn(&Path, &str) -> Result<ProcMacroDecls, String>,
+) -> io::Result<()> {
+    let mut input = BufReader::new(input);
+    let mut output = BufWriter::new(output);
+    let mut loaded: FxHashMap<(PathBuf, String), ProcMacroDecls> = Default::default();
+    while let Some(message) = Message::read(&mut input)? {
+        let (dylib, decls_symbol) = match &message {
+            Message::Run { dylib, decls_symbol, .. }
+            | Message::Describe { dylib, decls_symbol } => (dylib.clone(), decls_symbol.clone()),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "expected a request")),
+        };
+        let decls = match loaded.entry((dylib, decls_symbol)) {
+            std::collections::hash_map::Entry::Occupied(entry) => *entry.get(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let (dylib, decls_symbol) = entry.key();
+                match load(dylib, decls_symbol) {
+                    Ok(decls) => *entry.insert(decls),
+                    Err(err) => {
+                        Message::LoadError(err).write(&mut output)?;
+                        continue;
+                    }
+                }
+            }
+        };
+        let Message::Run { index, force_show_panics, input: buf, .. } = message else {
+            Message::Decls(decls.iter().map(RemoteDecl::new).collect()).write(&mut output)?;
+            continue;
+        };
+
+        let out = decls[index].run_remote(
+            Buffer::from(buf),
+            |request| {
+                let reply = Message::Dispatch(request.to_vec())
+                    .write(&mut output)
+                    .and_then(|()| Message::read(&mut input));
+                match reply {
+                    Ok(Some(Message::Reply(reply))) => Buffer::from(reply),
+                    Ok(Some(_)) => dispatch_failure("the compiler sent an invalid message".into()),
+                    Ok(None) => dispatch_failure("the compiler closed its connection".into()),
+                    Err(err) => dispatch_failure(format!("failed to reach the compiler: {err}")),
+                }
+            },
+            force_show_panics,
+        );
+        Message::Done(out.to_vec()).write(&mut output)?;
+    }
+    Ok(())
+}
+
+/// A running helper process.
+struct Helper {
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    /// The messages of the helper, read by a separate thread so that they can be waited for with
+    /// a timeout.
+    messages: Receiver<Message>,
+}
+
+impl Helper {
+    fn spawn(wrapper: Option<&Path>) -> io::Result<Helper> {
+        // `current_exe` may be another driver, which does not check for `HELPER_ENV`.
+        let sysroot = filesearch::get_or_default_sysroot().map_err(io::Error::other)?;
+        let exe = sysroot.join("bin").join(format!("rustc{}", env::consts::EXE_SUFFIX));
+        let mut command = match wrapper {
+            Some(wrapper) => {
+                let mut command = Command::new(wrapper);
+                command.arg(exe);
+                command
+            }
+            None => Command::new(exe),
+        };
+        let mut child = command
+            .env(HELPER_ENV, "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        debug!("started proc-macro helper process {}", child.id());
+
+        let stdin = BufWriter::new(child.stdin.take().unwrap());
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let (sender, messages) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(Some(message)) = Message::read(&mut stdout) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Helper { child, stdin, messages })
+    }
+
+    /// Waits for the next message of the helper until `deadline`, if any.
+    fn recv(&self, deadline: Option<Instant>) -> Result<Message, RecvTimeoutError> {
+        match deadline {
+            Some(deadline) => {
+                self.messages.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => self.messages.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        }
+    }
+
+    /// Describes why the helper stopped answering.
+    fn failure(&mut self) -> String {
+        match self.child.wait() {
+            Ok(status) => format!("the proc-macro helper process exited unexpectedly ({status})"),
+            Err(err) => format!("the proc-macro helper process stopped responding: {err}"),
+        }
+    }
+}
+
+impl Drop for Helper {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// The helper processes that are not currently running a macro.
+pub struct HelperPool {
+    idle: RefCell<Vec<Helper>>,
+    wrapper: Option<PathBuf>,
+    timeout: Option<Duration>,
+}
+
+impl HelperPool {
+    pub fn new(sess: &Session) -> HelperPool {
+        HelperPool {
+            idle: Default::default(),
+            wrapper: sess.opts.unstable_opts.proc_macro_helper_wrapper.clone(),
+            timeout: sess.opts.unstable_opts.proc_macro_timeout.map(Duration::from_secs),
+        }
+    }
+
+    fn run(
+        &self,
+        location: &ProcMacroLocation,
+        dispatcher: &mut impl DispatcherTrait,
+        input: Buffer,
+        force_show_panics: bool,
+    ) -> Result<Buffer, String> {
+        let idle = self.idle.borrow_mut().pop();
+        let mut helper = match idle {
+            Some(helper) => helper,
+            None => Helper::spawn(self.wrapper.as_deref())
+                .map_err(|err| format!("failed to start the proc-macro helper process: {err}"))?,
+        };
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+
+        let mut message = Message::Run {
+            dylib: location.dylib.path.clone(),
+            decls_symbol: location.dylib.decls_symbol.clone(),
+            index: location.index,
+            force_show_panics,
+            input: input.to_vec(),
+        };
+        loop {
+            if message.write(&mut helper.stdin).is_err() {
+                return Err(helper.failure());
+            }
+            match helper.recv(deadline) {
+                Ok(Message::Dispatch(request)) => {
+                    message = Message::Reply(dispatcher.dispatch(Buffer::from(request)).to_vec());
+                }
+                Ok(Message::Done(output)) => {
+                    self.idle.borrow_mut().push(helper);
+                    return Ok(Buffer::from(output));
+                }
+                Ok(Message::LoadError(err)) => {
+                    self.idle.borrow_mut().push(helper);
+                    return Err(format!("the proc-macro helper process failed to load it: {err}"));
+                }
+                Ok(
+                    Message::Run { .. }
+                    | Message::Reply(_)
+                    | Message::Describe { .. }
+                    | Message::Decls(_),
+                ) => {
+                    return Err("the proc-macro helper process sent an invalid message".to_owned());
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    // Dropping the helper kills it.
+                    return Err(format!(
+                        "the proc macro did not finish within {} seconds",
+                        self.timeout.unwrap().as_secs()
+                    ));
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(helper.failure()),
+            }
+        }
+    }
+}
+
+/// Obtains the proc-macro declarations of a dylib from a helper process, without loading the
+/// dylib into the compiler. The clients of the returned declarations must only be run with the
+/// out-of-process execution strategy.
+pub fn load_remote_proc_macros(
+    sess: &Session,
+    dylib: &ProcMacroDylib,
+) -> Result<ProcMacros, String> {
+    let opts = &sess.opts.unstable_opts;
+    let mut helper = Helper::spawn(opts.proc_macro_helper_wrapper.as_deref())
+        .map_err(|err| format!("failed to start the proc-macro helper process: {err}"))?;
+    let deadline = opts.proc_macro_timeout.map(|secs| Instant::now() + Duration::from_secs(secs));
+    let message =
+        Message::Describe { dylib: dylib.path.clone(), decls_symbol: dylib.decls_symbol.clone() };
+    if message.write(&mut helper.stdin).is_err() {
+        return Err(helper.failure());
+    }
+    match helper.recv(deadline) {
+        Ok(Message::Decls(decls)) => {
+            Ok(ProcMacros::Remote(decls.into_iter().map(RemoteDecl::into_remote).collect()))
+        }
+        Ok(Message::LoadError(err)) => Err(err),
+        Ok(_) => Err("the proc-macro helper process sent an invalid message".to_owned()),
+        Err(RecvTimeoutError::Timeout) => {
+            Err("the proc-macro helper process did not load it in time".to_owned())
+        }
+        Err(RecvTimeoutError::Disconnected) => Err(helper.failure()),
+    }
+}
+
+/// Runs a proc macro in a helper process.
+pub(crate) struct OutOfProcess<'a> {
+    pub(crate) pool: Rc<HelperPool>,
+    pub(crate) location: &'a ProcMacroLocation,
+}
+
+impl ExecutionStrategy for OutOfProcess<'_> {
+    fn run_bridge_and_client(
+        &self,
+        dispatcher: &mut impl DispatcherTrait,
+        input: Buffer,
+        _run_client: extern "C" fn(pm::bridge::BridgeConfig<'_>) -> Buffer,
+        force_show_panics: bool,
+    ) -> Buffer {
+        self.pool
+            .run(self.location, dispatcher, input, force_show_panics)
+            .unwrap_or_else(client_failure)
+    }
+}
@@ -358,6 +358,10 @@ pub struct BuiltinAttribute {
         ErrorFollowing, EncodeCrossCrate::No,
     ),
     ungated!(proc_macro_attribute, Normal, template!(Word), ErrorFollowing, EncodeCrossCrate::No),
+    gated!(
+        proc_macro_deterministic, Normal, template!(Word), WarnFollowing,
+        EncodeCrossCrate::Yes, experimental!(proc_macro_deterministic)
+    ),
 
     // Lints:
     ungated!(
@@ -535,6 +539,11 @@ pub struct BuiltinAttribute {
         EncodeCrossCrate::No, optimize_attribute, experimental!(optimize)
     ),
 
+    gated!(
+        blocking, Normal, template!(Word), WarnFollowing,
+        EncodeCrossCrate::Yes, blocking_attribute, experimental!(blocking)
+    ),
+
     gated!(
         unsafe ffi_pure, Normal, template!(Word), WarnFollowing,
         EncodeCrossCrate::No, experimental!(ffi_pure)
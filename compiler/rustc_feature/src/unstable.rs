@@ -397,6 +397,9 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (unstable, async_for_loop, "1.77.0", Some(118898)),
     /// Allows `async` trait bound modifier.
     (unstable, async_trait_bounds, "CURRENT_RUSTC_VERSION", Some(62290)),
+    /// Allows the `#[blocking]` attribute, which marks functions that block the current thread
+    /// for the `blocking_in_async` lint.
+    (unstable, blocking_attribute, "CURRENT_RUSTC_VERSION", None),
     /// Allows using C-variadics.
     (unstable, c_variadic, "1.34.0", Some(44930)),
     /// Allows the use of `#[cfg(<true/false>)]`.
@@ -583,6 +586,9 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (unstable, postfix_match, "1.79.0", Some(121618)),
     /// Allows `use<..>` precise capturign on impl Trait in traits.
     (unstable, precise_capturing_in_traits, "1.83.0", Some(130044)),
+    /// Allows proc macros to declare with `#[proc_macro_deterministic]` that their output only
+    /// depends on their input, which lets their expansions be cached.
+    (unstable, proc_macro_deterministic, "CURRENT_RUSTC_VERSION", None),
     /// Allows macro attributes on expressions, statements and non-inline modules.
     (unstable, proc_macro_hygiene, "1.30.0", Some(54727)),
     /// Makes `&` and `&mut` patterns eat only one layer of references in Rust 2024.
@@ -292,6 +292,10 @@ pub fn extract(attrs: &[impl AttributeExt]) -> Option<(Symbol, Span)> {
     ConstPanicFmt,           sym::const_panic_fmt,     const_panic_fmt,            Target::Fn,             GenericRequirement::None;
     PanicBoundsCheck,        sym::panic_bounds_check,  panic_bounds_check_fn,      Target::Fn,             GenericRequirement::Exact(0);
     PanicMisalignedPointerDereference, sym::panic_misaligned_pointer_dereference, panic_misaligned_pointer_dereference_fn, Target::Fn, GenericRequirement::Exact(0);
+    PanicNullPointerDereference, sym::panic_null_pointer_dereference, panic_null_pointer_dereference_fn, Target::Fn, GenericRequirement::Exact(0);
+    PanicInvalidBool,        sym::panic_invalid_bool,  panic_invalid_bool_fn,      Target::Fn,             GenericRequirement::Exact(0);
+    PanicInvalidChar,        sym::panic_invalid_char,  panic_invalid_char_fn,      Target::Fn,             GenericRequirement::Exact(0);
+    PanicInvalidEnumDiscriminant, sym::panic_invalid_enum_discriminant, panic_invalid_enum_discriminant_fn, Target::Fn, GenericRequirement::Exact(0);
     PanicInfo,               sym::panic_info,          panic_info,                 Target::Struct,         GenericRequirement::None;
     PanicLocation,           sym::panic_location,      panic_location,             Target::Struct,         GenericRequirement::None;
     PanicImpl,               sym::panic_impl,          panic_impl,                 Target::Fn,             GenericRequirement::None;
@@ -15,9 +15,9 @@
 mod persist;
 
 pub use persist::{
-    LoadResult, copy_cgu_workproduct_to_incr_comp_cache_dir, finalize_session_directory,
-    in_incr_comp_dir, in_incr_comp_dir_sess, load_query_result_cache, save_work_product_index,
-    setup_dep_graph,
+    LoadResult, copy_cgu_workproduct_to_incr_comp_cache_dir, enable_dep_graph_cache,
+    finalize_session_directory, in_incr_comp_dir, in_incr_comp_dir_sess, load_query_result_cache,
+    prefetch_dep_graphs, save_work_product_index, setup_dep_graph,
 };
 use rustc_middle::util::Providers;
 
@@ -0,0 +1,148 @@
+//! Keeps decoded dep graphs in memory between the sessions of a resident compiler (`-Zdaemon`).
+//!
+//! A session loads the dep graph saved by the previous session of the same crate, which is only
+//! known once that session is finalized. So when a session finalizes its session directory, the
+//! path of its dep graph is remembered, and [`prefetch_dep_graphs`] decodes it while the compiler
+//! waits for its next request. Loading then only has to check that the file is still the one that
+//! was decoded, by its size, modification time and (on Unix) inode. The session directory of the
+//! next session hard-links the file where possible, so it keeps all of them.
+//!
+//! The cache is disabled unless [`enable_dep_graph_cache`] was called. It holds one dep graph per
+//! crate directory of the incremental directory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::dep_graph::{DepsType, SerializedDepGraph};
+use rustc_serialize::Decodable;
+use rustc_serialize::opaque::MemDecoder;
+use rustc_session::Session;
+use tracing::debug;
+
+use super::file_format;
+use super::fs::DEP_GRAPH_FILENAME;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static CACHE: LazyLock<Mutex<DepGraphCache>> = LazyLock::new(Default::default);
+
+#[derive(Default)]
+struct DepGraphCache {
+    /// The decoded dep graph of each crate directory, with the stamp of its file.
+    graphs: FxHashMap<PathBuf, (FileStamp, Arc<SerializedDepGraph>)>,
+    /// The dep graphs saved by finalized sessions that were not decoded yet.
+    pending: Vec<PendingDepGraph>,
+}
+
+struct PendingDepGraph {
+    path: PathBuf,
+    is_nightly_build: bool,
+    cfg_version: &'static str,
+}
+
+/// What identifies a version of a dep graph file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct FileStamp {
+    len: u64,
+    modified: SystemTime,
+    #[cfg(unix)]
+    ino: u64,
+}
+
+impl FileStamp {
+    /// Returns the stamp of the file at `path`, or `None` if it cannot be checked for changes.
+    pub(crate) fn of(path: &Path) -> Option<FileStamp> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(FileStamp {
+            len: metadata.len(),
+            modified: metadata.modified().ok()?,
+            #[cfg(unix)]
+            ino: std::os::unix::fs::MetadataExt::ino(&metadata),
+        })
+    }
+}
+
+/// Makes decoded dep graphs stay in memory for the later sessions of this process.
+pub fn enable_dep_graph_cache() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Remembers the dep graph of a session whose directory was finalized as `session_dir`, so that
+/// it can be decoded before the next session of the crate needs it.
+pub(crate) fn note_finalized(sess: &Session, session_dir: &Path) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    CACHE.lock().unwrap().pending.push(PendingDepGraph {
+        path: session_dir.join(DEP_GRAPH_FILENAME),
+        is_nightly_build: sess.is_nightly_build(),
+        cfg_version: sess.cfg_version,
+    });
+}
+
+/// Decodes the dep graphs saved by the sessions finalized since the last call.
+pub fn prefetch_dep_graphs() {
+    let pending = std::mem::take(&mut CACHE.lock().unwrap().pending);
+    for PendingDepGraph { path, is_nightly_build, cfg_version } in pending {
+        let Some(stamp) = FileStamp::of(&path) else {
+            continue;
+        };
+        let Ok(Some((bytes, start_pos))) =
+            file_format::read_file(&path, false, is_nightly_build, cfg_version)
+        else {
+            continue;
+        };
+        let Ok(mut decoder) = MemDecoder::new(&bytes, start_pos) else {
+            continue;
+        };
+        // The dep graph is preceded by the hash of the command line it was built with.
+        let _commandline_args_hash = u64::decode(&mut decoder);
+        let graph = SerializedDepGraph::decode::<DepsType>(&mut decoder);
+
+        let crate_dir = path.parent().and_then(Path::parent).unwrap().to_path_buf();
+        debug!("prefetched the dep graph of {}", crate_dir.display());
+        // Do not cache a file that changed while it was being decoded.
+        if FileStamp::of(&path) == Some(stamp) {
+            CACHE.lock().unwrap().graphs.insert(crate_dir, (stamp, graph));
+        }
+    }
+}
+
+/// Returns the cached dep graph of the session's crate if it was decoded from the file with
+/// `stamp`, the stamp the dep graph file had before it was read.
+pub(crate) fn lookup(sess: &Session, stamp: Option<FileStamp>) -> Option<Arc<SerializedDepGraph>> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let stamp = stamp?;
+    let crate_dir = sess.incr_comp_session_dir().parent()?.to_path_buf();
+    let cache = CACHE.lock().unwrap();
+    let (cached_stamp, graph) = cache.graphs.get(&crate_dir)?;
+    (*cached_stamp == stamp).then(|| Arc::clone(graph))
+}
+
+/// Caches the dep graph of the session's crate that was decoded from the file at `path`, which
+/// had `stamp` before it was read.
+pub(crate) fn store(
+    sess: &Session,
+    path: &Path,
+    stamp: Option<FileStamp>,
+    graph: &Arc<SerializedDepGraph>,
+) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    // Files without a stamp are never reused, since they cannot be checked for changes, and
+    // neither are files that changed while they were being decoded.
+    let Some(stamp) = stamp.filter(|&stamp| FileStamp::of(path) == Some(stamp)) else {
+        return;
+    };
+    let Some(crate_dir) = sess.incr_comp_session_dir().parent().map(Path::to_path_buf) else {
+        return;
+    };
+    CACHE.lock().unwrap().graphs.insert(crate_dir, (stamp, Arc::clone(graph)));
+}
@@ -121,13 +121,14 @@
 use rustc_session::{Session, StableCrateId};
 use tracing::debug;
 
+use super::dep_graph_cache;
 use crate::errors;
 
 #[cfg(test)]
 mod tests;
 
 const LOCK_FILE_EXT: &str = ".lock";
-const DEP_GRAPH_FILENAME: &str = "dep-graph.bin";
+pub(crate) const DEP_GRAPH_FILENAME: &str = "dep-graph.bin";
 const STAGING_DEP_GRAPH_FILENAME: &str = "dep-graph.part.bin";
 const WORK_PRODUCTS_FILENAME: &str = "work-products.bin";
 const QUERY_CACHE_FILENAME: &str = "query-cache.bin";
@@ -358,6 +359,8 @@ pub fn finalize_session_directory(sess: &Session, svh: Option<Svh>) {
         Ok(_) => {
             debug!("finalize_session_directory() - directory renamed successfully");
 
+            dep_graph_cache::note_finalized(sess, &new_path);
+
             // This unlocks the directory
             sess.finalize_incr_comp_session(new_path);
         }
@@ -14,6 +14,7 @@
 use tracing::{debug, warn};
 
 use super::data::*;
+use super::dep_graph_cache;
 use super::fs::*;
 use super::save::build_dep_graph;
 use super::{file_format, work_product};
@@ -145,6 +146,9 @@ fn load_dep_graph(sess: &Session) -> LoadResult<(Arc<SerializedDepGraph>, WorkPr
 
     let _prof_timer = prof.generic_activity("incr_comp_load_dep_graph");
 
+    // Taken before reading the file, so that a cached dep graph is never matched with a file
+    // that changed since.
+    let stamp = dep_graph_cache::FileStamp::of(&path);
     match load_data(&path, sess) {
         LoadResult::DataOutOfDate => LoadResult::DataOutOfDate,
         LoadResult::LoadDepGraph(path, err) => LoadResult::LoadDepGraph(path, err),
@@ -169,7 +173,14 @@ fn load_dep_graph(sess: &Session) -> LoadResult<(Arc<SerializedDepGraph>, WorkPr
                 return LoadResult::DataOutOfDate;
             }
 
-            let dep_graph = SerializedDepGraph::decode::<DepsType>(&mut decoder);
+            let dep_graph = match dep_graph_cache::lookup(sess, stamp) {
+                Some(dep_graph) => dep_graph,
+                None => {
+                    let dep_graph = SerializedDepGraph::decode::<DepsType>(&mut decoder);
+                    dep_graph_cache::store(sess, &path, stamp, &dep_graph);
+                    dep_graph
+                }
+            };
 
             LoadResult::Ok { data: (dep_graph, prev_work_products) }
         }
@@ -3,6 +3,7 @@
 //! various HIR nodes.
 
 mod data;
+mod dep_graph_cache;
 mod dirty_clean;
 mod file_format;
 mod fs;
@@ -10,6 +11,7 @@
 mod save;
 mod work_product;
 
+pub use dep_graph_cache::{enable_dep_graph_cache, prefetch_dep_graphs};
 pub use fs::{finalize_session_directory, in_incr_comp_dir, in_incr_comp_dir_sess};
 pub use load::{LoadResult, load_query_result_cache, setup_dep_graph};
 pub(crate) use save::save_dep_graph;
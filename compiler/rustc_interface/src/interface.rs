@@ -498,6 +498,7 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
             // We must run `finish_diagnostics` in both cases.
             let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&compiler)));
 
+            compiler.sess.write_mir_remarks();
             compiler.sess.finish_diagnostics();
 
             // If error diagnostics have been emitted, we can't return an
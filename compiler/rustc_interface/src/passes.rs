@@ -207,6 +207,10 @@ fn configure_and_expand(
             ecx.check_unused_macros();
         });
 
+        sess.time("save_proc_macro_cache", || {
+            ecx.save_proc_macro_cache();
+        });
+
         // If we hit a recursion limit, exit early to avoid later passes getting overwhelmed
         // with a large AST
         if ecx.reduced_recursion_limit.is_some() {
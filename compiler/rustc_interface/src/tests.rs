@@ -6,16 +6,16 @@
 
 use rustc_data_structures::profiling::TimePassesFormat;
 use rustc_errors::emitter::HumanReadableErrorType;
-use rustc_errors::{ColorConfig, registry};
+use rustc_errors::{ApplySuggestionsMode, ColorConfig, registry};
 use rustc_session::config::{
     BranchProtection, CFGuard, Cfg, CollapseMacroDebuginfo, CoverageLevel, CoverageOptions,
     DebugInfo, DumpMonoStatsFormat, ErrorOutputType, ExternEntry, ExternLocation, Externs,
     FmtDebug, FunctionReturn, InliningThreshold, Input, InstrumentCoverage, InstrumentXRay,
     LinkSelfContained, LinkerPluginLto, LocationDetail, LtoCli, MirIncludeSpans, NextSolverConfig,
     OomStrategy, Options, OutFileName, OutputType, OutputTypes, PAuthKey, PacRet, Passes,
-    PatchableFunctionEntry, Polonius, ProcMacroExecutionStrategy, Strip, SwitchWithOptPath,
-    SymbolManglingVersion, WasiExecModel, build_configuration, build_session_options,
-    rustc_optgroups,
+    PatchableFunctionEntry, Polonius, ProcMacroExecutionStrategy, StableMirExportFormat, Strip,
+    SwitchWithOptPath, SymbolManglingVersion, WasiExecModel, build_configuration,
+    build_session_options, rustc_optgroups,
 };
 use rustc_session::lint::Level;
 use rustc_session::search_paths::SearchPath;
@@ -683,9 +683,15 @@ macro_rules! untracked {
 
     // Make sure that changing an [UNTRACKED] option leaves the hash unchanged.
     // tidy-alphabetical-start
+    untracked!(api_diff, Some((String::from("old"), String::from("new"))));
+    untracked!(apply_suggestions, Some(ApplySuggestionsMode::Diff));
+    untracked!(apply_suggestions_lints, vec![String::from("unused_mut")]);
     untracked!(assert_incr_state, Some(String::from("loaded")));
+    untracked!(cache_proc_macros, true);
+    untracked!(daemon, Some(PathBuf::from("rustc.sock")));
     untracked!(deduplicate_diagnostics, false);
     untracked!(dump_dep_graph, true);
+    untracked!(dump_failed_proof_trees, Some(PathBuf::from("proof-trees")));
     untracked!(dump_mir, Some(String::from("abc")));
     untracked!(dump_mir_dataflow, true);
     untracked!(dump_mir_dir, String::from("abc"));
@@ -696,12 +702,15 @@ macro_rules! untracked {
     untracked!(dump_mono_stats_format, DumpMonoStatsFormat::Json);
     untracked!(dylib_lto, true);
     untracked!(emit_stack_sizes, true);
+    untracked!(export_stable_mir, Some(StableMirExportFormat::Cbor));
     untracked!(future_incompat_test, true);
     untracked!(identify_regions, true);
     untracked!(incremental_info, true);
     untracked!(incremental_verify_ich, true);
     untracked!(input_stats, true);
     untracked!(link_native_libraries, false);
+    untracked!(link_repro, Some(PathBuf::from("link-repro")));
+    untracked!(link_repro_on_failure, true);
     untracked!(llvm_time_trace, true);
     untracked!(ls, vec!["all".to_owned()]);
     untracked!(macro_backtrace, true);
@@ -720,6 +729,8 @@ macro_rules! untracked {
     untracked!(print_type_sizes, true);
     untracked!(proc_macro_backtrace, true);
     untracked!(proc_macro_execution_strategy, ProcMacroExecutionStrategy::CrossThread);
+    untracked!(proc_macro_helper_wrapper, Some(PathBuf::from("sandbox")));
+    untracked!(proc_macro_timeout, Some(10));
     untracked!(profile_closures, true);
     untracked!(query_dep_graph, true);
     untracked!(self_profile, SwitchWithOptPath::Enabled(None));
@@ -739,6 +750,7 @@ macro_rules! untracked {
     untracked!(unpretty, Some("expanded".to_string()));
     untracked!(unstable_options, true);
     untracked!(validate_mir, true);
+    untracked!(verify_reproducibility, true);
     untracked!(write_long_types_to_disk, false);
     // tidy-alphabetical-end
 
@@ -806,10 +818,13 @@ macro_rules! tracked {
     tracked!(location_detail, LocationDetail { file: true, line: false, column: false });
     tracked!(maximal_hir_to_mir_coverage, true);
     tracked!(merge_functions, Some(MergeFunctions::Disabled));
+    tracked!(merge_mono_items, true);
     tracked!(mir_emit_retag, true);
     tracked!(mir_enable_passes, vec![("DestProp".to_string(), false)]);
     tracked!(mir_keep_place_mention, true);
     tracked!(mir_opt_level, Some(4));
+    tracked!(mir_remarks, Some(PathBuf::from("mir-remarks.json")));
+    tracked!(mir_remarks_passes, Passes::Some(vec![String::from("Inline")]));
     tracked!(move_size_limit, Some(4096));
     tracked!(mutable_noalias, false);
     tracked!(next_solver, NextSolverConfig { coherence: true, globally: true });
@@ -853,6 +868,8 @@ macro_rules! tracked {
     tracked!(split_lto_unit, Some(true));
     tracked!(src_hash_algorithm, Some(SourceFileHashAlgorithm::Sha1));
     tracked!(stack_protector, StackProtector::All);
+    tracked!(stack_usage_report, Some(PathBuf::from("stack-usage.json")));
+    tracked!(symbol_size_report, Some(PathBuf::from("symbol-sizes.json")));
     tracked!(teach, true);
     tracked!(thinlto, Some(true));
     tracked!(tiny_const_eval_limit, true);
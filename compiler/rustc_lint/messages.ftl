@@ -48,6 +48,10 @@ lint_bad_attribute_argument = bad attribute argument
 
 lint_bad_opt_access = {$msg}
 
+lint_blocking_in_async = call to blocking function `{$callee}` in async {$kind}
+    .note = blocking the thread that polls this future also stops the executor from running other tasks
+    .help = use an asynchronous alternative, or move the call to a thread where blocking is allowed
+
 lint_break_with_label_and_loop = this labeled break expression is easy to confuse with an unlabeled break with a labeled value expression
     .suggestion = wrap this expression in parentheses
 
@@ -191,6 +195,13 @@ lint_byte_slice_in_packed_struct_with_derive = {$ty} slice in a packed struct th
 lint_cfg_attr_no_attributes =
     `#[cfg_attr]` does not expand to any attributes
 
+lint_cfg_only_crate_dependency = extern crate `{$extern_crate}` is only used under `{$cfg}` in crate `{$local_crate}`
+    .help = only pass the dependency when `{$cfg}` holds, for example by making it a dev-dependency or an optional dependency
+
+lint_cfg_only_extern_crate = extern crate `{$extern_crate}` is only used under `{$cfg}`
+    .label = used here
+    .suggestion = only declare the `extern crate` item when `{$cfg}` holds
+
 lint_check_name_unknown_tool = unknown lint tool: `{$tool_name}`
 
 lint_closure_returning_async_block = closure returning async block can be made into an async closure
@@ -375,6 +386,8 @@ lint_improper_ctypes_enum_repr_help =
     consider adding a `#[repr(C)]`, `#[repr(transparent)]`, or integer `#[repr(...)]` attribute to this enum
 
 lint_improper_ctypes_enum_repr_reason = enum has no representation hint
+lint_improper_ctypes_field = the type is reached through field `{$field}` of `{$adt}`
+
 lint_improper_ctypes_fnptr_help = consider using an `extern fn(...) -> ...` function pointer instead
 
 lint_improper_ctypes_fnptr_reason = this function pointer has Rust-specific calling convention
@@ -499,6 +512,10 @@ lint_metavariable_wrong_operator = meta-variable repeats with different Kleene o
 
 lint_missing_fragment_specifier = missing fragment specifier
 
+lint_missing_must_use = {$descr} `{$name}` has no side effects but isn't marked `#[must_use]`
+    .note = it takes no mutable references or values with interior mutability, reaches no mutable statics and does no I/O, so discarding its result is almost certainly a bug
+    .suggestion = mark it with `#[must_use]` to warn when its result is discarded
+
 lint_missing_unsafe_on_extern = extern blocks should be unsafe
     .suggestion = needs `unsafe` before the extern keyword
 
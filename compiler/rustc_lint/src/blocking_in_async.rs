@@ -0,0 +1,114 @@
+use rustc_hir::{self as hir, CoroutineDesugaring, CoroutineKind, CoroutineSource};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_session::{declare_lint, declare_lint_pass};
+use rustc_span::def_id::DefId;
+use rustc_span::{Symbol, sym};
+
+use crate::lints::BlockingInAsyncDiag;
+use crate::{LateContext, LateLintPass, LintContext};
+
+declare_lint! {
+    /// The `blocking_in_async` lint detects calls to functions that block the
+    /// current thread, such as `std::thread::sleep` or `std::fs::read`, in
+    /// `async` functions, blocks and closures.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,edition2021
+    /// #![deny(blocking_in_async)]
+    /// async fn wait() {
+    ///     std::thread::sleep(std::time::Duration::from_secs(1));
+    /// }
+    /// ```
+    ///
+    /// {{produces}}
+    ///
+    /// ### Explanation
+    ///
+    /// A future is polled by an executor, which usually runs many other tasks
+    /// on the same thread. A call that blocks this thread prevents the
+    /// executor from making progress on any of these tasks until it returns.
+    /// Async code should use the asynchronous alternatives provided by its
+    /// runtime, or move the blocking call to a thread where blocking is fine.
+    ///
+    /// Besides the blocking functions of the standard library, this lint
+    /// reports calls to functions marked with the unstable `#[blocking]`
+    /// attribute.
+    ///
+    /// This lint is "allow" by default because many of these calls are
+    /// harmless in practice, such as locking an uncontended mutex.
+    pub BLOCKING_IN_ASYNC,
+    Allow,
+    "detects calls to blocking functions in async code"
+}
+
+declare_lint_pass!(
+    /// Lint for calls to blocking functions in the body of async coroutines.
+    BlockingInAsync => [BLOCKING_IN_ASYNC]
+);
+
+/// The diagnostic items of the standard library functions that block the
+/// current thread.
+const BLOCKING_DIAGNOSTIC_ITEMS: &[Symbol] = &[
+    sym::file_create,
+    sym::file_open,
+    sym::fs_create_dir,
+    sym::fs_read,
+    sym::fs_read_to_string,
+    sym::fs_remove_file,
+    sym::fs_write,
+    sym::mutex_lock,
+    sym::rwlock_read,
+    sym::rwlock_write,
+    sym::tcp_listener_accept,
+    sym::tcp_stream_connect,
+    sym::thread_sleep,
+];
+
+impl<'tcx> LateLintPass<'tcx> for BlockingInAsync {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx hir::Expr<'tcx>) {
+        let def_id = match expr.kind {
+            hir::ExprKind::Call(callee, _) => match callee.kind {
+                hir::ExprKind::Path(ref qpath) => {
+                    cx.typeck_results().qpath_res(qpath, callee.hir_id).opt_def_id()
+                }
+                _ => None,
+            },
+            hir::ExprKind::MethodCall(..) => cx.typeck_results().type_dependent_def_id(expr.hir_id),
+            _ => None,
+        };
+        let Some(def_id) = def_id else { return };
+        if !is_blocking(cx, def_id) {
+            return;
+        }
+
+        // Only the bodies of the async coroutines themselves are polled by
+        // the executor; closures defined inside them may run anywhere.
+        let Some(body_id) = cx.enclosing_body else { return };
+        let body_owner = cx.tcx.hir().body_owner_def_id(body_id);
+        let Some(CoroutineKind::Desugared(CoroutineDesugaring::Async, source)) =
+            cx.tcx.coroutine_kind(body_owner)
+        else {
+            return;
+        };
+
+        let kind = match source {
+            CoroutineSource::Fn => "function",
+            CoroutineSource::Block => "block",
+            CoroutineSource::Closure => "closure",
+        };
+        cx.emit_span_lint(
+            BLOCKING_IN_ASYNC,
+            expr.span,
+            BlockingInAsyncDiag {
+                callee: with_no_trimmed_paths!(cx.tcx.def_path_str(def_id)),
+                kind,
+            },
+        );
+    }
+}
+
+pub(crate) fn is_blocking(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    cx.tcx.get_diagnostic_name(def_id).is_some_and(|name| BLOCKING_DIAGNOSTIC_ITEMS.contains(&name))
+        || cx.tcx.has_attr(def_id, sym::blocking)
+}
@@ -302,6 +302,17 @@ pub(super) fn decorate_lint(
 
             lints::ExternCrateNotIdiomatic { span: suggestion_span, code }.decorate_lint(diag);
         }
+        BuiltinLintDiag::CfgOnlyExternCrate { extern_crate, cfg, uses, item_span } => {
+            let indent = sess.source_map().indentation_before(item_span).unwrap_or_default();
+            lints::CfgOnlyExternCrate {
+                extern_crate,
+                cfg,
+                uses,
+                suggestion: item_span.shrink_to_lo(),
+                indent,
+            }
+            .decorate_lint(diag);
+        }
         BuiltinLintDiag::AmbiguousGlobImports { diag: ambiguity } => {
             lints::AmbiguousGlobImports { ambiguity }.decorate_lint(diag);
         }
@@ -430,6 +441,9 @@ pub(super) fn decorate_lint(
         BuiltinLintDiag::UnusedCrateDependency { extern_crate, local_crate } => {
             lints::UnusedCrateDependency { extern_crate, local_crate }.decorate_lint(diag)
         }
+        BuiltinLintDiag::CfgOnlyCrateDependency { extern_crate, local_crate, cfg } => {
+            lints::CfgOnlyCrateDependency { extern_crate, local_crate, cfg }.decorate_lint(diag)
+        }
         BuiltinLintDiag::WasmCAbi => lints::WasmCAbi.decorate_lint(diag),
         BuiltinLintDiag::IllFormedAttributeInput { suggestions } => {
             lints::IllFormedAttributeInput {
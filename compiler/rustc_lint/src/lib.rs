@@ -38,6 +38,7 @@
 
 mod async_closures;
 mod async_fn_in_trait;
+mod blocking_in_async;
 pub mod builtin;
 mod context;
 mod dangling;
@@ -83,6 +84,7 @@
 
 use async_closures::AsyncClosureUsage;
 use async_fn_in_trait::AsyncFnInTrait;
+use blocking_in_async::*;
 use builtin::*;
 use dangling::*;
 use deref_into_dyn_supertrait::*;
@@ -234,8 +236,10 @@ fn lint_mod(tcx: TyCtxt<'_>, module_def_id: LocalModDefId) {
             MapUnitFn: MapUnitFn,
             MissingDebugImplementations: MissingDebugImplementations,
             MissingDoc: MissingDoc,
+            MissingMustUse: MissingMustUse,
             AsyncClosureUsage: AsyncClosureUsage,
             AsyncFnInTrait: AsyncFnInTrait,
+            BlockingInAsync: BlockingInAsync,
             NonLocalDefinitions: NonLocalDefinitions::default(),
             ImplTraitOvercaptures: ImplTraitOvercaptures,
             IfLetRescope: IfLetRescope::default(),
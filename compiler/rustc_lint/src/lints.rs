@@ -53,6 +53,16 @@ pub(crate) enum ShadowedIntoIterDiagSub {
     },
 }
 
+// blocking_in_async.rs
+#[derive(LintDiagnostic)]
+#[diag(lint_blocking_in_async)]
+#[note]
+#[help]
+pub(crate) struct BlockingInAsyncDiag {
+    pub callee: String,
+    pub kind: &'static str,
+}
+
 // builtin.rs
 #[derive(LintDiagnostic)]
 #[diag(lint_builtin_while_true)]
@@ -1868,6 +1878,16 @@ pub(crate) struct ImproperCTypes<'a> {
     pub help: Option<DiagMessage>,
     pub note: DiagMessage,
     pub span_note: Option<Span>,
+    pub fields: Vec<ImproperCTypesField<'a>>,
+}
+
+#[derive(Clone, Subdiagnostic)]
+#[note(lint_improper_ctypes_field)]
+pub(crate) struct ImproperCTypesField<'a> {
+    #[primary_span]
+    pub span: Span,
+    pub field: Symbol,
+    pub adt: Ty<'a>,
 }
 
 // Used because of the complexity of Option<DiagMessage>, DiagMessage, and Option<Span>
@@ -1884,6 +1904,9 @@ fn decorate_lint<'b>(self, diag: &'b mut Diag<'a, ()>) {
         if let Some(note) = self.span_note {
             diag.span_note(note, fluent::lint_note);
         }
+        for field in self.fields {
+            diag.subdiagnostic(field);
+        }
     }
 }
 
@@ -2053,6 +2076,21 @@ pub(crate) enum PathStatementDropSub {
 #[diag(lint_path_statement_no_effect)]
 pub(crate) struct PathStatementNoEffect;
 
+#[derive(LintDiagnostic)]
+#[diag(lint_missing_must_use)]
+#[note]
+pub(crate) struct MissingMustUseDiag {
+    pub descr: &'static str,
+    pub name: Symbol,
+    #[suggestion(
+        style = "verbose",
+        code = "#[must_use]\n{indent}",
+        applicability = "machine-applicable"
+    )]
+    pub suggestion: Span,
+    pub indent: String,
+}
+
 #[derive(LintDiagnostic)]
 #[diag(lint_unused_delim)]
 pub(crate) struct UnusedDelim<'a> {
@@ -2559,6 +2597,15 @@ pub(crate) struct UnusedCrateDependency {
     pub local_crate: Symbol,
 }
 
+#[derive(LintDiagnostic)]
+#[diag(lint_cfg_only_crate_dependency)]
+#[help]
+pub(crate) struct CfgOnlyCrateDependency {
+    pub extern_crate: Symbol,
+    pub local_crate: Symbol,
+    pub cfg: String,
+}
+
 #[derive(LintDiagnostic)]
 #[diag(lint_wasm_c_abi)]
 pub(crate) struct WasmCAbi;
@@ -3005,6 +3052,22 @@ pub(crate) struct ExternCrateNotIdiomatic {
     pub code: &'static str,
 }
 
+#[derive(LintDiagnostic)]
+#[diag(lint_cfg_only_extern_crate)]
+pub(crate) struct CfgOnlyExternCrate {
+    pub extern_crate: Ident,
+    pub cfg: String,
+    #[label]
+    pub uses: Vec<Span>,
+    #[suggestion(
+        style = "verbose",
+        code = "#[{cfg}]\n{indent}",
+        applicability = "maybe-incorrect"
+    )]
+    pub suggestion: Span,
+    pub indent: String,
+}
+
 // FIXME: make this translatable
 pub(crate) struct AmbiguousGlobImports {
     pub ambiguity: AmbiguityErrorDiag,
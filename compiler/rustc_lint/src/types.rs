@@ -21,10 +21,10 @@
 use crate::lints::{
     AmbiguousWidePointerComparisons, AmbiguousWidePointerComparisonsAddrMetadataSuggestion,
     AmbiguousWidePointerComparisonsAddrSuggestion, AtomicOrderingFence, AtomicOrderingLoad,
-    AtomicOrderingStore, ImproperCTypes, InvalidAtomicOrderingDiag, InvalidNanComparisons,
-    InvalidNanComparisonsSuggestion, UnpredictableFunctionPointerComparisons,
-    UnpredictableFunctionPointerComparisonsSuggestion, UnusedComparisons,
-    VariantSizeDifferencesDiag,
+    AtomicOrderingStore, ImproperCTypes, ImproperCTypesField, InvalidAtomicOrderingDiag,
+    InvalidNanComparisons, InvalidNanComparisonsSuggestion,
+    UnpredictableFunctionPointerComparisons, UnpredictableFunctionPointerComparisonsSuggestion,
+    UnusedComparisons, VariantSizeDifferencesDiag,
 };
 use crate::{LateContext, LateLintPass, LintContext, fluent_generated as fluent};
 
@@ -728,6 +728,9 @@ struct CTypesVisitorState<'tcx> {
     /// The original type being checked, before we recursed
     /// to any other types it contains.
     base_ty: Ty<'tcx>,
+    /// The fields of `#[repr(C)]` types through which the type that isn't
+    /// FFI-safe was reached, innermost first.
+    fields: Vec<ImproperCTypesField<'tcx>>,
 }
 
 enum FfiResult<'tcx> {
@@ -937,13 +940,19 @@ pub(crate) fn repr_nullable_ptr<'tcx>(
 
 impl<'a, 'tcx> ImproperCTypesVisitor<'a, 'tcx> {
     /// Check if the type is array and emit an unsafe type lint.
-    fn check_for_array_ty(&mut self, sp: Span, ty: Ty<'tcx>) -> bool {
+    fn check_for_array_ty(
+        &mut self,
+        sp: Span,
+        ty: Ty<'tcx>,
+        fields: Vec<ImproperCTypesField<'tcx>>,
+    ) -> bool {
         if let ty::Array(..) = ty.kind() {
             self.emit_ffi_unsafe_type_lint(
                 ty,
                 sp,
                 fluent::lint_improper_ctypes_array_reason,
                 Some(fluent::lint_improper_ctypes_array_help),
+                fields,
             );
             true
         } else {
@@ -1003,7 +1012,16 @@ fn check_variant_for_ffi(
                 // `()` fields are FFI-safe!
                 FfiUnsafe { ty, .. } if ty.is_unit() => false,
                 FfiPhantom(..) => true,
-                r @ FfiUnsafe { .. } => return r,
+                r @ FfiUnsafe { .. } => {
+                    if !def.repr().transparent() && !def.is_enum() {
+                        acc.fields.push(ImproperCTypesField {
+                            span: self.cx.tcx.def_span(field.did),
+                            field: field.name,
+                            adt: ty,
+                        });
+                    }
+                    return r;
+                }
             }
         }
 
@@ -1286,6 +1304,7 @@ fn emit_ffi_unsafe_type_lint(
         sp: Span,
         note: DiagMessage,
         help: Option<DiagMessage>,
+        fields: Vec<ImproperCTypesField<'tcx>>,
     ) {
         let lint = match self.mode {
             CItemKind::Declaration => IMPROPER_CTYPES,
@@ -1309,10 +1328,16 @@ fn emit_ffi_unsafe_type_lint(
             help,
             note,
             span_note,
+            fields,
         });
     }
 
-    fn check_for_opaque_ty(&mut self, sp: Span, ty: Ty<'tcx>) -> bool {
+    fn check_for_opaque_ty(
+        &mut self,
+        sp: Span,
+        ty: Ty<'tcx>,
+        fields: Vec<ImproperCTypesField<'tcx>>,
+    ) -> bool {
         struct ProhibitOpaqueTypes;
         impl<'tcx> ty::visit::TypeVisitor<TyCtxt<'tcx>> for ProhibitOpaqueTypes {
             type Result = ControlFlow<Ty<'tcx>>;
@@ -1338,21 +1363,30 @@ fn visit_ty(&mut self, ty: Ty<'tcx>) -> Self::Result {
             .visit_with(&mut ProhibitOpaqueTypes)
             .break_value()
         {
-            self.emit_ffi_unsafe_type_lint(ty, sp, fluent::lint_improper_ctypes_opaque, None);
+            self.emit_ffi_unsafe_type_lint(
+                ty,
+                sp,
+                fluent::lint_improper_ctypes_opaque,
+                None,
+                fields,
+            );
             true
         } else {
             false
         }
     }
 
+    /// Checks `ty` and reports it if it isn't FFI-safe. `outer_fields` are the fields of
+    /// `#[repr(C)]` types through which `ty` is reached from the checked item, outermost first.
     fn check_type_for_ffi_and_report_errors(
         &mut self,
         sp: Span,
         ty: Ty<'tcx>,
         is_static: bool,
         is_return_type: bool,
+        outer_fields: Vec<ImproperCTypesField<'tcx>>,
     ) {
-        if self.check_for_opaque_ty(sp, ty) {
+        if self.check_for_opaque_ty(sp, ty, outer_fields.clone()) {
             // We've already emitted an error due to an opaque type.
             return;
         }
@@ -1362,7 +1396,7 @@ fn check_type_for_ffi_and_report_errors(
         // C doesn't really support passing arrays by value - the only way to pass an array by value
         // is through a struct. So, first test that the top level isn't an array, and then
         // recursively check the types inside.
-        if !is_static && self.check_for_array_ty(sp, ty) {
+        if !is_static && self.check_for_array_ty(sp, ty, outer_fields.clone()) {
             return;
         }
 
@@ -1373,8 +1407,11 @@ fn check_type_for_ffi_and_report_errors(
             return;
         }
 
-        let mut acc = CTypesVisitorState { cache: FxHashSet::default(), base_ty: ty };
-        match self.check_type_for_ffi(&mut acc, ty) {
+        let mut acc =
+            CTypesVisitorState { cache: FxHashSet::default(), base_ty: ty, fields: Vec::new() };
+        let result = self.check_type_for_ffi(&mut acc, ty);
+        let fields = outer_fields.into_iter().chain(acc.fields.into_iter().rev()).collect();
+        match result {
             FfiResult::FfiSafe => {}
             FfiResult::FfiPhantom(ty) => {
                 self.emit_ffi_unsafe_type_lint(
@@ -1382,10 +1419,11 @@ fn check_type_for_ffi_and_report_errors(
                     sp,
                     fluent::lint_improper_ctypes_only_phantomdata,
                     None,
+                    fields,
                 );
             }
             FfiResult::FfiUnsafe { ty, reason, help } => {
-                self.emit_ffi_unsafe_type_lint(ty, sp, reason, help);
+                self.emit_ffi_unsafe_type_lint(ty, sp, reason, help, fields);
             }
         }
     }
@@ -1399,14 +1437,18 @@ fn check_fn(&mut self, def_id: LocalDefId, decl: &'tcx hir::FnDecl<'_>) {
         let sig = self.cx.tcx.instantiate_bound_regions_with_erased(sig);
 
         for (input_ty, input_hir) in iter::zip(sig.inputs(), decl.inputs) {
-            for (fn_ptr_ty, span) in self.find_fn_ptr_ty_with_external_abi(input_hir, *input_ty) {
-                self.check_type_for_ffi_and_report_errors(span, fn_ptr_ty, false, false);
+            for (fn_ptr_ty, span, fields) in
+                self.find_fn_ptr_ty_with_external_abi(input_hir, *input_ty)
+            {
+                self.check_type_for_ffi_and_report_errors(span, fn_ptr_ty, false, false, fields);
             }
         }
 
         if let hir::FnRetTy::Return(ret_hir) = decl.output {
-            for (fn_ptr_ty, span) in self.find_fn_ptr_ty_with_external_abi(ret_hir, sig.output()) {
-                self.check_type_for_ffi_and_report_errors(span, fn_ptr_ty, false, true);
+            for (fn_ptr_ty, span, fields) in
+                self.find_fn_ptr_ty_with_external_abi(ret_hir, sig.output())
+            {
+                self.check_type_for_ffi_and_report_errors(span, fn_ptr_ty, false, true, fields);
             }
         }
     }
@@ -1417,17 +1459,29 @@ fn check_foreign_fn(&mut self, def_id: LocalDefId, decl: &'tcx hir::FnDecl<'_>)
         let sig = self.cx.tcx.instantiate_bound_regions_with_erased(sig);
 
         for (input_ty, input_hir) in iter::zip(sig.inputs(), decl.inputs) {
-            self.check_type_for_ffi_and_report_errors(input_hir.span, *input_ty, false, false);
+            self.check_type_for_ffi_and_report_errors(
+                input_hir.span,
+                *input_ty,
+                false,
+                false,
+                Vec::new(),
+            );
         }
 
         if let hir::FnRetTy::Return(ret_hir) = decl.output {
-            self.check_type_for_ffi_and_report_errors(ret_hir.span, sig.output(), false, true);
+            self.check_type_for_ffi_and_report_errors(
+                ret_hir.span,
+                sig.output(),
+                false,
+                true,
+                Vec::new(),
+            );
         }
     }
 
     fn check_foreign_static(&mut self, id: hir::OwnerId, span: Span) {
         let ty = self.cx.tcx.type_of(id).instantiate_identity();
-        self.check_type_for_ffi_and_report_errors(span, ty, true, false);
+        self.check_type_for_ffi_and_report_errors(span, ty, true, false, Vec::new());
     }
 
     fn is_internal_abi(&self, abi: ExternAbi) -> bool {
@@ -1437,18 +1491,27 @@ fn is_internal_abi(&self, abi: ExternAbi) -> bool {
         )
     }
 
-    /// Find any fn-ptr types with external ABIs in `ty`.
+    /// Find any fn-ptr types with external ABIs in `ty`, along with the span of `hir_ty` they
+    /// are written at and the fields of `#[repr(C)]` types they are reached through.
     ///
-    /// For example, `Option<extern "C" fn()>` returns `extern "C" fn()`
+    /// For example, `Option<extern "C" fn()>` returns `extern "C" fn()`. The fields of
+    /// `#[repr(C)]` structs and unions are searched when their types depend on generic
+    /// parameters, so `Callbacks<String>` with a field of type `extern "C" fn(T)` returns
+    /// `extern "C" fn(String)`; the other fields are checked where they are defined.
     fn find_fn_ptr_ty_with_external_abi(
         &self,
         hir_ty: &hir::Ty<'tcx>,
         ty: Ty<'tcx>,
-    ) -> Vec<(Ty<'tcx>, Span)> {
+    ) -> Vec<(Ty<'tcx>, Span, Vec<ImproperCTypesField<'tcx>>)> {
         struct FnPtrFinder<'a, 'b, 'tcx> {
             visitor: &'a ImproperCTypesVisitor<'b, 'tcx>,
             spans: Vec<Span>,
-            tys: Vec<Ty<'tcx>>,
+            tys: Vec<(Ty<'tcx>, Vec<ImproperCTypesField<'tcx>>)>,
+            /// The fields the visitor is in, outermost first.
+            fields: Vec<ImproperCTypesField<'tcx>>,
+            /// The ADTs whose fields were searched, to not recurse infinitely. The depth is limited
+            /// too, as an ADT can contain itself with other generic arguments.
+            searched_adts: FxHashSet<Ty<'tcx>>,
         }
 
         impl<'a, 'b, 'tcx> hir::intravisit::Visitor<'_> for FnPtrFinder<'a, 'b, 'tcx> {
@@ -1471,18 +1534,69 @@ fn visit_ty(&mut self, ty: Ty<'tcx>) -> Self::Result {
                 if let ty::FnPtr(_, hdr) = ty.kind()
                     && !self.visitor.is_internal_abi(hdr.abi)
                 {
-                    self.tys.push(ty);
+                    self.tys.push((ty, self.fields.clone()));
+                }
+
+                if let ty::Adt(def, args) = *ty.kind()
+                    && def.repr().c()
+                    && !def.is_enum()
+                    && self.visitor.cx.tcx.recursion_limit().value_within_limit(self.fields.len())
+                    && self.searched_adts.insert(ty)
+                {
+                    let cx = self.visitor.cx;
+                    for field in def.all_fields() {
+                        if !cx.tcx.type_of(field.did).instantiate_identity().has_param() {
+                            continue;
+                        }
+                        let field_ty = field.ty(cx.tcx, args);
+                        let field_ty = cx
+                            .tcx
+                            .try_normalize_erasing_regions(cx.typing_env(), field_ty)
+                            .unwrap_or(field_ty);
+                        self.fields.push(ImproperCTypesField {
+                            span: cx.tcx.def_span(field.did),
+                            field: field.name,
+                            adt: ty,
+                        });
+                        field_ty.visit_with(self);
+                        self.fields.pop();
+                    }
                 }
 
                 ty.super_visit_with(self)
             }
         }
 
-        let mut visitor = FnPtrFinder { visitor: self, spans: Vec::new(), tys: Vec::new() };
+        let mut visitor = FnPtrFinder {
+            visitor: self,
+            spans: Vec::new(),
+            tys: Vec::new(),
+            fields: Vec::new(),
+            searched_adts: FxHashSet::default(),
+        };
         ty.visit_with(&mut visitor);
         hir::intravisit::Visitor::visit_ty(&mut visitor, hir_ty);
 
-        iter::zip(visitor.tys.drain(..), visitor.spans.drain(..)).collect()
+        // A function pointer given as the generic argument of a field is found both in the
+        // arguments and in the field, only keep the first.
+        let written: Vec<_> =
+            visitor.tys.iter().filter(|(_, fields)| fields.is_empty()).map(|&(ty, _)| ty).collect();
+        visitor.tys.retain(|(ty, fields)| fields.is_empty() || !written.contains(ty));
+
+        // The function pointers written in `hir_ty` are found in the same order in `ty`, unless
+        // some of them are behind type aliases. Those reached through fields, or that can't be
+        // matched with where they are written, are reported at `hir_ty`.
+        let mut spans =
+            if written.len() == visitor.spans.len() { visitor.spans } else { Vec::new() }
+                .into_iter();
+        visitor
+            .tys
+            .into_iter()
+            .map(|(ty, fields)| {
+                let span = if fields.is_empty() { spans.next() } else { None };
+                (ty, span.unwrap_or(hir_ty.span), fields)
+            })
+            .collect()
     }
 }
 
@@ -1515,8 +1629,8 @@ fn check_ty_maybe_containing_foreign_fnptr<'tcx>(
         ty: Ty<'tcx>,
     ) {
         let mut vis = ImproperCTypesVisitor { cx, mode: CItemKind::Definition };
-        for (fn_ptr_ty, span) in vis.find_fn_ptr_ty_with_external_abi(hir_ty, ty) {
-            vis.check_type_for_ffi_and_report_errors(span, fn_ptr_ty, true, false);
+        for (fn_ptr_ty, span, fields) in vis.find_fn_ptr_ty_with_external_abi(hir_ty, ty) {
+            vis.check_type_for_ffi_and_report_errors(span, fn_ptr_ty, true, false, fields);
         }
     }
 }
@@ -1,24 +1,31 @@
 use std::iter;
 use std::ops::ControlFlow;
 
+use rustc_abi::ExternAbi;
 use rustc_ast as ast;
 use rustc_ast::util::{classify, parser};
 use rustc_ast::{ExprKind, StmtKind};
+use rustc_data_structures::fx::FxHashSet;
 use rustc_errors::{MultiSpan, pluralize};
 use rustc_hir::def::{DefKind, Res};
-use rustc_hir::def_id::DefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_hir::intravisit::FnKind;
 use rustc_hir::{self as hir, LangItem};
 use rustc_infer::traits::util::elaborate;
+use rustc_middle::mir;
+use rustc_middle::mir::visit::Visitor;
 use rustc_middle::ty::{self, Ty, adjustment};
+use rustc_session::lint::Level;
 use rustc_session::{declare_lint, declare_lint_pass, impl_lint_pass};
 use rustc_span::{BytePos, Span, Symbol, kw, sym};
 use tracing::instrument;
 
+use crate::blocking_in_async::is_blocking;
 use crate::lints::{
-    PathStatementDrop, PathStatementDropSub, PathStatementNoEffect, UnusedAllocationDiag,
-    UnusedAllocationMutDiag, UnusedClosure, UnusedCoroutine, UnusedDef, UnusedDefSuggestion,
-    UnusedDelim, UnusedDelimSuggestion, UnusedImportBracesDiag, UnusedOp, UnusedOpSuggestion,
-    UnusedResult,
+    MissingMustUseDiag, PathStatementDrop, PathStatementDropSub, PathStatementNoEffect,
+    UnusedAllocationDiag, UnusedAllocationMutDiag, UnusedClosure, UnusedCoroutine, UnusedDef,
+    UnusedDefSuggestion, UnusedDelim, UnusedDelimSuggestion, UnusedImportBracesDiag, UnusedOp,
+    UnusedOpSuggestion, UnusedResult,
 };
 use crate::{EarlyContext, EarlyLintPass, LateContext, LateLintPass, Lint, LintContext};
 
@@ -241,143 +248,6 @@ fn check_fn_must_use(
             }
         }
 
-        /// A path through a type to a must_use source. Contains useful info for the lint.
-        #[derive(Debug)]
-        enum MustUsePath {
-            /// Suppress must_use checking.
-            Suppressed,
-            /// The root of the normal must_use lint with an optional message.
-            Def(Span, DefId, Option<Symbol>),
-            Boxed(Box<Self>),
-            Pinned(Box<Self>),
-            Opaque(Box<Self>),
-            TraitObject(Box<Self>),
-            TupleElement(Vec<(usize, Self)>),
-            Array(Box<Self>, u64),
-            /// The root of the unused_closures lint.
-            Closure(Span),
-            /// The root of the unused_coroutines lint.
-            Coroutine(Span),
-        }
-
-        #[instrument(skip(cx, expr), level = "debug", ret)]
-        fn is_ty_must_use<'tcx>(
-            cx: &LateContext<'tcx>,
-            ty: Ty<'tcx>,
-            expr: &hir::Expr<'_>,
-            span: Span,
-        ) -> Option<MustUsePath> {
-            if ty.is_unit()
-                || !ty.is_inhabited_from(
-                    cx.tcx,
-                    cx.tcx.parent_module(expr.hir_id).to_def_id(),
-                    cx.typing_env(),
-                )
-            {
-                return Some(MustUsePath::Suppressed);
-            }
-
-            match *ty.kind() {
-                ty::Adt(..) if let Some(boxed) = ty.boxed_ty() => {
-                    is_ty_must_use(cx, boxed, expr, span)
-                        .map(|inner| MustUsePath::Boxed(Box::new(inner)))
-                }
-                ty::Adt(def, args) if cx.tcx.is_lang_item(def.did(), LangItem::Pin) => {
-                    let pinned_ty = args.type_at(0);
-                    is_ty_must_use(cx, pinned_ty, expr, span)
-                        .map(|inner| MustUsePath::Pinned(Box::new(inner)))
-                }
-                ty::Adt(def, _) => is_def_must_use(cx, def.did(), span),
-                ty::Alias(ty::Opaque | ty::Projection, ty::AliasTy { def_id: def, .. }) => {
-                    elaborate(
-                        cx.tcx,
-                        cx.tcx.explicit_item_super_predicates(def).iter_identity_copied(),
-                    )
-                    // We only care about self bounds for the impl-trait
-                    .filter_only_self()
-                    .find_map(|(pred, _span)| {
-                        // We only look at the `DefId`, so it is safe to skip the binder here.
-                        if let ty::ClauseKind::Trait(ref poly_trait_predicate) =
-                            pred.kind().skip_binder()
-                        {
-                            let def_id = poly_trait_predicate.trait_ref.def_id;
-
-                            is_def_must_use(cx, def_id, span)
-                        } else {
-                            None
-                        }
-                    })
-                    .map(|inner| MustUsePath::Opaque(Box::new(inner)))
-                }
-                ty::Dynamic(binders, _, _) => binders.iter().find_map(|predicate| {
-                    if let ty::ExistentialPredicate::Trait(ref trait_ref) = predicate.skip_binder()
-                    {
-                        let def_id = trait_ref.def_id;
-                        is_def_must_use(cx, def_id, span)
-                            .map(|inner| MustUsePath::TraitObject(Box::new(inner)))
-                    } else {
-                        None
-                    }
-                }),
-                ty::Tuple(tys) => {
-                    let elem_exprs = if let hir::ExprKind::Tup(elem_exprs) = expr.kind {
-                        debug_assert_eq!(elem_exprs.len(), tys.len());
-                        elem_exprs
-                    } else {
-                        &[]
-                    };
-
-                    // Default to `expr`.
-                    let elem_exprs = elem_exprs.iter().chain(iter::repeat(expr));
-
-                    let nested_must_use = tys
-                        .iter()
-                        .zip(elem_exprs)
-                        .enumerate()
-                        .filter_map(|(i, (ty, expr))| {
-                            is_ty_must_use(cx, ty, expr, expr.span).map(|path| (i, path))
-                        })
-                        .collect::<Vec<_>>();
-
-                    if !nested_must_use.is_empty() {
-                        Some(MustUsePath::TupleElement(nested_must_use))
-                    } else {
-                        None
-                    }
-                }
-                ty::Array(ty, len) => match len.try_to_target_usize(cx.tcx) {
-                    // If the array is empty we don't lint, to avoid false positives
-                    Some(0) | None => None,
-                    // If the array is definitely non-empty, we can do `#[must_use]` checking.
-                    Some(len) => is_ty_must_use(cx, ty, expr, span)
-                        .map(|inner| MustUsePath::Array(Box::new(inner), len)),
-                },
-                ty::Closure(..) | ty::CoroutineClosure(..) => Some(MustUsePath::Closure(span)),
-                ty::Coroutine(def_id, ..) => {
-                    // async fn should be treated as "implementor of `Future`"
-                    let must_use = if cx.tcx.coroutine_is_async(def_id) {
-                        let def_id = cx.tcx.lang_items().future_trait()?;
-                        is_def_must_use(cx, def_id, span)
-                            .map(|inner| MustUsePath::Opaque(Box::new(inner)))
-                    } else {
-                        None
-                    };
-                    must_use.or(Some(MustUsePath::Coroutine(span)))
-                }
-                _ => None,
-            }
-        }
-
-        fn is_def_must_use(cx: &LateContext<'_>, def_id: DefId, span: Span) -> Option<MustUsePath> {
-            if let Some(attr) = cx.tcx.get_attr(def_id, sym::must_use) {
-                // check for #[must_use = "..."]
-                let reason = attr.value_str();
-                Some(MustUsePath::Def(span, def_id, reason))
-            } else {
-                None
-            }
-        }
-
         // Returns whether further errors should be suppressed because either a lint has been
         // emitted or the type should be ignored.
         fn check_must_use_def(
@@ -527,6 +397,387 @@ fn emit_must_use_untranslated(
     }
 }
 
+/// A path through a type to a must_use source. Contains useful info for the lint.
+#[derive(Debug)]
+enum MustUsePath {
+    /// Suppress must_use checking.
+    Suppressed,
+    /// The root of the normal must_use lint with an optional message.
+    Def(Span, DefId, Option<Symbol>),
+    Boxed(Box<Self>),
+    Pinned(Box<Self>),
+    Opaque(Box<Self>),
+    TraitObject(Box<Self>),
+    TupleElement(Vec<(usize, Self)>),
+    Array(Box<Self>, u64),
+    /// The root of the unused_closures lint.
+    Closure(Span),
+    /// The root of the unused_coroutines lint.
+    Coroutine(Span),
+}
+
+#[instrument(skip(cx, expr), level = "debug", ret)]
+fn is_ty_must_use<'tcx>(
+    cx: &LateContext<'tcx>,
+    ty: Ty<'tcx>,
+    expr: &hir::Expr<'_>,
+    span: Span,
+) -> Option<MustUsePath> {
+    if ty.is_unit()
+        || !ty.is_inhabited_from(
+            cx.tcx,
+            cx.tcx.parent_module(expr.hir_id).to_def_id(),
+            cx.typing_env(),
+        )
+    {
+        return Some(MustUsePath::Suppressed);
+    }
+
+    match *ty.kind() {
+        ty::Adt(..) if let Some(boxed) = ty.boxed_ty() => {
+            is_ty_must_use(cx, boxed, expr, span).map(|inner| MustUsePath::Boxed(Box::new(inner)))
+        }
+        ty::Adt(def, args) if cx.tcx.is_lang_item(def.did(), LangItem::Pin) => {
+            let pinned_ty = args.type_at(0);
+            is_ty_must_use(cx, pinned_ty, expr, span)
+                .map(|inner| MustUsePath::Pinned(Box::new(inner)))
+        }
+        ty::Adt(def, _) => is_def_must_use(cx, def.did(), span),
+        ty::Alias(ty::Opaque | ty::Projection, ty::AliasTy { def_id: def, .. }) => {
+            elaborate(cx.tcx, cx.tcx.explicit_item_super_predicates(def).iter_identity_copied())
+                // We only care about self bounds for the impl-trait
+                .filter_only_self()
+                .find_map(|(pred, _span)| {
+                    // We only look at the `DefId`, so it is safe to skip the binder here.
+                    if let ty::ClauseKind::Trait(ref poly_trait_predicate) =
+                        pred.kind().skip_binder()
+                    {
+                        let def_id = poly_trait_predicate.trait_ref.def_id;
+
+                        is_def_must_use(cx, def_id, span)
+                    } else {
+                        None
+                    }
+                })
+                .map(|inner| MustUsePath::Opaque(Box::new(inner)))
+        }
+        ty::Dynamic(binders, _, _) => binders.iter().find_map(|predicate| {
+            if let ty::ExistentialPredicate::Trait(ref trait_ref) = predicate.skip_binder() {
+                let def_id = trait_ref.def_id;
+                is_def_must_use(cx, def_id, span)
+                    .map(|inner| MustUsePath::TraitObject(Box::new(inner)))
+            } else {
+                None
+            }
+        }),
+        ty::Tuple(tys) => {
+            let elem_exprs = if let hir::ExprKind::Tup(elem_exprs) = expr.kind {
+                debug_assert_eq!(elem_exprs.len(), tys.len());
+                elem_exprs
+            } else {
+                &[]
+            };
+
+            // Default to `expr`.
+            let elem_exprs = elem_exprs.iter().chain(iter::repeat(expr));
+
+            let nested_must_use = tys
+                .iter()
+                .zip(elem_exprs)
+                .enumerate()
+                .filter_map(|(i, (ty, expr))| {
+                    is_ty_must_use(cx, ty, expr, expr.span).map(|path| (i, path))
+                })
+                .collect::<Vec<_>>();
+
+            if !nested_must_use.is_empty() {
+                Some(MustUsePath::TupleElement(nested_must_use))
+            } else {
+                None
+            }
+        }
+        ty::Array(ty, len) => match len.try_to_target_usize(cx.tcx) {
+            // If the array is empty we don't lint, to avoid false positives
+            Some(0) | None => None,
+            // If the array is definitely non-empty, we can do `#[must_use]` checking.
+            Some(len) => is_ty_must_use(cx, ty, expr, span)
+                .map(|inner| MustUsePath::Array(Box::new(inner), len)),
+        },
+        ty::Closure(..) | ty::CoroutineClosure(..) => Some(MustUsePath::Closure(span)),
+        ty::Coroutine(def_id, ..) => {
+            // async fn should be treated as "implementor of `Future`"
+            let must_use = if cx.tcx.coroutine_is_async(def_id) {
+                let def_id = cx.tcx.lang_items().future_trait()?;
+                is_def_must_use(cx, def_id, span).map(|inner| MustUsePath::Opaque(Box::new(inner)))
+            } else {
+                None
+            };
+            must_use.or(Some(MustUsePath::Coroutine(span)))
+        }
+        _ => None,
+    }
+}
+
+fn is_def_must_use(cx: &LateContext<'_>, def_id: DefId, span: Span) -> Option<MustUsePath> {
+    if let Some(attr) = cx.tcx.get_attr(def_id, sym::must_use) {
+        // check for #[must_use = "..."]
+        let reason = attr.value_str();
+        Some(MustUsePath::Def(span, def_id, reason))
+    } else {
+        None
+    }
+}
+
+declare_lint! {
+    /// The `missing_must_use` lint detects public functions which have no
+    /// effect besides computing their result, but which aren't marked
+    /// `#[must_use]`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,compile_fail
+    /// #![deny(missing_must_use)]
+    /// pub fn double(x: u32) -> u32 {
+    ///     x * 2
+    /// }
+    /// ```
+    ///
+    /// {{produces}}
+    ///
+    /// ### Explanation
+    ///
+    /// Calling a function which takes no mutable references or values with
+    /// interior mutability, reaches no mutable statics and does no I/O only
+    /// computes its result, so discarding the result is almost certainly a
+    /// mistake. Marking the function with the [`must_use` attribute] makes
+    /// the [`unused_must_use` lint] warn about such calls.
+    ///
+    /// The function is analyzed in its MIR, along with the functions of the
+    /// same crate that it calls. The functions of other crates are assumed
+    /// to have no side effects, unless they are foreign functions or known
+    /// to do I/O, like `std::fs::read` or the functions behind `println!`.
+    /// Functions whose result is already of a `#[must_use]` type, like
+    /// `Result`, are not reported.
+    ///
+    /// This lint is "allow" by default because it analyzes the bodies of all
+    /// public functions, and because the result of some functions without
+    /// side effects is meant to be ignored at times.
+    ///
+    /// [`must_use` attribute]: https://doc.rust-lang.org/reference/attributes/diagnostics.html#the-must_use-attribute
+    /// [`unused_must_use` lint]: warn-by-default.html#unused-must-use
+    pub MISSING_MUST_USE,
+    Allow,
+    "detects public functions without side effects which aren't marked `#[must_use]`"
+}
+
+declare_lint_pass!(MissingMustUse => [MISSING_MUST_USE]);
+
+impl<'tcx> LateLintPass<'tcx> for MissingMustUse {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        kind: FnKind<'tcx>,
+        _: &'tcx hir::FnDecl<'tcx>,
+        body: &'tcx hir::Body<'tcx>,
+        span: Span,
+        def_id: LocalDefId,
+    ) {
+        let Some(header) = kind.header() else { return };
+        if header.abi != ExternAbi::Rust
+            || span.from_expansion()
+            || !cx.effective_visibilities.is_exported(def_id)
+            || cx.tcx.has_attr(def_id, sym::must_use)
+        {
+            return;
+        }
+        // `#[must_use]` has no effect on the methods of trait impls.
+        if let Some(impl_def_id) = cx.tcx.impl_of_method(def_id.to_def_id())
+            && cx.tcx.trait_id_of_impl(impl_def_id).is_some()
+        {
+            return;
+        }
+        // Avoid analyzing the MIR of the function if the lint is allowed.
+        let (level, _) =
+            cx.tcx.lint_level_at_node(MISSING_MUST_USE, cx.tcx.local_def_id_to_hir_id(def_id));
+        if level == Level::Allow {
+            return;
+        }
+
+        let output = cx.tcx.fn_sig(def_id).instantiate_identity().output();
+        let output = cx.tcx.instantiate_bound_regions_with_erased(output);
+        if is_ty_must_use(cx, output, body.value, span).is_some()
+            || !is_pure(cx, def_id.to_def_id(), &mut FxHashSet::default())
+        {
+            return;
+        }
+
+        let indent = cx.sess().source_map().indentation_before(span).unwrap_or_default();
+        cx.emit_span_lint(
+            MISSING_MUST_USE,
+            cx.tcx.def_span(def_id),
+            MissingMustUseDiag {
+                descr: cx.tcx.def_descr(def_id.to_def_id()),
+                name: cx.tcx.item_name(def_id.to_def_id()),
+                suggestion: span.shrink_to_lo(),
+                indent,
+            },
+        );
+    }
+}
+
+/// The diagnostic items of the standard library functions which do I/O,
+/// besides those which block the current thread.
+const IO_DIAGNOSTIC_ITEMS: &[Symbol] = &[
+    sym::instant_now,
+    sym::io_eprint,
+    sym::io_print,
+    sym::io_stderr,
+    sym::io_stdin,
+    sym::io_stdout,
+    sym::process_exit,
+    sym::system_time_now,
+];
+
+fn does_io(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    cx.tcx.get_diagnostic_name(def_id).is_some_and(|name| IO_DIAGNOSTIC_ITEMS.contains(&name))
+        || cx.tcx.trait_of_item(def_id).is_some_and(|trait_def_id| {
+            matches!(cx.tcx.get_diagnostic_name(trait_def_id), Some(sym::IoRead | sym::IoWrite))
+        })
+        || is_blocking(cx, def_id)
+}
+
+/// Whether calling `def_id` has no effect besides computing its result: it
+/// takes no mutable references or values with interior mutability, its MIR
+/// reaches no mutable statics and only calls functions which are pure too.
+/// The functions of other crates are assumed to be pure unless they are
+/// foreign or do I/O.
+fn is_pure<'tcx>(cx: &LateContext<'tcx>, def_id: DefId, visited: &mut FxHashSet<DefId>) -> bool {
+    if cx.tcx.is_foreign_item(def_id) || does_io(cx, def_id) {
+        return false;
+    }
+    // Recursive calls don't add effects.
+    if !def_id.is_local() || !visited.insert(def_id) || !cx.tcx.is_mir_available(def_id) {
+        return true;
+    }
+
+    let typing_env = ty::TypingEnv::post_analysis(cx.tcx, def_id);
+    let body = cx.tcx.optimized_mir(def_id);
+    // The environment of a closure refers to the locals of the function which
+    // defines it, which it may mutate.
+    let skipped_args = if cx.tcx.is_closure_like(def_id) { 1 } else { 0 };
+    if body.args_iter().skip(skipped_args).any(|arg| {
+        reaches_mutable_state(cx, typing_env, body.local_decls[arg].ty, &mut FxHashSet::default())
+    }) {
+        return false;
+    }
+
+    let mut checker = PurityChecker { cx, typing_env, visited, is_pure: true };
+    checker.visit_body(body);
+    checker.is_pure
+}
+
+/// Whether a value of type `ty` gives access to state which can be mutated,
+/// through a mutable reference or pointer, a trait object or interior
+/// mutability.
+fn reaches_mutable_state<'tcx>(
+    cx: &LateContext<'tcx>,
+    typing_env: ty::TypingEnv<'tcx>,
+    ty: Ty<'tcx>,
+    seen: &mut FxHashSet<Ty<'tcx>>,
+) -> bool {
+    if !seen.insert(ty) {
+        return false;
+    }
+    match *ty.kind() {
+        ty::Ref(_, _, ty::Mutability::Mut)
+        | ty::RawPtr(_, ty::Mutability::Mut)
+        | ty::Dynamic(..) => true,
+        ty::Ref(_, ty, _)
+        | ty::RawPtr(ty, _)
+        | ty::Array(ty, _)
+        | ty::Slice(ty)
+        | ty::Pat(ty, _) => reaches_mutable_state(cx, typing_env, ty, seen),
+        ty::Tuple(tys) => tys.iter().any(|ty| reaches_mutable_state(cx, typing_env, ty, seen)),
+        ty::Adt(def, args) => {
+            !ty.is_freeze(cx.tcx, typing_env)
+                || def.all_fields().any(|field| {
+                    reaches_mutable_state(cx, typing_env, field.ty(cx.tcx, args), seen)
+                })
+        }
+        _ => false,
+    }
+}
+
+struct PurityChecker<'a, 'tcx> {
+    cx: &'a LateContext<'tcx>,
+    typing_env: ty::TypingEnv<'tcx>,
+    visited: &'a mut FxHashSet<DefId>,
+    is_pure: bool,
+}
+
+impl<'tcx> PurityChecker<'_, 'tcx> {
+    fn check_call(&mut self, func: &mir::Operand<'tcx>) {
+        // Calls through function pointers can't be known.
+        let Some((mut callee, args)) = func.const_fn_def() else {
+            self.is_pure = false;
+            return;
+        };
+        if self.cx.tcx.trait_of_item(callee).is_some() && !does_io(self.cx, callee) {
+            match ty::Instance::try_resolve(self.cx.tcx, self.typing_env, callee, args) {
+                Ok(Some(instance)) => match instance.def {
+                    ty::InstanceKind::Item(def_id) => callee = def_id,
+                    ty::InstanceKind::Virtual(..) | ty::InstanceKind::FnPtrShim(..) => {
+                        self.is_pure = false;
+                        return;
+                    }
+                    // The other shims are generated by the compiler, and only
+                    // call the functions of their instance.
+                    _ => return,
+                },
+                // Methods of generic parameters can't be known.
+                Ok(None) | Err(_) => {
+                    self.is_pure = false;
+                    return;
+                }
+            }
+        }
+        if !is_pure(self.cx, callee, self.visited) {
+            self.is_pure = false;
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for PurityChecker<'_, 'tcx> {
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: mir::Location) {
+        match &terminator.kind {
+            mir::TerminatorKind::Call { func, .. } | mir::TerminatorKind::TailCall { func, .. } => {
+                self.check_call(func)
+            }
+            mir::TerminatorKind::InlineAsm { .. } => self.is_pure = false,
+            _ => {}
+        }
+        self.super_terminator(terminator, location);
+    }
+
+    fn visit_const_operand(&mut self, constant: &mir::ConstOperand<'tcx>, location: mir::Location) {
+        let tcx = self.cx.tcx;
+        if let Some(def_id) = constant.check_static_ptr(tcx)
+            && (tcx.is_mutable_static(def_id)
+                || !tcx.type_of(def_id).instantiate_identity().is_freeze(tcx, self.typing_env))
+        {
+            self.is_pure = false;
+        }
+        self.super_const_operand(constant, location);
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: mir::Location) {
+        if let mir::Rvalue::ThreadLocalRef(_) = rvalue {
+            self.is_pure = false;
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
+
 declare_lint! {
     /// The `path_statements` lint detects path statements with no effect.
     ///
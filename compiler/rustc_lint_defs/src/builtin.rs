@@ -28,6 +28,7 @@
         BINDINGS_WITH_VARIANT_NAME,
         BREAK_WITH_LABEL_AND_LOOP,
         CENUM_IMPL_DROP_CAST,
+        CFG_ONLY_CRATE_DEPENDENCIES,
         COHERENCE_LEAK_CHECK,
         CONFLICTING_REPR_HINTS,
         CONST_EVALUATABLE_UNCHECKED,
@@ -582,6 +583,66 @@
     crate_level_only
 }
 
+declare_lint! {
+    /// The `cfg_only_crate_dependencies` lint detects crate dependencies that
+    /// are only used in code compiled under some `cfg`s.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,ignore (needs extern crate)
+    /// #![deny(cfg_only_crate_dependencies)]
+    ///
+    /// #[cfg(test)]
+    /// mod tests {
+    ///     use regex::Regex;
+    /// }
+    /// ```
+    ///
+    /// This will produce:
+    ///
+    /// ```text
+    /// error: extern crate `regex` is only used under `cfg(test)` in crate `lint_example`
+    ///  --> src/lib.rs:5:9
+    ///   |
+    /// 5 |     use regex::Regex;
+    ///   |         ^^^^^
+    ///   |
+    ///   = help: only pass the dependency when `cfg(test)` holds, for example by making it a dev-dependency or an optional dependency
+    /// note: the lint level is defined here
+    ///  --> src/lib.rs:1:9
+    ///   |
+    /// 1 | #![deny(cfg_only_crate_dependencies)]
+    ///   |         ^^^^^^^^^^^^^^^^^^^^^^^^^^^
+    /// ```
+    ///
+    /// ### Explanation
+    ///
+    /// A dependency which is only used under some `cfg`s, for example only
+    /// in tests or only when a feature is enabled, is still built when these
+    /// `cfg`s don't hold. This lint can be enabled to detect such dependencies
+    /// (more specifically, any dependency passed with the `--extern`
+    /// command-line flag whose references via [`use`], [`extern crate`], or in
+    /// any [path] are all in items, statements or expressions with a `#[cfg]`
+    /// attribute), so that the build configuration only passes them under the
+    /// same conditions. It also detects the [`extern crate`] items which are
+    /// compiled unconditionally while their crate is only referenced under
+    /// some `cfg`s.
+    ///
+    /// This lint is "allow" by default because it only sees the `cfg`s which
+    /// hold in the current compilation, so a dependency that is also used
+    /// under `cfg`s which don't hold is reported too, and because it has the
+    /// same false positives as the [`unused_crate_dependencies`] lint when a
+    /// package consists of multiple crates.
+    ///
+    /// [path]: https://doc.rust-lang.org/reference/paths.html
+    /// [`use`]: https://doc.rust-lang.org/reference/items/use-declarations.html
+    /// [`extern crate`]: https://doc.rust-lang.org/reference/items/extern-crates.html
+    /// [`unused_crate_dependencies`]: #unused-crate-dependencies
+    pub CFG_ONLY_CRATE_DEPENDENCIES,
+    Allow,
+    "crate dependencies that are only used under some `cfg`s"
+}
+
 declare_lint! {
     /// The `unused_qualifications` lint detects unnecessarily qualified
     /// names.
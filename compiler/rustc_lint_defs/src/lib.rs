@@ -712,6 +712,12 @@ pub enum BuiltinLintDiag {
         vis_span: Span,
         ident_span: Span,
     },
+    CfgOnlyExternCrate {
+        extern_crate: Ident,
+        cfg: String,
+        uses: Vec<Span>,
+        item_span: Span,
+    },
     AmbiguousGlobImports {
         diag: AmbiguityErrorDiag,
     },
@@ -783,6 +789,11 @@ pub enum BuiltinLintDiag {
         extern_crate: Symbol,
         local_crate: Symbol,
     },
+    CfgOnlyCrateDependency {
+        extern_crate: Symbol,
+        local_crate: Symbol,
+        cfg: String,
+    },
     WasmCAbi,
     IllFormedAttributeInput {
         suggestions: Vec<String>,
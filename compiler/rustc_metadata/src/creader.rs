@@ -10,20 +10,21 @@
 use proc_macro::bridge::client::ProcMacro;
 use rustc_ast::expand::allocator::{AllocatorKind, alloc_error_handler_name, global_fn_name};
 use rustc_ast::{self as ast, *};
-use rustc_data_structures::fx::FxHashSet;
+use rustc_data_structures::fx::{FxHashSet, FxIndexMap};
 use rustc_data_structures::owned_slice::OwnedSlice;
 use rustc_data_structures::svh::Svh;
-use rustc_data_structures::sync::{self, FreezeReadGuard, FreezeWriteGuard};
+use rustc_data_structures::sync::{self, FreezeReadGuard, FreezeWriteGuard, Lrc};
 use rustc_errors::DiagCtxtHandle;
 use rustc_expand::base::SyntaxExtension;
+use rustc_expand::proc_macro_process::{ProcMacroDylib, ProcMacros, load_remote_proc_macros};
 use rustc_fs_util::try_canonicalize;
 use rustc_hir as hir;
-use rustc_hir::def_id::{CrateNum, LOCAL_CRATE, LocalDefId, StableCrateId};
+use rustc_hir::def_id::{CrateNum, LOCAL_CRATE, LocalDefId};
 use rustc_hir::definitions::Definitions;
 use rustc_index::IndexVec;
 use rustc_middle::bug;
 use rustc_middle::ty::{TyCtxt, TyCtxtFeed};
-use rustc_session::config::{self, CrateType, ExternLocation};
+use rustc_session::config::{self, CrateType, ExternLocation, ProcMacroExecutionStrategy};
 use rustc_session::cstore::{CrateDepKind, CrateSource, ExternCrate, ExternCrateSource};
 use rustc_session::lint::{self, BuiltinLintDiag};
 use rustc_session::output::validate_crate_name;
@@ -34,7 +35,7 @@
 use tracing::{debug, info, trace};
 
 use crate::errors;
-use crate::locator::{CrateError, CrateLocator, CratePaths};
+use crate::locator::{self, CrateError, CrateLocator, CratePaths, UndeclaredCrateItem};
 use crate::rmeta::{CrateDep, CrateMetadata, CrateNumMap, CrateRoot, MetadataBlob};
 
 /// The backend's way to give the crate store access to the metadata in a library.
@@ -296,6 +297,22 @@ pub fn report_unused_deps(&self, tcx: TyCtxt<'_>) {
         }
     }
 
+    /// Indexes by name the items of the crates in the library search paths which
+    /// `is_declared` doesn't hold for, without loading them, to suggest them for
+    /// names which don't resolve.
+    pub fn find_undeclared_crate_items(
+        &self,
+        tcx: TyCtxt<'_>,
+        is_declared: &dyn Fn(Symbol) -> bool,
+    ) -> FxIndexMap<Symbol, Vec<UndeclaredCrateItem>> {
+        locator::find_undeclared_crate_items(
+            tcx.sess,
+            &*self.metadata_loader,
+            is_declared,
+            &|feature| tcx.features().enabled(feature),
+        )
+    }
+
     pub fn new(metadata_loader: Box<MetadataLoaderDyn>) -> CStore {
         CStore {
             metadata_loader,
@@ -449,10 +466,24 @@ fn register_crate(
                 None => (&source, &crate_root),
             };
             let dlsym_dylib = dlsym_source.dylib.as_ref().expect("no dylib for a proc-macro crate");
-            Some(self.dlsym_proc_macros(&dlsym_dylib.0, dlsym_root.stable_crate_id())?)
+            let decls_symbol =
+                self.sess.generate_proc_macro_decls_symbol(dlsym_root.stable_crate_id());
+            let dylib = ProcMacroDylib { path: dlsym_dylib.0.clone(), decls_symbol };
+            // Macros that run in a helper process are never loaded into the compiler.
+            let raw_proc_macros = if self.sess.opts.unstable_opts.proc_macro_execution_strategy
+                == ProcMacroExecutionStrategy::OutOfProcess
+            {
+                load_remote_proc_macros(self.sess, &dylib).map_err(|err| {
+                    CrateError::DlOpen(dylib.path.display().to_string(), format!(": {err}"))
+                })?
+            } else {
+                ProcMacros::Loaded(self.dlsym_proc_macros(&dylib.path, &dylib.decls_symbol)?)
+            };
+            Some((raw_proc_macros, Lrc::new(dylib)))
         } else {
             None
         };
+        let (raw_proc_macros, proc_macro_dylib) = raw_proc_macros.unzip();
 
         let crate_metadata = CrateMetadata::new(
             self.sess,
@@ -460,6 +491,7 @@ fn register_crate(
             metadata,
             crate_root,
             raw_proc_macros,
+            proc_macro_dylib,
             cnum,
             cnum_map,
             dep_kind,
@@ -703,13 +735,12 @@ fn resolve_crate_deps(
     fn dlsym_proc_macros(
         &self,
         path: &Path,
-        stable_crate_id: StableCrateId,
+        sym_name: &str,
     ) -> Result<&'static [ProcMacro], CrateError> {
-        let sym_name = self.sess.generate_proc_macro_decls_symbol(stable_crate_id);
         debug!("trying to dlsym proc_macros {} for symbol `{}`", path.display(), sym_name);
 
         unsafe {
-            let result = load_symbol_from_dylib::<*const &[ProcMacro]>(path, &sym_name);
+            let result = load_symbol_from_dylib::<*const &[ProcMacro]>(path, sym_name);
             match result {
                 Ok(result) => {
                     debug!("loaded dlsym proc_macros {} for symbol `{}`", path.display(), sym_name);
@@ -924,8 +955,10 @@ fn inject_allocator_crate(&mut self, krate: &ast::Crate) {
     }
 
     fn inject_forced_externs(&mut self) {
+        // `-Zapi-diff` inspects crates that the local crate never mentions, so load them too.
+        let api_diff = self.sess.opts.unstable_opts.api_diff.as_ref();
         for (name, entry) in self.sess.opts.externs.iter() {
-            if entry.force {
+            if entry.force || api_diff.is_some_and(|(old, new)| name == old || name == new) {
                 let name_interned = Symbol::intern(name);
                 if !self.used_extern_options.contains(&name_interned) {
                     self.resolve_crate(name_interned, DUMMY_SP, CrateDepKind::Explicit);
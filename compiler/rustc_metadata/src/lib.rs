@@ -25,6 +25,7 @@
 
 mod dependency_format;
 mod foreign_modules;
+mod metadata_cache;
 mod native_libs;
 mod rmeta;
 
@@ -35,10 +36,13 @@
 
 pub use creader::{DylibError, load_symbol_from_dylib};
 pub use fs::{METADATA_FILENAME, emit_wrapper_file};
+pub use metadata_cache::enable_metadata_cache;
 pub use native_libs::{
     find_native_static_library, try_find_native_dynamic_library, try_find_native_static_library,
     walk_native_lib_search_dirs,
 };
-pub use rmeta::{EncodedMetadata, METADATA_HEADER, encode_metadata, rendered_const};
+pub use rmeta::{
+    EncodedMetadata, METADATA_HEADER, describe_metadata_position, encode_metadata, rendered_const,
+};
 
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
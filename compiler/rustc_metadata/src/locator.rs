@@ -216,7 +216,7 @@
 use std::io::{Result as IoResult, Write};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
-use std::{cmp, fmt};
+use std::{cmp, fmt, iter};
 
 use rustc_data_structures::fx::{FxHashSet, FxIndexMap};
 use rustc_data_structures::memmap::Mmap;
@@ -224,6 +224,7 @@
 use rustc_data_structures::svh::Svh;
 use rustc_errors::{DiagArgValue, IntoDiagArg};
 use rustc_fs_util::try_canonicalize;
+use rustc_hir::def::DefKind;
 use rustc_session::Session;
 use rustc_session::cstore::CrateSource;
 use rustc_session::filesearch::FileSearch;
@@ -235,6 +236,7 @@
 
 use crate::creader::{Library, MetadataLoader};
 use crate::errors;
+use crate::metadata_cache;
 use crate::rmeta::{METADATA_HEADER, MetadataBlob, rustc_version};
 
 #[derive(Clone)]
@@ -272,7 +274,7 @@ pub(crate) fn new(name: Symbol, source: CrateSource) -> CratePaths {
     }
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum CrateFlavor {
     Rlib,
     Rmeta,
@@ -787,6 +789,18 @@ fn get_metadata_section<'p>(
     if !filename.exists() {
         return Err(MetadataError::NotPresent(filename));
     }
+    metadata_cache::get_or_load(filename, flavor, || {
+        load_metadata_section(target, flavor, filename, loader, cfg_version)
+    })
+}
+
+fn load_metadata_section<'p>(
+    target: &Target,
+    flavor: CrateFlavor,
+    filename: &'p Path,
+    loader: &dyn MetadataLoader,
+    cfg_version: &'static str,
+) -> Result<MetadataBlob, MetadataError<'p>> {
     let raw_bytes = match flavor {
         CrateFlavor::Rlib => {
             loader.get_rlib_metadata(target, filename).map_err(MetadataError::LoadFailure)?
@@ -879,6 +893,87 @@ pub fn list_file_metadata(
     }
 }
 
+/// An item of a crate in the library search paths which isn't declared by the
+/// local crate, as found by [`find_undeclared_crate_items`].
+#[derive(Clone, Debug)]
+pub struct UndeclaredCrateItem {
+    pub krate: Symbol,
+    /// The path of the item, starting with the name of its crate.
+    pub path: Vec<Symbol>,
+    pub def_kind: DefKind,
+    /// Whether the crate is in the sysroot, like `alloc`, rather than in a
+    /// search path given with `-L`.
+    pub in_sysroot: bool,
+}
+
+/// Reads the metadata of the crates which `extern crate` could load from the
+/// library search paths but for which `is_declared` doesn't hold, and indexes
+/// their nameable items by name. The crates are not loaded.
+pub(crate) fn find_undeclared_crate_items(
+    sess: &Session,
+    metadata_loader: &dyn MetadataLoader,
+    is_declared: &dyn Fn(Symbol) -> bool,
+    is_feature_enabled: &dyn Fn(Symbol) -> bool,
+) -> FxIndexMap<Symbol, Vec<UndeclaredCrateItem>> {
+    let mut seen_crates = FxHashSet::default();
+    let mut items: FxIndexMap<Symbol, Vec<UndeclaredCrateItem>> = Default::default();
+    for search_path in sess.target_filesearch().search_paths(PathKind::Crate) {
+        let in_sysroot = search_path.dir == sess.target_tlib_path.dir;
+        for (prefix, suffix, flavor) in [
+            ("lib", ".rlib", CrateFlavor::Rlib),
+            ("lib", ".rmeta", CrateFlavor::Rmeta),
+            (&*sess.target.dll_prefix, &*sess.target.dll_suffix, CrateFlavor::Dylib),
+        ] {
+            // The crates of the sysroot are all available as rlibs, and the
+            // dylibs there include the compiler itself.
+            if in_sysroot && flavor == CrateFlavor::Dylib {
+                continue;
+            }
+            let Some(files) = search_path.files.query(prefix, suffix) else {
+                continue;
+            };
+            for (name, file) in files {
+                // Most file names are `{prefix}{crate_name}-{hash}{suffix}`, so
+                // skip the crates which are declared before reading their
+                // metadata.
+                let name = Symbol::intern(name.split('-').next().unwrap());
+                if seen_crates.contains(&name) || is_declared(name) {
+                    continue;
+                }
+                let Ok(metadata) = get_metadata_section(
+                    &sess.target,
+                    flavor,
+                    &file.path,
+                    metadata_loader,
+                    sess.cfg_version,
+                ) else {
+                    continue;
+                };
+                let header = metadata.get_header();
+                if header.triple != sess.opts.target_triple
+                    || header.is_proc_macro_crate
+                    || is_declared(header.name)
+                    || !seen_crates.insert(header.name)
+                {
+                    continue;
+                }
+                debug!("indexing undeclared crate `{}` at {}", header.name, file.path.display());
+
+                for (path, def_kind) in metadata.nameable_items(is_feature_enabled) {
+                    let path: Vec<_> = iter::once(header.name).chain(path).collect();
+                    items.entry(*path.last().unwrap()).or_default().push(UndeclaredCrateItem {
+                        krate: header.name,
+                        path,
+                        def_kind,
+                        in_sysroot,
+                    });
+                }
+            }
+        }
+    }
+    items
+}
+
 fn get_flavor_from_path(path: &Path) -> CrateFlavor {
     let filename = path.file_name().unwrap().to_str().unwrap();
 
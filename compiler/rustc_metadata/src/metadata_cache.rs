@@ -0,0 +1,79 @@
+//! Keeps the metadata of the crates loaded by one session in memory for the next sessions of the
+//! same process, which saves reading and validating it again in a resident compiler
+//! (`-Zdaemon`).
+//!
+//! The cache is disabled unless [`enable_metadata_cache`] was called. Each entry remembers the
+//! size, modification time and (on Unix) inode of its file, and is only reused while all of them
+//! are unchanged, so rebuilt dependencies and updated sysroots are loaded again.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+use rustc_data_structures::fx::FxHashMap;
+
+use crate::locator::CrateFlavor;
+use crate::rmeta::MetadataBlob;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static CACHE: LazyLock<Mutex<FxHashMap<(PathBuf, CrateFlavor), (FileStamp, MetadataBlob)>>> =
+    LazyLock::new(Default::default);
+
+/// Makes the metadata of loaded crates stay in memory for the later sessions of this process.
+pub fn enable_metadata_cache() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// What identifies a version of a file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+    #[cfg(unix)]
+    ino: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<FileStamp> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(FileStamp {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            ino: std::os::unix::fs::MetadataExt::ino(&metadata),
+        })
+    }
+}
+
+/// Returns the cached metadata of `path` if its file did not change since it was loaded, and
+/// loads and caches it otherwise. Errors are not cached.
+pub(crate) fn get_or_load<E>(
+    path: &Path,
+    flavor: CrateFlavor,
+    load: impl FnOnce() -> Result<MetadataBlob, E>,
+) -> Result<MetadataBlob, E> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return load();
+    }
+    // Files without a stamp are never reused, since they cannot be checked for changes.
+    let Some(stamp) = FileStamp::of(path) else {
+        return load();
+    };
+
+    let key = (path.to_path_buf(), flavor);
+    if let Some((cached_stamp, blob)) = CACHE.lock().unwrap().get(&key)
+        && *cached_stamp == stamp
+    {
+        return Ok(blob.clone());
+    }
+
+    let blob = load()?;
+    // Do not cache a file that changed while it was being loaded.
+    if FileStamp::of(path) == Some(stamp) {
+        CACHE.lock().unwrap().insert(key, (stamp, blob.clone()));
+    }
+    Ok(blob)
+}
@@ -1,6 +1,7 @@
 // Decoding metadata from a single crate's metadata
 
 use std::iter::TrustedLen;
+use std::ops::Deref;
 use std::path::Path;
 use std::{io, iter, mem};
 
@@ -10,11 +11,13 @@
 use rustc_data_structures::captures::Captures;
 use rustc_data_structures::fingerprint::Fingerprint;
 use rustc_data_structures::fx::FxIndexMap;
-use rustc_data_structures::owned_slice::OwnedSlice;
+use rustc_data_structures::owned_slice::{OwnedSlice, slice_owned};
 use rustc_data_structures::sync::{Lock, Lrc, OnceLock};
 use rustc_data_structures::unhash::UnhashMap;
 use rustc_expand::base::{SyntaxExtension, SyntaxExtensionKind};
 use rustc_expand::proc_macro::{AttrProcMacro, BangProcMacro, DeriveProcMacro};
+use rustc_expand::proc_macro_cache::CacheableProcMacro;
+use rustc_expand::proc_macro_process::{ProcMacroDylib, ProcMacroLocation, ProcMacros};
 use rustc_hir::Safety;
 use rustc_hir::def::Res;
 use rustc_hir::def_id::{CRATE_DEF_INDEX, LOCAL_CRATE};
@@ -31,7 +34,7 @@
 use rustc_session::Session;
 use rustc_session::cstore::{CrateSource, ExternCrate};
 use rustc_span::hygiene::HygieneDecodeContext;
-use rustc_span::{BytePos, DUMMY_SP, Pos, SpanData, SpanDecoder, SyntaxContext, kw};
+use rustc_span::{BytePos, DUMMY_SP, Pos, SpanData, SpanDecoder, SyntaxContext, kw, sym};
 use tracing::debug;
 
 use crate::creader::CStore;
@@ -43,6 +46,7 @@
 /// A reference to the raw binary version of crate metadata.
 /// This struct applies [`MemDecoder`]'s validation when constructed
 /// so that later constructions are guaranteed to succeed.
+#[derive(Clone)]
 pub(crate) struct MetadataBlob(OwnedSlice);
 
 impl std::ops::Deref for MetadataBlob {
@@ -90,7 +94,9 @@ pub(crate) struct CrateMetadata {
     /// or `#[rustc_allow_incoherent_impl]`.
     incoherent_impls: FxIndexMap<SimplifiedType, LazyArray<DefIndex>>,
     /// Proc macro descriptions for this crate, if it's a proc macro crate.
-    raw_proc_macros: Option<&'static [ProcMacro]>,
+    raw_proc_macros: Option<ProcMacros>,
+    /// The dylib the proc macro descriptions were loaded from.
+    proc_macro_dylib: Option<Lrc<ProcMacroDylib>>,
     /// Source maps for code from the crate.
     source_map_import_info: Lock<Vec<Option<ImportedSourceFile>>>,
     /// For every definition in this crate, maps its `DefPathHash` to its `DefIndex`.
@@ -743,6 +749,88 @@ pub(crate) fn get_root(&self) -> CrateRoot {
         LazyValue::<CrateRoot>::from_position(pos).decode(self)
     }
 
+    /// Describes what the metadata encodes at `pos`, like the `optimized_mir`
+    /// of `foo::bar`.
+    fn describe_position(&self, pos: usize) -> String {
+        if pos < METADATA_HEADER.len() + 8 {
+            return "the header".to_owned();
+        }
+        if pos >= self.root_pos().get() {
+            return "the crate root".to_owned();
+        }
+        let root = self.get_root();
+
+        let mut ranges = root.tables.byte_ranges();
+        ranges.push(("source_map", root.source_map.byte_range(), root.source_map.width()));
+        ranges.push((
+            "syntax_contexts",
+            root.syntax_contexts.byte_range(),
+            root.syntax_contexts.width(),
+        ));
+        ranges.push(("expn_data", root.expn_data.byte_range(), root.expn_data.width()));
+        ranges.push(("expn_hashes", root.expn_hashes.byte_range(), root.expn_hashes.width()));
+        if let Some((table, range, width)) =
+            ranges.into_iter().find(|(_, range, _)| range.contains(&pos))
+        {
+            return root.describe_entry(self, table, (pos - range.start) / width);
+        }
+
+        // Values are encoded before the tables and arrays referring to them, so
+        // the position is in the value starting last before it.
+        let mut starts: Vec<_> = root
+            .tables
+            .lazy_positions(self)
+            .into_iter()
+            .map(|(table, index, start)| (start, table, Some(index)))
+            .collect();
+        for (table, lazy_positions) in [
+            ("source_map", root.source_map.lazy_positions(self)),
+            ("syntax_contexts", root.syntax_contexts.lazy_positions(self)),
+            ("expn_data", root.expn_data.lazy_positions(self)),
+            ("expn_hashes", root.expn_hashes.lazy_positions(self)),
+        ] {
+            starts.extend(
+                lazy_positions.into_iter().map(|(index, start)| (start, table, Some(index))),
+            );
+        }
+        macro_rules! arrays {
+            ($($field:ident),+) => {
+                $(if root.$field.num_elems > 0 {
+                    starts.push((root.$field.position.get(), stringify!($field), None));
+                })+
+            };
+        }
+        arrays!(
+            crate_deps,
+            dylib_dependency_formats,
+            lib_features,
+            stability_implications,
+            lang_items,
+            lang_items_missing,
+            stripped_cfg_items,
+            diagnostic_items,
+            native_libraries,
+            foreign_modules,
+            traits,
+            impls,
+            incoherent_impls,
+            interpret_alloc_index,
+            debugger_visualizers,
+            exported_symbols
+        );
+        starts.push((root.def_path_hash_map.position.get(), "def_path_hash_map", None));
+
+        match starts
+            .into_iter()
+            .filter(|&(start, ..)| start <= pos)
+            .max_by_key(|&(start, ..)| start)
+        {
+            Some((_, table, Some(index))) => root.describe_entry(self, table, index),
+            Some((_, field, None)) => format!("`{field}`"),
+            None => "the header".to_owned(),
+        }
+    }
+
     pub(crate) fn list_crate_metadata(
         &self,
         out: &mut dyn io::Write,
@@ -936,6 +1024,69 @@ fn print_item(
 
         Ok(())
     }
+
+    /// Collects the items which other crates can name through the public
+    /// modules of this crate, with their paths relative to the crate root.
+    /// Unstable items are skipped unless `is_feature_enabled` holds for their
+    /// feature, and so are all the items of an unstable crate.
+    pub(crate) fn nameable_items(
+        &self,
+        is_feature_enabled: &dyn Fn(Symbol) -> bool,
+    ) -> Vec<(Vec<Symbol>, DefKind)> {
+        let root = self.get_root();
+        let is_stable = |index| {
+            root.tables.lookup_stability.get(self, index).is_none_or(|stability| {
+                let stability = stability.decode(self);
+                !stability.is_unstable() || is_feature_enabled(stability.feature)
+            })
+        };
+        let def_key_of = |index| Some(root.tables.def_keys.get(self, index)?.decode(self));
+        let is_public = |index| {
+            root.tables
+                .visibility
+                .get(self, index)
+                .is_some_and(|visibility| visibility.decode(self).is_public())
+        };
+
+        let mut items = Vec::new();
+        if root.is_proc_macro_crate() || !is_stable(CRATE_DEF_INDEX) {
+            return items;
+        }
+        let mut modules = vec![(CRATE_DEF_INDEX, Vec::new())];
+        while let Some((module, path)) = modules.pop() {
+            let Some(children) = root.tables.module_children_non_reexports.get(self, module) else {
+                continue;
+            };
+            for child in children.decode(self) {
+                let Some(mut def_key) = def_key_of(child) else {
+                    continue;
+                };
+                // Constructors are named after their struct.
+                if def_key.disambiguated_data.data == DefPathData::Ctor
+                    && let Some(parent) = def_key.parent.and_then(def_key_of)
+                {
+                    def_key = parent;
+                }
+                let Some(name) = def_key.get_opt_name() else {
+                    continue;
+                };
+                let Some(def_kind) = root.tables.def_kind.get(self, child) else {
+                    continue;
+                };
+                if name == kw::Underscore || !is_public(child) || !is_stable(child) {
+                    continue;
+                }
+
+                let mut child_path = path.clone();
+                child_path.push(name);
+                if def_kind == DefKind::Mod {
+                    modules.push((child, child_path.clone()));
+                }
+                items.push((child_path, def_kind));
+            }
+        }
+        items
+    }
 }
 
 impl CrateRoot {
@@ -961,6 +1112,39 @@ pub(crate) fn decode_crate_deps<'a>(
     ) -> impl ExactSizeIterator<Item = CrateDep> + Captures<'a> {
         self.crate_deps.decode(metadata)
     }
+
+    /// Describes the entry `index` of `table`, with the path of the item of
+    /// the entry for the tables indexed by `DefIndex`.
+    fn describe_entry(&self, blob: &MetadataBlob, table: &str, index: usize) -> String {
+        let indexed_by_def_index = !matches!(
+            table,
+            "proc_macro_quoted_spans"
+                | "source_map"
+                | "syntax_contexts"
+                | "expn_data"
+                | "expn_hashes"
+        );
+        let def_index = DefIndex::from_usize(index);
+        if indexed_by_def_index && self.tables.def_keys.get(blob, def_index).is_some() {
+            let path = DefPath::make(LOCAL_CRATE, def_index, |parent| {
+                self.tables.def_keys.get(blob, parent).unwrap().decode(blob)
+            });
+            format!("`{table}` of `{}{}`", self.name(), path.to_string_no_crate_verbose())
+        } else {
+            format!("entry {index} of `{table}`")
+        }
+    }
+}
+
+/// Describes what the metadata `blob` encodes at `pos`, like the `optimized_mir`
+/// of `foo::bar`, to tell which part of the metadata of a crate two encodings
+/// of it start to differ in. Returns `None` if `blob` isn't metadata.
+pub fn describe_metadata_position(blob: &[u8], pos: usize) -> Option<String> {
+    if !blob.starts_with(METADATA_HEADER) || pos >= blob.len() {
+        return None;
+    }
+    let blob = MetadataBlob::new(slice_owned(blob.to_vec(), Deref::deref)).ok()?;
+    Some(blob.describe_position(pos))
 }
 
 impl<'a> CrateMetadataRef<'a> {
@@ -968,19 +1152,22 @@ fn missing(self, descr: &str, id: DefIndex) -> ! {
         bug!("missing `{descr}` for {:?}", self.local_def_id(id))
     }
 
-    fn raw_proc_macro(self, id: DefIndex) -> &'a ProcMacro {
+    /// Returns the declaration of a proc macro with its name and helper attributes.
+    fn raw_proc_macro(self, id: DefIndex) -> (&'a ProcMacro, Symbol, Vec<Symbol>) {
+        self.cdata.raw_proc_macros.as_ref().unwrap().get(self.proc_macro_index(id))
+    }
+
+    fn proc_macro_index(self, id: DefIndex) -> usize {
         // DefIndex's in root.proc_macro_data have a one-to-one correspondence
         // with items in 'raw_proc_macros'.
-        let pos = self
-            .root
+        self.root
             .proc_macro_data
             .as_ref()
             .unwrap()
             .macros
             .decode(self)
             .position(|i| i == id)
-            .unwrap();
-        &self.raw_proc_macros.unwrap()[pos]
+            .unwrap()
     }
 
     fn opt_item_name(self, item_index: DefIndex) -> Option<Symbol> {
@@ -1038,26 +1225,34 @@ fn get_span(self, index: DefIndex, sess: &Session) -> Span {
     }
 
     fn load_proc_macro<'tcx>(self, id: DefIndex, tcx: TyCtxt<'tcx>) -> SyntaxExtension {
-        let (name, kind, helper_attrs) = match *self.raw_proc_macro(id) {
-            ProcMacro::CustomDerive { trait_name, attributes, client } => {
-                let helper_attrs =
-                    attributes.iter().cloned().map(Symbol::intern).collect::<Vec<_>>();
-                (
-                    trait_name,
-                    SyntaxExtensionKind::Derive(Box::new(DeriveProcMacro { client })),
-                    helper_attrs,
-                )
+        let sess = tcx.sess;
+        let attrs: Vec<_> = self.get_item_attrs(id, sess).collect();
+        let (raw_proc_macro, name, helper_attrs) = self.raw_proc_macro(id);
+        let cacheable = attrs
+            .iter()
+            .any(|attr| attr.has_name(sym::proc_macro_deterministic))
+            .then(|| CacheableProcMacro { crate_hash: self.root.header.hash, name });
+        let location = self.proc_macro_dylib.as_ref().map(|dylib| ProcMacroLocation {
+            dylib: Lrc::clone(dylib),
+            index: self.proc_macro_index(id),
+        });
+
+        let kind = match *raw_proc_macro {
+            ProcMacro::CustomDerive { client, .. } => {
+                SyntaxExtensionKind::Derive(Box::new(DeriveProcMacro {
+                    client,
+                    cacheable,
+                    location,
+                }))
             }
-            ProcMacro::Attr { name, client } => {
-                (name, SyntaxExtensionKind::Attr(Box::new(AttrProcMacro { client })), Vec::new())
+            ProcMacro::Attr { client, .. } => {
+                SyntaxExtensionKind::Attr(Box::new(AttrProcMacro { client, cacheable, location }))
             }
-            ProcMacro::Bang { name, client } => {
-                (name, SyntaxExtensionKind::Bang(Box::new(BangProcMacro { client })), Vec::new())
+            ProcMacro::Bang { client, .. } => {
+                SyntaxExtensionKind::Bang(Box::new(BangProcMacro { client, cacheable, location }))
             }
         };
 
-        let sess = tcx.sess;
-        let attrs: Vec<_> = self.get_item_attrs(id, sess).collect();
         SyntaxExtension::new(
             sess,
             tcx.features(),
@@ -1065,7 +1260,7 @@ fn load_proc_macro<'tcx>(self, id: DefIndex, tcx: TyCtxt<'tcx>) -> SyntaxExtensi
             self.get_span(id, sess),
             helper_attrs,
             self.root.edition,
-            Symbol::intern(name),
+            name,
             &attrs,
             false,
         )
@@ -1820,7 +2015,8 @@ pub(crate) fn new(
         cstore: &CStore,
         blob: MetadataBlob,
         root: CrateRoot,
-        raw_proc_macros: Option<&'static [ProcMacro]>,
+        raw_proc_macros: Option<ProcMacros>,
+        proc_macro_dylib: Option<Lrc<ProcMacroDylib>>,
         cnum: CrateNum,
         cnum_map: CrateNumMap,
         dep_kind: CrateDepKind,
@@ -1847,6 +2043,7 @@ pub(crate) fn new(
             trait_impls,
             incoherent_impls: Default::default(),
             raw_proc_macros,
+            proc_macro_dylib,
             source_map_import_info: Lock::new(Vec::new()),
             def_path_hash_map,
             expn_hash_map: Default::default(),
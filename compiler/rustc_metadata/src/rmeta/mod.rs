@@ -1,6 +1,8 @@
 use std::marker::PhantomData;
 use std::num::NonZero;
+use std::ops::Range;
 
+pub use decoder::describe_metadata_position;
 pub(crate) use decoder::{CrateMetadata, CrateNumMap, MetadataBlob};
 use decoder::{DecodeContext, Metadata};
 use def_path_hash_map::DefPathHashMapRef;
@@ -370,6 +372,31 @@ fn encode(&self, buf: &mut FileEncoder) -> LazyTables {
                 }
             }
         }
+
+        impl LazyTables {
+            /// Returns the name of each table, with the range of the metadata
+            /// holding its entries and the width of the entries.
+            fn byte_ranges(&self) -> Vec<(&'static str, Range<usize>, usize)> {
+                vec![
+                    $((stringify!($name1), self.$name1.byte_range(), self.$name1.width()),)+
+                    $((stringify!($name2), self.$name2.byte_range(), self.$name2.width()),)+
+                ]
+            }
+
+            /// Returns the name of the table and the index of each entry
+            /// referring to a lazily-decoded value, with the position of the
+            /// value.
+            fn lazy_positions(&self, blob: &MetadataBlob) -> Vec<(&'static str, usize, usize)> {
+                let mut positions = Vec::new();
+                $(positions.extend(self.$name1.lazy_positions(blob).into_iter().map(
+                    |(index, position)| (stringify!($name1), index, position)
+                ));)+
+                $(positions.extend(self.$name2.lazy_positions(blob).into_iter().map(
+                    |(index, position)| (stringify!($name2), index, position)
+                ));)+
+                positions
+            }
+        }
     }
 }
 
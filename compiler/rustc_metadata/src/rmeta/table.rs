@@ -64,6 +64,11 @@ pub(super) trait FixedSizeEncoding: IsDefault {
 
     fn from_bytes(b: &Self::ByteArray) -> Self;
     fn write_to_bytes(self, b: &mut Self::ByteArray);
+
+    /// The position of the lazily-decoded value this refers to, if any.
+    fn lazy_position(&self) -> Option<usize> {
+        None
+    }
 }
 
 /// This implementation is not used generically, but for reading/writing
@@ -368,6 +373,10 @@ impl<T> FixedSizeEncoding for Option<LazyValue<T>> {
             }
         }
     }
+
+    fn lazy_position(&self) -> Option<usize> {
+        self.as_ref().map(|lazy| lazy.position.get())
+    }
 }
 
 impl<T> LazyArray<T> {
@@ -434,6 +443,10 @@ impl<T> FixedSizeEncoding for LazyArray<T> {
         assert!(!self.is_default());
         self.write_to_bytes_impl(b)
     }
+
+    fn lazy_position(&self) -> Option<usize> {
+        (self.num_elems > 0).then(|| self.position.get())
+    }
 }
 
 impl<T> FixedSizeEncoding for Option<LazyArray<T>> {
@@ -453,6 +466,10 @@ impl<T> FixedSizeEncoding for Option<LazyArray<T>> {
             Some(lazy) => lazy.write_to_bytes_impl(b),
         }
     }
+
+    fn lazy_position(&self) -> Option<usize> {
+        self.as_ref().and_then(|lazy| lazy.lazy_position())
+    }
 }
 
 /// Helper for constructing a table's serialization (also see `Table`).
@@ -552,4 +569,26 @@ pub(super) fn get<'a, 'tcx, M: Metadata<'a, 'tcx>>(&self, metadata: M, i: I) ->
     pub(super) fn size(&self) -> usize {
         self.len
     }
+
+    /// The range of the metadata holding the entries of the table.
+    pub(super) fn byte_range(&self) -> Range<usize> {
+        let start = self.position.get();
+        start..start + self.width * self.len
+    }
+
+    /// The width of the entries of the table in the metadata.
+    pub(super) fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the indices of the entries referring to lazily-decoded values,
+    /// with the positions of the values.
+    pub(super) fn lazy_positions<'a, 'tcx, M: Metadata<'a, 'tcx>>(
+        &self,
+        metadata: M,
+    ) -> Vec<(usize, usize)> {
+        (0..self.len)
+            .filter_map(|i| Some((i, self.get(metadata, I::new(i)).lazy_position()?)))
+            .collect()
+    }
 }
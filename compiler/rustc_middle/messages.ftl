@@ -14,9 +14,21 @@ middle_assert_divide_by_zero =
 
 middle_assert_gen_resume_after_panic = `gen` fn or block cannot be further iterated on after it panicked
 
+middle_assert_invalid_bool =
+    loaded an invalid `bool`: the byte must be 0x0 or 0x1 but is {$found}
+
+middle_assert_invalid_char =
+    loaded an invalid `char`: {$found} is not a Unicode scalar value
+
+middle_assert_invalid_enum_discriminant =
+    loaded an enum with an invalid discriminant: the tag is {$found}
+
 middle_assert_misaligned_ptr_deref =
     misaligned pointer dereference: address must be a multiple of {$required} but is {$found}
 
+middle_assert_null_ptr_deref =
+    null pointer dereference occurred
+
 middle_assert_op_overflow =
     attempt to compute `{$left} {$op} {$right}`, which would overflow
 
@@ -8,18 +8,21 @@
 use rustc_data_structures::stable_hasher::{Hash128, HashStable, StableHasher, ToStableHashKey};
 use rustc_data_structures::unord::UnordMap;
 use rustc_hir::ItemId;
+use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{CrateNum, DefId, LOCAL_CRATE};
 use rustc_index::Idx;
 use rustc_macros::{HashStable, TyDecodable, TyEncodable};
 use rustc_query_system::ich::StableHashingContext;
 use rustc_session::config::OptLevel;
+use rustc_session::symbol_size::SymbolOwner;
 use rustc_span::{Span, Symbol};
 use rustc_target::spec::SymbolVisibility;
 use tracing::debug;
 
 use crate::dep_graph::{DepNode, WorkProduct, WorkProductId};
 use crate::middle::codegen_fn_attrs::CodegenFnAttrFlags;
-use crate::ty::{GenericArgs, Instance, InstanceKind, SymbolName, TyCtxt};
+use crate::ty::print::with_no_trimmed_paths;
+use crate::ty::{self, GenericArgs, Instance, InstanceKind, SymbolName, Ty, TyCtxt};
 
 /// Describes how a monomorphization will be instantiated in object files.
 #[derive(PartialEq)]
@@ -259,6 +262,9 @@ pub struct CodegenUnit<'tcx> {
     /// True if this is CGU is used to hold code coverage information for dead code,
     /// false otherwise.
     is_code_coverage_dead_code_cgu: bool,
+    /// Functions of this CGU that are defined as aliases of another function of
+    /// this CGU with an identical body, see `-Zmerge-mono-items`.
+    fn_aliases: FxIndexMap<Instance<'tcx>, Instance<'tcx>>,
 }
 
 /// Auxiliary info about a `MonoItem`.
@@ -331,6 +337,7 @@ pub fn new(name: Symbol) -> CodegenUnit<'tcx> {
             size_estimate: 0,
             primary: false,
             is_code_coverage_dead_code_cgu: false,
+            fn_aliases: Default::default(),
         }
     }
 
@@ -367,6 +374,18 @@ pub fn make_code_coverage_dead_code_cgu(&mut self) {
         self.is_code_coverage_dead_code_cgu = true;
     }
 
+    /// Returns the function that `instance` is an alias of, if any.
+    pub fn fn_alias(&self, instance: Instance<'tcx>) -> Option<Instance<'tcx>> {
+        self.fn_aliases.get(&instance).copied()
+    }
+
+    /// Defines `instance` as an alias of `aliasee`. Both must be items of this CGU.
+    pub fn add_fn_alias(&mut self, instance: Instance<'tcx>, aliasee: Instance<'tcx>) {
+        debug_assert!(self.items.contains_key(&MonoItem::Fn(instance)));
+        debug_assert!(self.items.contains_key(&MonoItem::Fn(aliasee)));
+        self.fn_aliases.insert(instance, aliasee);
+    }
+
     pub fn mangle_name(human_readable_name: &str) -> BaseNString {
         let mut hasher = StableHasher::new();
         human_readable_name.hash(&mut hasher);
@@ -582,3 +601,43 @@ pub enum CollectionMode {
     /// that it is optimization-independent.
     MentionedItems,
 }
+
+/// Returns the name of the local symbol given to the vtable of `ty` for `trait_ref`, for
+/// `-Z symbol-size-report`, like `<foo::Circle as foo::Shape>::{vtable}`.
+pub fn vtable_symbol_name<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ty: Ty<'tcx>,
+    trait_ref: Option<ty::PolyExistentialTraitRef<'tcx>>,
+) -> String {
+    with_no_trimmed_paths!(match trait_ref {
+        Some(trait_ref) => {
+            let trait_ref = trait_ref.with_self_ty(tcx, ty);
+            let trait_ref = tcx.instantiate_bound_regions_with_erased(trait_ref);
+            format!("{trait_ref}::{{vtable}}")
+        }
+        None => format!("<{ty} as _>::{{vtable}}"),
+    })
+}
+
+/// Returns the owner of a symbol defined for the item `def_id`, for `-Z symbol-size-report`.
+///
+/// `instance` describes what exactly the symbol defines, e.g. an instantiation of `def_id`.
+pub fn symbol_owner(
+    tcx: TyCtxt<'_>,
+    kind: &'static str,
+    def_id: DefId,
+    instance: String,
+) -> SymbolOwner {
+    let mut module = def_id;
+    while tcx.def_kind(module) != DefKind::Mod {
+        module = tcx.parent(module);
+    }
+    let krate = tcx.crate_name(def_id.krate).to_string();
+    SymbolOwner {
+        kind,
+        module: format!("{krate}{}", tcx.def_path(module).to_string_no_crate_verbose()),
+        krate,
+        item: with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+        instance,
+    }
+}
@@ -161,6 +161,9 @@ pub fn is_optional_overflow_check(&self) -> bool {
     /// invoking the appropriate lang item (panic_bounds_check/panic_misaligned_pointer_dereference)
     /// instead of printing a static message. Those have dynamic arguments that aren't present for
     /// the rest of the messages here.
+    ///
+    /// The lang items of `InvalidBool`, `InvalidChar` and `InvalidEnumDiscriminant` take the
+    /// invalid value as their only argument, which the caller is expected to pass.
     pub fn panic_function(&self) -> LangItem {
         use AssertKind::*;
         match self {
@@ -196,6 +199,11 @@ pub fn panic_function(&self) -> LangItem {
                 LangItem::PanicGenFnNonePanic
             }
 
+            NullPointerDereference => LangItem::PanicNullPointerDereference,
+            InvalidBool(_) => LangItem::PanicInvalidBool,
+            InvalidChar(_) => LangItem::PanicInvalidChar,
+            InvalidEnumDiscriminant(_) => LangItem::PanicInvalidEnumDiscriminant,
+
             BoundsCheck { .. } | MisalignedPointerDereference { .. } => {
                 bug!("Unexpected AssertKind")
             }
@@ -260,6 +268,19 @@ pub fn fmt_assert_args<W: fmt::Write>(&self, f: &mut W) -> fmt::Result
                     "\"misaligned pointer dereference: address must be a multiple of {{}} but is {{}}\", {required:?}, {found:?}"
                 )
             }
+            NullPointerDereference => write!(f, "\"null pointer dereference occurred\""),
+            InvalidBool(found) => write!(
+                f,
+                "\"loaded an invalid `bool`: the byte must be 0x0 or 0x1 but is {{:#x}}\", {found:?}"
+            ),
+            InvalidChar(found) => write!(
+                f,
+                "\"loaded an invalid `char`: {{:#x}} is not a Unicode scalar value\", {found:?}"
+            ),
+            InvalidEnumDiscriminant(found) => write!(
+                f,
+                "\"loaded an enum with an invalid discriminant: the tag is {{:#x}}\", {found:?}"
+            ),
             ResumedAfterReturn(CoroutineKind::Coroutine(_)) => {
                 write!(f, "\"coroutine resumed after completion\"")
             }
@@ -332,6 +353,10 @@ pub fn diagnostic_message(&self) -> DiagMessage {
             }
 
             MisalignedPointerDereference { .. } => middle_assert_misaligned_ptr_deref,
+            NullPointerDereference => middle_assert_null_ptr_deref,
+            InvalidBool(_) => middle_assert_invalid_bool,
+            InvalidChar(_) => middle_assert_invalid_char,
+            InvalidEnumDiscriminant(_) => middle_assert_invalid_enum_discriminant,
         }
     }
 
@@ -363,11 +388,14 @@ macro_rules! add {
                 add!("left", format!("{left:#?}"));
                 add!("right", format!("{right:#?}"));
             }
-            ResumedAfterReturn(_) | ResumedAfterPanic(_) => {}
+            ResumedAfterReturn(_) | ResumedAfterPanic(_) | NullPointerDereference => {}
             MisalignedPointerDereference { required, found } => {
                 add!("required", format!("{required:#?}"));
                 add!("found", format!("{found:#?}"));
             }
+            InvalidBool(found) | InvalidChar(found) | InvalidEnumDiscriminant(found) => {
+                add!("found", format!("{found:#?}"));
+            }
         }
     }
 }
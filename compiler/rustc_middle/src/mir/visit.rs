@@ -633,10 +633,15 @@ fn super_assert_message(&mut self,
                         self.visit_operand(l, location);
                         self.visit_operand(r, location);
                     }
-                    OverflowNeg(op) | DivisionByZero(op) | RemainderByZero(op) => {
+                    OverflowNeg(op)
+                    | DivisionByZero(op)
+                    | RemainderByZero(op)
+                    | InvalidBool(op)
+                    | InvalidChar(op)
+                    | InvalidEnumDiscriminant(op) => {
                         self.visit_operand(op, location);
                     }
-                    ResumedAfterReturn(_) | ResumedAfterPanic(_) => {
+                    ResumedAfterReturn(_) | ResumedAfterPanic(_) | NullPointerDereference => {
                         // Nothing to visit
                     }
                     MisalignedPointerDereference { required, found } => {
@@ -1319,6 +1319,18 @@ enum TestKind<'tcx> {
         ty: Ty<'tcx>,
     },
 
+    /// Test what length a `&str` has, before comparing it with the string
+    /// literals of that length.
+    ///
+    /// The test's target lengths are not stored here; instead they are extracted
+    /// from the string literals of the candidates participating in the test.
+    SwitchStrLen,
+
+    /// Test what the byte at `index` of a `&str` is, before comparing it with
+    /// the string literals that have this byte. Only used in the branch of a
+    /// `SwitchStrLen` test that fixed the length of the string to `len`.
+    SwitchStrByte { len: u64, index: u64 },
+
     /// Test whether the value falls within an inclusive or exclusive range.
     Range(Box<PatRange<'tcx>>),
 
@@ -2067,7 +2079,8 @@ fn test_remaining_match_pairs_after_or(
     /// pair of the first candidate in the list.
     ///
     /// *Note:* taking the first match pair is somewhat arbitrary, and we might do better here by
-    /// choosing more carefully what to test.
+    /// choosing more carefully what to test. Large matches of a `&str` against string literals are
+    /// the exception, see [`Builder::pick_str_decision_test`].
     ///
     /// For example, consider the following possible match-pairs:
     ///
@@ -2082,9 +2095,11 @@ fn test_remaining_match_pairs_after_or(
     fn pick_test(&mut self, candidates: &[&mut Candidate<'_, 'tcx>]) -> (Place<'tcx>, Test<'tcx>) {
         // Extract the match-pair from the highest priority candidate
         let match_pair = &candidates[0].match_pairs[0];
-        let test = self.pick_test_for_match_pair(match_pair);
         // Unwrap is ok after simplification.
         let match_place = match_pair.place.unwrap();
+        let test = self
+            .pick_str_decision_test(match_place, candidates)
+            .unwrap_or_else(|| self.pick_test_for_match_pair(match_pair));
         debug!(?test, ?match_pair);
 
         (match_place, test)
@@ -2293,9 +2308,20 @@ fn test_candidates<'pat, 'b, 'c>(
             .into_iter()
             .map(|(branch, mut candidates)| {
                 let branch_start = self.cfg.start_new_block();
+                // The string has a known length in this branch, so that its bytes can be tested.
+                let str_len = match (&test.kind, branch) {
+                    (TestKind::SwitchStrLen, TestBranch::Constant(_, len)) => Some(len as u64),
+                    _ => None,
+                };
+                if let Some(len) = str_len {
+                    self.str_lens.push((match_place, len));
+                }
                 // Recursively lower the rest of the match tree after the relevant outcome.
                 let branch_otherwise =
                     self.match_candidates(span, scrutinee_span, branch_start, &mut *candidates);
+                if str_len.is_some() {
+                    self.str_lens.pop();
+                }
 
                 // Link up the `otherwise` block of the subtree to `remainder_start`.
                 let source_info = self.source_info(span);
@@ -5,9 +5,9 @@
 // identify what tests are needed, perform the tests, and then filter
 // the candidates based on the result.
 
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 
-use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::fx::{FxIndexMap, FxIndexSet};
 use rustc_hir::{LangItem, RangeEnd};
 use rustc_middle::mir::*;
 use rustc_middle::ty::adjustment::PointerCoercion;
@@ -22,6 +22,10 @@
 use crate::builder::Builder;
 use crate::builder::matches::{Candidate, MatchPairTree, Test, TestBranch, TestCase, TestKind};
 
+/// The number of string literals a `&str` must be matched against for the match to be lowered as
+/// a decision tree, see [`Builder::pick_str_decision_test`].
+const MIN_STR_DECISION_TREE_LITERALS: usize = 4;
+
 impl<'a, 'tcx> Builder<'a, 'tcx> {
     /// Identifies what test is needed to decide if `match_pair` is applicable.
     ///
@@ -65,6 +69,66 @@ pub(super) fn pick_test_for_match_pair<'pat>(
         Test { span: match_pair.pattern.span, kind }
     }
 
+    /// Picks a test to lower a large match of a `&str` against string literals as a decision
+    /// tree, instead of a chain of `<str as PartialEq>::eq` calls: we first switch on the length of
+    /// the string, then on the byte at which the literals of that length differ the most, until
+    /// few enough literals remain to compare the string with each of them.
+    ///
+    /// These tests never fully match a match pair, the final [`TestKind::Eq`] test still does, so
+    /// they don't change which arm is taken. They still make progress: the length is tested once,
+    /// after which the length of the string is known in each branch, and a byte is only tested if
+    /// it splits the string literals of the candidates into at least two branches, the literals in
+    /// each branch then all having the same byte at `index`.
+    pub(super) fn pick_str_decision_test(
+        &self,
+        match_place: Place<'tcx>,
+        candidates: &[&mut Candidate<'_, 'tcx>],
+    ) -> Option<Test<'tcx>> {
+        let match_pair = &candidates[0].match_pairs[0];
+        if !is_str_ref(match_pair.pattern.ty) {
+            return None;
+        }
+
+        // The string literals of the candidates that `sort_candidate` will sort.
+        let literals: Vec<&[u8]> = candidates
+            .iter()
+            .map_while(|candidate| {
+                let match_pair =
+                    candidate.match_pairs.iter().find(|mp| mp.place == Some(match_place))?;
+                match match_pair.test_case {
+                    TestCase::Constant { value } => str_literal_bytes(self.tcx, value),
+                    _ => None,
+                }
+            })
+            .collect();
+        if literals.len() < MIN_STR_DECISION_TREE_LITERALS {
+            return None;
+        }
+
+        let known_len =
+            self.str_lens.iter().rev().find(|&&(place, _)| place == match_place).map(|&(_, len)| len);
+        let kind = match known_len {
+            None => TestKind::SwitchStrLen,
+            Some(len) => {
+                // All the literals have this length, as the candidates of other lengths were
+                // sorted into the other branches of the `SwitchStrLen` test.
+                let (index, distinct_bytes) = (0..len as usize)
+                    .map(|index| {
+                        let bytes: FxIndexSet<u8> =
+                            literals.iter().map(|literal| literal[index]).collect();
+                        (index, bytes.len())
+                    })
+                    .max_by_key(|&(index, distinct_bytes)| (distinct_bytes, Reverse(index)))?;
+                if distinct_bytes < 2 {
+                    return None;
+                }
+                TestKind::SwitchStrByte { len, index: index as u64 }
+            }
+        };
+
+        Some(Test { span: match_pair.pattern.span, kind })
+    }
+
     #[instrument(skip(self, target_blocks, place), level = "debug")]
     pub(super) fn perform_test(
         &mut self,
@@ -133,6 +197,81 @@ pub(super) fn perform_test(
                 self.cfg.terminate(block, self.source_info(match_start_span), terminator);
             }
 
+            TestKind::SwitchStrLen => {
+                let otherwise_block = target_block(TestBranch::Failure);
+                let switch_targets = SwitchTargets::new(
+                    target_blocks.iter().filter_map(|(&branch, &block)| {
+                        if let TestBranch::Constant(_, bits) = branch {
+                            Some((bits, block))
+                        } else {
+                            None
+                        }
+                    }),
+                    otherwise_block,
+                );
+                let len = self.temp(self.tcx.types.usize, test.span);
+                // len = PtrMetadata(place)
+                self.cfg.push_assign(
+                    block,
+                    source_info,
+                    len,
+                    Rvalue::UnaryOp(UnOp::PtrMetadata, Operand::Copy(place)),
+                );
+                let terminator = TerminatorKind::SwitchInt {
+                    discr: Operand::Move(len),
+                    targets: switch_targets,
+                };
+                self.cfg.terminate(block, self.source_info(match_start_span), terminator);
+            }
+
+            TestKind::SwitchStrByte { len, index } => {
+                let tcx = self.tcx;
+                let otherwise_block = target_block(TestBranch::Failure);
+                let switch_targets = SwitchTargets::new(
+                    target_blocks.iter().filter_map(|(&branch, &block)| {
+                        if let TestBranch::Constant(_, bits) = branch {
+                            Some((bits, block))
+                        } else {
+                            None
+                        }
+                    }),
+                    otherwise_block,
+                );
+
+                // The enclosing `SwitchStrLen` test fixed the length of the string to `len`, so the
+                // byte at `index` can be read.
+                // bytes = &raw const (*place) as *const [u8] (PtrToPtr)
+                let str_ptr = self.temp(Ty::new_imm_ptr(tcx, tcx.types.str_), test.span);
+                self.cfg.push_assign(
+                    block,
+                    source_info,
+                    str_ptr,
+                    Rvalue::RawPtr(Mutability::Not, tcx.mk_place_deref(place)),
+                );
+                let bytes_ty = Ty::new_imm_ptr(tcx, Ty::new_slice(tcx, tcx.types.u8));
+                let bytes = self.temp(bytes_ty, test.span);
+                self.cfg.push_assign(
+                    block,
+                    source_info,
+                    bytes,
+                    Rvalue::Cast(CastKind::PtrToPtr, Operand::Move(str_ptr), bytes_ty),
+                );
+                // switchInt((*bytes)[index of len])
+                let byte = tcx.mk_place_elem(
+                    tcx.mk_place_deref(bytes),
+                    ProjectionElem::ConstantIndex {
+                        offset: index,
+                        min_length: len,
+                        from_end: false,
+                    },
+                );
+                let terminator = TerminatorKind::SwitchInt {
+                    discr: Operand::Copy(byte),
+                    targets: switch_targets,
+                };
+                self.cfg.terminate(block, self.source_info(match_start_span), terminator);
+            }
+
             TestKind::If => {
                 let success_block = target_block(TestBranch::Success);
                 let fail_block = target_block(TestBranch::Failure);
@@ -710,6 +849,27 @@ pub(super) fn sort_candidate(
                 }
             }
 
+            (TestKind::SwitchStrLen, &TestCase::Constant { value }) => {
+                // The string still has to be compared with the literal.
+                fully_matched = false;
+                let len = str_literal_bytes(self.tcx, value)?.len() as u64;
+                Some(TestBranch::Constant(Const::from_usize(self.tcx, len), len.into()))
+            }
+
+            (&TestKind::SwitchStrByte { len, index }, &TestCase::Constant { value }) => {
+                // The string still has to be compared with the literal.
+                fully_matched = false;
+                let bytes = str_literal_bytes(self.tcx, value)?;
+                if bytes.len() as u64 != len {
+                    Some(TestBranch::Failure)
+                } else {
+                    let byte = bytes[index as usize];
+                    let u8_ty = self.tcx.types.u8;
+                    let value = Const::from_bits(self.tcx, byte.into(), self.typing_env(), u8_ty);
+                    Some(TestBranch::Constant(value, byte.into()))
+                }
+            }
+
             (TestKind::Eq { value: test_val, .. }, TestCase::Constant { value: case_val }) => {
                 if test_val == case_val {
                     fully_matched = true;
@@ -736,6 +896,8 @@ pub(super) fn sort_candidate(
                 TestKind::Switch { .. }
                 | TestKind::SwitchInt { .. }
                 | TestKind::If
+                | TestKind::SwitchStrLen
+                | TestKind::SwitchStrByte { .. }
                 | TestKind::Len { .. }
                 | TestKind::Range { .. }
                 | TestKind::Eq { .. }
@@ -763,6 +925,20 @@ fn is_switch_ty(ty: Ty<'_>) -> bool {
     ty.is_integral() || ty.is_char()
 }
 
+fn is_str_ref(ty: Ty<'_>) -> bool {
+    matches!(ty.kind(), ty::Ref(_, inner_ty, _) if inner_ty.is_str())
+}
+
+/// Returns the bytes of a string literal pattern.
+fn str_literal_bytes<'tcx>(tcx: TyCtxt<'tcx>, value: Const<'tcx>) -> Option<&'tcx [u8]> {
+    let Const::Ty(ty, ct) = value else { return None };
+    if !is_str_ref(ty) {
+        return None;
+    }
+    let ty::ConstKind::Value(_, valtree) = ct.kind() else { return None };
+    valtree.try_to_raw_bytes(tcx, ty)
+}
+
 fn trait_method<'tcx>(
     tcx: TyCtxt<'tcx>,
     trait_def_id: DefId,
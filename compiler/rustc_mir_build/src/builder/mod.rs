@@ -209,6 +209,10 @@ struct Builder<'a, 'tcx> {
     /// Scope of temporaries that should be deduplicated using [Self::fixed_temps].
     fixed_temps_scope: Option<region::Scope>,
 
+    /// The lengths of the `&str` places that a `SwitchStrLen` test has fixed,
+    /// in the branch of the match tree that is being lowered.
+    str_lens: Vec<(Place<'tcx>, u64)>,
+
     /// Maps `HirId`s of variable bindings to the `Local`s created for them.
     /// (A match binding can have two locals; the 2nd is for the arm's guard.)
     var_indices: FxHashMap<LocalVarId, LocalsForNode>,
@@ -789,6 +793,7 @@ fn new(
             guard_context: vec![],
             fixed_temps: Default::default(),
             fixed_temps_scope: None,
+            str_lens: vec![],
             local_decls: IndexVec::from_elem_n(LocalDecl::new(return_ty, return_span), 1),
             canonical_user_type_annotations: IndexVec::new(),
             upvars: CaptureMap::new(),
@@ -0,0 +1,414 @@
+use rustc_abi::{Endian, Integer, Primitive, Size, TagEncoding, Variants, WrappingRange};
+use rustc_hir::lang_items::LangItem;
+use rustc_index::IndexVec;
+use rustc_middle::mir::interpret::Scalar;
+use rustc_middle::mir::visit::{NonMutatingUseContext, PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::layout::IntegerExt;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_session::Session;
+use tracing::{debug, trace};
+
+use crate::check_alignment::split_block;
+
+/// Inserts checks that values of `bool`, `char` and enum type that are loaded through raw pointers
+/// or produced by transmutes are valid for their type.
+pub(super) struct CheckInvalidValues;
+
+impl<'tcx> crate::MirPass<'tcx> for CheckInvalidValues {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        sess.ub_checks()
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        // This pass emits new panics. If for whatever reason we do not have a panic
+        // implementation, running this pass may cause otherwise-valid code to not compile.
+        if tcx.lang_items().get(LangItem::PanicImpl).is_none() {
+            return;
+        }
+
+        let typing_env = body.typing_env(tcx);
+        let basic_blocks = body.basic_blocks.as_mut();
+        let local_decls = &mut body.local_decls;
+
+        // Like in `CheckAlignment`, we iterate backwards so that inserting new blocks does not
+        // change the Locations we have yet to handle.
+        for block in (0..basic_blocks.len()).rev() {
+            let block = block.into();
+            for statement_index in (0..basic_blocks[block].statements.len()).rev() {
+                let location = Location { block, statement_index };
+                let statement = &basic_blocks[block].statements[statement_index];
+                let source_info = statement.source_info;
+
+                let mut finder = ValueFinder { tcx, local_decls, typing_env, values: Vec::new() };
+                finder.visit_statement(statement, location);
+
+                for (value, check) in finder.values {
+                    debug!("Inserting validity check for {:?}", value);
+                    let new_block = split_block(basic_blocks, location);
+                    insert_validity_check(
+                        tcx,
+                        local_decls,
+                        basic_blocks,
+                        block,
+                        value,
+                        check,
+                        source_info,
+                        new_block,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The integer in a value that decides whether the value is valid.
+struct ValidityCheck<'tcx> {
+    /// The offset of the integer in the value.
+    offset: Size,
+    /// The unsigned integer type the integer is read as.
+    int_ty: Ty<'tcx>,
+    valid: ValidBits,
+    kind: CheckedKind,
+}
+
+enum ValidBits {
+    /// The integer must be in this range.
+    Range(WrappingRange),
+    /// The integer must be a Unicode scalar value.
+    Char,
+    /// The integer must be one of these values.
+    Values(Vec<u128>),
+}
+
+#[derive(Clone, Copy)]
+enum CheckedKind {
+    Bool,
+    Char,
+    EnumDiscriminant,
+}
+
+#[derive(Debug)]
+enum Value<'tcx> {
+    /// A place behind a raw pointer, whose bytes are read through the pointer.
+    Pointee(Place<'tcx>),
+    /// The operand of a transmute, which is reinterpreted as integers like the transmute does, so
+    /// that it is not forced into memory.
+    Transmuted { operand: Place<'tcx>, size: Size },
+}
+
+fn validity_check<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    typing_env: ty::TypingEnv<'tcx>,
+    ty: Ty<'tcx>,
+) -> Option<ValidityCheck<'tcx>> {
+    match *ty.kind() {
+        ty::Bool => Some(ValidityCheck {
+            offset: Size::ZERO,
+            int_ty: tcx.types.u8,
+            valid: ValidBits::Range(WrappingRange { start: 0, end: 1 }),
+            kind: CheckedKind::Bool,
+        }),
+        ty::Char => Some(ValidityCheck {
+            offset: Size::ZERO,
+            int_ty: tcx.types.u32,
+            valid: ValidBits::Char,
+            kind: CheckedKind::Char,
+        }),
+        ty::Adt(adt_def, _) if adt_def.is_enum() => {
+            // Generic enums whose layout is not known yet are not checked.
+            let layout = tcx.layout_of(typing_env.as_query_input(ty)).ok()?;
+            let Variants::Multiple { tag, ref tag_encoding, tag_field, ref variants } =
+                layout.variants
+            else {
+                return None;
+            };
+            let Primitive::Int(int, _) = tag.primitive() else {
+                trace!("Enum with a non-integer tag, not checking {:?}", ty);
+                return None;
+            };
+            let valid = match tag_encoding {
+                // The tag must be the discriminant of an inhabited variant.
+                TagEncoding::Direct => ValidBits::Values(
+                    adt_def
+                        .discriminants(tcx)
+                        .filter(|(index, _)| !variants[*index].is_uninhabited())
+                        .map(|(_, discr)| int.size().truncate(discr.val))
+                        .collect(),
+                ),
+                TagEncoding::Niche { .. } => {
+                    if tag.is_always_valid(&tcx) {
+                        return None;
+                    }
+                    ValidBits::Range(tag.valid_range(&tcx))
+                }
+            };
+            Some(ValidityCheck {
+                offset: layout.fields.offset(tag_field),
+                int_ty: int.to_ty(tcx, false),
+                valid,
+                kind: CheckedKind::EnumDiscriminant,
+            })
+        }
+        _ => None,
+    }
+}
+
+struct ValueFinder<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    local_decls: &'a mut LocalDecls<'tcx>,
+    typing_env: ty::TypingEnv<'tcx>,
+    values: Vec<(Value<'tcx>, ValidityCheck<'tcx>)>,
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for ValueFinder<'a, 'tcx> {
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        // A transmute reinterprets the bytes of its operand, so the bytes that decide the validity
+        // of the result can be read from the operand. Constants are not checked, as their value
+        // would have to be evaluated first.
+        if let Rvalue::Cast(CastKind::Transmute, operand, ty) = rvalue
+            && let Some(operand) = operand.place()
+            && let Some(check) = validity_check(self.tcx, self.typing_env, *ty)
+        {
+            let operand_ty = operand.ty(&*self.local_decls, self.tcx).ty;
+            if let Ok(layout) = self.tcx.layout_of(self.typing_env.as_query_input(operand_ty)) {
+                self.values.push((Value::Transmuted { operand, size: layout.size }, check));
+            }
+        }
+
+        self.super_rvalue(rvalue, location);
+    }
+
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        // We only want to check loads, including those of the discriminant of an enum.
+        match context {
+            PlaceContext::NonMutatingUse(
+                NonMutatingUseContext::Copy
+                | NonMutatingUseContext::Move
+                | NonMutatingUseContext::Inspect,
+            ) => {}
+            _ => {
+                return;
+            }
+        }
+
+        if !place.is_indirect() {
+            return;
+        }
+
+        // We only want to check places based on unsafe pointers
+        if !self.local_decls[place.local].ty.is_unsafe_ptr() {
+            trace!("Indirect, but not based on an unsafe ptr, not checking {:?}", place);
+            return;
+        }
+
+        let ty = place.ty(&*self.local_decls, self.tcx).ty;
+        if let Some(check) = validity_check(self.tcx, self.typing_env, ty) {
+            self.values.push((Value::Pointee(*place), check));
+        }
+
+        self.super_place(place, context, location);
+    }
+}
+
+fn insert_validity_check<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    local_decls: &mut IndexVec<Local, LocalDecl<'tcx>>,
+    basic_blocks: &mut IndexVec<BasicBlock, BasicBlockData<'tcx>>,
+    block: BasicBlock,
+    value: Value<'tcx>,
+    check: ValidityCheck<'tcx>,
+    source_info: SourceInfo,
+    new_block: BasicBlock,
+) {
+    let span = source_info.span;
+    let is_cleanup = basic_blocks[block].is_cleanup;
+    let block_data = &mut basic_blocks[block];
+    let mut assign = |local_decls: &mut IndexVec<Local, LocalDecl<'tcx>>, ty, rvalue| {
+        let place: Place<'tcx> =
+            local_decls.push(LocalDecl::with_source_info(ty, source_info)).into();
+        block_data.statements.push(Statement {
+            source_info,
+            kind: StatementKind::Assign(Box::new((place, rvalue))),
+        });
+        place
+    };
+    let int_size = check.int_ty.primitive_size(tcx);
+    let int_const = |value: u128| {
+        Operand::const_from_scalar(tcx, check.int_ty, Scalar::from_uint(value, int_size), span)
+    };
+
+    let bytes_ty = Ty::new_array(tcx, tcx.types.u8, int_size.bytes());
+    let bits = match value {
+        Value::Pointee(place) => {
+            // Get a pointer to the bytes of the value (equivalent to
+            // `ptr::addr_of!(value).cast::<u8>()`)
+            let value_ty = place.ty(&*local_decls, tcx).ty;
+            let rvalue = Rvalue::RawPtr(Mutability::Not, place);
+            let value_ptr = assign(local_decls, Ty::new_imm_ptr(tcx, value_ty), rvalue);
+            let byte_ptr_ty = Ty::new_imm_ptr(tcx, tcx.types.u8);
+            let rvalue = Rvalue::Cast(CastKind::PtrToPtr, Operand::Copy(value_ptr), byte_ptr_ty);
+            let mut byte_ptr = assign(local_decls, byte_ptr_ty, rvalue);
+
+            // Move the pointer to the checked integer
+            if check.offset != Size::ZERO {
+                let offset = Operand::Constant(Box::new(ConstOperand {
+                    span,
+                    user_ty: None,
+                    const_: Const::Val(
+                        ConstValue::Scalar(Scalar::from_target_usize(check.offset.bytes(), &tcx)),
+                        tcx.types.usize,
+                    ),
+                }));
+                let rvalue =
+                    Rvalue::BinaryOp(BinOp::Offset, Box::new((Operand::Copy(byte_ptr), offset)));
+                byte_ptr = assign(local_decls, byte_ptr_ty, rvalue);
+            }
+
+            // Read the integer as bytes, which makes no assumptions about their value or
+            // alignment, and transmute them to the integer
+            let bytes_ptr_ty = Ty::new_imm_ptr(tcx, bytes_ty);
+            let rvalue = Rvalue::Cast(CastKind::PtrToPtr, Operand::Copy(byte_ptr), bytes_ptr_ty);
+            let bytes_ptr = assign(local_decls, bytes_ptr_ty, rvalue);
+            let rvalue = Rvalue::Use(Operand::Copy(tcx.mk_place_deref(bytes_ptr)));
+            let bytes = assign(local_decls, bytes_ty, rvalue);
+            let rvalue = Rvalue::Cast(CastKind::Transmute, Operand::Copy(bytes), check.int_ty);
+            assign(local_decls, check.int_ty, rvalue)
+        }
+        Value::Transmuted { operand, size } => match Integer::from_size(size) {
+            // Transmute the whole value to an integer, and shift the checked integer into its
+            // low bits
+            Ok(whole) => {
+                let whole_ty = whole.to_ty(tcx, false);
+                let rvalue = Rvalue::Cast(CastKind::Transmute, Operand::Copy(operand), whole_ty);
+                let mut bits = assign(local_decls, whole_ty, rvalue);
+                let shift = match tcx.data_layout.endian {
+                    Endian::Little => check.offset,
+                    Endian::Big => size - check.offset - int_size,
+                };
+                if shift != Size::ZERO {
+                    let amount = Scalar::from_uint(shift.bits(), size);
+                    let amount = Operand::const_from_scalar(tcx, whole_ty, amount, span);
+                    let rvalue =
+                        Rvalue::BinaryOp(BinOp::Shr, Box::new((Operand::Copy(bits), amount)));
+                    bits = assign(local_decls, whole_ty, rvalue);
+                }
+                if whole_ty != check.int_ty {
+                    let rvalue =
+                        Rvalue::Cast(CastKind::IntToInt, Operand::Copy(bits), check.int_ty);
+                    bits = assign(local_decls, check.int_ty, rvalue);
+                }
+                bits
+            }
+            // Transmute values of other sizes to bytes, and transmute those of the checked
+            // integer to the integer
+            Err(_) => {
+                let all_bytes_ty = Ty::new_array(tcx, tcx.types.u8, size.bytes());
+                let rvalue =
+                    Rvalue::Cast(CastKind::Transmute, Operand::Copy(operand), all_bytes_ty);
+                let all_bytes = assign(local_decls, all_bytes_ty, rvalue);
+                let elements = (0..int_size.bytes())
+                    .map(|index| {
+                        let elem = ProjectionElem::ConstantIndex {
+                            offset: check.offset.bytes() + index,
+                            min_length: size.bytes(),
+                            from_end: false,
+                        };
+                        Operand::Copy(tcx.mk_place_elem(all_bytes, elem))
+                    })
+                    .collect();
+                let rvalue =
+                    Rvalue::Aggregate(Box::new(AggregateKind::Array(tcx.types.u8)), elements);
+                let bytes = assign(local_decls, bytes_ty, rvalue);
+                let rvalue = Rvalue::Cast(CastKind::Transmute, Operand::Copy(bytes), check.int_ty);
+                assign(local_decls, check.int_ty, rvalue)
+            }
+        },
+    };
+
+    // Widen the discriminants of enums, which can have any integer type, to the u128 that
+    // panic_invalid_enum_discriminant expects
+    let msg = match check.kind {
+        CheckedKind::Bool => AssertKind::InvalidBool(Operand::Copy(bits)),
+        CheckedKind::Char => AssertKind::InvalidChar(Operand::Copy(bits)),
+        CheckedKind::EnumDiscriminant => {
+            let rvalue = Rvalue::Cast(CastKind::IntToInt, Operand::Copy(bits), tcx.types.u128);
+            let found = assign(local_decls, tcx.types.u128, rvalue);
+            AssertKind::InvalidEnumDiscriminant(Operand::Copy(found))
+        }
+    };
+
+    let is_ok = match check.valid {
+        // Check that `bits.wrapping_sub(start) <= end.wrapping_sub(start)`
+        ValidBits::Range(WrappingRange { start, end }) => {
+            let rvalue =
+                Rvalue::BinaryOp(BinOp::Sub, Box::new((Operand::Copy(bits), int_const(start))));
+            let relative = assign(local_decls, check.int_ty, rvalue);
+            let len = int_size.truncate(end.wrapping_sub(start));
+            let rvalue =
+                Rvalue::BinaryOp(BinOp::Le, Box::new((Operand::Copy(relative), int_const(len))));
+            assign(local_decls, tcx.types.bool, rvalue)
+        }
+        // Check that `(bits ^ 0xD800).wrapping_sub(0x800) < 0x110000 - 0x800`, like
+        // `char::from_u32` does
+        ValidBits::Char => {
+            let rvalue =
+                Rvalue::BinaryOp(BinOp::BitXor, Box::new((Operand::Copy(bits), int_const(0xD800))));
+            let flipped = assign(local_decls, check.int_ty, rvalue);
+            let rvalue =
+                Rvalue::BinaryOp(BinOp::Sub, Box::new((Operand::Copy(flipped), int_const(0x800))));
+            let shifted = assign(local_decls, check.int_ty, rvalue);
+            let rvalue = Rvalue::BinaryOp(
+                BinOp::Lt,
+                Box::new((Operand::Copy(shifted), int_const(0x110000 - 0x800))),
+            );
+            assign(local_decls, tcx.types.bool, rvalue)
+        }
+        // Switch on the integer, continuing to new_block if it is one of the values and failing
+        // the assert in a new block otherwise
+        ValidBits::Values(values) => {
+            let fail_block = basic_blocks.push(BasicBlockData {
+                statements: Vec::new(),
+                terminator: Some(Terminator {
+                    source_info,
+                    kind: TerminatorKind::Assert {
+                        cond: Operand::const_from_scalar(
+                            tcx,
+                            tcx.types.bool,
+                            Scalar::from_bool(false),
+                            span,
+                        ),
+                        expected: true,
+                        target: new_block,
+                        msg: Box::new(msg),
+                        // See below.
+                        unwind: UnwindAction::Unreachable,
+                    },
+                }),
+                is_cleanup,
+            });
+            let targets =
+                SwitchTargets::new(values.into_iter().map(|v| (v, new_block)), fail_block);
+            basic_blocks[block].terminator = Some(Terminator {
+                source_info,
+                kind: TerminatorKind::SwitchInt { discr: Operand::Copy(bits), targets },
+            });
+            return;
+        }
+    };
+
+    // Set this block's terminator to our assert, continuing to new_block if we pass
+    basic_blocks[block].terminator = Some(Terminator {
+        source_info,
+        kind: TerminatorKind::Assert {
+            cond: Operand::Copy(is_ok),
+            expected: true,
+            target: new_block,
+            msg: Box::new(msg),
+            // This calls one of the panic_invalid_* lang items, which are #[rustc_nounwind].
+            // We never want to insert an unwind into unsafe code, because unwinding could
+            // make a failing UB check turn into much worse UB when we start unwinding.
+            unwind: UnwindAction::Unreachable,
+        },
+    });
+}
@@ -107,6 +107,7 @@
 use smallvec::SmallVec;
 use tracing::{debug, instrument, trace};
 
+use crate::remarks::{MirRemarkArg, MirRemarkKind, Remarks};
 use crate::ssa::{AssignedValue, SsaLocals};
 
 pub(super) struct GVN;
@@ -151,6 +152,7 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
 
         // Stop creating opaques during replacement as it is useless.
         state.next_opaque = None;
+        state.remarks = Remarks::new(tcx, crate::MirPass::name(self), body);
 
         let reverse_postorder = body.basic_blocks.reverse_postorder().to_vec();
         for bb in reverse_postorder {
@@ -259,6 +261,8 @@ struct VnState<'body, 'tcx> {
     ssa: &'body SsaLocals,
     dominators: Dominators<BasicBlock>,
     reused_locals: BitSet<Local>,
+    /// Set during replacement if the remarks of the pass were requested with `-Zmir-remarks`.
+    remarks: Option<Remarks<'tcx>>,
 }
 
 impl<'body, 'tcx> VnState<'body, 'tcx> {
@@ -290,6 +294,7 @@ fn new(
             ssa,
             dominators,
             reused_locals: BitSet::new_empty(local_decls.len()),
+            remarks: None,
         }
     }
 
@@ -1650,10 +1655,32 @@ fn visit_statement(&mut self, stmt: &mut Statement<'tcx>, location: Location) {
             let Some(value) = value else { return };
 
             if let Some(const_) = self.try_as_constant(value) {
+                if let Some(remarks) = &self.remarks {
+                    remarks.record(
+                        MirRemarkKind::Passed,
+                        stmt.source_info.span,
+                        format!("replaced the value assigned to `{lhs:?}` with `{const_}`"),
+                        [
+                            ("place", MirRemarkArg::from(format!("{lhs:?}"))),
+                            ("constant", const_.to_string().into()),
+                        ],
+                    );
+                }
                 *rvalue = Rvalue::Use(Operand::Constant(Box::new(const_)));
             } else if let Some(local) = self.try_as_local(value, location)
                 && *rvalue != Rvalue::Use(Operand::Move(local.into()))
             {
+                if let Some(remarks) = &self.remarks {
+                    remarks.record(
+                        MirRemarkKind::Passed,
+                        stmt.source_info.span,
+                        format!("reused the value of `{local:?}` for `{lhs:?}`"),
+                        [
+                            ("place", MirRemarkArg::from(format!("{lhs:?}"))),
+                            ("reused_local", format!("{local:?}").into()),
+                        ],
+                    );
+                }
                 *rvalue = Rvalue::Use(Operand::Copy(local.into()));
                 self.reused_locals.insert(local);
             }
@@ -21,6 +21,7 @@
 
 use crate::cost_checker::CostChecker;
 use crate::deref_separator::deref_finder;
+use crate::remarks::{MirRemarkArg, MirRemarkKind, Remarks};
 use crate::simplify::simplify_cfg;
 use crate::util;
 use crate::validate::validate_types;
@@ -41,6 +42,27 @@ struct CallSite<'tcx> {
     source_info: SourceInfo,
 }
 
+/// The cost of a callee body, and the threshold it is compared with.
+#[derive(Clone, Copy, Debug)]
+struct InlineCost {
+    cost: usize,
+    threshold: usize,
+}
+
+/// Why a callsite was not inlined.
+#[derive(Debug)]
+struct NotInlined {
+    reason: &'static str,
+    /// Only known if the callee was rejected after its body was examined.
+    cost: Option<InlineCost>,
+}
+
+impl From<&'static str> for NotInlined {
+    fn from(reason: &'static str) -> Self {
+        NotInlined { reason, cost: None }
+    }
+}
+
 impl<'tcx> crate::MirPass<'tcx> for Inline {
     fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
         // FIXME(#127234): Coverage instrumentation currently doesn't handle inlined
@@ -67,7 +89,8 @@ fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
     fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
         let span = trace_span!("inline", body = %tcx.def_path_str(body.source.def_id()));
         let _guard = span.enter();
-        if inline(tcx, body) {
+        let remarks = Remarks::new(tcx, crate::MirPass::name(self), body);
+        if inline(tcx, body, remarks) {
             debug!("running simplify cfg on {:?}", body.source);
             simplify_cfg(body);
             deref_finder(tcx, body);
@@ -75,7 +98,7 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
     }
 }
 
-fn inline<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) -> bool {
+fn inline<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>, remarks: Option<Remarks<'tcx>>) -> bool {
     let def_id = body.source.def_id().expect_local();
 
     // Only do inlining into fn bodies.
@@ -105,6 +128,7 @@ fn inline<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) -> bool {
             codegen_fn_attrs.inline,
             InlineAttr::Hint | InlineAttr::Always
         ) && body_is_forwarder(body),
+        remarks,
     };
     let blocks = START_BLOCK..body.basic_blocks.next_index();
     this.process_blocks(body, blocks);
@@ -127,6 +151,8 @@ struct Inliner<'tcx> {
     /// Indicates that the caller is #[inline] and just calls another function,
     /// and thus we can inline less into it as it'll be inlined itself.
     caller_is_inline_forwarder: bool,
+    /// Set if the remarks of the inliner were requested with `-Zmir-remarks`.
+    remarks: Option<Remarks<'tcx>>,
 }
 
 impl<'tcx> Inliner<'tcx> {
@@ -153,11 +179,13 @@ fn process_blocks(&mut self, caller_body: &mut Body<'tcx>, blocks: Range<BasicBl
             let _guard = span.enter();
 
             match self.try_inlining(caller_body, &callsite) {
-                Err(reason) => {
-                    debug!("not-inlined {} [{}]", callsite.callee, reason);
+                Err(not_inlined) => {
+                    debug!("not-inlined {} [{}]", callsite.callee, not_inlined.reason);
+                    self.remark(&callsite, Err(&not_inlined));
                 }
-                Ok(new_blocks) => {
+                Ok((new_blocks, cost)) => {
                     debug!("inlined {}", callsite.callee);
+                    self.remark(&callsite, Ok(cost));
                     self.changed = true;
 
                     self.history.push(callsite.callee.def_id());
@@ -174,14 +202,48 @@ fn process_blocks(&mut self, caller_body: &mut Body<'tcx>, blocks: Range<BasicBl
         }
     }
 
+    /// Records the decision of the inliner on a callsite, if remarks were requested.
+    fn remark(&self, callsite: &CallSite<'tcx>, result: Result<InlineCost, &NotInlined>) {
+        let Some(remarks) = &self.remarks else { return };
+        let callee = callsite.callee.to_string();
+        // The callsite may be in the body of a callee that was just inlined.
+        let caller = match self.history.last() {
+            Some(&def_id) => self.tcx.def_path_str(def_id),
+            None => remarks.function().to_owned(),
+        };
+        let (kind, cost, mut message) = match result {
+            Ok(cost) => {
+                (MirRemarkKind::Passed, Some(cost), format!("inlined `{callee}` into `{caller}`"))
+            }
+            Err(not_inlined) => (
+                MirRemarkKind::Missed,
+                not_inlined.cost,
+                format!("`{callee}` not inlined into `{caller}`: {}", not_inlined.reason),
+            ),
+        };
+        let mut args = vec![("callee", MirRemarkArg::from(callee)), ("caller", caller.into())];
+        if let Some(InlineCost { cost, threshold }) = cost {
+            message.push_str(&format!(" (cost={cost}, threshold={threshold})"));
+            args.extend([("cost", cost.into()), ("threshold", threshold.into())]);
+        }
+        if let Err(not_inlined) = result {
+            args.push(("reason", not_inlined.reason.into()));
+        }
+        remarks.record(kind, callsite.source_info.span, message, args);
+    }
+
     /// Attempts to inline a callsite into the caller body. When successful returns basic blocks
-    /// containing the inlined body. Otherwise returns an error describing why inlining didn't take
-    /// place.
+    /// containing the inlined body, and the cost of the callee. Otherwise returns an error
+    /// describing why inlining didn't take place.
     fn try_inlining(
         &self,
         caller_body: &mut Body<'tcx>,
         callsite: &CallSite<'tcx>,
-    ) -> Result<std::ops::Range<BasicBlock>, &'static str> {
+    ) -> Result<(std::ops::Range<BasicBlock>, InlineCost), NotInlined> {
+        if self.history.contains(&callsite.callee.def_id()) {
+            return Err("recursion through an inlined callee".into());
+        }
+
         self.check_mir_is_available(caller_body, callsite.callee)?;
 
         let callee_attrs = self.tcx.codegen_fn_attrs(callsite.callee.def_id());
@@ -192,7 +254,7 @@ fn try_inlining(
         // but at this stage we don't know whether codegen knows the intrinsic,
         // so just conservatively don't inline it.
         if self.tcx.has_attr(callsite.callee.def_id(), sym::rustc_intrinsic) {
-            return Err("Callee is an intrinsic, do not inline fallback bodies");
+            return Err("Callee is an intrinsic, do not inline fallback bodies".into());
         }
 
         let terminator = caller_body[callsite.block].terminator.as_ref().unwrap();
@@ -203,25 +265,26 @@ fn try_inlining(
             {
                 // We do not allow inlining functions with unsized params. Inlining these functions
                 // could create unsized locals, which are unsound and being phased out.
-                return Err("Call has unsized argument");
+                return Err("Call has unsized argument".into());
             }
         }
 
         let callee_body = try_instance_mir(self.tcx, callsite.callee.def)?;
-        self.check_mir_body(callsite, callee_body, callee_attrs, cross_crate_inlinable)?;
+        let cost =
+            self.check_mir_body(callsite, callee_body, callee_attrs, cross_crate_inlinable)?;
 
         let Ok(callee_body) = callsite.callee.try_instantiate_mir_and_normalize_erasing_regions(
             self.tcx,
             self.typing_env,
             ty::EarlyBinder::bind(callee_body.clone()),
         ) else {
-            return Err("failed to normalize callee body");
+            return Err("failed to normalize callee body".into());
         };
 
         // Normally, this shouldn't be required, but trait normalization failure can create a
         // validation ICE.
         if !validate_types(self.tcx, self.typing_env, &callee_body, &caller_body).is_empty() {
-            return Err("failed to validate callee body");
+            return Err("failed to validate callee body".into());
         }
 
         // Check call signature compatibility.
@@ -230,14 +293,14 @@ fn try_inlining(
         let output_type = callee_body.return_ty();
         if !util::sub_types(self.tcx, self.typing_env, output_type, destination_ty) {
             trace!(?output_type, ?destination_ty);
-            return Err("failed to normalize return type");
+            return Err("failed to normalize return type".into());
         }
         if callsite.fn_sig.abi() == ExternAbi::RustCall {
             // FIXME: Don't inline user-written `extern "rust-call"` functions,
             // since this is generally perf-negative on rustc, and we hope that
             // LLVM will inline these functions instead.
             if callee_body.spread_arg.is_some() {
-                return Err("do not inline user-written rust-call functions");
+                return Err("do not inline user-written rust-call functions".into());
             }
 
             let (self_arg, arg_tuple) = match &args[..] {
@@ -260,7 +323,7 @@ fn try_inlining(
                 let input_type = callee_body.local_decls[input].ty;
                 if !util::sub_types(self.tcx, self.typing_env, input_type, arg_ty) {
                     trace!(?arg_ty, ?input_type);
-                    return Err("failed to normalize tuple argument type");
+                    return Err("failed to normalize tuple argument type".into());
                 }
             }
         } else {
@@ -269,7 +332,7 @@ fn try_inlining(
                 let arg_ty = arg.node.ty(&caller_body.local_decls, self.tcx);
                 if !util::sub_types(self.tcx, self.typing_env, input_type, arg_ty) {
                     trace!(?arg_ty, ?input_type);
-                    return Err("failed to normalize argument type");
+                    return Err("failed to normalize argument type".into());
                 }
             }
         }
@@ -278,7 +341,7 @@ fn try_inlining(
         self.inline_call(caller_body, callsite, callee_body);
         let new_blocks = old_blocks..caller_body.basic_blocks.next_index();
 
-        Ok(new_blocks)
+        Ok((new_blocks, cost))
     }
 
     fn check_mir_is_available(
@@ -378,10 +441,6 @@ fn resolve_callsite(
                     return None;
                 }
 
-                if self.history.contains(&callee.def_id()) {
-                    return None;
-                }
-
                 let fn_sig = self.tcx.fn_sig(def_id).instantiate(self.tcx, args);
 
                 // Additionally, check that the body that we're inlining actually agrees
@@ -470,11 +529,11 @@ fn check_mir_body(
         callee_body: &Body<'tcx>,
         callee_attrs: &CodegenFnAttrs,
         cross_crate_inlinable: bool,
-    ) -> Result<(), &'static str> {
+    ) -> Result<InlineCost, NotInlined> {
         let tcx = self.tcx;
 
         if let Some(_) = callee_body.tainted_by_errors {
-            return Err("Body is tainted");
+            return Err("Body is tainted".into());
         }
 
         let mut threshold = if self.caller_is_inline_forwarder {
@@ -533,11 +592,11 @@ fn check_mir_body(
                 // assign one. However, during this stage we require an exact match when any
                 // inline-asm is detected. LLVM will still possibly do an inline later on
                 // if the no-attribute function ends up with the same instruction set anyway.
-                return Err("Cannot move inline-asm across instruction sets");
+                return Err("Cannot move inline-asm across instruction sets".into());
             } else if let TerminatorKind::TailCall { .. } = term.kind {
                 // FIXME(explicit_tail_calls): figure out how exactly functions containing tail
                 // calls can be inlined (and if they even should)
-                return Err("can't inline functions with tail calls");
+                return Err("can't inline functions with tail calls".into());
             } else {
                 work_list.extend(term.successors())
             }
@@ -550,10 +609,13 @@ fn check_mir_body(
         let cost = checker.cost();
         if cost <= threshold {
             debug!("INLINING {:?} [cost={} <= threshold={}]", callsite, cost, threshold);
-            Ok(())
+            Ok(InlineCost { cost, threshold })
         } else {
             debug!("NOT inlining {:?} [cost={} > threshold={}]", callsite, cost, threshold);
-            Err("cost above threshold")
+            Err(NotInlined {
+                reason: "cost above threshold",
+                cost: Some(InlineCost { cost, threshold }),
+            })
         }
     }
 
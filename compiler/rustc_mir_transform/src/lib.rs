@@ -51,6 +51,7 @@
 mod ffi_unwind_calls;
 mod lint;
 mod lint_tail_expr_drop_order;
+mod remarks;
 mod shim;
 mod ssa;
 
@@ -116,6 +117,8 @@ macro_rules! declare_passes {
     mod add_subtyping_projections : Subtyper;
     mod check_alignment : CheckAlignment;
     mod check_const_item_mutation : CheckConstItemMutation;
+    mod check_invalid_values : CheckInvalidValues;
+    mod check_null : CheckNull;
     mod check_packed_ref : CheckPackedRef;
     mod check_undefined_transmutes : CheckUndefinedTransmutes;
     // This pass is public to allow external drivers to perform MIR cleanup
@@ -655,6 +658,9 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
         &[
             // Add some UB checks before any UB gets optimized away.
             &check_alignment::CheckAlignment,
+            &check_null::CheckNull,
+            // Runs after the pointer checks, since its checks read through the checked pointers.
+            &check_invalid_values::CheckInvalidValues,
             // Before inlining: trim down MIR with passes to reduce inlining work.
 
             // Has to be done before inlining, otherwise actual call will be almost always inlined.
@@ -770,6 +776,28 @@ fn inner_optimized_mir(tcx: TyCtxt<'_>, did: LocalDefId) -> Body<'_> {
     body
 }
 
+/// Prepares a body that replaces the optimized MIR `original` of an item for codegen, such as a
+/// body provided by a `stable_mir` driver.
+///
+/// This records the constants and items that are required or mentioned by the new body in
+/// addition to those of the original one, and checks that the new body is valid MIR.
+pub fn finish_replaced_optimized_mir<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    original: &Body<'tcx>,
+    body: &mut Body<'tcx>,
+) {
+    body.required_consts = None;
+    RequiredConstsVisitor::compute_required_consts(body);
+    body.required_consts.as_mut().unwrap().extend_from_slice(original.required_consts());
+
+    body.mentioned_items = None;
+    mentioned_items::MentionedItems.run_pass(tcx, body);
+    body.mentioned_items.as_mut().unwrap().extend_from_slice(original.mentioned_items());
+
+    validate::Validator { when: "after replacing the optimized MIR".to_owned() }
+        .run_pass(tcx, body);
+}
+
 /// Fetch all the promoteds of an item and prepare their MIR bodies to be ready for
 /// constant evaluation once all generic parameters become known.
 fn promoted_mir(tcx: TyCtxt<'_>, def: LocalDefId) -> &IndexVec<Promoted, Body<'_>> {
@@ -5,6 +5,7 @@
 use rustc_middle::mir::{self, Body, MirPhase, RuntimePhase};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
+use rustc_session::config::Passes;
 use tracing::trace;
 
 use crate::lint::lint_body;
@@ -206,6 +207,14 @@ fn run_passes_inner<'tcx>(
         tcx.dcx().emit_warn(errors::UnknownPassName { name });
     }
 
+    if let Passes::Some(remarked_passes) = &tcx.sess.opts.unstable_opts.mir_remarks_passes {
+        for name in remarked_passes {
+            if !crate::PASS_NAMES.contains(name.as_str()) {
+                tcx.dcx().emit_warn(errors::UnknownPassName { name });
+            }
+        }
+    }
+
     // Verify that no passes are missing from the `declare_passes` invocation
     #[cfg(debug_assertions)]
     #[allow(rustc::diagnostic_outside_of_impl)]
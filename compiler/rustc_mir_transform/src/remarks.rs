@@ -0,0 +1,141 @@
+//! Optimization remarks of the MIR passes, written with `-Z mir-remarks`.
+//!
+//! Each remark is recorded as a JSON object while a pass optimizes a body, and the remarks of the
+//! session are written to the file given to `-Z mir-remarks`, one object per line.
+
+use std::fmt::Write as _;
+
+use rustc_middle::mir::Body;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Span;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum MirRemarkKind {
+    /// The pass applied an optimization.
+    Passed,
+    /// The pass considered an optimization, and rejected it.
+    Missed,
+}
+
+impl MirRemarkKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MirRemarkKind::Passed => "passed",
+            MirRemarkKind::Missed => "missed",
+        }
+    }
+}
+
+/// A value attached to a remark, for tools that process the remarks.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum MirRemarkArg {
+    Int(u64),
+    Str(String),
+}
+
+impl MirRemarkArg {
+    fn write_json(&self, out: &mut String) {
+        match self {
+            MirRemarkArg::Int(value) => write!(out, "{value}").unwrap(),
+            MirRemarkArg::Str(value) => write_json_string(out, value),
+        }
+    }
+}
+
+impl From<usize> for MirRemarkArg {
+    fn from(value: usize) -> Self {
+        MirRemarkArg::Int(value as u64)
+    }
+}
+
+impl From<String> for MirRemarkArg {
+    fn from(value: String) -> Self {
+        MirRemarkArg::Str(value)
+    }
+}
+
+impl From<&str> for MirRemarkArg {
+    fn from(value: &str) -> Self {
+        MirRemarkArg::Str(value.to_owned())
+    }
+}
+
+/// Records the remarks of one pass on one body.
+pub(crate) struct Remarks<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pass: &'static str,
+    function: String,
+}
+
+impl<'tcx> Remarks<'tcx> {
+    /// Returns `None` if the remarks of `pass` were not requested, so that passes only describe
+    /// their decisions when needed.
+    pub(crate) fn new(tcx: TyCtxt<'tcx>, pass: &'static str, body: &Body<'tcx>) -> Option<Self> {
+        let opts = &tcx.sess.opts.unstable_opts;
+        if opts.mir_remarks.is_none() || !opts.mir_remarks_passes.contains(pass) {
+            return None;
+        }
+        let function = tcx.def_path_str(body.source.def_id());
+        Some(Remarks { tcx, pass, function })
+    }
+
+    /// The path of the function whose body the pass optimizes.
+    pub(crate) fn function(&self) -> &str {
+        &self.function
+    }
+
+    pub(crate) fn record(
+        &self,
+        kind: MirRemarkKind,
+        span: Span,
+        message: String,
+        args: impl IntoIterator<Item = (&'static str, MirRemarkArg)>,
+    ) {
+        // Point at the macro invocation rather than into the macro definition.
+        let loc = self.tcx.sess.source_map().lookup_char_pos(span.source_callsite().lo());
+        let file = self.tcx.sess.source_map().filename_for_diagnostics(&loc.file.name).to_string();
+
+        let mut line = String::from("{");
+        for (name, value) in [
+            ("pass", self.pass),
+            ("kind", kind.as_str()),
+            ("function", self.function.as_str()),
+            ("file", file.as_str()),
+        ] {
+            write_json_string(&mut line, name);
+            line.push(':');
+            write_json_string(&mut line, value);
+            line.push(',');
+        }
+        write!(line, r#""line":{},"column":{},"message":"#, loc.line, loc.col.0 + 1).unwrap();
+        write_json_string(&mut line, &message);
+        line.push_str(r#","args":{"#);
+        for (index, (name, value)) in args.into_iter().enumerate() {
+            if index > 0 {
+                line.push(',');
+            }
+            write_json_string(&mut line, name);
+            line.push(':');
+            value.write_json(&mut line);
+        }
+        line.push_str("}}");
+        self.tcx.sess.mir_remarks.record(line);
+    }
+}
+
+/// Writes `value` as a JSON string literal.
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
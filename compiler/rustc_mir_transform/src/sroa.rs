@@ -11,6 +11,8 @@
 use rustc_mir_dataflow::value_analysis::{excluded_locals, iter_fields};
 use tracing::{debug, instrument};
 
+use crate::remarks::{MirRemarkArg, MirRemarkKind, Remarks};
+
 pub(super) struct ScalarReplacementOfAggregates;
 
 impl<'tcx> crate::MirPass<'tcx> for ScalarReplacementOfAggregates {
@@ -27,6 +29,7 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
             return;
         }
 
+        let remarks = Remarks::new(tcx, crate::MirPass::name(self), body);
         let mut excluded = excluded_locals(body);
         let typing_env = body.typing_env(tcx);
         loop {
@@ -35,6 +38,9 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
             debug!(?escaping);
             let replacements = compute_flattening(tcx, typing_env, body, escaping);
             debug!(?replacements);
+            if let Some(remarks) = &remarks {
+                remark_flattening(remarks, body, &replacements);
+            }
             let all_dead_locals = replace_flattened_locals(tcx, body, replacements);
             if !all_dead_locals.is_empty() {
                 excluded.union(&all_dead_locals);
@@ -221,6 +227,29 @@ fn compute_flattening<'tcx>(
     ReplacementMap { fragments }
 }
 
+/// Record which locals are replaced by one local for each of their fields.
+fn remark_flattening<'tcx>(
+    remarks: &Remarks<'tcx>,
+    body: &Body<'tcx>,
+    replacements: &ReplacementMap<'tcx>,
+) {
+    for (local, fields) in replacements.fragments.iter_enumerated() {
+        let Some(fields) = fields else { continue };
+        let decl = &body.local_decls[local];
+        let fields = fields.iter().flatten().count();
+        remarks.record(
+            MirRemarkKind::Passed,
+            decl.source_info.span,
+            format!("replaced `{local:?}` of type `{}` by {fields} locals", decl.ty),
+            [
+                ("local", MirRemarkArg::from(format!("{local:?}"))),
+                ("ty", decl.ty.to_string().into()),
+                ("fields", fields.into()),
+            ],
+        );
+    }
+}
+
 /// Perform the replacement computed by `compute_flattening`.
 fn replace_flattened_locals<'tcx>(
     tcx: TyCtxt<'tcx>,
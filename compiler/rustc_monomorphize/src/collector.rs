@@ -278,6 +278,10 @@ fn record_used<'a>(&mut self, user_item: MonoItem<'tcx>, used_items: &'a MonoIte
         assert!(self.used_map.insert(user_item, used_items.items().collect()).is_none());
     }
 
+    pub(crate) fn get_used_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
+        self.used_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
+    }
+
     pub(crate) fn get_user_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
         self.user_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
     }
@@ -1114,6 +1118,59 @@ fn create_fn_mono_item<'tcx>(
     respan(source, MonoItem::Fn(instance))
 }
 
+/// Returns the types and traits of the vtables created by the unsizing casts
+/// of `instance`, for `-Z symbol-size-report`.
+pub(crate) fn vtables_of_instance<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+) -> Vec<(Ty<'tcx>, Option<ty::PolyExistentialTraitRef<'tcx>>)> {
+    struct VtableCollector<'a, 'tcx> {
+        tcx: TyCtxt<'tcx>,
+        body: &'a mir::Body<'tcx>,
+        instance: Instance<'tcx>,
+        vtables: Vec<(Ty<'tcx>, Option<ty::PolyExistentialTraitRef<'tcx>>)>,
+    }
+
+    impl<'a, 'tcx> MirVisitor<'tcx> for VtableCollector<'a, 'tcx> {
+        fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
+            if let mir::Rvalue::Cast(
+                mir::CastKind::PointerCoercion(PointerCoercion::Unsize, _)
+                | mir::CastKind::PointerCoercion(PointerCoercion::DynStar, _),
+                ref operand,
+                target_ty,
+            ) = *rvalue
+            {
+                let span = self.body.source_info(location).span;
+                let monomorphize = |ty| {
+                    self.instance.instantiate_mir_and_normalize_erasing_regions(
+                        self.tcx,
+                        ty::TypingEnv::fully_monomorphized(),
+                        ty::EarlyBinder::bind(ty),
+                    )
+                };
+                let source_ty = monomorphize(operand.ty(self.body, self.tcx));
+                let target_ty = monomorphize(target_ty);
+                let (source_ty, target_ty) =
+                    find_vtable_types_for_unsizing(self.tcx.at(span), source_ty, target_ty);
+                // Like in `MirUsedCollector::visit_rvalue`, only the casts to a
+                // trait object from another type create a vtable.
+                if let ty::Dynamic(data, ..) = *target_ty.kind()
+                    && ((target_ty.is_trait() && !source_ty.is_trait())
+                        || (target_ty.is_dyn_star() && !source_ty.is_dyn_star()))
+                {
+                    self.vtables.push((source_ty, data.principal()));
+                }
+            }
+            self.super_rvalue(rvalue, location);
+        }
+    }
+
+    let body = tcx.instance_mir(instance.def);
+    let mut collector = VtableCollector { tcx, body, instance, vtables: Vec::new() };
+    collector.visit_body(body);
+    collector.vtables
+}
+
 /// Creates a `MonoItem` for each method that is referenced by the vtable for
 /// the given trait/impl pair.
 fn create_mono_items_for_vtable_methods<'tcx>(
@@ -17,8 +17,10 @@
 
 mod collector;
 mod errors;
+mod merging;
 mod mono_checks;
 mod partitioning;
+mod stack_usage;
 mod util;
 
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
@@ -0,0 +1,344 @@
+//! Merging of identical monomorphized functions, enabled with `-Zmerge-mono-items`.
+//!
+//! Instances of a generic function often compile to the same code, for example
+//! when the only difference between their generic arguments is the pointee type
+//! of a reference, or when the generic arguments have the same layout. Rather
+//! than leaving it to LLVM's `mergefunc` pass, which only sees one codegen unit
+//! at a time and runs after the duplicate bodies were already code generated
+//! and optimized, we find such instances before codegen and define all but one
+//! of them as aliases.
+//!
+//! Two instances of the same function are considered identical when their
+//! monomorphized MIR is the same after replacing every type but enums by its
+//! layout, and they refer to identical functions, vtables and drop glue. As instances may
+//! refer to each other, the latter is computed as a fixpoint: we start by
+//! grouping the instances by their MIR alone, then split the groups by the
+//! groups of the functions they refer to, until no group is split anymore.
+
+use std::fmt::Write;
+
+use rustc_abi::Layout;
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
+use rustc_hir::def_id::DefId;
+use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+use rustc_middle::mir::mono::{CodegenUnit, Linkage, MonoItem, Visibility};
+use rustc_middle::mir::visit::{MutVisitor, Visitor};
+use rustc_middle::mir::{self, Body, ConstOperand, Location};
+use rustc_middle::ty::adjustment::PointerCoercion;
+use rustc_middle::ty::fold::{TypeFoldable, TypeFolder, TypeSuperFoldable};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Instance, InstanceKind, Ty, TyCtxt};
+use rustc_span::Symbol;
+use tracing::debug;
+
+/// Something a function body refers to, whose identity matters for codegen.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum Reference<'tcx> {
+    /// A function, called or used as a function pointer.
+    Fn(Instance<'tcx>),
+    /// A function that is itself a merge candidate, by its current class.
+    Class(usize),
+    /// An unsizing cast, which may need the vtable of `source` for `target`.
+    Unsize(Ty<'tcx>, Ty<'tcx>),
+}
+
+struct Candidate<'tcx> {
+    /// The index of the codegen unit that defines the instance.
+    cgu: usize,
+    instance: Instance<'tcx>,
+    /// The MIR of the instance with types replaced by their layouts, printed.
+    body: String,
+    references: Vec<Reference<'tcx>>,
+}
+
+/// Defines each function whose body is identical to the one of a function
+/// defined earlier as an alias of that function, in the codegen unit of the
+/// latter.
+pub(crate) fn merge_identical_fns<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    codegen_units: &mut [CodegenUnit<'tcx>],
+) {
+    let mut layouts = FxIndexMap::default();
+    let mut candidates = vec![];
+    for (cgu_index, cgu) in codegen_units.iter().enumerate() {
+        for (item, data) in cgu.items_in_deterministic_order(tcx) {
+            let MonoItem::Fn(instance) = item else { continue };
+            // Only functions with a single definition in a single codegen unit
+            // can be turned into aliases.
+            if data.inlined || !matches!(data.linkage, Linkage::External | Linkage::Internal) {
+                continue;
+            }
+            if !is_mergeable(tcx, instance) {
+                continue;
+            }
+            if let Some((body, references)) = describe_body(tcx, instance, &mut layouts) {
+                candidates.push(Candidate { cgu: cgu_index, instance, body, references });
+            }
+        }
+    }
+
+    let classes = compute_classes(&candidates);
+
+    let mut representatives = FxHashMap::default();
+    let mut merged = 0;
+    for (candidate, class) in candidates.iter().zip(classes) {
+        let Some(&(representative_cgu, representative)) = representatives.get(&class) else {
+            representatives.insert(class, (candidate.cgu, candidate.instance));
+            continue;
+        };
+
+        debug!("merging {} into {}", candidate.instance, representative);
+        let item = MonoItem::Fn(candidate.instance);
+        let mut data = codegen_units[candidate.cgu].items_mut().shift_remove(&item).unwrap();
+        if candidate.cgu != representative_cgu && data.linkage == Linkage::Internal {
+            // The users of the function were all in its original codegen unit,
+            // and now refer to it from another one.
+            data.linkage = Linkage::External;
+            data.visibility = Visibility::Hidden;
+        }
+        let cgu = &mut codegen_units[representative_cgu];
+        cgu.items_mut().insert(item, data);
+        cgu.add_fn_alias(candidate.instance, representative);
+        merged += 1;
+    }
+    debug!("merged {merged} of {} candidate functions", candidates.len());
+
+    for cgu in codegen_units.iter_mut() {
+        cgu.compute_size_estimate();
+    }
+}
+
+fn is_mergeable<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> bool {
+    match instance.def {
+        // Only generic functions have several instances to merge.
+        InstanceKind::Item(def_id) => {
+            let attrs = tcx.codegen_fn_attrs(def_id);
+            instance.args.non_erasable_generics().next().is_some()
+                && !attrs.contains_extern_indicator()
+                && !attrs.flags.contains(CodegenFnAttrFlags::NAKED)
+        }
+        InstanceKind::DropGlue(_, Some(_)) => true,
+        _ => false,
+    }
+}
+
+/// Returns the printed MIR of `instance`, with every type replaced by its
+/// layout, and the functions and vtables it refers to, or `None` if the body
+/// cannot be compared with the body of other instances.
+fn describe_body<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    layouts: &mut FxIndexMap<Layout<'tcx>, u32>,
+) -> Option<(String, Vec<Reference<'tcx>>)> {
+    let typing_env = ty::TypingEnv::fully_monomorphized();
+    let body = tcx.instance_mir(instance.def);
+    if body.coroutine.is_some() {
+        return None;
+    }
+    let mut body = instance.instantiate_mir_and_normalize_erasing_regions(
+        tcx,
+        typing_env,
+        ty::EarlyBinder::bind(body.clone()),
+    );
+
+    // Constants that depend on the generic arguments, like `size_of::<T>()`,
+    // are compared by value.
+    let mut evaluator = ConstEvaluator { tcx, failed: false };
+    evaluator.visit_body(&mut body);
+    if evaluator.failed {
+        return None;
+    }
+
+    let mut collector = ReferenceCollector { tcx, body: &body, references: vec![], failed: false };
+    collector.visit_body(&body);
+    if collector.failed {
+        return None;
+    }
+    let references = collector.references;
+
+    let body = body.fold_with(&mut LayoutFolder { tcx, layouts });
+    let mut printed = String::new();
+    with_no_trimmed_paths!({
+        writeln!(printed, "args: {} {:?}", body.arg_count, body.spread_arg).unwrap();
+        for decl in body.local_decls.iter() {
+            writeln!(printed, "{:?}", decl.ty).unwrap();
+        }
+        for block in body.basic_blocks.iter() {
+            for statement in &block.statements {
+                writeln!(printed, "{statement:?}").unwrap();
+            }
+            writeln!(printed, "{:?}", block.terminator().kind).unwrap();
+        }
+    });
+    Some((printed, references))
+}
+
+/// Groups the candidates into classes of identical functions, and returns the
+/// class of each candidate.
+fn compute_classes(candidates: &[Candidate<'_>]) -> Vec<usize> {
+    let mut initial = FxIndexMap::default();
+    let mut classes: Vec<usize> = candidates
+        .iter()
+        .map(|candidate| {
+            let key: (DefId, &str) = (candidate.instance.def_id(), &candidate.body);
+            let len = initial.len();
+            *initial.entry(key).or_insert(len)
+        })
+        .collect();
+    let mut class_count = initial.len();
+
+    let index: FxHashMap<_, _> =
+        candidates.iter().enumerate().map(|(i, candidate)| (candidate.instance, i)).collect();
+    loop {
+        let mut refined = FxIndexMap::default();
+        let new_classes: Vec<usize> = candidates
+            .iter()
+            .zip(&classes)
+            .map(|(candidate, &class)| {
+                let references: Vec<Reference<'_>> = candidate
+                    .references
+                    .iter()
+                    .map(|reference| match *reference {
+                        Reference::Fn(instance) => match index.get(&instance) {
+                            Some(&i) => Reference::Class(classes[i]),
+                            None => *reference,
+                        },
+                        _ => *reference,
+                    })
+                    .collect();
+                let len = refined.len();
+                *refined.entry((class, references)).or_insert(len)
+            })
+            .collect();
+        classes = new_classes;
+        // Refining never merges classes, so the classes are stable once their
+        // number stops growing.
+        if refined.len() == class_count {
+            return classes;
+        }
+        class_count = refined.len();
+    }
+}
+
+struct ConstEvaluator<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    failed: bool,
+}
+
+impl<'tcx> MutVisitor<'tcx> for ConstEvaluator<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_const_operand(&mut self, constant: &mut ConstOperand<'tcx>, _location: Location) {
+        if let mir::Const::Val(..) = constant.const_ {
+            return;
+        }
+        let typing_env = ty::TypingEnv::fully_monomorphized();
+        match constant.const_.eval(self.tcx, typing_env, constant.span) {
+            Ok(val) => constant.const_ = mir::Const::Val(val, constant.const_.ty()),
+            Err(_) => self.failed = true,
+        }
+    }
+}
+
+struct ReferenceCollector<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    references: Vec<Reference<'tcx>>,
+    failed: bool,
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for ReferenceCollector<'a, 'tcx> {
+    fn visit_const_operand(&mut self, constant: &ConstOperand<'tcx>, _location: Location) {
+        if let ty::FnDef(def_id, args) = *constant.const_.ty().kind() {
+            let typing_env = ty::TypingEnv::fully_monomorphized();
+            match Instance::try_resolve(self.tcx, typing_env, def_id, args) {
+                Ok(Some(instance)) => self.references.push(Reference::Fn(instance)),
+                _ => self.failed = true,
+            }
+        }
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
+        match *rvalue {
+            mir::Rvalue::Cast(
+                mir::CastKind::PointerCoercion(PointerCoercion::Unsize, _)
+                | mir::CastKind::PointerCoercion(PointerCoercion::DynStar, _),
+                ref operand,
+                target_ty,
+            ) => {
+                let source_ty = operand.ty(self.body, self.tcx);
+                self.references.push(Reference::Unsize(source_ty, target_ty));
+            }
+            mir::Rvalue::Cast(
+                mir::CastKind::PointerCoercion(PointerCoercion::ClosureFnPointer(_), _),
+                ref operand,
+                _,
+            ) => {
+                if let ty::Closure(def_id, args) = *operand.ty(self.body, self.tcx).kind() {
+                    let instance =
+                        Instance::resolve_closure(self.tcx, def_id, args, ty::ClosureKind::FnOnce);
+                    self.references.push(Reference::Fn(instance));
+                } else {
+                    self.failed = true;
+                }
+            }
+            _ => {}
+        }
+        self.super_rvalue(rvalue, location);
+    }
+
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Location) {
+        if let mir::TerminatorKind::Drop { place, .. } = terminator.kind {
+            let ty = place.ty(self.body, self.tcx).ty;
+            self.references.push(Reference::Fn(Instance::resolve_drop_in_place(self.tcx, ty)));
+        }
+        self.super_terminator(terminator, location);
+    }
+}
+
+/// Replaces the types whose values are handled as a whole by their layout,
+/// represented by a type parameter. Pointee types are folded too: a sized
+/// pointee is replaced by its layout like any other type, while an unsized
+/// pointee keeps its tail, which determines the metadata of the pointer.
+///
+/// Enums are kept, with their generic arguments folded: their layout does not
+/// describe the values of their discriminants, so two enums with the same
+/// layout may still read and write different tags for the same variant.
+struct LayoutFolder<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    layouts: &'a mut FxIndexMap<Layout<'tcx>, u32>,
+}
+
+impl<'a, 'tcx> TypeFolder<TyCtxt<'tcx>> for LayoutFolder<'a, 'tcx> {
+    fn cx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn fold_ty(&mut self, ty: Ty<'tcx>) -> Ty<'tcx> {
+        match *ty.kind() {
+            ty::Adt(def, _) if def.is_box() || def.is_enum() => ty.super_fold_with(self),
+            ty::Adt(..) | ty::Tuple(_) | ty::Closure(..) | ty::CoroutineClosure(..) => {
+                let typing_env = ty::TypingEnv::fully_monomorphized();
+                match self.tcx.layout_of(typing_env.as_query_input(ty)) {
+                    Ok(layout) if !layout.is_unsized() => {
+                        let len = self.layouts.len() as u32;
+                        let index = *self.layouts.entry(layout.layout).or_insert(len);
+                        Ty::new_param(self.tcx, index, Symbol::intern(&format!("L{index}")))
+                    }
+                    // Unsized types keep their tail, which determines the
+                    // metadata of pointers to them.
+                    _ => ty.super_fold_with(self),
+                }
+            }
+            // The functions are compared through the references of the body.
+            ty::FnDef(..) => Ty::new_param(self.tcx, u32::MAX, Symbol::intern("fn")),
+            ty::Ref(..) | ty::RawPtr(..) | ty::Array(..) | ty::Slice(_) | ty::FnPtr(..) => {
+                ty.super_fold_with(self)
+            }
+            // Primitive types, `str` and trait objects are compared as is.
+            _ => ty,
+        }
+    }
+}
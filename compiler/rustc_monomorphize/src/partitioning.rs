@@ -110,7 +110,7 @@
 use rustc_middle::middle::exported_symbols::{SymbolExportInfo, SymbolExportLevel};
 use rustc_middle::mir::mono::{
     CodegenUnit, CodegenUnitNameBuilder, InstantiationMode, Linkage, MonoItem, MonoItemData,
-    Visibility,
+    Visibility, symbol_owner, vtable_symbol_name,
 };
 use rustc_middle::ty::print::{characteristic_def_id_of_type, with_no_trimmed_paths};
 use rustc_middle::ty::{self, InstanceKind, TyCtxt};
@@ -123,6 +123,7 @@
 
 use crate::collector::{self, MonoItemCollectionStrategy, UsageMap};
 use crate::errors::{CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode};
+use crate::{merging, stack_usage};
 
 struct PartitioningCx<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -178,6 +179,22 @@ fn partition<'tcx, I>(
         debug_dump(tcx, "INTERNALIZE", &codegen_units);
     }
 
+    // Define functions with identical bodies as aliases of a single copy. This
+    // is done after internalization, which would otherwise have to know about
+    // the codegen units the aliases end up in. Coverage counters are per
+    // instance, and with CFI an alias would carry the type id of the function
+    // it aliases, so indirect calls through it would fail the type check.
+    if tcx.sess.opts.unstable_opts.merge_mono_items
+        && !tcx.sess.instrument_coverage()
+        && !tcx.sess.is_sanitizer_cfi_enabled()
+        && !tcx.sess.is_sanitizer_kcfi_enabled()
+    {
+        let _prof_timer = tcx.prof.generic_activity("cgu_partitioning_merge_mono_items");
+        merging::merge_identical_fns(tcx, &mut codegen_units);
+
+        debug_dump(tcx, "MERGE_MONO_ITEMS", &codegen_units);
+    }
+
     // Mark one CGU for dead code, if necessary.
     if tcx.sess.instrument_coverage() {
         mark_code_coverage_dead_code_cgu(&mut codegen_units);
@@ -1114,6 +1131,51 @@ fn assert_symbols_are_distinct<'a, 'tcx, I>(tcx: TyCtxt<'tcx>, mono_items: I)
     }
 }
 
+/// Records the items owning the symbols of the mono items, for `-Z symbol-size-report`.
+fn record_symbol_owners<'tcx>(tcx: TyCtxt<'tcx>, codegen_units: &[CodegenUnit<'tcx>]) {
+    for cgu in codegen_units {
+        for &item in cgu.items().keys() {
+            let (kind, instance) = match item {
+                MonoItem::Fn(instance) => {
+                    ("function", with_no_trimmed_paths!(instance.to_string()))
+                }
+                MonoItem::Static(def_id) => {
+                    ("static", with_no_trimmed_paths!(tcx.def_path_str(def_id)))
+                }
+                MonoItem::GlobalAsm(_) => continue,
+            };
+            let owner = symbol_owner(tcx, kind, item.def_id(), instance);
+            tcx.sess.symbol_owners.record(item.symbol_name(tcx).name.to_owned(), owner);
+        }
+    }
+}
+
+/// Records the types or traits owning the symbols of the vtables that the mono
+/// items create, for `-Z symbol-size-report`. They are found in the MIR rather
+/// than during codegen, which does not run for the codegen units reused by
+/// incremental compilation.
+fn record_vtable_owners<'tcx>(tcx: TyCtxt<'tcx>, codegen_units: &[CodegenUnit<'tcx>]) {
+    let mut vtables = FxIndexSet::default();
+    for cgu in codegen_units {
+        for &item in cgu.items().keys() {
+            if let MonoItem::Fn(instance) = item {
+                vtables.extend(collector::vtables_of_instance(tcx, instance));
+            }
+        }
+    }
+    for (ty, trait_ref) in vtables {
+        // Attribute the vtable to the type if it is defined by a crate, and to the trait otherwise.
+        let Some(def_id) = ty.ty_adt_def().map(|adt| adt.did()).or(trait_ref.map(|t| t.def_id()))
+        else {
+            continue;
+        };
+        let name = vtable_symbol_name(tcx, ty, trait_ref);
+        let mut owner = symbol_owner(tcx, "vtable", def_id, name.clone());
+        owner.item = name.clone();
+        tcx.sess.symbol_owners.record(name, owner);
+    }
+}
+
 fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[CodegenUnit<'_>]) {
     let collection_strategy = match tcx.sess.opts.unstable_opts.print_mono_items {
         Some(ref s) => {
@@ -1156,6 +1218,15 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[Co
         )
     });
 
+    if tcx.sess.opts.unstable_opts.stack_usage_report.is_some() {
+        stack_usage::record_call_graph(tcx, &items, &usage_map, codegen_units);
+    }
+
+    if tcx.sess.opts.unstable_opts.symbol_size_report.is_some() {
+        record_symbol_owners(tcx, codegen_units);
+        record_vtable_owners(tcx, codegen_units);
+    }
+
     if tcx.prof.enabled() {
         // Record CGU size estimates for self-profiling.
         for cgu in codegen_units {
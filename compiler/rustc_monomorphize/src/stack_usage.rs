@@ -0,0 +1,123 @@
+//! Gathering of the static call graph of the crate for `-Z stack-usage-report`.
+//!
+//! The call graph comes from the usage map of the collector, completed with
+//! the calls the usage map does not describe: calls to functions of other
+//! crates, which are not mono items of this crate, and calls through function
+//! pointers and trait objects, whose callees are not known statically. The
+//! stack sizes of the functions are only known once the backend has code
+//! generated them, so the report itself is written by `rustc_codegen_ssa`.
+
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_hir::lang_items::LangItem;
+use rustc_middle::mir::mono::{CodegenUnit, MonoItem};
+use rustc_middle::mir::{self, Operand};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Instance, InstanceKind, TyCtxt};
+use rustc_session::stack_usage::StackUsageFn;
+
+use crate::collector::UsageMap;
+
+pub(crate) fn record_call_graph<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &[MonoItem<'tcx>],
+    usage_map: &UsageMap<'tcx>,
+    codegen_units: &[CodegenUnit<'tcx>],
+) {
+    let entry_fn = tcx.entry_fn(()).map(|(def_id, _)| def_id);
+    let mut fns: Vec<StackUsageFn> = items
+        .iter()
+        .filter_map(|&item| {
+            let MonoItem::Fn(instance) = item else { return None };
+            let mut callees: FxIndexSet<String> = usage_map
+                .get_used_items(item)
+                .iter()
+                .filter(|used| matches!(used, MonoItem::Fn(_)))
+                .map(|used| used.symbol_name(tcx).name.to_owned())
+                .collect();
+            // A function defined as an alias of another one, see
+            // `-Zmerge-mono-items`, uses the stack frame of the latter.
+            for cgu in codegen_units {
+                if let Some(aliasee) = cgu.fn_alias(instance) {
+                    callees.insert(tcx.symbol_name(aliasee).name.to_owned());
+                }
+            }
+            let (external_callees, has_indirect_calls) = scan_calls(tcx, instance);
+            let is_entry_point = match instance.def {
+                InstanceKind::Item(def_id) => {
+                    entry_fn == Some(def_id) || tcx.is_reachable_non_generic(def_id)
+                }
+                _ => false,
+            };
+            Some(StackUsageFn {
+                symbol: item.symbol_name(tcx).name.to_owned(),
+                name: with_no_trimmed_paths!(instance.to_string()),
+                is_entry_point,
+                callees: callees.into_iter().collect(),
+                external_callees: external_callees.into_iter().collect(),
+                has_indirect_calls,
+            })
+        })
+        .collect();
+    fns.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    tcx.sess.stack_usage_call_graph.record(fns);
+}
+
+/// Returns the paths of the functions of other crates that `instance` calls,
+/// and whether it makes calls whose callee is not known statically.
+fn scan_calls<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> (FxIndexSet<String>, bool) {
+    let mut callees = vec![];
+    let mut has_indirect_calls = false;
+
+    let body = tcx.instance_mir(instance.def);
+    let typing_env = ty::TypingEnv::fully_monomorphized();
+    for block in body.basic_blocks.iter() {
+        match &block.terminator().kind {
+            mir::TerminatorKind::Call { func, .. } | mir::TerminatorKind::TailCall { func, .. } => {
+                let Operand::Constant(constant) = func else {
+                    has_indirect_calls = true;
+                    continue;
+                };
+                let fn_ty = instance.instantiate_mir_and_normalize_erasing_regions(
+                    tcx,
+                    typing_env,
+                    ty::EarlyBinder::bind(constant.const_.ty()),
+                );
+                let ty::FnDef(def_id, args) = *fn_ty.kind() else {
+                    has_indirect_calls = true;
+                    continue;
+                };
+                match Instance::try_resolve(tcx, typing_env, def_id, args) {
+                    Ok(Some(callee)) => callees.push(callee),
+                    _ => has_indirect_calls = true,
+                }
+            }
+            mir::TerminatorKind::Assert { msg, .. } => {
+                let lang_item = match &**msg {
+                    mir::AssertKind::BoundsCheck { .. } => LangItem::PanicBoundsCheck,
+                    mir::AssertKind::MisalignedPointerDereference { .. } => {
+                        LangItem::PanicMisalignedPointerDereference
+                    }
+                    _ => msg.panic_function(),
+                };
+                let def_id = tcx.require_lang_item(lang_item, None);
+                callees.push(Instance::mono(tcx, def_id));
+            }
+            _ => {}
+        }
+    }
+
+    let mut external_callees = FxIndexSet::default();
+    for callee in callees {
+        match callee.def {
+            InstanceKind::Virtual(..) => has_indirect_calls = true,
+            // Intrinsics are code generated inline.
+            InstanceKind::Intrinsic(_) => {}
+            _ => {
+                if !tcx.should_codegen_locally(callee) {
+                    external_callees.insert(with_no_trimmed_paths!(callee.to_string()));
+                }
+            }
+        }
+    }
+    (external_callees, has_indirect_calls)
+}
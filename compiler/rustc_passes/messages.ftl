@@ -621,6 +621,10 @@ passes_pass_by_value =
 
 passes_proc_macro_bad_sig = {$kind} has incorrect signature
 
+passes_proc_macro_deterministic_not_proc_macro =
+    `#[proc_macro_deterministic]` can only be applied to proc macro functions
+    .label = not a proc macro function
+
 passes_remove_fields =
     consider removing { $num ->
       [one] this
@@ -123,6 +123,7 @@ fn check_attributes(
                 [sym::inline, ..] => self.check_inline(hir_id, attr, span, target),
                 [sym::coverage, ..] => self.check_coverage(attr, span, target),
                 [sym::optimize, ..] => self.check_optimize(hir_id, attr, span, target),
+                [sym::blocking, ..] => self.check_blocking(hir_id, attr, span, target),
                 [sym::no_sanitize, ..] => self.check_no_sanitize(attr, span, target),
                 [sym::non_exhaustive, ..] => self.check_non_exhaustive(hir_id, attr, span, target),
                 [sym::marker, ..] => self.check_marker(hir_id, attr, span, target),
@@ -239,6 +240,9 @@ fn check_attributes(
                     self.check_generic_attr(hir_id, attr, target, Target::Fn);
                     self.check_proc_macro(hir_id, target, ProcMacroKind::Derive)
                 }
+                [sym::proc_macro_deterministic, ..] => {
+                    self.check_proc_macro_deterministic(attr, span, attrs)
+                }
                 [sym::autodiff, ..] => {
                     self.check_autodiff(hir_id, attr, span, target)
                 }
@@ -1478,6 +1482,20 @@ fn check_cold(&self, hir_id: HirId, attr: &Attribute, span: Span, target: Target
         }
     }
 
+    /// Checks if `#[blocking]` is applied to a function.
+    fn check_blocking(&self, hir_id: HirId, attr: &Attribute, span: Span, target: Target) {
+        match target {
+            Target::Fn | Target::Method(..) | Target::ForeignFn => {}
+            _ => {
+                self.dcx().emit_err(errors::AttrShouldBeAppliedToFn {
+                    attr_span: attr.span,
+                    defn_span: span,
+                    on_crate: hir_id == CRATE_HIR_ID,
+                });
+            }
+        }
+    }
+
     /// Checks if `#[link]` is applied to an item other than a foreign module.
     fn check_link(&self, hir_id: HirId, attr: &Attribute, span: Span, target: Target) {
         if target == Target::ForeignMod
@@ -2303,6 +2321,16 @@ fn check_unused_attribute(&self, hir_id: HirId, attr: &Attribute) {
         });
     }
 
+    /// Checks that `#[proc_macro_deterministic]` is only applied to proc macro functions.
+    fn check_proc_macro_deterministic(&self, attr: &Attribute, span: Span, attrs: &[Attribute]) {
+        if !attrs.iter().any(|attr| attr.is_proc_macro_attr()) {
+            self.dcx().emit_err(errors::ProcMacroDeterministicNotProcMacro {
+                attr_span: attr.span,
+                span,
+            });
+        }
+    }
+
     /// A best effort attempt to create an error for a mismatching proc macro signature.
     ///
     /// If this best effort goes wrong, it will just emit a worse error later (see #102923)
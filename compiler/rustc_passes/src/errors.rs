@@ -1667,6 +1667,15 @@ pub(crate) enum ChangeFields {
     Remove { num: usize },
 }
 
+#[derive(Diagnostic)]
+#[diag(passes_proc_macro_deterministic_not_proc_macro)]
+pub(crate) struct ProcMacroDeterministicNotProcMacro {
+    #[primary_span]
+    pub attr_span: Span,
+    #[label]
+    pub span: Span,
+}
+
 #[derive(Diagnostic)]
 #[diag(passes_proc_macro_bad_sig)]
 pub(crate) struct ProcMacroBadSig {
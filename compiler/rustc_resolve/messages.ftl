@@ -131,6 +131,15 @@ resolve_const_param_in_ty_of_const_param =
 resolve_constructor_private_if_any_field_private =
     a constructor is private if any of the fields is private
 
+resolve_declare_undeclared_crate =
+    {$in_sysroot ->
+        [true] add `extern crate {$krate};` to the crate root to use crate `{$krate}`
+        *[false] add crate `{$krate}` as a dependency of this crate to use it
+    }
+
+resolve_defined_in_undeclared_crate =
+    `{$path}` is defined in crate `{$krate}`, which is not a dependency of this crate
+
 resolve_elided_anonymous_lifetime_report_error =
     `&` without an explicit lifetime name cannot be used here
     .label = explicit lifetime name needed here
@@ -23,17 +23,24 @@
 //  - `check_unused` finally emits the diagnostics based on the data generated
 //    in the last step
 
+use std::ops::Range;
+
 use rustc_ast as ast;
 use rustc_ast::visit::{self, Visitor};
+use rustc_ast_pretty::pprust;
 use rustc_data_structures::fx::{FxHashMap, FxIndexMap, FxIndexSet};
 use rustc_data_structures::unord::UnordSet;
 use rustc_errors::MultiSpan;
 use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_session::config::ExternLocation;
 use rustc_session::lint::BuiltinLintDiag;
 use rustc_session::lint::builtin::{
-    MACRO_USE_EXTERN_CRATE, UNUSED_EXTERN_CRATES, UNUSED_IMPORTS, UNUSED_QUALIFICATIONS,
+    CFG_ONLY_CRATE_DEPENDENCIES, MACRO_USE_EXTERN_CRATE, UNUSED_EXTERN_CRATES, UNUSED_IMPORTS,
+    UNUSED_QUALIFICATIONS,
 };
-use rustc_span::{DUMMY_SP, Ident, Span, kw};
+use rustc_span::source_map::SourceMap;
+use rustc_span::{BytePos, DUMMY_SP, Ident, Span, Symbol, kw, sym};
 
 use crate::imports::{Import, ImportKind};
 use crate::{LexicalScopeBinding, NameBindingKind, Resolver, module_to_string};
@@ -59,6 +66,8 @@ struct UnusedImportCheckVisitor<'a, 'ra, 'tcx> {
     base_use_tree: Option<&'a ast::UseTree>,
     base_id: ast::NodeId,
     item_span: Span,
+    /// The parts of the crate which are only compiled under some `cfg`s, with these `cfg`s.
+    cfg_regions: Vec<(Span, Vec<String>)>,
 }
 
 struct ExternCrateToLint {
@@ -209,6 +218,140 @@ fn report_unused_extern_crate_items(
             );
         }
     }
+
+    /// Records that the node spanning `span` is only compiled under the `cfg`s of its `attrs`.
+    fn record_cfg_region(&mut self, attrs: &[ast::Attribute], span: Span) {
+        // The uses of the crates are attributed to the code written by the user, see
+        // `cfg_of_use`, so the `cfg`s of the nodes produced by macros are not tracked.
+        if span.from_expansion() {
+            return;
+        }
+        let cfgs: Vec<_> = attrs
+            .iter()
+            .filter(|attr| attr.has_name(sym::cfg))
+            .filter_map(|attr| match attr.meta_item_list()?.as_slice() {
+                [cfg] => Some(pprust::meta_list_item_to_string(cfg)),
+                _ => None,
+            })
+            .collect();
+        if !cfgs.is_empty() {
+            let span = attrs.iter().fold(span, |acc, attr| acc.to(attr.span));
+            self.cfg_regions.push((span, cfgs));
+        }
+    }
+
+    /// Returns the `cfg` predicate under which the code at `span` is compiled, or `None` if it
+    /// is compiled unconditionally.
+    fn cfg_of_use(&self, span: Span) -> Option<String> {
+        let span = span.source_callsite();
+        let cfgs: Vec<&str> = self
+            .cfg_regions
+            .iter()
+            .filter(|(region, _)| region.contains(span))
+            .flat_map(|(_, cfgs)| cfgs.iter().map(String::as_str))
+            .collect();
+        match cfgs[..] {
+            [] => None,
+            [cfg] => Some(cfg.to_string()),
+            _ => Some(format!("all({})", cfgs.join(", "))),
+        }
+    }
+
+    /// Returns the `cfg` under which all the `uses` are compiled, or `None` if some of them
+    /// are compiled unconditionally.
+    fn cfg_of_uses(&self, uses: impl IntoIterator<Item = Span>) -> Option<String> {
+        let mut cfgs = FxIndexSet::default();
+        for span in uses {
+            cfgs.insert(self.cfg_of_use(span)?);
+        }
+        let cfgs: Vec<_> = cfgs.into_iter().collect();
+        match &cfgs[..] {
+            [] => None,
+            [cfg] => Some(format!("cfg({cfg})")),
+            _ => Some(format!("cfg(any({}))", cfgs.join(", "))),
+        }
+    }
+
+    /// Reports the crate dependencies and the `extern crate` items which are only used in code
+    /// compiled under some `cfg`s, and could be restricted to these `cfg`s.
+    fn report_cfg_only_crate_dependencies(&mut self) {
+        let tcx = self.r.tcx();
+        let extern_crate_uses = &self.r.extern_crate_uses;
+        let no_uses = FxIndexSet::default();
+
+        // `extern crate` items which are compiled unconditionally but whose crate is only used
+        // under some `cfg`s.
+        for extern_crate in &self.extern_crate_items {
+            // Items with attributes, like `#[macro_use]`, may be needed even if the crate is
+            // never named, and the crates renamed to `_` can't be named at all.
+            if extern_crate.has_attrs || extern_crate.ident.name == kw::Underscore {
+                continue;
+            }
+            let def_id = self.r.local_def_id(extern_crate.id);
+            if self.r.effective_visibilities.is_exported(def_id)
+                || self.cfg_of_use(extern_crate.span).is_some()
+            {
+                continue;
+            }
+            let Some(cnum) = self.r.extern_crate_map.get(&def_id) else { continue };
+            let Some(uses) = extern_crate_uses.get(cnum) else { continue };
+            let Some(cfg) = self.cfg_of_uses(uses.iter().copied()) else { continue };
+            self.r.lint_buffer.buffer_lint(
+                CFG_ONLY_CRATE_DEPENDENCIES,
+                extern_crate.id,
+                extern_crate.span,
+                BuiltinLintDiag::CfgOnlyExternCrate {
+                    extern_crate: extern_crate.ident,
+                    cfg,
+                    uses: uses.iter().copied().collect(),
+                    item_span: extern_crate.span_with_attributes,
+                },
+            );
+        }
+
+        // Dependencies passed with `--extern`, whose uses and `extern crate` items are all
+        // compiled under some `cfg`s.
+        for (name, entry) in tcx.sess.opts.externs.iter() {
+            if let ExternLocation::FoundInLibrarySearchDirectories = entry.location {
+                // Don't worry about pathless `--extern foo` sysroot references
+                continue;
+            }
+            if entry.nounused_dep || entry.force {
+                continue;
+            }
+            let name = Symbol::intern(name);
+            let Some(cnum) = self
+                .r
+                .extern_prelude
+                .get(&Ident::with_dummy_span(name))
+                .and_then(|entry| entry.binding?.module()?.opt_def_id())
+                .map(|def_id| def_id.krate)
+            else {
+                // The crate is never used, which is reported by `unused_crate_dependencies`.
+                continue;
+            };
+            let items = self
+                .extern_crate_items
+                .iter()
+                .filter(|item| {
+                    self.r.extern_crate_map.get(&self.r.local_def_id(item.id)) == Some(&cnum)
+                })
+                .map(|item| item.span);
+            let uses = extern_crate_uses.get(&cnum).unwrap_or(&no_uses);
+            let spans: Vec<_> = uses.iter().copied().chain(items).collect();
+            let Some(cfg) = self.cfg_of_uses(spans.iter().copied()) else { continue };
+            self.r.lint_buffer.buffer_lint(
+                CFG_ONLY_CRATE_DEPENDENCIES,
+                ast::CRATE_NODE_ID,
+                spans,
+                BuiltinLintDiag::CfgOnlyCrateDependency {
+                    extern_crate: name,
+                    local_crate: tcx.crate_name(LOCAL_CRATE),
+                    cfg,
+                },
+            );
+        }
+    }
 }
 
 impl<'a, 'ra, 'tcx> Visitor<'a> for UnusedImportCheckVisitor<'a, 'ra, 'tcx> {
@@ -234,9 +377,45 @@ fn visit_item(&mut self, item: &'a ast::Item) {
         }
 
         self.item_span = item.span_with_attributes();
+        self.record_cfg_region(&item.attrs, item.span);
+        visit::walk_item(self, item);
+    }
+
+    fn visit_foreign_item(&mut self, item: &'a ast::ForeignItem) {
+        self.record_cfg_region(&item.attrs, item.span);
         visit::walk_item(self, item);
     }
 
+    fn visit_assoc_item(&mut self, item: &'a ast::AssocItem, ctxt: visit::AssocCtxt) {
+        self.record_cfg_region(&item.attrs, item.span);
+        visit::walk_assoc_item(self, item, ctxt);
+    }
+
+    fn visit_field_def(&mut self, field: &'a ast::FieldDef) {
+        self.record_cfg_region(&field.attrs, field.span);
+        visit::walk_field_def(self, field);
+    }
+
+    fn visit_variant(&mut self, variant: &'a ast::Variant) {
+        self.record_cfg_region(&variant.attrs, variant.span);
+        visit::walk_variant(self, variant);
+    }
+
+    fn visit_local(&mut self, local: &'a ast::Local) {
+        self.record_cfg_region(&local.attrs, local.span);
+        visit::walk_local(self, local);
+    }
+
+    fn visit_arm(&mut self, arm: &'a ast::Arm) {
+        self.record_cfg_region(&arm.attrs, arm.span);
+        visit::walk_arm(self, arm);
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        self.record_cfg_region(&expr.attrs, expr.span);
+        visit::walk_expr(self, expr);
+    }
+
     fn visit_use_tree(&mut self, use_tree: &'a ast::UseTree, id: ast::NodeId, nested: bool) {
         // Use the base UseTree's NodeId as the item id
         // This allows the grouping of all the lints in the same item
@@ -269,6 +448,7 @@ enum UnusedSpanResult {
 }
 
 fn calc_unused_spans(
+    sm: &SourceMap,
     unused_import: &UnusedImport,
     use_tree: &ast::UseTree,
     use_tree_id: ast::NodeId,
@@ -295,87 +475,124 @@ fn calc_unused_spans(
 
             let mut unused_spans = Vec::new();
             let mut to_remove = Vec::new();
-            let mut used_children = 0;
-            let mut contains_self = false;
-            let mut previous_unused = false;
-            for (pos, (use_tree, use_tree_id)) in nested.iter().enumerate() {
-                let remove = match calc_unused_spans(unused_import, use_tree, *use_tree_id) {
-                    UnusedSpanResult::Used => {
-                        used_children += 1;
-                        None
-                    }
-                    UnusedSpanResult::Unused { mut spans, remove } => {
+            // Whether each tree of the group is removed as a whole.
+            let mut removed = Vec::with_capacity(nested.len());
+            for (use_tree, use_tree_id) in nested {
+                match calc_unused_spans(sm, unused_import, use_tree, *use_tree_id) {
+                    UnusedSpanResult::Used => removed.push(false),
+                    UnusedSpanResult::Unused { mut spans, .. } => {
                         unused_spans.append(&mut spans);
-                        Some(remove)
+                        removed.push(true);
                     }
                     UnusedSpanResult::PartialUnused { mut spans, remove: mut to_remove_extra } => {
-                        used_children += 1;
                         unused_spans.append(&mut spans);
                         to_remove.append(&mut to_remove_extra);
-                        None
-                    }
-                };
-                if let Some(remove) = remove {
-                    let remove_span = if nested.len() == 1 {
-                        remove
-                    } else if pos == nested.len() - 1 || used_children > 0 {
-                        // Delete everything from the end of the last import, to delete the
-                        // previous comma
-                        nested[pos - 1].0.span.shrink_to_hi().to(use_tree.span)
-                    } else {
-                        // Delete everything until the next import, to delete the trailing commas
-                        use_tree.span.to(nested[pos + 1].0.span.shrink_to_lo())
-                    };
-
-                    // Try to collapse adjacent spans into a single one. This prevents all cases of
-                    // overlapping removals, which are not supported by rustfix
-                    if previous_unused && !to_remove.is_empty() {
-                        let previous = to_remove.pop().unwrap();
-                        to_remove.push(previous.to(remove_span));
-                    } else {
-                        to_remove.push(remove_span);
+                        removed.push(false);
                     }
                 }
-                contains_self |= use_tree.prefix == kw::SelfLower
-                    && matches!(use_tree.kind, ast::UseTreeKind::Simple(None));
-                previous_unused = remove.is_some();
             }
             if unused_spans.is_empty() {
-                UnusedSpanResult::Used
-            } else if used_children == 0 {
-                UnusedSpanResult::Unused { spans: unused_spans, remove: full_span }
-            } else {
-                // If there is only one remaining child that is used, the braces around the use
-                // tree are not needed anymore. In that case, we determine the span of the left
-                // brace and the right brace, and tell rustfix to remove them as well.
+                return UnusedSpanResult::Used;
+            }
+
+            let mut kept_trees = nested.iter().zip(&removed).filter(|(_, removed)| !**removed);
+            match (kept_trees.next(), kept_trees.next()) {
+                (None, _) => UnusedSpanResult::Unused { spans: unused_spans, remove: full_span },
+                // If there is only one remaining tree, the braces around it are not needed
+                // anymore. In that case, we tell rustfix to remove everything from the left
+                // brace to the tree and from the tree to the right brace, which also removes the
+                // other trees of the group.
                 //
                 // This means that `use a::{B, C};` will be turned into `use a::B;` rather than
                 // `use a::{B};`, removing a rustfmt roundtrip.
                 //
-                // Note that we cannot remove the braces if the only item inside the use tree is
-                // `self`: `use foo::{self};` is valid Rust syntax, while `use foo::self;` errors
-                // out. We also cannot turn `use foo::{self}` into `use foo`, as the former doesn't
-                // import types with the same name as the module.
-                if used_children == 1 && !contains_self {
-                    // Left brace, from the start of the nested group to the first item.
-                    to_remove.push(
-                        tree_span.shrink_to_lo().to(nested.first().unwrap().0.span.shrink_to_lo()),
-                    );
-                    // Right brace, from the end of the last item to the end of the nested group.
-                    to_remove.push(
-                        nested.last().unwrap().0.span.shrink_to_hi().to(tree_span.shrink_to_hi()),
-                    );
+                // Note that we cannot remove the braces if the remaining tree is `self`:
+                // `use foo::{self};` and `use foo::{self as bar};` are valid Rust syntax, while
+                // `use foo::self;` errors out. We also cannot turn `use foo::{self}` into
+                // `use foo`, as the former doesn't import types with the same name as the module.
+                (Some(((kept, _), _)), None)
+                    if !(kept.prefix == kw::SelfLower
+                        && matches!(kept.kind, ast::UseTreeKind::Simple(..))) =>
+                {
+                    to_remove.push(tree_span.shrink_to_lo().to(kept.span.shrink_to_lo()));
+                    to_remove.push(kept.span.shrink_to_hi().to(tree_span.shrink_to_hi()));
+                    UnusedSpanResult::PartialUnused { spans: unused_spans, remove: to_remove }
+                }
+                _ => {
+                    // Remove each run of adjacent removed trees with a single span, as
+                    // overlapping removals are not supported by rustfix.
+                    let mut start = 0;
+                    while start < nested.len() {
+                        if !removed[start] {
+                            start += 1;
+                            continue;
+                        }
+                        let end =
+                            (start..nested.len()).find(|&i| !removed[i]).unwrap_or(nested.len());
+                        to_remove.push(run_removal_span(sm, nested, tree_span, start..end));
+                        start = end;
+                    }
+                    UnusedSpanResult::PartialUnused { spans: unused_spans, remove: to_remove }
                 }
-
-                UnusedSpanResult::PartialUnused { spans: unused_spans, remove: to_remove }
             }
         }
     }
 }
 
+/// Computes the span to remove for the trees of `nested` in `run`, which are removed while some
+/// other trees of the group are kept.
+///
+/// When the trees of the run are on lines of their own, whole lines are removed, so that the
+/// indentation of the group and the comments of the kept trees are preserved. Otherwise, the
+/// separating comma is removed along with the trees, the one before them if there is a kept tree
+/// before, the one after them otherwise.
+fn run_removal_span(
+    sm: &SourceMap,
+    nested: &[(ast::UseTree, ast::NodeId)],
+    tree_span: Span,
+    run: Range<usize>,
+) -> Span {
+    let first = nested[run.start].0.span;
+    let last = nested[run.end - 1].0.span;
+    let before = match run.start.checked_sub(1) {
+        Some(prev) => nested[prev].0.span.shrink_to_hi(),
+        None => tree_span.with_lo(tree_span.lo() + BytePos(1)).shrink_to_lo(),
+    };
+    let after = match nested.get(run.end) {
+        Some((next, _)) => next.span.shrink_to_lo(),
+        None => tree_span.with_hi(tree_span.hi() - BytePos(1)).shrink_to_hi(),
+    };
+
+    // The indentation of the first tree, if nothing else precedes it on its line.
+    let indent = sm.span_to_snippet(before.between(first)).ok().and_then(|gap| {
+        let (_, indent) = gap.rsplit_once('\n')?;
+        indent.trim().is_empty().then_some(indent.len())
+    });
+    // The end of the line of the last tree, if only a comma and a comment follow it.
+    let line_end = sm.span_to_snippet(last.between(after)).ok().and_then(|gap| {
+        let (rest, _) = gap.split_once('\n')?;
+        let code = rest.find("//").map_or(rest, |comment| &rest[..comment]);
+        matches!(code.trim(), "" | ",").then_some(rest.len() + 1)
+    });
+    if let (Some(indent), Some(line_end)) = (indent, line_end) {
+        return first
+            .with_lo(first.lo() - BytePos(indent as u32))
+            .with_hi(last.hi() + BytePos(line_end as u32));
+    }
+
+    if run.start > 0 {
+        // Delete everything from the end of the last kept tree, to delete the previous comma
+        before.to(last)
+    } else {
+        // Delete everything until the next tree, to delete the trailing commas
+        first.to(after)
+    }
+}
+
 impl Resolver<'_, '_> {
     pub(crate) fn check_unused(&mut self, krate: &ast::Crate) {
         let tcx = self.tcx;
+        let sm = tcx.sess.source_map();
         let mut maybe_unused_extern_crates = FxHashMap::default();
 
         for import in self.potentially_unused_imports.iter() {
@@ -425,14 +642,16 @@ pub(crate) fn check_unused(&mut self, krate: &ast::Crate) {
             base_use_tree: None,
             base_id: ast::DUMMY_NODE_ID,
             item_span: DUMMY_SP,
+            cfg_regions: Vec::new(),
         };
         visit::walk_crate(&mut visitor, krate);
 
         visitor.report_unused_extern_crate_items(maybe_unused_extern_crates);
+        visitor.report_cfg_only_crate_dependencies();
 
         for unused in visitor.unused_imports.values() {
             let (spans, remove_spans) =
-                match calc_unused_spans(unused, &unused.use_tree, unused.use_tree_id) {
+                match calc_unused_spans(sm, unused, &unused.use_tree, unused.use_tree_id) {
                     UnusedSpanResult::Used => continue,
                     UnusedSpanResult::Unused { spans, remove } => (spans, vec![remove]),
                     UnusedSpanResult::PartialUnused { spans, remove } => (spans, remove),
@@ -5,7 +5,7 @@
     self as ast, CRATE_NODE_ID, Crate, ItemKind, MetaItemInner, MetaItemKind, ModKind, NodeId, Path,
 };
 use rustc_ast_pretty::pprust;
-use rustc_data_structures::fx::FxHashSet;
+use rustc_data_structures::fx::{FxHashSet, FxIndexSet};
 use rustc_errors::codes::*;
 use rustc_errors::{
     Applicability, Diag, DiagCtxtHandle, ErrorGuaranteed, MultiSpan, SuggestionStyle,
@@ -15,7 +15,7 @@
 use rustc_hir::PrimTy;
 use rustc_hir::def::Namespace::{self, *};
 use rustc_hir::def::{self, CtorKind, CtorOf, DefKind, NonMacroAttrKind, PerNS};
-use rustc_hir::def_id::{CRATE_DEF_ID, DefId};
+use rustc_hir::def_id::{CRATE_DEF_ID, DefId, LOCAL_CRATE};
 use rustc_middle::bug;
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
@@ -2563,6 +2563,50 @@ pub(crate) fn find_cfg_stripped(&self, err: &mut Diag<'_>, segment: &Symbol, mod
             }
         }
     }
+
+    /// Finds the items named `name` in the crates of the library search paths
+    /// which aren't declared, for a name which doesn't resolve to any item of
+    /// the declared crates.
+    pub(crate) fn find_undeclared_crate_items(
+        &mut self,
+        err: &mut Diag<'_>,
+        name: Symbol,
+        is_expected: impl Fn(Res) -> bool,
+    ) {
+        if self.undeclared_crate_items.is_none() {
+            let local_crate = self.tcx.crate_name(LOCAL_CRATE);
+            let items = self.cstore().find_undeclared_crate_items(self.tcx, &|krate| {
+                krate == local_crate
+                    || self.extern_prelude.contains_key(&Ident::with_dummy_span(krate))
+            });
+            self.undeclared_crate_items = Some(items);
+        }
+        let Some(items) = self.undeclared_crate_items.as_ref().and_then(|items| items.get(&name))
+        else {
+            return;
+        };
+
+        let mut items: Vec<_> = items
+            .iter()
+            .filter(|item| is_expected(Res::Def(item.def_kind, CRATE_DEF_ID.to_def_id())))
+            .map(|item| {
+                let path =
+                    item.path.iter().map(|name| name.as_str()).collect::<Vec<_>>().join("::");
+                (path, item.krate, item.in_sysroot)
+            })
+            .collect();
+        items.sort();
+        items.dedup();
+
+        let mut crates = FxIndexSet::default();
+        for (path, krate, in_sysroot) in items.into_iter().take(5) {
+            err.subdiagnostic(errs::DefinedInUndeclaredCrate { path, krate });
+            crates.insert((krate, in_sysroot));
+        }
+        for (krate, in_sysroot) in crates {
+            err.subdiagnostic(errs::DeclareUndeclaredCrate { krate, in_sysroot });
+        }
+    }
 }
 
 /// Given a `binding_span` of a binding within a use statement:
@@ -1254,6 +1254,20 @@ pub(crate) struct ItemWasCfgOut {
     pub(crate) span: Span,
 }
 
+#[derive(Subdiagnostic)]
+#[note(resolve_defined_in_undeclared_crate)]
+pub(crate) struct DefinedInUndeclaredCrate {
+    pub(crate) path: String,
+    pub(crate) krate: Symbol,
+}
+
+#[derive(Subdiagnostic)]
+#[help(resolve_declare_undeclared_crate)]
+pub(crate) struct DeclareUndeclaredCrate {
+    pub(crate) krate: Symbol,
+    pub(crate) in_sysroot: bool,
+}
+
 #[derive(Diagnostic)]
 #[diag(resolve_trait_impl_mismatch)]
 pub(crate) struct TraitImplMismatch {
@@ -497,6 +497,13 @@ pub(crate) fn smart_resolve_report_errors(
         if let Some(module) = base_error.module {
             self.r.find_cfg_stripped(&mut err, &path.last().unwrap().ident.name, module);
         }
+        if let [segment] = path
+            && candidates.is_empty()
+        {
+            self.r.find_undeclared_crate_items(&mut err, segment.ident.name, |res| {
+                source.is_expected(res)
+            });
+        }
 
         (err, candidates)
     }
@@ -58,6 +58,7 @@
 use rustc_hir::{PrimTy, TraitCandidate};
 use rustc_index::IndexVec;
 use rustc_metadata::creader::{CStore, CrateLoader};
+use rustc_metadata::locator::UndeclaredCrateItem;
 use rustc_middle::metadata::ModChild;
 use rustc_middle::middle::privacy::EffectiveVisibilities;
 use rustc_middle::query::Providers;
@@ -1046,6 +1047,9 @@ pub struct Resolver<'ra, 'tcx> {
 
     /// `CrateNum` resolutions of `extern crate` items.
     extern_crate_map: FxHashMap<LocalDefId, CrateNum>,
+    /// Spans of the paths which refer to other crates by their names, for the
+    /// `cfg_only_crate_dependencies` lint.
+    extern_crate_uses: FxHashMap<CrateNum, FxIndexSet<Span>>,
     module_children: LocalDefIdMap<Vec<ModChild>>,
     trait_map: NodeMap<Vec<TraitCandidate>>,
 
@@ -1183,6 +1187,9 @@ pub struct Resolver<'ra, 'tcx> {
 
     /// Names of items that were stripped out via cfg with their corresponding cfg meta item.
     stripped_cfg_items: Vec<StrippedCfgItem<NodeId>>,
+    /// Items of the crates in the library search paths which aren't declared,
+    /// indexed by name, read when a name first fails to resolve.
+    undeclared_crate_items: Option<FxIndexMap<Symbol, Vec<UndeclaredCrateItem>>>,
 
     effective_visibilities: EffectiveVisibilities,
     doc_link_resolutions: FxIndexMap<LocalDefId, DocLinkResMap>,
@@ -1449,6 +1456,7 @@ pub fn new(
             lifetimes_res_map: Default::default(),
             extra_lifetime_params_map: Default::default(),
             extern_crate_map: Default::default(),
+            extern_crate_uses: Default::default(),
             module_children: Default::default(),
             trait_map: NodeMap::default(),
             underscore_disambiguator: 0,
@@ -1539,6 +1547,7 @@ pub fn new(
             confused_type_with_std_module: Default::default(),
             lifetime_elision_allowed: Default::default(),
             stripped_cfg_items: Default::default(),
+            undeclared_crate_items: None,
             effective_visibilities: Default::default(),
             doc_link_resolutions: Default::default(),
             doc_link_traits_in_scope: Default::default(),
@@ -1902,6 +1911,9 @@ fn record_use_inner(
             }
         }
         if let NameBindingKind::Import { import, binding } = used_binding.kind {
+            if let ImportKind::ExternCrate { .. } = import.kind {
+                self.record_extern_crate_use(ident, binding);
+            }
             if let ImportKind::MacroUse { warn_private: true } = import.kind {
                 self.lint_buffer().buffer_lint(
                     PRIVATE_MACRO_USE,
@@ -2111,10 +2123,23 @@ fn extern_prelude_get(&mut self, ident: Ident, finalize: bool) -> Option<NameBin
         if let Some(entry) = self.extern_prelude.get_mut(&norm_ident) {
             entry.binding = binding;
         }
+        if finalize && let Some(binding) = binding {
+            self.record_extern_crate_use(ident, binding);
+        }
 
         binding
     }
 
+    /// Records that `ident` refers to the root of another crate through `binding`.
+    fn record_extern_crate_use(&mut self, ident: Ident, binding: NameBinding<'ra>) {
+        if let Some(module) = binding.module()
+            && let Some(def_id) = module.opt_def_id()
+            && !def_id.is_local()
+        {
+            self.extern_crate_uses.entry(def_id.krate).or_default().insert(ident.span);
+        }
+    }
+
     /// Rustdoc uses this to resolve doc link paths in a recoverable way. `PathResult<'a>`
     /// isn't something that can be returned because it can't be made to live that long,
     /// and also it's a private type. Fortunately rustdoc doesn't need to know the error,
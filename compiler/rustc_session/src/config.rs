@@ -841,12 +841,15 @@ pub enum PrintKind {
     CodeModels,
     TlsModels,
     TargetSpec,
+    TargetSpecJsonSchema,
     AllTargetSpecs,
     NativeStaticLibs,
     StackProtectorStrategies,
     LinkArgs,
     SplitDebuginfo,
     DeploymentTarget,
+    ExportedSymbols,
+    ExportedSymbolsJson,
 }
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -1323,6 +1326,13 @@ pub(crate) fn extend(&mut self, passes: impl IntoIterator<Item = String>) {
             Passes::All => {}
         }
     }
+
+    pub fn contains(&self, pass: &str) -> bool {
+        match *self {
+            Passes::Some(ref v) => v.iter().any(|p| p == pass),
+            Passes::All => true,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Hash, Debug, PartialEq)]
@@ -1543,8 +1553,9 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
             "Compiler information to print on stdout",
             "[crate-name|file-names|sysroot|target-libdir|cfg|check-cfg|calling-conventions|\
              target-list|target-cpus|target-features|relocation-models|code-models|\
-             tls-models|target-spec-json|all-target-specs-json|native-static-libs|\
-             stack-protector-strategies|link-args|deployment-target]",
+             tls-models|target-spec-json|target-spec-json-schema|all-target-specs-json|\
+             native-static-libs|stack-protector-strategies|link-args|deployment-target|\
+             exported-symbols|exported-symbols-json]",
         ),
         opt(Stable, FlagMulti, "g", "", "Equivalent to -C debuginfo=2", ""),
         opt(Stable, FlagMulti, "O", "", "Equivalent to -C opt-level=2", ""),
@@ -1981,6 +1992,8 @@ fn collect_print_requests(
         ("code-models", PrintKind::CodeModels),
         ("crate-name", PrintKind::CrateName),
         ("deployment-target", PrintKind::DeploymentTarget),
+        ("exported-symbols", PrintKind::ExportedSymbols),
+        ("exported-symbols-json", PrintKind::ExportedSymbolsJson),
         ("file-names", PrintKind::FileNames),
         ("host-tuple", PrintKind::HostTuple),
         ("link-args", PrintKind::LinkArgs),
@@ -1994,6 +2007,7 @@ fn collect_print_requests(
         ("target-libdir", PrintKind::TargetLibdir),
         ("target-list", PrintKind::TargetList),
         ("target-spec-json", PrintKind::TargetSpec),
+        ("target-spec-json-schema", PrintKind::TargetSpecJsonSchema),
         ("tls-models", PrintKind::TlsModels),
         // tidy-alphabetical-end
     ];
@@ -2018,6 +2032,16 @@ fn collect_print_requests(
                     );
                 }
             }
+            Some((_, PrintKind::TargetSpecJsonSchema)) => {
+                if unstable_opts.unstable_options {
+                    PrintKind::TargetSpecJsonSchema
+                } else {
+                    early_dcx.early_fatal(
+                        "the `-Z unstable-options` flag must also be passed to \
+                         enable the target-spec-json-schema print option",
+                    );
+                }
+            }
             Some((_, PrintKind::AllTargetSpecs)) => {
                 if unstable_opts.unstable_options {
                     PrintKind::AllTargetSpecs
@@ -2038,6 +2062,26 @@ fn collect_print_requests(
                     );
                 }
             }
+            Some((_, PrintKind::ExportedSymbols)) => {
+                if unstable_opts.unstable_options {
+                    PrintKind::ExportedSymbols
+                } else {
+                    early_dcx.early_fatal(
+                        "the `-Z unstable-options` flag must also be passed to \
+                         enable the exported-symbols print option",
+                    );
+                }
+            }
+            Some((_, PrintKind::ExportedSymbolsJson)) => {
+                if unstable_opts.unstable_options {
+                    PrintKind::ExportedSymbolsJson
+                } else {
+                    early_dcx.early_fatal(
+                        "the `-Z unstable-options` flag must also be passed to \
+                         enable the exported-symbols-json print option",
+                    );
+                }
+            }
             Some(&(_, print_kind)) => print_kind,
             None => {
                 let prints =
@@ -2914,9 +2958,9 @@ pub(crate) mod dep_tracking {
         CrateType, DebugInfo, DebugInfoCompression, ErrorOutputType, FmtDebug, FunctionReturn,
         InliningThreshold, InstrumentCoverage, InstrumentXRay, LinkerPluginLto, LocationDetail,
         LtoCli, MirStripDebugInfo, NextSolverConfig, OomStrategy, OptLevel, OutFileName,
-        OutputType, OutputTypes, PatchableFunctionEntry, Polonius, RemapPathScopeComponents,
-        ResolveDocLinks, SourceFileHashAlgorithm, SplitDwarfKind, SwitchWithOptPath,
-        SymbolManglingVersion, WasiExecModel,
+        OutputType, OutputTypes, Passes, PatchableFunctionEntry, Polonius,
+        RemapPathScopeComponents, ResolveDocLinks, SourceFileHashAlgorithm, SplitDwarfKind,
+        SwitchWithOptPath, SymbolManglingVersion, WasiExecModel,
     };
     use crate::lint;
     use crate::utils::NativeLib;
@@ -3017,6 +3061,7 @@ fn hash(
         InliningThreshold,
         FunctionReturn,
         WasmCAbi,
+        Passes,
     );
 
     impl<T1, T2> DepTrackingHash for (T1, T2)
@@ -3151,6 +3196,9 @@ pub enum ProcMacroExecutionStrategy {
 
     /// Run the proc-macro code on a different thread.
     CrossThread,
+
+    /// Run the proc-macro code in a separate helper process.
+    OutOfProcess,
 }
 
 /// How to perform collapse macros debug info
@@ -3190,6 +3238,24 @@ pub fn extension(self) -> &'static str {
     }
 }
 
+/// Which format to use for `-Z export-stable-mir`
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum StableMirExportFormat {
+    /// Emit structured JSON
+    Json,
+    /// Emit the same structure as CBOR (RFC 8949)
+    Cbor,
+}
+
+impl StableMirExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "smir.json",
+            Self::Cbor => "smir.cbor",
+        }
+    }
+}
+
 /// `-Z patchable-function-entry` representation - how many nops to put before and after function
 /// entry.
 #[derive(Clone, Copy, PartialEq, Hash, Debug, Default)]
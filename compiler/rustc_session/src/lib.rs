@@ -19,8 +19,11 @@
 pub mod config;
 pub mod cstore;
 pub mod filesearch;
+pub mod mir_remarks;
 mod options;
 pub mod search_paths;
+pub mod stack_usage;
+pub mod symbol_size;
 
 mod session;
 pub use session::*;
@@ -0,0 +1,28 @@
+//! The optimization remarks of MIR passes, gathered for `-Z mir-remarks`.
+
+use std::path::Path;
+use std::{fs, io};
+
+use rustc_data_structures::sync::Lock;
+
+/// The remarks recorded so far, each a JSON object.
+#[derive(Default)]
+pub struct MirRemarks {
+    lines: Lock<Vec<String>>,
+}
+
+impl MirRemarks {
+    pub fn record(&self, remark: String) {
+        self.lines.lock().push(remark);
+    }
+
+    /// Writes the remarks to `path`, one per line.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut contents = String::new();
+        for line in self.lines.lock().iter() {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+        fs::write(path, contents)
+    }
+}
@@ -7,7 +7,7 @@
 use rustc_data_structures::fx::FxIndexMap;
 use rustc_data_structures::profiling::TimePassesFormat;
 use rustc_data_structures::stable_hasher::Hash64;
-use rustc_errors::{ColorConfig, LanguageIdentifier, TerminalUrl};
+use rustc_errors::{ApplySuggestionsMode, ColorConfig, LanguageIdentifier, TerminalUrl};
 use rustc_feature::UnstableFeatures;
 use rustc_span::edition::Edition;
 use rustc_span::{RealFileName, SourceFileHashAlgorithm};
@@ -372,6 +372,8 @@ mod desc {
         "a comma-separated list of strings, with elements beginning with + or -";
     pub(crate) const parse_comma_list: &str = "a comma-separated list of strings";
     pub(crate) const parse_opt_comma_list: &str = parse_comma_list;
+    pub(crate) const parse_api_diff: &str = "two extern crate names separated by a comma";
+    pub(crate) const parse_apply_suggestions: &str = "one of: `write`, `diff`, or `dry-run`";
     pub(crate) const parse_number: &str = "a number";
     pub(crate) const parse_opt_number: &str = parse_number;
     pub(crate) const parse_frame_pointer: &str = "one of `true`/`yes`/`on`, `false`/`no`/`off`, or (with -Zunstable-options) `non-leaf` or `always`";
@@ -397,6 +399,7 @@ mod desc {
     pub(crate) const parse_strip: &str = "either `none`, `debuginfo`, or `symbols`";
     pub(crate) const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavorCli::one_of();
     pub(crate) const parse_dump_mono_stats: &str = "`markdown` (default) or `json`";
+    pub(crate) const parse_stable_mir_export_format: &str = "either `json` or `cbor`";
     pub(crate) const parse_instrument_coverage: &str = parse_bool;
     pub(crate) const parse_coverage_options: &str =
         "`block` | `branch` | `condition` | `mcdc` | `no-mir-spans`";
@@ -445,7 +448,7 @@ mod desc {
         "one of (`none` (default), `basic`, `strong`, or `all`)";
     pub(crate) const parse_branch_protection: &str = "a `,` separated combination of `bti`, `pac-ret`, followed by a combination of `pc`, `b-key`, or `leaf`";
     pub(crate) const parse_proc_macro_execution_strategy: &str =
-        "one of supported execution strategies (`same-thread`, or `cross-thread`)";
+        "one of supported execution strategies (`same-thread`, `cross-thread`, or `out-of-process`)";
     pub(crate) const parse_remap_path_scope: &str =
         "comma separated list of scopes: `macro`, `diagnostics`, `debuginfo`, `object`, `all`";
     pub(crate) const parse_inlining_threshold: &str =
@@ -661,6 +664,29 @@ pub(crate) fn parse_opt_comma_list(slot: &mut Option<Vec<String>>, v: Option<&st
         }
     }
 
+    pub(crate) fn parse_api_diff(slot: &mut Option<(String, String)>, v: Option<&str>) -> bool {
+        match v.and_then(|s| s.split_once(',')) {
+            Some((old, new)) if !old.is_empty() && !new.is_empty() && !new.contains(',') => {
+                *slot = Some((old.to_string(), new.to_string()));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn parse_apply_suggestions(
+        slot: &mut Option<ApplySuggestionsMode>,
+        v: Option<&str>,
+    ) -> bool {
+        *slot = match v {
+            Some("write") => Some(ApplySuggestionsMode::Write),
+            Some("diff") => Some(ApplySuggestionsMode::Diff),
+            Some("dry-run") => Some(ApplySuggestionsMode::DryRun),
+            _ => return false,
+        };
+        true
+    }
+
     pub(crate) fn parse_threads(slot: &mut usize, v: Option<&str>) -> bool {
         let ret = match v.and_then(|s| s.parse().ok()) {
             Some(0) => {
@@ -1000,6 +1026,18 @@ pub(crate) fn parse_dump_mono_stats(slot: &mut DumpMonoStatsFormat, v: Option<&s
         }
     }
 
+    pub(crate) fn parse_stable_mir_export_format(
+        slot: &mut Option<StableMirExportFormat>,
+        v: Option<&str>,
+    ) -> bool {
+        *slot = match v {
+            Some("json") => Some(StableMirExportFormat::Json),
+            Some("cbor") => Some(StableMirExportFormat::Cbor),
+            _ => return false,
+        };
+        true
+    }
+
     pub(crate) fn parse_instrument_coverage(
         slot: &mut InstrumentCoverage,
         v: Option<&str>,
@@ -1454,6 +1492,7 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         *slot = match v {
             Some("same-thread") => ProcMacroExecutionStrategy::SameThread,
             Some("cross-thread") => ProcMacroExecutionStrategy::CrossThread,
+            Some("out-of-process") => ProcMacroExecutionStrategy::OutOfProcess,
             _ => return false,
         };
         true
@@ -1684,6 +1723,14 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "only allow the listed language features to be enabled in code (comma separated)"),
     always_encode_mir: bool = (false, parse_bool, [TRACKED],
         "encode MIR of all functions into the crate metadata (default: no)"),
+    api_diff: Option<(String, String)> = (None, parse_api_diff, [UNTRACKED],
+        "compare the public API of two versions of a crate passed via `--extern` as `OLD,NEW` \
+        and report added, removed and changed items"),
+    apply_suggestions: Option<ApplySuggestionsMode> = (None, parse_apply_suggestions, [UNTRACKED],
+        "apply machine-applicable suggestions to the source files (`write`), print them as a \
+        unified diff (`diff`), or only report the files they would change (`dry-run`)"),
+    apply_suggestions_lints: Vec<String> = (Vec::new(), parse_comma_list, [UNTRACKED],
+        "only apply suggestions of the given lints with `-Z apply-suggestions` (comma separated)"),
     assert_incr_state: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "assert that the incremental cache is in given state: \
          either `loaded` or `not-loaded`."),
@@ -1697,6 +1744,9 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "emit noalias metadata for box (default: yes)"),
     branch_protection: Option<BranchProtection> = (None, parse_branch_protection, [TRACKED],
         "set options for branch target identification and pointer authentication on AArch64"),
+    cache_proc_macros: bool = (false, parse_bool, [UNTRACKED],
+        "cache the expansions of `#[proc_macro_deterministic]` proc macros across incremental \
+        sessions (default: no)"),
     cf_protection: CFProtection = (CFProtection::None, parse_cfprotection, [TRACKED],
         "instrument control-flow architecture protection"),
     check_cfg_all_expected: bool = (false, parse_bool, [UNTRACKED],
@@ -1713,6 +1763,9 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "inject the given attribute in the crate"),
     cross_crate_inline_threshold: InliningThreshold = (InliningThreshold::Sometimes(100), parse_inlining_threshold, [TRACKED],
         "threshold to allow cross crate inlining of functions"),
+    daemon: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "stay resident and compile the requests sent to the given Unix socket by compiler \
+        invocations that have `RUSTC_DAEMON` set to it"),
     debug_info_for_profiling: bool = (false, parse_bool, [TRACKED],
         "emit discriminators and other data necessary for AutoFDO"),
     debug_info_type_line_numbers: bool = (false, parse_bool, [TRACKED],
@@ -1733,6 +1786,9 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     dump_dep_graph: bool = (false, parse_bool, [UNTRACKED],
         "dump the dependency graph to $RUST_DEP_GRAPH (default: /tmp/dep_graph.gv) \
         (default: no)"),
+    dump_failed_proof_trees: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the proof tree of each goal that fails with the next trait solver \
+        as JSON to the given directory"),
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "dump MIR state to file.
         `val` is used to select which passes and functions to dump. For example:
@@ -1775,6 +1831,9 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "enforce the type length limit when monomorphizing instances in codegen"),
     export_executable_symbols: bool = (false, parse_bool, [TRACKED],
         "export symbols from executables, as if they were dynamic libraries"),
+    export_stable_mir: Option<StableMirExportFormat> = (None, parse_stable_mir_export_format, [UNTRACKED],
+        "write the stable MIR of the reachable functions of the crate, and of the types they use, \
+        to a `.smir.json` or `.smir.cbor` file"),
     external_clangrt: bool = (false, parse_bool, [UNTRACKED],
         "rely on user specified linker commands to find clangrt"),
     extra_const_ub_checks: bool = (false, parse_bool, [TRACKED],
@@ -1858,6 +1917,11 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "link native libraries in the linker invocation (default: yes)"),
     link_only: bool = (false, parse_bool, [TRACKED],
         "link the `.rlink` file generated by `-Z no-link` (default: no)"),
+    link_repro: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the linker invocations, with copies of their inputs and a script replaying them, \
+        to this directory"),
+    link_repro_on_failure: bool = (false, parse_bool, [UNTRACKED],
+        "only write the linker invocations of `-Z link-repro` which failed (default: no)"),
     linker_features: LinkerFeaturesCli = (LinkerFeaturesCli::default(), parse_linker_features, [UNTRACKED],
         "a comma-separated list of linker features to enable (+) or disable (-): `lld`"),
     lint_llvm_ir: bool = (false, parse_bool, [TRACKED],
@@ -1885,6 +1949,9 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     merge_functions: Option<MergeFunctions> = (None, parse_merge_functions, [TRACKED],
         "control the operation of the MergeFunctions LLVM pass, taking \
         the same values as the target option of the same name"),
+    merge_mono_items: bool = (false, parse_bool, [TRACKED],
+        "define monomorphized functions with identical MIR and layouts as aliases of a \
+        single copy (default: no)"),
     meta_stats: bool = (false, parse_bool, [UNTRACKED],
         "gather metadata statistics (default: no)"),
     metrics_dir: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
@@ -1906,6 +1973,11 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     #[rustc_lint_opt_deny_field_access("use `Session::mir_opt_level` instead of this field")]
     mir_opt_level: Option<usize> = (None, parse_opt_number, [TRACKED],
         "MIR optimization level (0-4; default: 1 in non optimized builds and 2 in optimized builds)"),
+    mir_remarks: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
+        "write the optimization remarks of MIR passes as JSON lines to this file"),
+    mir_remarks_passes: Passes = (Passes::All, parse_passes, [TRACKED],
+        "only write the optimization remarks of these MIR passes with `-Z mir-remarks` \
+        (space separated, or \"all\")"),
     mir_strip_debuginfo: MirStripDebugInfo = (MirStripDebugInfo::None, parse_mir_strip_debuginfo, [TRACKED],
         "Whether to remove some of the MIR debug info from methods.  Default: None"),
     move_size_limit: Option<usize> = (None, parse_opt_number, [TRACKED],
@@ -1991,6 +2063,10 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     proc_macro_execution_strategy: ProcMacroExecutionStrategy = (ProcMacroExecutionStrategy::SameThread,
         parse_proc_macro_execution_strategy, [UNTRACKED],
         "how to run proc-macro code (default: same-thread)"),
+    proc_macro_helper_wrapper: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "start the out-of-process proc-macro helper through this program, e.g. to sandbox it"),
+    proc_macro_timeout: Option<u64> = (None, parse_opt_number, [UNTRACKED],
+        "fail out-of-process proc macros that run for longer than this many seconds"),
     profile_closures: bool = (false, parse_no_value, [UNTRACKED],
         "profile size of closures"),
     profile_sample_use: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
@@ -2082,12 +2158,17 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     #[rustc_lint_opt_deny_field_access("use `Session::stack_protector` instead of this field")]
     stack_protector: StackProtector = (StackProtector::None, parse_stack_protector, [TRACKED],
         "control stack smash protection strategy (`rustc --print stack-protector-strategies` for details)"),
+    stack_usage_report: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
+        "write the worst-case stack usage of the entry points of the crate as JSON to this file"),
     staticlib_allow_rdylib_deps: bool = (false, parse_bool, [TRACKED],
         "allow staticlibs to have rust dylib dependencies"),
     staticlib_prefer_dynamic: bool = (false, parse_bool, [TRACKED],
         "prefer dynamic linking to static linking for staticlibs (default: no)"),
     strict_init_checks: bool = (false, parse_bool, [TRACKED],
         "control if mem::uninitialized and mem::zeroed panic on more UB"),
+    symbol_size_report: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
+        "write the size of the symbols of the linked artifacts, grouped by crate, module and \
+        item, as JSON to this file"),
     #[rustc_lint_opt_deny_field_access("use `Session::teach` instead of this field")]
     teach: bool = (false, parse_bool, [TRACKED],
         "show extended diagnostic help (default: no)"),
@@ -2189,6 +2270,10 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     #[rustc_lint_opt_deny_field_access("use `Session::verify_llvm_ir` instead of this field")]
     verify_llvm_ir: bool = (false, parse_bool, [TRACKED],
         "verify LLVM IR (default: no)"),
+    verify_reproducibility: bool = (false, parse_bool, [UNTRACKED],
+        "compile the crate a second time with a different working directory, output directory \
+        and number of threads, and report where the outputs of the two compilations differ \
+        (default: no)"),
     virtual_function_elimination: bool = (false, parse_bool, [TRACKED],
         "enables dead virtual function elimination optimization. \
         Requires `-Clto[=[fat,yes]]`"),
@@ -42,8 +42,11 @@
     SwitchWithOptPath,
 };
 use crate::filesearch::FileSearch;
+use crate::mir_remarks::MirRemarks;
 use crate::parse::{ParseSess, add_feature_diagnostics};
 use crate::search_paths::SearchPath;
+use crate::stack_usage::StackUsageCallGraph;
+use crate::symbol_size::SymbolOwners;
 use crate::{errors, filesearch, lint};
 
 /// The behavior of the CTFE engine when an error occurs with regards to backtraces.
@@ -153,6 +156,17 @@ pub struct Session {
     /// Data about code being compiled, gathered during compilation.
     pub code_stats: CodeStats,
 
+    /// The optimization remarks of MIR passes, gathered for `-Z mir-remarks`.
+    pub mir_remarks: MirRemarks,
+
+    /// The call graph of the functions code generated in this crate, gathered
+    /// for `-Z stack-usage-report`.
+    pub stack_usage_call_graph: StackUsageCallGraph,
+
+    /// The items owning the symbols code generated in this crate, gathered
+    /// for `-Z symbol-size-report`.
+    pub symbol_owners: SymbolOwners,
+
     /// This only ever stores a `LintStore` but we don't want a dependency on that type here.
     pub lint_store: Option<Lrc<dyn LintStoreMarker>>,
 
@@ -271,6 +285,7 @@ pub fn finish_diagnostics(&self) -> Option<ErrorGuaranteed> {
         let mut guar = None;
         guar = guar.or(self.check_miri_unleashed_features());
         guar = guar.or(self.dcx().emit_stashed_diagnostics());
+        self.dcx().apply_suggestions();
         self.dcx().print_error_count();
         if self.opts.json_future_incompat {
             self.dcx().emit_future_breakage_report();
@@ -278,6 +293,15 @@ pub fn finish_diagnostics(&self) -> Option<ErrorGuaranteed> {
         guar
     }
 
+    /// Writes the optimization remarks of MIR passes requested with `-Z mir-remarks`.
+    pub fn write_mir_remarks(&self) {
+        if let Some(path) = &self.opts.unstable_opts.mir_remarks
+            && let Err(err) = self.mir_remarks.write(path)
+        {
+            self.dcx().emit_err(errors::FileWriteFail { path, err: err.to_string() });
+        }
+    }
+
     /// Returns true if the crate is a testing one.
     pub fn is_test_crate(&self) -> bool {
         self.opts.test
@@ -299,6 +323,7 @@ pub fn create_feature_err<'a>(&'a self, err: impl Diagnostic<'a>, feature: Symbo
     /// checking about whether its cost was justified.
     pub fn record_trimmed_def_paths(&self) {
         if self.opts.unstable_opts.print_type_sizes
+            || self.opts.unstable_opts.mir_remarks.is_some()
             || self.opts.unstable_opts.query_dep_graph
             || self.opts.unstable_opts.dump_mir.is_some()
             || self.opts.unstable_opts.unpretty.is_some()
@@ -1001,6 +1026,10 @@ pub fn build_session(
     if let Some(ice_file) = ice_file {
         dcx = dcx.with_ice_file(ice_file);
     }
+    if let Some(mode) = sopts.unstable_opts.apply_suggestions {
+        let lints = sopts.unstable_opts.apply_suggestions_lints.clone();
+        dcx = dcx.with_suggestion_collection(mode, lints);
+    }
 
     // Now that the proper handler has been constructed, drop early_dcx to
     // prevent accidental use.
@@ -1069,6 +1098,9 @@ pub fn build_session(
         incr_comp_session: RwLock::new(IncrCompSession::NotInitialized),
         prof,
         code_stats: Default::default(),
+        mir_remarks: Default::default(),
+        stack_usage_call_graph: Default::default(),
+        symbol_owners: Default::default(),
         lint_store: None,
         driver_lint_caps,
         ctfe_backtrace,
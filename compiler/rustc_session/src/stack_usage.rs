@@ -0,0 +1,34 @@
+//! The static call graph of the crate, gathered for `-Z stack-usage-report`.
+
+use rustc_data_structures::sync::Lock;
+
+/// A function that is code generated in this crate.
+#[derive(Clone, Debug)]
+pub struct StackUsageFn {
+    pub symbol: String,
+    /// The path of the function, for the report.
+    pub name: String,
+    /// Whether the function can be called from outside the crate.
+    pub is_entry_point: bool,
+    /// The symbols of the functions of this crate that the function calls.
+    pub callees: Vec<String>,
+    /// The paths of the functions of other crates that the function calls.
+    pub external_callees: Vec<String>,
+    /// Whether the function calls a function pointer or a trait object method.
+    pub has_indirect_calls: bool,
+}
+
+#[derive(Default)]
+pub struct StackUsageCallGraph {
+    fns: Lock<Vec<StackUsageFn>>,
+}
+
+impl StackUsageCallGraph {
+    pub fn record(&self, fns: Vec<StackUsageFn>) {
+        *self.fns.lock() = fns;
+    }
+
+    pub fn fns(&self) -> Vec<StackUsageFn> {
+        self.fns.lock().clone()
+    }
+}
@@ -0,0 +1,36 @@
+//! The owners of the symbols code generated in this crate, gathered for
+//! `-Z symbol-size-report`.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lock;
+
+/// The item a symbol of the final artifact belongs to.
+#[derive(Clone, Debug)]
+pub struct SymbolOwner {
+    /// The kind of the symbol, `function`, `static` or `vtable`.
+    pub kind: &'static str,
+    /// The name of the crate defining the item.
+    pub krate: String,
+    /// The path of the module containing the item.
+    pub module: String,
+    /// The path of the item, without generic arguments, which groups all the
+    /// instantiations of a generic item.
+    pub item: String,
+    /// The path of the item with its generic arguments.
+    pub instance: String,
+}
+
+#[derive(Default)]
+pub struct SymbolOwners {
+    owners: Lock<FxHashMap<String, SymbolOwner>>,
+}
+
+impl SymbolOwners {
+    pub fn record(&self, symbol: String, owner: SymbolOwner) {
+        self.owners.lock().insert(symbol, owner);
+    }
+
+    pub fn owners(&self) -> FxHashMap<String, SymbolOwner> {
+        self.owners.lock().clone()
+    }
+}
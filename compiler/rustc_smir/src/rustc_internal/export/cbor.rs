@@ -0,0 +1,428 @@
+//! A minimal CBOR ([RFC 8949]) serializer for the stable MIR export.
+//!
+//! Values have the same shape as in the JSON export: structs and maps are maps with text keys,
+//! sequences and tuples are arrays, unit and `None` are `null`, unit variants are text strings,
+//! and other enum variants are maps with a single entry, from the name of the variant to its
+//! data. Integers that do not fit in 64 bits are bignums (tags 2 and 3).
+//!
+//! [RFC 8949]: https://www.rfc-editor.org/rfc/rfc8949.html
+
+use std::fmt;
+use std::io::{self, Write};
+
+use serde::Serialize;
+use serde::ser::{self, Serializer as _};
+
+#[cfg(test)]
+mod tests;
+
+/// Serialize `value` as CBOR into `writer`.
+pub(super) fn to_writer<W: Write, T: Serialize + ?Sized>(writer: W, value: &T) -> io::Result<()> {
+    value.serialize(&mut Serializer { writer }).map_err(|Error(err)| err)
+}
+
+// The major types of the data items.
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+// The additional information of the data items that only have a head.
+const FALSE: u8 = 20;
+const TRUE: u8 = 21;
+const NULL: u8 = 22;
+const FLOAT32: u8 = 26;
+const FLOAT64: u8 = 27;
+const INDEFINITE: u8 = 31;
+
+const POSITIVE_BIGNUM: u64 = 2;
+const NEGATIVE_BIGNUM: u64 = 3;
+
+#[derive(Debug)]
+pub(super) struct Error(io::Error);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(io::Error::other(msg.to_string()))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error(err)
+    }
+}
+
+struct Serializer<W> {
+    writer: W,
+}
+
+impl<W: Write> Serializer<W> {
+    /// Write the head of a data item, using the shortest encoding of `argument`.
+    fn write_head(&mut self, major: u8, argument: u64) -> Result<(), Error> {
+        let major = major << 5;
+        if argument < 24 {
+            self.writer.write_all(&[major | argument as u8])?;
+        } else if let Ok(argument) = u8::try_from(argument) {
+            self.writer.write_all(&[major | 24, argument])?;
+        } else if let Ok(argument) = u16::try_from(argument) {
+            self.writer.write_all(&[major | 25])?;
+            self.writer.write_all(&argument.to_be_bytes())?;
+        } else if let Ok(argument) = u32::try_from(argument) {
+            self.writer.write_all(&[major | 26])?;
+            self.writer.write_all(&argument.to_be_bytes())?;
+        } else {
+            self.writer.write_all(&[major | 27])?;
+            self.writer.write_all(&argument.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write the head of an array or a map, of indefinite length if `len` is unknown.
+    fn write_len(&mut self, major: u8, len: Option<usize>) -> Result<(), Error> {
+        match len {
+            Some(len) => self.write_head(major, len as u64),
+            None => Ok(self.writer.write_all(&[major << 5 | INDEFINITE])?),
+        }
+    }
+
+    fn write_simple(&mut self, value: u8) -> Result<(), Error> {
+        Ok(self.writer.write_all(&[SIMPLE << 5 | value])?)
+    }
+
+    fn write_break(&mut self) -> Result<(), Error> {
+        self.write_simple(INDEFINITE)
+    }
+
+    fn write_bytes(&mut self, major: u8, bytes: &[u8]) -> Result<(), Error> {
+        self.write_head(major, bytes.len() as u64)?;
+        Ok(self.writer.write_all(bytes)?)
+    }
+
+    /// Write `major` integer `argument`, which is a bignum if it does not fit in 64 bits.
+    fn write_u128(&mut self, major: u8, bignum_tag: u64, argument: u128) -> Result<(), Error> {
+        if let Ok(argument) = u64::try_from(argument) {
+            return self.write_head(major, argument);
+        }
+        let bytes = argument.to_be_bytes();
+        let leading_zeros = (argument.leading_zeros() / 8) as usize;
+        self.write_head(TAG, bignum_tag)?;
+        self.write_bytes(BYTES, &bytes[leading_zeros..])
+    }
+
+    /// Write the head of the single-entry map of an enum variant, and the name of the variant.
+    fn write_variant(&mut self, variant: &'static str) -> Result<(), Error> {
+        self.write_head(MAP, 1)?;
+        self.write_bytes(TEXT, variant.as_bytes())
+    }
+}
+
+impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.write_simple(if v { TRUE } else { FALSE })
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        // A negative integer `n` is encoded as `-1 - n`, which is `!n`.
+        if v < 0 {
+            self.write_head(NEGATIVE, !v as u64)
+        } else {
+            self.write_head(UNSIGNED, v as u64)
+        }
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> {
+        if v < 0 {
+            self.write_u128(NEGATIVE, NEGATIVE_BIGNUM, !v as u128)
+        } else {
+            self.write_u128(UNSIGNED, POSITIVE_BIGNUM, v as u128)
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.write_head(UNSIGNED, v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> {
+        self.write_u128(UNSIGNED, POSITIVE_BIGNUM, v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.write_simple(FLOAT32)?;
+        Ok(self.writer.write_all(&v.to_be_bytes())?)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.write_simple(FLOAT64)?;
+        Ok(self.writer.write_all(&v.to_be_bytes())?)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.write_bytes(TEXT, v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.write_bytes(BYTES, v)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.write_simple(NULL)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.write_simple(NULL)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.write_simple(NULL)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.write_variant(variant)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Compound<'a, W>, Error> {
+        self.write_len(ARRAY, len)?;
+        Ok(Compound { ser: self, indefinite: len.is_none() })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a, W>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        self.write_variant(variant)?;
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'a, W>, Error> {
+        self.write_len(MAP, len)?;
+        Ok(Compound { ser: self, indefinite: len.is_none() })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        self.write_variant(variant)?;
+        self.serialize_map(Some(len))
+    }
+}
+
+/// The serializer of the elements of an array or the entries of a map.
+struct Compound<'a, W> {
+    ser: &'a mut Serializer<W>,
+    /// Whether the array or map has an indefinite length, and must be terminated by a break.
+    indefinite: bool,
+}
+
+impl<W: Write> Compound<'_, W> {
+    fn end(self) -> Result<(), Error> {
+        if self.indefinite { self.ser.write_break() } else { Ok(()) }
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<W: Write> ser::SerializeTupleVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<W: Write> ser::SerializeMap for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        key.serialize(&mut *self.ser)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        (&mut *self.ser).serialize_str(key)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        (&mut *self.ser).serialize_str(key)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
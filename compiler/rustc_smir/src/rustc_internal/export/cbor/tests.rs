@@ -0,0 +1,210 @@
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use super::*;
+
+fn encode<T: Serialize + ?Sized>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    to_writer(&mut bytes, value).unwrap();
+    bytes
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> &'a [u8] {
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    taken
+}
+
+/// Decode the data item at the start of `bytes` into the value it has in the JSON export.
+///
+/// Returns `None` for the break that ends an array or a map of indefinite length.
+fn decode_item(bytes: &mut &[u8]) -> Option<Value> {
+    let (&head, rest) = bytes.split_first().expect("unexpected end of input");
+    *bytes = rest;
+    let (major, info) = (head >> 5, head & 0x1f);
+    if major == SIMPLE {
+        return match info {
+            FALSE => Some(Value::Bool(false)),
+            TRUE => Some(Value::Bool(true)),
+            NULL => Some(Value::Null),
+            FLOAT32 => Some(json!(f32::from_be_bytes(take(bytes, 4).try_into().unwrap()))),
+            FLOAT64 => Some(json!(f64::from_be_bytes(take(bytes, 8).try_into().unwrap()))),
+            INDEFINITE => None,
+            _ => panic!("unexpected simple value {info}"),
+        };
+    }
+    let argument = match info {
+        0..24 => Some(u64::from(info)),
+        24 => Some(u64::from(take(bytes, 1)[0])),
+        25 => Some(u64::from(u16::from_be_bytes(take(bytes, 2).try_into().unwrap()))),
+        26 => Some(u64::from(u32::from_be_bytes(take(bytes, 4).try_into().unwrap()))),
+        27 => Some(u64::from_be_bytes(take(bytes, 8).try_into().unwrap())),
+        INDEFINITE => None,
+        _ => panic!("reserved additional information {info}"),
+    };
+    let value = match major {
+        UNSIGNED => json!(argument.unwrap()),
+        NEGATIVE => json!(-1 - i64::try_from(argument.unwrap()).unwrap()),
+        BYTES => Value::from(take(bytes, argument.unwrap() as usize).to_vec()),
+        TEXT => {
+            let text = take(bytes, argument.unwrap() as usize);
+            Value::from(std::str::from_utf8(text).unwrap())
+        }
+        ARRAY => Value::Array(decode_items(bytes, argument)),
+        MAP => {
+            let mut items = decode_items(bytes, argument.map(|len| 2 * len)).into_iter();
+            let mut map = Map::new();
+            while let Some(key) = items.next() {
+                let Value::String(key) = key else { panic!("map key {key} is not a string") };
+                map.insert(key, items.next().expect("map key without a value"));
+            }
+            Value::Object(map)
+        }
+        _ => panic!("unexpected major type {major}"),
+    };
+    Some(value)
+}
+
+/// Decode `len` data items, or the items up to the next break if `len` is `None`.
+fn decode_items(bytes: &mut &[u8], len: Option<u64>) -> Vec<Value> {
+    match len {
+        Some(len) => (0..len).map(|_| decode_item(bytes).expect("unexpected break")).collect(),
+        None => std::iter::from_fn(|| decode_item(bytes)).collect(),
+    }
+}
+
+fn decode(mut bytes: &[u8]) -> Value {
+    let value = decode_item(&mut bytes).expect("unexpected break");
+    assert!(bytes.is_empty(), "trailing bytes after the data item");
+    value
+}
+
+/// Check that `value` encodes to a CBOR item that decodes to its JSON value.
+#[track_caller]
+fn assert_round_trip<T: Serialize + ?Sized>(value: &T) {
+    assert_eq!(decode(&encode(value)), serde_json::to_value(value).unwrap());
+}
+
+#[test]
+fn encodings() {
+    // Examples from appendix A of RFC 8949.
+    let hex = |bytes: Vec<u8>| bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    assert_eq!(hex(encode(&0u8)), "00");
+    assert_eq!(hex(encode(&23u8)), "17");
+    assert_eq!(hex(encode(&24u8)), "1818");
+    assert_eq!(hex(encode(&1000u32)), "1903e8");
+    assert_eq!(hex(encode(&1000000u32)), "1a000f4240");
+    assert_eq!(hex(encode(&1000000000000u64)), "1b000000e8d4a51000");
+    assert_eq!(hex(encode(&u64::MAX)), "1bffffffffffffffff");
+    assert_eq!(hex(encode(&18446744073709551616u128)), "c249010000000000000000");
+    assert_eq!(hex(encode(&-18446744073709551617i128)), "c349010000000000000000");
+    assert_eq!(hex(encode(&-1i8)), "20");
+    assert_eq!(hex(encode(&-1000i16)), "3903e7");
+    assert_eq!(hex(encode(&1.1f64)), "fb3ff199999999999a");
+    assert_eq!(hex(encode(&false)), "f4");
+    assert_eq!(hex(encode(&None::<u8>)), "f6");
+    assert_eq!(hex(encode("\u{6c34}")), "63e6b0b4");
+    assert_eq!(hex(encode(&(1, [2, 3], [4, 5]))), "8301820203820405");
+}
+
+#[test]
+fn integers() {
+    for value in [0, 23, 24, 255, 256, 65535, 65536, u32::MAX.into(), u64::from(u32::MAX) + 1] {
+        assert_round_trip(&value);
+    }
+    for value in [-1, -24, -25, -256, -257, i64::MIN, i64::MAX] {
+        assert_round_trip(&value);
+    }
+    assert_round_trip(&(u8::MAX, i8::MIN, u16::MAX, i16::MIN, u32::MAX, i32::MIN));
+    // 128-bit integers that fit in 64 bits are encoded like 64-bit ones.
+    assert_eq!(encode(&u128::from(u64::MAX)), encode(&u64::MAX));
+    assert_eq!(encode(&-1i128), encode(&-1i64));
+}
+
+#[test]
+fn floats_and_strings() {
+    assert_round_trip(&(0.5f32, -2.25f64, 'x', "", "stable mir", true));
+    assert_round_trip(&"a".repeat(300));
+}
+
+#[derive(Serialize)]
+struct Unit;
+
+#[derive(Serialize)]
+struct Newtype(u32);
+
+#[derive(Serialize)]
+struct Tuple(u32, String);
+
+#[derive(Serialize)]
+enum Kind {
+    Unit,
+    Newtype(Option<u64>),
+    Tuple(i32, Vec<u8>),
+    Struct { name: &'static str, nested: Box<Kind> },
+}
+
+#[derive(Serialize)]
+struct Item {
+    unit: Unit,
+    newtype: Newtype,
+    tuple: Tuple,
+    kinds: Vec<Kind>,
+    #[serde(with = "as_bytes")]
+    bytes: Vec<u8>,
+}
+
+mod as_bytes {
+    pub(super) fn serialize<S: serde::Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(v)
+    }
+}
+
+#[test]
+fn structs_and_enums() {
+    let item = Item {
+        unit: Unit,
+        newtype: Newtype(7),
+        tuple: Tuple(1, "one".to_string()),
+        kinds: vec![
+            Kind::Unit,
+            Kind::Newtype(None),
+            Kind::Newtype(Some(1 << 40)),
+            Kind::Tuple(-3, vec![0, 255]),
+            Kind::Struct { name: "outer", nested: Box::new(Kind::Unit) },
+        ],
+        bytes: vec![1, 2, 3],
+    };
+    assert_round_trip(&item);
+    assert_round_trip(&[Some(Kind::Unit), None]);
+}
+
+/// A sequence whose length is not known in advance, which is encoded with an indefinite length.
+struct Filtered(Vec<u32>);
+
+impl Serialize for Filtered {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(self.0.iter().filter(|&&v| v % 2 == 0))
+    }
+}
+
+/// A map whose length is not known in advance.
+struct FilteredMap(Vec<(&'static str, u32)>);
+
+impl Serialize for FilteredMap {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_map(self.0.iter().copied().filter(|&(_, v)| v != 0))
+    }
+}
+
+#[test]
+fn indefinite_lengths() {
+    let seq = Filtered(vec![1, 2, 3, 4, 6]);
+    assert_eq!(encode(&seq).first(), Some(&(ARRAY << 5 | INDEFINITE)));
+    assert_round_trip(&seq);
+    assert_round_trip(&Filtered(vec![1, 3]));
+    let map = FilteredMap(vec![("a", 1), ("b", 0), ("c", 3)]);
+    assert_eq!(encode(&map).first(), Some(&(MAP << 5 | INDEFINITE)));
+    assert_round_trip(&map);
+    assert_round_trip(&vec![Filtered(vec![2]), Filtered(vec![]), Filtered(vec![4, 8])]);
+}
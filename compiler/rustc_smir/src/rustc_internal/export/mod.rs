@@ -0,0 +1,321 @@
+//! Export of the stable MIR of a whole crate (`-Zexport-stable-mir`).
+//!
+//! The export is a single [`CrateMir`] document, written as JSON or CBOR. It contains the body of
+//! every function instance that is reachable from the non-generic functions and statics of the
+//! crate, the polymorphic body of its generic functions, and tables of the types, ADTs and spans
+//! that these bodies refer to.
+//!
+//! Types, spans and definitions are referred to by the same ids as in the stable MIR API. These
+//! ids are only meaningful within one document: the `types` table maps each type id to its kind,
+//! the `adts` table describes each ADT definition, and the `spans` table gives the location of each
+//! span. A function can be found from a call to it by matching its `def` and `args` with those
+//! of the `FnDef` type of the callee.
+//!
+//! The layout of the document is described by [`SCHEMA_VERSION`], which must be incremented
+//! whenever the layout of [`CrateMir`] or of any stable MIR type it contains changes.
+
+use std::io;
+use std::ops::ControlFlow;
+
+use rustc_data_structures::fx::{FxHashSet, FxIndexMap, FxIndexSet};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::StableMirExportFormat;
+use serde::Serialize;
+use stable_mir::mir::mono::{Instance, InstanceKind};
+use stable_mir::mir::visit::{Location, MirVisitor};
+use stable_mir::mir::{
+    Body, CastKind, LocalDecl, Operand, PointerCoercion, Rvalue, Terminator, TerminatorKind,
+};
+use stable_mir::ty::{
+    AdtDef, AdtKind, ClosureKind, GenericArgs, LineInfo, RigidTy, Span, Ty, TyKind,
+};
+use stable_mir::visitor::{Visitable, Visitor};
+use stable_mir::{CrateDef, CrateItem, DefId, Filename, ItemKind};
+
+use super::run;
+
+mod cbor;
+
+/// The version of the layout of [`CrateMir`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The stable MIR of a crate.
+#[derive(Serialize)]
+pub struct CrateMir {
+    /// The [`SCHEMA_VERSION`] of the document.
+    pub schema_version: u32,
+    pub crate_name: String,
+    /// The function instances reachable from the non-generic items of the crate.
+    pub functions: Vec<FunctionMir>,
+    /// The generic functions of the crate, with their polymorphic body.
+    pub generic_functions: Vec<GenericFunctionMir>,
+    pub types: Vec<TypeEntry>,
+    pub adts: Vec<AdtEntry>,
+    pub spans: Vec<SpanEntry>,
+}
+
+#[derive(Serialize)]
+pub struct FunctionMir {
+    pub name: String,
+    pub mangled_name: String,
+    pub kind: InstanceKind,
+    pub def: DefId,
+    pub args: GenericArgs,
+    pub ty: Ty,
+    /// The body of the instance, unless it is an intrinsic or a foreign function.
+    pub body: Option<Body>,
+}
+
+#[derive(Serialize)]
+pub struct GenericFunctionMir {
+    pub name: String,
+    pub item: CrateItem,
+    pub ty: Ty,
+    pub body: Body,
+}
+
+#[derive(Serialize)]
+pub struct TypeEntry {
+    pub id: Ty,
+    pub kind: TyKind,
+}
+
+#[derive(Serialize)]
+pub struct AdtEntry {
+    pub def: AdtDef,
+    pub name: String,
+    pub kind: AdtKind,
+    pub variants: Vec<VariantEntry>,
+}
+
+#[derive(Serialize)]
+pub struct VariantEntry {
+    pub name: String,
+    pub fields: Vec<FieldEntry>,
+}
+
+/// A field of an ADT, whose type refers to the generic parameters of the ADT.
+#[derive(Serialize)]
+pub struct FieldEntry {
+    pub name: String,
+    pub ty: Ty,
+}
+
+#[derive(Serialize)]
+pub struct SpanEntry {
+    pub id: Span,
+    pub file: Filename,
+    pub lines: LineInfo,
+}
+
+/// Write the stable MIR of the crate of `tcx` in the given format.
+pub fn write_crate_mir<W: io::Write>(
+    tcx: TyCtxt<'_>,
+    format: StableMirExportFormat,
+    mut w: W,
+) -> io::Result<()> {
+    run(tcx, || {
+        let crate_mir = crate_mir();
+        match format {
+            StableMirExportFormat::Json => serde_json::to_writer(&mut w, &crate_mir)?,
+            StableMirExportFormat::Cbor => cbor::to_writer(&mut w, &crate_mir)?,
+        }
+        w.flush()
+    })
+    .map_err(io::Error::other)?
+}
+
+/// Collect the stable MIR of the local crate.
+///
+/// This must be called from a stable MIR context.
+pub fn crate_mir() -> CrateMir {
+    let mut collector = Collector::default();
+    for item in stable_mir::all_local_items() {
+        if !matches!(item.kind(), ItemKind::Fn | ItemKind::Static) {
+            continue;
+        }
+        if item.requires_monomorphization() {
+            let body = item.body();
+            collector.monomorphic = false;
+            collector.visit_body(&body);
+            collector.generic_functions.push(GenericFunctionMir {
+                name: item.name(),
+                item,
+                ty: item.ty(),
+                body,
+            });
+        } else {
+            collector.push_instance(Instance::try_from(item).unwrap());
+        }
+    }
+    while let Some(instance) = collector.instances_to_visit.pop() {
+        let body = instance.body();
+        if let Some(body) = &body {
+            collector.monomorphic = true;
+            collector.visit_body(body);
+        }
+        collector.types_to_visit.push(instance.ty());
+        collector.functions.push(FunctionMir {
+            name: instance.name(),
+            mangled_name: instance.mangled_name(),
+            kind: instance.kind,
+            def: instance.def.def_id(),
+            args: instance.args(),
+            ty: instance.ty(),
+            body,
+        });
+    }
+    collector.visit_types();
+
+    let spans = collector
+        .spans
+        .into_iter()
+        .map(|span| SpanEntry { id: span, file: span.get_filename(), lines: span.get_lines() })
+        .collect();
+    CrateMir {
+        schema_version: SCHEMA_VERSION,
+        crate_name: stable_mir::local_crate().name,
+        functions: collector.functions,
+        generic_functions: collector.generic_functions,
+        types: collector.types.into_iter().map(|(id, kind)| TypeEntry { id, kind }).collect(),
+        adts: collector.adts,
+        spans,
+    }
+}
+
+#[derive(Default)]
+struct Collector {
+    functions: Vec<FunctionMir>,
+    generic_functions: Vec<GenericFunctionMir>,
+    instances: FxHashSet<Instance>,
+    instances_to_visit: Vec<Instance>,
+    types: FxIndexMap<Ty, TyKind>,
+    types_to_visit: Vec<Ty>,
+    adts: Vec<AdtEntry>,
+    spans: FxIndexSet<Span>,
+    /// The local declarations of the body being visited.
+    locals: Vec<LocalDecl>,
+    /// Whether the body being visited is the body of an instance, whose callees can be resolved.
+    monomorphic: bool,
+}
+
+impl Collector {
+    fn push_instance(&mut self, instance: Instance) {
+        if self.instances.insert(instance) {
+            self.instances_to_visit.push(instance);
+        }
+    }
+
+    /// Add the types to visit, and all the types they refer to, to the type table.
+    fn visit_types(&mut self) {
+        while let Some(ty) = self.types_to_visit.pop() {
+            if self.types.contains_key(&ty) {
+                continue;
+            }
+            let kind = ty.kind();
+            let mut components = TypeComponents(vec![]);
+            let _ = ty.super_visit(&mut components);
+            self.types_to_visit.extend(components.0);
+            if let TyKind::RigidTy(RigidTy::Adt(def, _)) = &kind {
+                self.push_adt(*def);
+            }
+            self.types.insert(ty, kind);
+        }
+    }
+
+    fn push_adt(&mut self, def: AdtDef) {
+        if self.adts.iter().any(|adt| adt.def == def) {
+            return;
+        }
+        let variants = def
+            .variants_iter()
+            .map(|variant| VariantEntry {
+                name: variant.name(),
+                fields: variant
+                    .fields()
+                    .into_iter()
+                    .map(|field| {
+                        let ty = field.ty();
+                        self.types_to_visit.push(ty);
+                        FieldEntry { name: field.name, ty }
+                    })
+                    .collect(),
+            })
+            .collect();
+        self.adts.push(AdtEntry { def, name: def.name(), kind: def.kind(), variants });
+    }
+
+    /// Find the instance a function operand refers to, if it is a constant.
+    fn resolve_callee(&mut self, func: &Operand) {
+        let Operand::Constant(constant) = func else { return };
+        if let Some((def, args)) = constant.const_.ty().kind().fn_def()
+            && let Ok(instance) = Instance::resolve(def, args)
+        {
+            self.push_instance(instance);
+        }
+    }
+}
+
+impl MirVisitor for Collector {
+    fn visit_body(&mut self, body: &Body) {
+        self.locals = body.locals().to_vec();
+        self.spans.extend(self.locals.iter().map(|decl| decl.span));
+        self.super_body(body)
+    }
+
+    fn visit_terminator(&mut self, term: &Terminator, location: Location) {
+        match &term.kind {
+            _ if !self.monomorphic => {}
+            TerminatorKind::Call { func, .. } => self.resolve_callee(func),
+            TerminatorKind::Drop { place, .. } => {
+                if let Ok(ty) = place.ty(&self.locals) {
+                    self.push_instance(Instance::resolve_drop_in_place(ty));
+                }
+            }
+            _ => {}
+        }
+        self.super_terminator(term, location)
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue, location: Location) {
+        if self.monomorphic
+            && let Rvalue::Cast(CastKind::PointerCoercion(coercion), Operand::Constant(constant), _) =
+                rvalue
+        {
+            let instance = match (coercion, constant.const_.ty().kind()) {
+                (PointerCoercion::ReifyFnPointer, TyKind::RigidTy(RigidTy::FnDef(def, args))) => {
+                    Instance::resolve_for_fn_ptr(def, &args).ok()
+                }
+                (
+                    PointerCoercion::ClosureFnPointer(_),
+                    TyKind::RigidTy(RigidTy::Closure(def, args)),
+                ) => Instance::resolve_closure(def, &args, ClosureKind::FnOnce).ok(),
+                _ => None,
+            };
+            if let Some(instance) = instance {
+                self.push_instance(instance);
+            }
+        }
+        self.super_rvalue(rvalue, location)
+    }
+
+    fn visit_ty(&mut self, ty: &Ty, _location: Location) {
+        self.types_to_visit.push(*ty);
+    }
+
+    fn visit_span(&mut self, span: &Span) {
+        self.spans.insert(*span);
+    }
+}
+
+/// Collects the types a type immediately refers to.
+struct TypeComponents(Vec<Ty>);
+
+impl Visitor for TypeComponents {
+    type Break = ();
+
+    fn visit_ty(&mut self, ty: &Ty) -> ControlFlow<()> {
+        self.0.push(*ty);
+        ControlFlow::Continue(())
+    }
+}
@@ -7,10 +7,17 @@
 
 use rustc_middle::ty::{self as rustc_ty, Const as InternalConst, Ty as InternalTy, TyCtxt};
 use rustc_span::Symbol;
+use rustc_span::source_map::Spanned;
 use stable_mir::abi::Layout;
 use stable_mir::mir::alloc::AllocId;
 use stable_mir::mir::mono::{Instance, MonoItem, StaticDef};
-use stable_mir::mir::{BinOp, Mutability, Place, ProjectionElem, Safety, UnOp};
+use stable_mir::mir::{
+    AggregateKind, AssertMessage, BinOp, BorrowKind, CastKind, ConstOperand, CoroutineDesugaring,
+    CoroutineKind, CoroutineSource, FakeBorrowKind, MutBorrowKind, Mutability,
+    NonDivergingIntrinsic, NullOp, Operand, Place, PointerCoercion, ProjectionElem, RetagKind,
+    Rvalue, Safety, Statement, StatementKind, Terminator, TerminatorKind, UnOp, UnwindAction,
+    VarDebugInfo, VarDebugInfoContents,
+};
 use stable_mir::ty::{
     Abi, AdtDef, Binder, BoundRegionKind, BoundTyKind, BoundVariableKind, ClosureKind, DynKind,
     ExistentialPredicate, ExistentialProjection, ExistentialTraitRef, FloatTy, FnSig,
@@ -602,6 +609,504 @@ fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::
     }
 }
 
+impl RustcInternal for Operand {
+    type T<'tcx> = rustc_middle::mir::Operand<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            Operand::Copy(place) => rustc_middle::mir::Operand::Copy(place.internal(tables, tcx)),
+            Operand::Move(place) => rustc_middle::mir::Operand::Move(place.internal(tables, tcx)),
+            Operand::Constant(constant) => {
+                rustc_middle::mir::Operand::Constant(Box::new(constant.internal(tables, tcx)))
+            }
+        }
+    }
+}
+
+impl RustcInternal for ConstOperand {
+    type T<'tcx> = rustc_middle::mir::ConstOperand<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        rustc_middle::mir::ConstOperand {
+            span: self.span.internal(tables, tcx),
+            user_ty: self.user_ty.map(rustc_ty::UserTypeAnnotationIndex::from_usize),
+            const_: self.const_.internal(tables, tcx),
+        }
+    }
+}
+
+impl RustcInternal for Rvalue {
+    type T<'tcx> = rustc_middle::mir::Rvalue<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::Rvalue as InternalRvalue;
+        match self {
+            Rvalue::AddressOf(mutability, place) => InternalRvalue::RawPtr(
+                mutability.internal(tables, tcx),
+                place.internal(tables, tcx),
+            ),
+            Rvalue::Aggregate(kind, operands) => InternalRvalue::Aggregate(
+                Box::new(kind.internal(tables, tcx)),
+                operands.iter().map(|operand| operand.internal(tables, tcx)).collect(),
+            ),
+            Rvalue::BinaryOp(bin_op, lhs, rhs) => InternalRvalue::BinaryOp(
+                bin_op.internal(tables, tcx),
+                Box::new((lhs.internal(tables, tcx), rhs.internal(tables, tcx))),
+            ),
+            Rvalue::CheckedBinaryOp(bin_op, lhs, rhs) => InternalRvalue::BinaryOp(
+                bin_op.internal(tables, tcx).wrapping_to_overflowing().unwrap(),
+                Box::new((lhs.internal(tables, tcx), rhs.internal(tables, tcx))),
+            ),
+            Rvalue::Cast(kind, operand, ty) => InternalRvalue::Cast(
+                kind.internal(tables, tcx),
+                operand.internal(tables, tcx),
+                ty.internal(tables, tcx),
+            ),
+            Rvalue::CopyForDeref(place) => {
+                InternalRvalue::CopyForDeref(place.internal(tables, tcx))
+            }
+            Rvalue::Discriminant(place) => {
+                InternalRvalue::Discriminant(place.internal(tables, tcx))
+            }
+            Rvalue::Len(place) => InternalRvalue::Len(place.internal(tables, tcx)),
+            Rvalue::Ref(region, kind, place) => InternalRvalue::Ref(
+                region.internal(tables, tcx),
+                kind.internal(tables, tcx),
+                place.internal(tables, tcx),
+            ),
+            Rvalue::Repeat(operand, count) => {
+                InternalRvalue::Repeat(operand.internal(tables, tcx), count.internal(tables, tcx))
+            }
+            Rvalue::ShallowInitBox(operand, ty) => InternalRvalue::ShallowInitBox(
+                operand.internal(tables, tcx),
+                ty.internal(tables, tcx),
+            ),
+            Rvalue::ThreadLocalRef(item) => {
+                InternalRvalue::ThreadLocalRef(item.internal(tables, tcx))
+            }
+            Rvalue::NullaryOp(null_op, ty) => {
+                InternalRvalue::NullaryOp(null_op.internal(tables, tcx), ty.internal(tables, tcx))
+            }
+            Rvalue::UnaryOp(un_op, operand) => {
+                InternalRvalue::UnaryOp(un_op.internal(tables, tcx), operand.internal(tables, tcx))
+            }
+            Rvalue::Use(operand) => InternalRvalue::Use(operand.internal(tables, tcx)),
+        }
+    }
+}
+
+impl RustcInternal for AggregateKind {
+    type T<'tcx> = rustc_middle::mir::AggregateKind<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::AggregateKind as InternalAggregateKind;
+        match self {
+            AggregateKind::Array(ty) => InternalAggregateKind::Array(ty.internal(tables, tcx)),
+            AggregateKind::Tuple => InternalAggregateKind::Tuple,
+            AggregateKind::Adt(def, variant, args, user_ty, field) => InternalAggregateKind::Adt(
+                def.0.internal(tables, tcx),
+                variant.internal(tables, tcx),
+                args.internal(tables, tcx),
+                user_ty.map(rustc_ty::UserTypeAnnotationIndex::from_usize),
+                field.map(rustc_abi::FieldIdx::from_usize),
+            ),
+            AggregateKind::Closure(def, args) => InternalAggregateKind::Closure(
+                def.0.internal(tables, tcx),
+                args.internal(tables, tcx),
+            ),
+            AggregateKind::Coroutine(def, args, _movability) => InternalAggregateKind::Coroutine(
+                def.0.internal(tables, tcx),
+                args.internal(tables, tcx),
+            ),
+            AggregateKind::CoroutineClosure(def, args) => InternalAggregateKind::CoroutineClosure(
+                def.0.internal(tables, tcx),
+                args.internal(tables, tcx),
+            ),
+            AggregateKind::RawPtr(ty, mutability) => InternalAggregateKind::RawPtr(
+                ty.internal(tables, tcx),
+                mutability.internal(tables, tcx),
+            ),
+        }
+    }
+}
+
+impl RustcInternal for BorrowKind {
+    type T<'tcx> = rustc_middle::mir::BorrowKind;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::{
+            BorrowKind as InternalBorrowKind, FakeBorrowKind as InternalFakeBorrowKind,
+            MutBorrowKind as InternalMutBorrowKind,
+        };
+        match self {
+            BorrowKind::Shared => InternalBorrowKind::Shared,
+            BorrowKind::Fake(FakeBorrowKind::Deep) => {
+                InternalBorrowKind::Fake(InternalFakeBorrowKind::Deep)
+            }
+            BorrowKind::Fake(FakeBorrowKind::Shallow) => {
+                InternalBorrowKind::Fake(InternalFakeBorrowKind::Shallow)
+            }
+            BorrowKind::Mut { kind: MutBorrowKind::Default } => {
+                InternalBorrowKind::Mut { kind: InternalMutBorrowKind::Default }
+            }
+            BorrowKind::Mut { kind: MutBorrowKind::TwoPhaseBorrow } => {
+                InternalBorrowKind::Mut { kind: InternalMutBorrowKind::TwoPhaseBorrow }
+            }
+            BorrowKind::Mut { kind: MutBorrowKind::ClosureCapture } => {
+                InternalBorrowKind::Mut { kind: InternalMutBorrowKind::ClosureCapture }
+            }
+        }
+    }
+}
+
+impl RustcInternal for CastKind {
+    type T<'tcx> = rustc_middle::mir::CastKind;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::{CastKind as InternalCastKind, CoercionSource};
+        use rustc_middle::ty::adjustment::PointerCoercion as InternalPointerCoercion;
+        match self {
+            CastKind::PointerExposeAddress => InternalCastKind::PointerExposeProvenance,
+            CastKind::PointerWithExposedProvenance => {
+                InternalCastKind::PointerWithExposedProvenance
+            }
+            CastKind::PointerCoercion(coercion) => InternalCastKind::PointerCoercion(
+                coercion.internal(tables, tcx),
+                CoercionSource::Implicit,
+            ),
+            CastKind::DynStar => InternalCastKind::PointerCoercion(
+                InternalPointerCoercion::DynStar,
+                CoercionSource::Implicit,
+            ),
+            CastKind::IntToInt => InternalCastKind::IntToInt,
+            CastKind::FloatToInt => InternalCastKind::FloatToInt,
+            CastKind::FloatToFloat => InternalCastKind::FloatToFloat,
+            CastKind::IntToFloat => InternalCastKind::IntToFloat,
+            CastKind::PtrToPtr => InternalCastKind::PtrToPtr,
+            CastKind::FnPtrToPtr => InternalCastKind::FnPtrToPtr,
+            CastKind::Transmute => InternalCastKind::Transmute,
+        }
+    }
+}
+
+impl RustcInternal for PointerCoercion {
+    type T<'tcx> = rustc_middle::ty::adjustment::PointerCoercion;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::ty::adjustment::PointerCoercion as InternalPointerCoercion;
+        match self {
+            PointerCoercion::ReifyFnPointer => InternalPointerCoercion::ReifyFnPointer,
+            PointerCoercion::UnsafeFnPointer => InternalPointerCoercion::UnsafeFnPointer,
+            PointerCoercion::ClosureFnPointer(safety) => {
+                InternalPointerCoercion::ClosureFnPointer(safety.internal(tables, tcx))
+            }
+            PointerCoercion::MutToConstPointer => InternalPointerCoercion::MutToConstPointer,
+            PointerCoercion::ArrayToPointer => InternalPointerCoercion::ArrayToPointer,
+            PointerCoercion::Unsize => InternalPointerCoercion::Unsize,
+        }
+    }
+}
+
+impl RustcInternal for NullOp {
+    type T<'tcx> = rustc_middle::mir::NullOp<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            NullOp::SizeOf => rustc_middle::mir::NullOp::SizeOf,
+            NullOp::AlignOf => rustc_middle::mir::NullOp::AlignOf,
+            NullOp::OffsetOf(indices) => rustc_middle::mir::NullOp::OffsetOf(
+                tcx.mk_offset_of_from_iter(indices.iter().map(|(variant, field)| {
+                    (variant.internal(tables, tcx), rustc_abi::FieldIdx::from_usize(*field))
+                })),
+            ),
+            NullOp::UbChecks => rustc_middle::mir::NullOp::UbChecks,
+        }
+    }
+}
+
+/// Statements are converted with the outermost source scope, since stable MIR does not record it.
+impl RustcInternal for Statement {
+    type T<'tcx> = rustc_middle::mir::Statement<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        rustc_middle::mir::Statement {
+            source_info: rustc_middle::mir::SourceInfo::outermost(self.span.internal(tables, tcx)),
+            kind: self.kind.internal(tables, tcx),
+        }
+    }
+}
+
+impl RustcInternal for StatementKind {
+    type T<'tcx> = rustc_middle::mir::StatementKind<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::StatementKind as InternalStatementKind;
+        match self {
+            StatementKind::Assign(place, rvalue) => InternalStatementKind::Assign(Box::new((
+                place.internal(tables, tcx),
+                rvalue.internal(tables, tcx),
+            ))),
+            StatementKind::SetDiscriminant { place, variant_index } => {
+                InternalStatementKind::SetDiscriminant {
+                    place: Box::new(place.internal(tables, tcx)),
+                    variant_index: variant_index.internal(tables, tcx),
+                }
+            }
+            StatementKind::Deinit(place) => {
+                InternalStatementKind::Deinit(Box::new(place.internal(tables, tcx)))
+            }
+            StatementKind::StorageLive(local) => {
+                InternalStatementKind::StorageLive(rustc_middle::mir::Local::from_usize(*local))
+            }
+            StatementKind::StorageDead(local) => {
+                InternalStatementKind::StorageDead(rustc_middle::mir::Local::from_usize(*local))
+            }
+            StatementKind::Retag(kind, place) => InternalStatementKind::Retag(
+                kind.internal(tables, tcx),
+                Box::new(place.internal(tables, tcx)),
+            ),
+            StatementKind::PlaceMention(place) => {
+                InternalStatementKind::PlaceMention(Box::new(place.internal(tables, tcx)))
+            }
+            StatementKind::Intrinsic(intrinsic) => {
+                InternalStatementKind::Intrinsic(Box::new(intrinsic.internal(tables, tcx)))
+            }
+            StatementKind::ConstEvalCounter => InternalStatementKind::ConstEvalCounter,
+            StatementKind::Nop => InternalStatementKind::Nop,
+            // These only keep an opaque representation of some of their parts in stable MIR.
+            // Replacements of the optimized MIR containing them are rejected before conversion.
+            StatementKind::FakeRead(..)
+            | StatementKind::AscribeUserType { .. }
+            | StatementKind::Coverage(_) => {
+                unimplemented!("`{self:?}` cannot be converted back from stable MIR")
+            }
+        }
+    }
+}
+
+impl RustcInternal for RetagKind {
+    type T<'tcx> = rustc_middle::mir::RetagKind;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            RetagKind::FnEntry => rustc_middle::mir::RetagKind::FnEntry,
+            RetagKind::TwoPhase => rustc_middle::mir::RetagKind::TwoPhase,
+            RetagKind::Raw => rustc_middle::mir::RetagKind::Raw,
+            RetagKind::Default => rustc_middle::mir::RetagKind::Default,
+        }
+    }
+}
+
+impl RustcInternal for NonDivergingIntrinsic {
+    type T<'tcx> = rustc_middle::mir::NonDivergingIntrinsic<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            NonDivergingIntrinsic::Assume(operand) => {
+                rustc_middle::mir::NonDivergingIntrinsic::Assume(operand.internal(tables, tcx))
+            }
+            NonDivergingIntrinsic::CopyNonOverlapping(copy) => {
+                rustc_middle::mir::NonDivergingIntrinsic::CopyNonOverlapping(
+                    rustc_middle::mir::CopyNonOverlapping {
+                        src: copy.src.internal(tables, tcx),
+                        dst: copy.dst.internal(tables, tcx),
+                        count: copy.count.internal(tables, tcx),
+                    },
+                )
+            }
+        }
+    }
+}
+
+/// Terminators are converted with the outermost source scope, since stable MIR does not record
+/// it.
+impl RustcInternal for Terminator {
+    type T<'tcx> = rustc_middle::mir::Terminator<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::TerminatorKind as InternalTerminatorKind;
+        let span = self.span.internal(tables, tcx);
+        let kind = match &self.kind {
+            TerminatorKind::Goto { target } => {
+                InternalTerminatorKind::Goto { target: (*target).into() }
+            }
+            TerminatorKind::SwitchInt { discr, targets } => InternalTerminatorKind::SwitchInt {
+                discr: discr.internal(tables, tcx),
+                targets: rustc_middle::mir::SwitchTargets::new(
+                    targets.branches().map(|(value, target)| (value, target.into())),
+                    targets.otherwise().into(),
+                ),
+            },
+            TerminatorKind::Resume => InternalTerminatorKind::UnwindResume,
+            TerminatorKind::Abort => InternalTerminatorKind::UnwindTerminate(
+                rustc_middle::mir::UnwindTerminateReason::Abi,
+            ),
+            TerminatorKind::Return => InternalTerminatorKind::Return,
+            TerminatorKind::Unreachable => InternalTerminatorKind::Unreachable,
+            TerminatorKind::Drop { place, target, unwind } => InternalTerminatorKind::Drop {
+                place: place.internal(tables, tcx),
+                target: (*target).into(),
+                unwind: unwind.internal(tables, tcx),
+                replace: false,
+            },
+            TerminatorKind::Call { func, args, destination, target, unwind } => {
+                InternalTerminatorKind::Call {
+                    func: func.internal(tables, tcx),
+                    args: args
+                        .iter()
+                        .map(|arg| Spanned { node: arg.internal(tables, tcx), span })
+                        .collect(),
+                    destination: destination.internal(tables, tcx),
+                    target: target.map(Into::into),
+                    unwind: unwind.internal(tables, tcx),
+                    call_source: rustc_middle::mir::CallSource::Normal,
+                    fn_span: span,
+                }
+            }
+            TerminatorKind::Assert { cond, expected, msg, target, unwind } => {
+                InternalTerminatorKind::Assert {
+                    cond: cond.internal(tables, tcx),
+                    expected: *expected,
+                    msg: Box::new(msg.internal(tables, tcx)),
+                    target: (*target).into(),
+                    unwind: unwind.internal(tables, tcx),
+                }
+            }
+            // Stable MIR only keeps a textual representation of inline assembly. Replacements of
+            // the optimized MIR containing it are rejected before conversion.
+            TerminatorKind::InlineAsm { .. } => {
+                unimplemented!("inline assembly cannot be converted back from stable MIR")
+            }
+        };
+        rustc_middle::mir::Terminator {
+            source_info: rustc_middle::mir::SourceInfo::outermost(span),
+            kind,
+        }
+    }
+}
+
+impl RustcInternal for UnwindAction {
+    type T<'tcx> = rustc_middle::mir::UnwindAction;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            UnwindAction::Continue => rustc_middle::mir::UnwindAction::Continue,
+            UnwindAction::Unreachable => rustc_middle::mir::UnwindAction::Unreachable,
+            UnwindAction::Terminate => rustc_middle::mir::UnwindAction::Terminate(
+                rustc_middle::mir::UnwindTerminateReason::Abi,
+            ),
+            UnwindAction::Cleanup(block) => {
+                rustc_middle::mir::UnwindAction::Cleanup((*block).into())
+            }
+        }
+    }
+}
+
+impl RustcInternal for AssertMessage {
+    type T<'tcx> = rustc_middle::mir::AssertMessage<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::AssertKind;
+        match self {
+            AssertMessage::BoundsCheck { len, index } => AssertKind::BoundsCheck {
+                len: len.internal(tables, tcx),
+                index: index.internal(tables, tcx),
+            },
+            AssertMessage::Overflow(bin_op, lhs, rhs) => AssertKind::Overflow(
+                bin_op.internal(tables, tcx),
+                lhs.internal(tables, tcx),
+                rhs.internal(tables, tcx),
+            ),
+            AssertMessage::OverflowNeg(operand) => {
+                AssertKind::OverflowNeg(operand.internal(tables, tcx))
+            }
+            AssertMessage::DivisionByZero(operand) => {
+                AssertKind::DivisionByZero(operand.internal(tables, tcx))
+            }
+            AssertMessage::RemainderByZero(operand) => {
+                AssertKind::RemainderByZero(operand.internal(tables, tcx))
+            }
+            AssertMessage::ResumedAfterReturn(coroutine) => {
+                AssertKind::ResumedAfterReturn(coroutine.internal(tables, tcx))
+            }
+            AssertMessage::ResumedAfterPanic(coroutine) => {
+                AssertKind::ResumedAfterPanic(coroutine.internal(tables, tcx))
+            }
+            AssertMessage::MisalignedPointerDereference { required, found } => {
+                AssertKind::MisalignedPointerDereference {
+                    required: required.internal(tables, tcx),
+                    found: found.internal(tables, tcx),
+                }
+            }
+            AssertMessage::NullPointerDereference => AssertKind::NullPointerDereference,
+            AssertMessage::InvalidBool(found) => {
+                AssertKind::InvalidBool(found.internal(tables, tcx))
+            }
+            AssertMessage::InvalidChar(found) => {
+                AssertKind::InvalidChar(found.internal(tables, tcx))
+            }
+            AssertMessage::InvalidEnumDiscriminant(found) => {
+                AssertKind::InvalidEnumDiscriminant(found.internal(tables, tcx))
+            }
+        }
+    }
+}
+
+impl RustcInternal for CoroutineKind {
+    type T<'tcx> = rustc_hir::CoroutineKind;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            CoroutineKind::Desugared(desugaring, source) => {
+                let desugaring = match desugaring {
+                    CoroutineDesugaring::Async => rustc_hir::CoroutineDesugaring::Async,
+                    CoroutineDesugaring::Gen => rustc_hir::CoroutineDesugaring::Gen,
+                    CoroutineDesugaring::AsyncGen => rustc_hir::CoroutineDesugaring::AsyncGen,
+                };
+                let source = match source {
+                    CoroutineSource::Block => rustc_hir::CoroutineSource::Block,
+                    CoroutineSource::Closure => rustc_hir::CoroutineSource::Closure,
+                    CoroutineSource::Fn => rustc_hir::CoroutineSource::Fn,
+                };
+                rustc_hir::CoroutineKind::Desugared(desugaring, source)
+            }
+            CoroutineKind::Coroutine(movability) => {
+                rustc_hir::CoroutineKind::Coroutine(movability.internal(tables, tcx))
+            }
+        }
+    }
+}
+
+impl RustcInternal for VarDebugInfo {
+    type T<'tcx> = rustc_middle::mir::VarDebugInfo<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::VarDebugInfoContents as InternalContents;
+        rustc_middle::mir::VarDebugInfo {
+            name: Symbol::intern(&self.name),
+            source_info: rustc_middle::mir::SourceInfo {
+                span: self.source_info.span.internal(tables, tcx),
+                scope: rustc_middle::mir::SourceScope::from_u32(self.source_info.scope),
+            },
+            composite: self.composite.as_ref().map(|composite| {
+                Box::new(rustc_middle::mir::VarDebugInfoFragment {
+                    ty: composite.ty.internal(tables, tcx),
+                    projection: composite.projection.internal(tables, tcx),
+                })
+            }),
+            value: match &self.value {
+                VarDebugInfoContents::Place(place) => {
+                    InternalContents::Place(place.internal(tables, tcx))
+                }
+                VarDebugInfoContents::Const(constant) => {
+                    InternalContents::Const(constant.internal(tables, tcx))
+                }
+            },
+            argument_index: self.argument_index,
+        }
+    }
+}
+
 impl<T> RustcInternal for &T
 where
     T: RustcInternal,
@@ -0,0 +1,207 @@
+//! Lets a driver replace the optimized MIR of local functions with a stable MIR body.
+//!
+//! The driver registers a replacement with [`replace_optimized_mir`], and makes the compiler use
+//! [`override_optimized_mir`] as (part of) its `override_queries` callback, which the [`run!`]
+//! macro does already. Whenever the compiler computes the optimized MIR of a local item, the
+//! replacement is called with the stable body of the item. If it returns a body, that body is
+//! converted back to MIR, checked by the MIR validator and used instead of the original one, for
+//! code generation as well as for the metadata of the crate.
+//!
+//! Stable MIR does not record the source scope of statements and terminators. The scope of a
+//! statement or terminator of the new body is the one of the original statement or terminator
+//! with the same span, if any, and the outermost scope otherwise.
+//!
+//! Bodies with inline assembly or coverage statements cannot be converted back from stable MIR
+//! yet. A replacement with such code, or with a different number of arguments than the original
+//! body, is reported as an error and the original body is kept.
+
+use std::sync::OnceLock;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::mir;
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::util::Providers;
+use rustc_session::Session;
+use rustc_span::def_id::LocalDefId;
+use stable_mir::CrateItem;
+use stable_mir::mir::{Body, StatementKind, TerminatorKind, UnwindAction};
+
+use super::{RustcInternal, TLV, run, with_tables};
+use crate::rustc_smir::{Stable, Tables};
+
+/// A function that returns the body that replaces the optimized MIR of an item, if any.
+pub type OptimizedMirReplacement = fn(CrateItem, &Body) -> Option<Body>;
+
+static REPLACEMENT: OnceLock<OptimizedMirReplacement> = OnceLock::new();
+
+type OptimizedMirProvider = for<'tcx> fn(TyCtxt<'tcx>, LocalDefId) -> &'tcx mir::Body<'tcx>;
+
+/// The provider of `optimized_mir` that was overridden by [`override_optimized_mir`].
+static DEFAULT_PROVIDER: OnceLock<OptimizedMirProvider> = OnceLock::new();
+
+/// Register the function that may replace the optimized MIR of the local items.
+///
+/// The stable MIR items it is called with are only valid until it returns, unless the optimized
+/// MIR is computed by a stable MIR call, in which case they belong to the context of that call.
+///
+/// # Panics
+///
+/// This function panics if a replacement was already registered.
+pub fn replace_optimized_mir(replacement: OptimizedMirReplacement) {
+    if REPLACEMENT.set(replacement).is_err() {
+        panic!("a replacement of the optimized MIR was already registered");
+    }
+}
+
+/// Make the compiler use the replacement registered with [`replace_optimized_mir`], if any.
+///
+/// This is meant to be (called from) the `override_queries` callback of the compiler
+/// configuration.
+pub fn override_optimized_mir(_sess: &Session, providers: &mut Providers) {
+    // The provider of the first configuration is the same as the one of any later configuration.
+    let _ = DEFAULT_PROVIDER.set(providers.optimized_mir);
+    providers.optimized_mir = optimized_mir;
+}
+
+fn optimized_mir<'tcx>(tcx: TyCtxt<'tcx>, def: LocalDefId) -> &'tcx mir::Body<'tcx> {
+    let original = DEFAULT_PROVIDER.get().unwrap()(tcx, def);
+    let Some(replacement) = REPLACEMENT.get() else {
+        return original;
+    };
+    if original.tainted_by_errors.is_some() {
+        return original;
+    }
+
+    let replace = || {
+        let (item, body) =
+            with_tables(|tables| (tables.crate_item(def.to_def_id()), original.stable(tables)));
+        let new_body = replacement(item, &body)?;
+        Some(
+            check_replacement(original, &new_body)
+                .map(|()| with_tables(|tables| internal_body(tables, tcx, original, &new_body))),
+        )
+    };
+    // The optimized MIR is often computed outside of any stable MIR call, e.g. during codegen.
+    let new_body = if TLV.is_set() { replace() } else { run(tcx, replace).unwrap() };
+
+    match new_body {
+        Some(Ok(mut body)) => {
+            rustc_mir_transform::finish_replaced_optimized_mir(tcx, original, &mut body);
+            tcx.arena.alloc(body)
+        }
+        Some(Err(err)) => {
+            tcx.dcx().span_err(
+                tcx.def_span(def),
+                format!("cannot replace the optimized MIR of `{}`: {err}", tcx.def_path_str(def)),
+            );
+            original
+        }
+        None => original,
+    }
+}
+
+/// Check that the stable body that replaces `original` can be converted back to MIR.
+fn check_replacement(original: &mir::Body<'_>, body: &Body) -> Result<(), String> {
+    if body.arg_locals().len() != original.arg_count {
+        return Err(format!(
+            "the new body has {} arguments instead of {}",
+            body.arg_locals().len(),
+            original.arg_count
+        ));
+    }
+    for block in &body.blocks {
+        for statement in &block.statements {
+            // These only keep an opaque representation of some of their parts in stable MIR.
+            let name = match statement.kind {
+                StatementKind::FakeRead(..) => "FakeRead",
+                StatementKind::AscribeUserType { .. } => "AscribeUserType",
+                StatementKind::Coverage(_) => "Coverage",
+                _ => continue,
+            };
+            return Err(format!("`{name}` statements cannot be converted back from stable MIR"));
+        }
+        if let TerminatorKind::InlineAsm { .. } = block.terminator.kind {
+            return Err("inline assembly cannot be converted back from stable MIR".to_owned());
+        }
+    }
+    Ok(())
+}
+
+/// Convert the stable body that replaces `original` back to MIR, once it passed
+/// [`check_replacement`].
+fn internal_body<'tcx>(
+    tables: &mut Tables<'_>,
+    tcx: TyCtxt<'tcx>,
+    original: &mir::Body<'tcx>,
+    body: &Body,
+) -> mir::Body<'tcx> {
+    let mut scopes = FxHashMap::default();
+    for block in original.basic_blocks.iter() {
+        for source_info in block
+            .statements
+            .iter()
+            .map(|statement| statement.source_info)
+            .chain(Some(block.terminator().source_info))
+        {
+            scopes.entry(source_info.span).or_insert(source_info.scope);
+        }
+    }
+    let scope_of = |span| scopes.get(&span).copied().unwrap_or(mir::OUTERMOST_SOURCE_SCOPE);
+
+    let is_cleanup = cleanup_blocks(body);
+    let mut new_body = original.clone();
+    *new_body.basic_blocks_mut() = body
+        .blocks
+        .iter()
+        .zip(is_cleanup)
+        .map(|(block, is_cleanup)| {
+            let mut terminator = block.terminator.internal(tables, tcx);
+            terminator.source_info.scope = scope_of(terminator.source_info.span);
+            let mut data = mir::BasicBlockData::new(Some(terminator), is_cleanup);
+            data.statements = block.statements.internal(tables, tcx);
+            for statement in &mut data.statements {
+                statement.source_info.scope = scope_of(statement.source_info.span);
+            }
+            data
+        })
+        .collect();
+    new_body.local_decls = body
+        .local_decls()
+        .map(|(local, decl)| {
+            let ty = decl.ty.internal(tables, tcx);
+            let span = decl.span.internal(tables, tcx);
+            let mut internal_decl = match original.local_decls.get(mir::Local::from_usize(local)) {
+                Some(original_decl) => original_decl.clone(),
+                None => mir::LocalDecl::new(ty, span),
+            };
+            internal_decl.ty = ty;
+            internal_decl.source_info.span = span;
+            internal_decl.mutability = decl.mutability.internal(tables, tcx);
+            internal_decl
+        })
+        .collect();
+    new_body.var_debug_info = body.var_debug_info.internal(tables, tcx);
+    new_body.spread_arg = body.spread_arg().map(mir::Local::from_usize);
+    new_body.span = body.span.internal(tables, tcx);
+    new_body
+}
+
+/// Find the cleanup blocks of a stable body, which are the blocks that unwinding continues to,
+/// and all the blocks that can be reached from them.
+fn cleanup_blocks(body: &Body) -> Vec<bool> {
+    let mut is_cleanup = vec![false; body.blocks.len()];
+    let mut worklist: Vec<_> = body
+        .blocks
+        .iter()
+        .filter_map(|block| match block.terminator.kind.unwind() {
+            Some(UnwindAction::Cleanup(cleanup)) => Some(*cleanup),
+            _ => None,
+        })
+        .collect();
+    while let Some(block) = worklist.pop() {
+        if !std::mem::replace(&mut is_cleanup[block], true) {
+            worklist.extend(body.blocks[block].terminator.successors());
+        }
+    }
+    is_cleanup
+}
@@ -23,9 +23,13 @@
 use crate::rustc_smir::context::TablesWrapper;
 use crate::rustc_smir::{Stable, Tables};
 
+pub mod export;
 mod internal;
+mod mir_replacement;
 pub mod pretty;
 
+pub use mir_replacement::{OptimizedMirReplacement, override_optimized_mir, replace_optimized_mir};
+
 /// Convert an internal Rust compiler item into its stable counterpart, if one exists.
 ///
 /// # Warning
@@ -374,6 +378,13 @@ impl<B, C, F> Callbacks for StableMir<B, C, F>
             C: Send,
             F: FnOnce($(optional!($with_tcx TyCtxt))?) -> ControlFlow<B, C> + Send,
         {
+            /// Called before creating the compiler instance. Makes the compiler use the
+            /// replacement of the optimized MIR registered with
+            /// `rustc_internal::replace_optimized_mir`, if any.
+            fn config(&mut self, config: &mut interface::Config) {
+                config.override_queries = Some(rustc_internal::override_optimized_mir);
+            }
+
             /// Called after analysis. Return value instructs the compiler whether to
             /// continue the compilation afterwards (defaults to `Compilation::Continue`)
             fn after_analysis<'tcx>(
@@ -27,19 +27,33 @@ pub(crate) fn new(tcx: TyCtxt<'tcx>, instance: ty::Instance<'tcx>) -> Self {
         BodyBuilder { tcx, instance }
     }
 
+    /// Compute the MIR body of the instance, so that `build` does not need to compute it while
+    /// the tables are borrowed.
+    pub(crate) fn compute_mir(&self) {
+        self.tcx.instance_mir(self.instance.def);
+    }
+
     /// Build a stable monomorphic body for a given instance based on the MIR body.
     ///
     /// All constants are also evaluated.
-    pub(crate) fn build(mut self, tables: &mut Tables<'tcx>) -> stable_mir::mir::Body {
-        let body = tables.tcx.instance_mir(self.instance.def).clone();
-        let mono_body = if !self.instance.args.is_empty()
+    pub(crate) fn build(self, tables: &mut Tables<'tcx>) -> stable_mir::mir::Body {
+        self.build_internal().stable(tables)
+    }
+
+    /// Build a monomorphic body for a given instance based on the MIR body, without converting it
+    /// to stable MIR.
+    ///
+    /// All constants are also evaluated.
+    pub(crate) fn build_internal(mut self) -> mir::Body<'tcx> {
+        let body = self.tcx.instance_mir(self.instance.def).clone();
+        if !self.instance.args.is_empty()
             // Without the `generic_const_exprs` feature gate, anon consts in signatures do not
             // get generic parameters. Which is wrong, but also not a problem without
             // generic_const_exprs
             || self.tcx.def_kind(self.instance.def_id()) != DefKind::AnonConst
         {
             let mut mono_body = self.instance.instantiate_mir_and_normalize_erasing_regions(
-                tables.tcx,
+                self.tcx,
                 ty::TypingEnv::fully_monomorphized(),
                 ty::EarlyBinder::bind(body),
             );
@@ -48,8 +62,7 @@ pub(crate) fn build(mut self, tables: &mut Tables<'tcx>) -> stable_mir::mir::Bod
         } else {
             // Already monomorphic.
             body
-        };
-        mono_body.stable(tables)
+        }
     }
 }
 
@@ -22,6 +22,7 @@
 use stable_mir::abi::{FnAbi, Layout, LayoutShape};
 use stable_mir::compiler_interface::Context;
 use stable_mir::mir::alloc::GlobalAlloc;
+use stable_mir::mir::dataflow::{BitSet, MaybeInitializedPlaces, Results};
 use stable_mir::mir::mono::{InstanceDef, StaticDef};
 use stable_mir::mir::{BinOp, Body, Place, UnOp};
 use stable_mir::target::{MachineInfo, MachineSize};
@@ -34,7 +35,9 @@
 
 use crate::rustc_internal::RustcInternal;
 use crate::rustc_smir::builder::BodyBuilder;
-use crate::rustc_smir::{Stable, Tables, alloc, filter_def_ids, new_item_kind, smir_crate};
+use crate::rustc_smir::{
+    Stable, Tables, alloc, dataflow, filter_def_ids, new_item_kind, smir_crate,
+};
 
 impl<'tcx> Context for TablesWrapper<'tcx> {
     fn target_info(&self) -> MachineInfo {
@@ -59,9 +62,14 @@ fn all_local_items(&self) -> stable_mir::CrateItems {
     }
 
     fn mir_body(&self, item: stable_mir::DefId) -> stable_mir::mir::Body {
-        let mut tables = self.0.borrow_mut();
-        let def_id = tables[item];
-        tables.tcx.instance_mir(rustc_middle::ty::InstanceKind::Item(def_id)).stable(&mut tables)
+        let (tcx, def_id) = {
+            let tables = self.0.borrow();
+            (tables.tcx, tables[item])
+        };
+        // The tables must not be borrowed while computing the MIR, since a replacement of the
+        // optimized MIR uses them.
+        let body = tcx.instance_mir(rustc_middle::ty::InstanceKind::Item(def_id));
+        body.stable(&mut self.0.borrow_mut())
     }
 
     fn has_body(&self, def: DefId) -> bool {
@@ -572,11 +580,33 @@ fn rigid_ty_discriminant_ty(&self, ty: &RigidTy) -> stable_mir::ty::Ty {
     }
 
     fn instance_body(&self, def: InstanceDef) -> Option<Body> {
+        let (tcx, instance) = {
+            let tables = self.0.borrow();
+            let instance = tables.instances[def];
+            if !tables.instance_has_body(instance) {
+                return None;
+            }
+            (tables.tcx, instance)
+        };
+        let builder = BodyBuilder::new(tcx, instance);
+        // See `mir_body`.
+        builder.compute_mir();
+        Some(builder.build(&mut self.0.borrow_mut()))
+    }
+
+    fn maybe_borrowed_locals(&self, def: InstanceDef) -> Results<BitSet> {
+        let (tcx, instance) = self.instance_with_body(def);
+        dataflow::maybe_borrowed_locals(tcx, instance)
+    }
+
+    fn maybe_initialized_places(&self, def: InstanceDef) -> MaybeInitializedPlaces {
+        let (tcx, instance) = self.instance_with_body(def);
+        let (move_paths, results) = dataflow::maybe_initialized_places(tcx, instance);
         let mut tables = self.0.borrow_mut();
-        let instance = tables.instances[def];
-        tables
-            .instance_has_body(instance)
-            .then(|| BodyBuilder::new(tables.tcx, instance).build(&mut *tables))
+        MaybeInitializedPlaces {
+            move_paths: move_paths.iter().map(|place| place.stable(&mut *tables)).collect(),
+            results,
+        }
     }
 
     fn instance_ty(&self, def: InstanceDef) -> stable_mir::ty::Ty {
@@ -818,6 +848,17 @@ fn unop_ty(&self, un_op: UnOp, arg: Ty) -> Ty {
 
 pub(crate) struct TablesWrapper<'tcx>(pub RefCell<Tables<'tcx>>);
 
+impl<'tcx> TablesWrapper<'tcx> {
+    /// Get an instance whose body is about to be computed, which must not happen while the
+    /// tables are borrowed.
+    fn instance_with_body(&self, def: InstanceDef) -> (TyCtxt<'tcx>, Instance<'tcx>) {
+        let tables = self.0.borrow();
+        let instance = tables.instances[def];
+        assert!(tables.instance_has_body(instance), "{instance:?} has no body");
+        (tables.tcx, instance)
+    }
+}
+
 /// Implement error handling for extracting function ABI information.
 impl<'tcx> FnAbiOfHelpers<'tcx> for Tables<'tcx> {
     type FnAbiOfResult = Result<&'tcx rustc_target::callconv::FnAbi<'tcx, ty::Ty<'tcx>>, Error>;
@@ -484,6 +484,18 @@ fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
                     found: found.stable(tables),
                 }
             }
+            AssertKind::NullPointerDereference => {
+                stable_mir::mir::AssertMessage::NullPointerDereference
+            }
+            AssertKind::InvalidBool(found) => {
+                stable_mir::mir::AssertMessage::InvalidBool(found.stable(tables))
+            }
+            AssertKind::InvalidChar(found) => {
+                stable_mir::mir::AssertMessage::InvalidChar(found.stable(tables))
+            }
+            AssertKind::InvalidEnumDiscriminant(found) => {
+                stable_mir::mir::AssertMessage::InvalidEnumDiscriminant(found.stable(tables))
+            }
         }
     }
 }
@@ -0,0 +1,74 @@
+//! Run the dataflow analyses of the compiler on the body of an instance, and convert their
+//! results to the stable dataflow results.
+
+use rustc_index::Idx;
+use rustc_middle::mir;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_mir_dataflow::impls::{MaybeBorrowedLocals, MaybeInitializedPlaces};
+use rustc_mir_dataflow::move_paths::MoveData;
+use rustc_mir_dataflow::{Analysis, MaybeReachable, ResultsCursor};
+use stable_mir::mir::dataflow::{BitSet, BlockStates, Results};
+
+use crate::rustc_smir::builder::BodyBuilder;
+
+pub(crate) fn maybe_borrowed_locals<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: ty::Instance<'tcx>,
+) -> Results<BitSet> {
+    let body = BodyBuilder::new(tcx, instance).build_internal();
+    let cursor =
+        MaybeBorrowedLocals.iterate_to_fixpoint(tcx, &body, None).into_results_cursor(&body);
+    let domain_size = body.local_decls.len();
+    collect_states(cursor, |locals| bit_set(domain_size, locals.iter()))
+}
+
+/// Returns the places of the move paths of the body, and the results of the analysis.
+pub(crate) fn maybe_initialized_places<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: ty::Instance<'tcx>,
+) -> (Vec<mir::Place<'tcx>>, Results<BitSet>) {
+    let body = BodyBuilder::new(tcx, instance).build_internal();
+    let move_data = MoveData::gather_moves(&body, tcx, |_| true);
+    let cursor = MaybeInitializedPlaces::new(tcx, &body, &move_data)
+        .iterate_to_fixpoint(tcx, &body, None)
+        .into_results_cursor(&body);
+    let domain_size = move_data.move_paths.len();
+    let results = collect_states(cursor, |state| match state {
+        MaybeReachable::Reachable(paths) => bit_set(domain_size, paths.iter()),
+        MaybeReachable::Unreachable => BitSet::new_empty(domain_size),
+    });
+    (move_data.move_paths.iter().map(|path| path.place).collect(), results)
+}
+
+/// Collect the states before and after every statement and terminator of a forward analysis.
+fn collect_states<'tcx, A: Analysis<'tcx>>(
+    mut cursor: ResultsCursor<'_, 'tcx, A>,
+    convert: impl Fn(&A::Domain) -> BitSet,
+) -> Results<BitSet> {
+    let body = cursor.body();
+    let blocks = body
+        .basic_blocks
+        .iter_enumerated()
+        .map(|(block, data)| {
+            let (before, after) = (0..=data.statements.len())
+                .map(|statement_index| {
+                    let location = mir::Location { block, statement_index };
+                    cursor.seek_before_primary_effect(location);
+                    let before = convert(cursor.get());
+                    cursor.seek_after_primary_effect(location);
+                    (before, convert(cursor.get()))
+                })
+                .unzip();
+            BlockStates { before, after }
+        })
+        .collect();
+    Results { blocks }
+}
+
+fn bit_set<T: Idx>(domain_size: usize, elements: impl Iterator<Item = T>) -> BitSet {
+    let mut set = BitSet::new_empty(domain_size);
+    for element in elements {
+        set.insert(element.index());
+    }
+    set
+}
@@ -26,6 +26,7 @@
 mod builder;
 pub(crate) mod context;
 mod convert;
+mod dataflow;
 
 pub struct Tables<'tcx> {
     pub(crate) tcx: TyCtxt<'tcx>,
@@ -524,6 +524,8 @@
         bitxor_assign,
         black_box,
         block,
+        blocking,
+        blocking_attribute,
         bool,
         bool_then,
         borrowck_graphviz_format,
@@ -928,6 +930,8 @@
         field,
         field_init_shorthand,
         file,
+        file_create,
+        file_open,
         file_options,
         float,
         float_to_int_unchecked,
@@ -987,6 +991,10 @@
         from_usize,
         from_yeet,
         fs_create_dir,
+        fs_read,
+        fs_read_to_string,
+        fs_remove_file,
+        fs_write,
         fsub_algebraic,
         fsub_fast,
         fsxr,
@@ -1122,7 +1130,10 @@
         intrinsics,
         intrinsics_unaligned_volatile_load,
         intrinsics_unaligned_volatile_store,
+        io_eprint,
+        io_print,
         io_stderr,
+        io_stdin,
         io_stdout,
         irrefutable_let_patterns,
         is,
@@ -1323,6 +1334,7 @@
         must_use,
         mut_preserve_binding_mode_2024,
         mut_ref,
+        mutex_lock,
         naked,
         naked_asm,
         naked_functions,
@@ -1469,9 +1481,13 @@
         panic_implementation,
         panic_in_cleanup,
         panic_info,
+        panic_invalid_bool,
+        panic_invalid_char,
+        panic_invalid_enum_discriminant,
         panic_location,
         panic_misaligned_pointer_dereference,
         panic_nounwind,
+        panic_null_pointer_dereference,
         panic_runtime,
         panic_str_2015,
         panic_unwind,
@@ -1540,6 +1556,7 @@
         proc_macro,
         proc_macro_attribute,
         proc_macro_derive,
+        proc_macro_deterministic,
         proc_macro_expr,
         proc_macro_gen,
         proc_macro_hygiene,
@@ -1791,6 +1808,8 @@
         rustdoc_missing_doc_code_examples,
         rustfmt,
         rvalue_static_promotion,
+        rwlock_read,
+        rwlock_write,
         rwpi,
         s,
         s390x_target_feature,
@@ -1976,6 +1995,7 @@
         sym,
         sync,
         synthetic,
+        system_time_now,
         t32,
         target,
         target_abi,
@@ -1993,6 +2013,8 @@
         target_thread_local,
         target_vendor,
         tbm_target_feature,
+        tcp_listener_accept,
+        tcp_stream_connect,
         termination,
         termination_trait,
         termination_trait_test,
@@ -2006,6 +2028,7 @@
         thread,
         thread_local,
         thread_local_macro,
+        thread_sleep,
         three_way_compare,
         thumb2,
         thumb_mode: "thumb-mode",
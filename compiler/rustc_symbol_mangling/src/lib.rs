@@ -123,6 +123,13 @@ pub fn symbol_name_for_instance_in_crate<'tcx>(
     compute_symbol_name(tcx, instance, || instantiating_crate)
 }
 
+/// Demangles a symbol of either mangling scheme into the path of the item it defines, without
+/// the hash of legacy symbols and the crate disambiguators of v0 symbols. Returns `None` for
+/// symbols which are not Rust symbols.
+pub fn demangle_symbol(symbol: &str) -> Option<String> {
+    rustc_demangle::try_demangle(symbol).ok().map(|demangled| format!("{demangled:#}"))
+}
+
 pub fn provide(providers: &mut Providers) {
     *providers = Providers { symbol_name: symbol_name_provider, ..*providers };
 }
@@ -2,14 +2,45 @@
 use std::collections::BTreeMap;
 use std::str::FromStr;
 
-use serde_json::Value;
+use rustc_span::edit_distance::edit_distance;
+use serde_json::{Value, json};
 
 use super::{Target, TargetKind, TargetOptions, TargetWarnings};
 use crate::json::{Json, ToJson};
 
+#[cfg(test)]
+mod tests;
+
 impl Target {
     /// Loads a target descriptor from a JSON object.
     pub fn from_json(obj: Json) -> Result<(Target, TargetWarnings), String> {
+        let obj = match obj {
+            Value::Object(obj) => obj,
+            _ => return Err("Expected JSON object for target")?,
+        };
+
+        let mut fields = Fields::new(obj);
+        let (mut base, incorrect_type) = Target::from_fields(&mut fields)?;
+        fields.check_unknown()?;
+
+        base.update_from_cli();
+        base.check_consistency(TargetKind::Json)?;
+
+        Ok((base, TargetWarnings { incorrect_type }))
+    }
+
+    /// Returns the JSON schema of the target descriptors accepted by [`Target::from_json`].
+    pub fn json_schema() -> Json {
+        // Parsing a target descriptor looks up all the fields it may contain,
+        // so the schema is collected while parsing a minimal descriptor.
+        let mut fields = Fields::new(minimal_descriptor());
+        Target::from_fields(&mut fields).expect("a minimal target descriptor is valid");
+        fields.schema()
+    }
+
+    /// Parses the fields of a target descriptor, returning the target and the
+    /// fields whose value doesn't have the correct JSON type.
+    fn from_fields(obj: &mut Fields) -> Result<(Target, Vec<String>), String> {
         // While ugly, this code must remain this way to retain
         // compatibility with existing JSON fields and the internal
         // expected naming of the Target and TargetOptions structs.
@@ -17,13 +48,8 @@ pub fn from_json(obj: Json) -> Result<(Target, TargetWarnings), String> {
         // are round-tripped through this code to catch cases where
         // the JSON parser is not updated to match the structs.
 
-        let mut obj = match obj {
-            Value::Object(obj) => obj,
-            _ => return Err("Expected JSON object for target")?,
-        };
-
         let mut get_req_field = |name: &str| {
-            obj.remove(name)
+            obj.remove_required(name)
                 .and_then(|j| j.as_str().map(str::to_string))
                 .ok_or_else(|| format!("Field {name} in target specification is required"))
         };
@@ -42,7 +68,16 @@ pub fn from_json(obj: Json) -> Result<(Target, TargetWarnings), String> {
         // FIXME: This doesn't properly validate anything and just ignores the data if it's invalid.
         // That's okay for now, the only use of this is when generating docs, which we don't do for
         // custom targets.
-        if let Some(Json::Object(mut metadata)) = obj.remove("metadata") {
+        let metadata_schema = json!({
+            "type": "object",
+            "properties": {
+                "description": string_schema(),
+                "tier": { "type": "integer", "minimum": 1, "maximum": 3 },
+                "host_tools": { "type": "boolean" },
+                "std": { "type": "boolean" },
+            },
+        });
+        if let Some(Json::Object(mut metadata)) = obj.remove("metadata", metadata_schema) {
             base.metadata.description = metadata
                 .remove("description")
                 .and_then(|desc| desc.as_str().map(|desc| desc.to_owned().into()));
@@ -60,31 +95,32 @@ pub fn from_json(obj: Json) -> Result<(Target, TargetWarnings), String> {
         macro_rules! key {
             ($key_name:ident) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(s) = obj.remove(&name).and_then(|s| s.as_str().map(str::to_string).map(Cow::from)) {
+                if let Some(s) = obj.remove(&name, string_schema()).and_then(|s| s.as_str().map(str::to_string).map(Cow::from)) {
                     base.$key_name = s;
                 }
             } );
             ($key_name:ident = $json_name:expr) => ( {
                 let name = $json_name;
-                if let Some(s) = obj.remove(name).and_then(|s| s.as_str().map(str::to_string).map(Cow::from)) {
+                if let Some(s) = obj.remove(name, string_schema()).and_then(|s| s.as_str().map(str::to_string).map(Cow::from)) {
                     base.$key_name = s;
                 }
             } );
             ($key_name:ident, bool) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(s) = obj.remove(&name).and_then(|b| b.as_bool()) {
+                if let Some(s) = obj.remove(&name, json!({ "type": "boolean" })).and_then(|b| b.as_bool()) {
                     base.$key_name = s;
                 }
             } );
             ($key_name:ident = $json_name:expr, bool) => ( {
                 let name = $json_name;
-                if let Some(s) = obj.remove(name).and_then(|b| b.as_bool()) {
+                if let Some(s) = obj.remove(name, json!({ "type": "boolean" })).and_then(|b| b.as_bool()) {
                     base.$key_name = s;
                 }
             } );
             ($key_name:ident, u32) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(s) = obj.remove(&name).and_then(|b| b.as_u64()) {
+                let schema = json!({ "type": "integer", "minimum": 1, "maximum": 5 });
+                if let Some(s) = obj.remove(&name, schema).and_then(|b| b.as_u64()) {
                     if s < 1 || s > 5 {
                         return Err("Not a valid DWARF version number".into());
                     }
@@ -93,19 +129,20 @@ macro_rules! key {
             } );
             ($key_name:ident, Option<bool>) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(s) = obj.remove(&name).and_then(|b| b.as_bool()) {
+                if let Some(s) = obj.remove(&name, json!({ "type": "boolean" })).and_then(|b| b.as_bool()) {
                     base.$key_name = Some(s);
                 }
             } );
             ($key_name:ident, Option<u64>) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(s) = obj.remove(&name).and_then(|b| b.as_u64()) {
+                let schema = json!({ "type": "integer", "minimum": 0 });
+                if let Some(s) = obj.remove(&name, schema).and_then(|b| b.as_u64()) {
                     base.$key_name = Some(s);
                 }
             } );
             ($key_name:ident, MergeFunctions) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, enum_schema(&["disabled", "trampolines", "aliases"])).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::MergeFunctions>() {
                         Ok(mergefunc) => base.$key_name = mergefunc,
                         _ => return Some(Err(format!("'{}' is not a valid value for \
@@ -118,7 +155,9 @@ macro_rules! key {
             } );
             ($key_name:ident, RelocModel) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, enum_schema(&[
+                    "static", "pic", "pie", "dynamic-no-pic", "ropi", "rwpi", "ropi-rwpi",
+                ])).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::RelocModel>() {
                         Ok(relocation_model) => base.$key_name = relocation_model,
                         _ => return Some(Err(format!("'{}' is not a valid relocation model. \
@@ -130,7 +169,7 @@ macro_rules! key {
             } );
             ($key_name:ident, CodeModel) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, enum_schema(&["tiny", "small", "kernel", "medium", "large"])).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::CodeModel>() {
                         Ok(code_model) => base.$key_name = Some(code_model),
                         _ => return Some(Err(format!("'{}' is not a valid code model. \
@@ -142,7 +181,9 @@ macro_rules! key {
             } );
             ($key_name:ident, TlsModel) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, enum_schema(&[
+                    "global-dynamic", "local-dynamic", "initial-exec", "local-exec", "emulated",
+                ])).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::TlsModel>() {
                         Ok(tls_model) => base.$key_name = tls_model,
                         _ => return Some(Err(format!("'{}' is not a valid TLS model. \
@@ -153,7 +194,10 @@ macro_rules! key {
                 })).unwrap_or(Ok(()))
             } );
             ($key_name:ident, SmallDataThresholdSupport) => ( {
-                obj.remove("small-data-threshold-support").and_then(|o| o.as_str().and_then(|s| {
+                obj.remove("small-data-threshold-support", json!({
+                    "type": "string",
+                    "pattern": "^(none|default-for-arch|llvm-module-flag=.*|llvm-arg=.*)$",
+                })).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::SmallDataThresholdSupport>() {
                         Ok(support) => base.small_data_threshold_support = support,
                         _ => return Some(Err(format!("'{s}' is not a valid value for small-data-threshold-support."))),
@@ -163,7 +207,7 @@ macro_rules! key {
             } );
             ($key_name:ident, PanicStrategy) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, enum_schema(&["unwind", "abort"])).and_then(|o| o.as_str().and_then(|s| {
                     match s {
                         "unwind" => base.$key_name = super::PanicStrategy::Unwind,
                         "abort" => base.$key_name = super::PanicStrategy::Abort,
@@ -176,7 +220,7 @@ macro_rules! key {
             } );
             ($key_name:ident, RelroLevel) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, enum_schema(&["full", "partial", "off", "none"])).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::RelroLevel>() {
                         Ok(level) => base.$key_name = level,
                         _ => return Some(Err(format!("'{}' is not a valid value for \
@@ -188,7 +232,7 @@ macro_rules! key {
             } );
             ($key_name:ident, Option<SymbolVisibility>) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, enum_schema(&["hidden", "protected", "interposable"])).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::SymbolVisibility>() {
                         Ok(level) => base.$key_name = Some(level),
                         _ => return Some(Err(format!("'{}' is not a valid value for \
@@ -200,7 +244,7 @@ macro_rules! key {
             } );
             ($key_name:ident, DebuginfoKind) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, enum_schema(&["dwarf", "dwarf-dsym", "pdb"])).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::DebuginfoKind>() {
                         Ok(level) => base.$key_name = level,
                         _ => return Some(Err(
@@ -213,7 +257,7 @@ macro_rules! key {
             } );
             ($key_name:ident, SplitDebuginfo) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, enum_schema(&["off", "unpacked", "packed"])).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::SplitDebuginfo>() {
                         Ok(level) => base.$key_name = level,
                         _ => return Some(Err(format!("'{}' is not a valid value for \
@@ -225,7 +269,7 @@ macro_rules! key {
             } );
             ($key_name:ident, list) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(j) = obj.remove(&name) {
+                if let Some(j) = obj.remove(&name, string_list_schema()) {
                     if let Some(v) = j.as_array() {
                         base.$key_name = v.iter()
                             .map(|a| a.as_str().unwrap().to_string().into())
@@ -237,7 +281,7 @@ macro_rules! key {
             } );
             ($key_name:ident, opt_list) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(j) = obj.remove(&name) {
+                if let Some(j) = obj.remove(&name, string_list_schema()) {
                     if let Some(v) = j.as_array() {
                         base.$key_name = Some(v.iter()
                             .map(|a| a.as_str().unwrap().to_string().into())
@@ -249,7 +293,7 @@ macro_rules! key {
             } );
             ($key_name:ident, fallible_list) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|j| {
+                obj.remove(&name, string_list_schema()).and_then(|j| {
                     if let Some(v) = j.as_array() {
                         match v.iter().map(|a| FromStr::from_str(a.as_str().unwrap())).collect() {
                             Ok(l) => { base.$key_name = l },
@@ -268,7 +312,7 @@ macro_rules! key {
             } );
             ($key_name:ident, optional) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(o) = obj.remove(&name) {
+                if let Some(o) = obj.remove(&name, string_schema()) {
                     base.$key_name = o
                         .as_str()
                         .map(|s| s.to_string().into());
@@ -276,7 +320,7 @@ macro_rules! key {
             } );
             ($key_name:ident = $json_name:expr, LldFlavor) => ( {
                 let name = $json_name;
-                obj.remove(name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(name, enum_schema(&["darwin", "gnu", "link", "wasm"])).and_then(|o| o.as_str().and_then(|s| {
                     if let Some(flavor) = super::LldFlavor::from_str(&s) {
                         base.$key_name = flavor;
                     } else {
@@ -290,7 +334,7 @@ macro_rules! key {
             } );
             ($key_name:ident = $json_name:expr, LinkerFlavorCli) => ( {
                 let name = $json_name;
-                obj.remove(name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(name, linker_flavor_schema()).and_then(|o| o.as_str().and_then(|s| {
                     match super::LinkerFlavorCli::from_str(s) {
                         Some(linker_flavor) => base.$key_name = linker_flavor,
                         _ => return Some(Err(format!("'{}' is not a valid value for linker-flavor. \
@@ -301,7 +345,17 @@ macro_rules! key {
             } );
             ($key_name:ident, StackProbeType) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| match super::StackProbeType::from_json(&o) {
+                obj.remove(&name, json!({
+                    "type": "object",
+                    "properties": {
+                        "kind": enum_schema(&["none", "inline", "call", "inline-or-call"]),
+                        "min-llvm-version-for-inline": {
+                            "type": "array",
+                            "items": { "type": "integer", "minimum": 0 },
+                        },
+                    },
+                    "required": ["kind"],
+                })).and_then(|o| match super::StackProbeType::from_json(&o) {
                     Ok(v) => {
                         base.$key_name = v;
                         Some(Ok(()))
@@ -313,7 +367,13 @@ macro_rules! key {
             } );
             ($key_name:ident, SanitizerSet) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(o) = obj.remove(&name) {
+                if let Some(o) = obj.remove(&name, json!({
+                    "type": "array",
+                    "items": enum_schema(&[
+                        "address", "cfi", "dataflow", "kcfi", "kernel-address", "leak", "memory",
+                        "memtag", "safestack", "shadow-call-stack", "thread", "hwaddress",
+                    ]),
+                })) {
                     if let Some(a) = o.as_array() {
                         for s in a {
                             use super::SanitizerSet;
@@ -351,7 +411,19 @@ macro_rules! key {
                 // }
                 // ```
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(o) = obj.remove(&name) {
+                if let Some(o) = obj.remove(&name, json!({
+                    "type": "object",
+                    "properties": {
+                        "components": {
+                            "type": "array",
+                            "items": enum_schema(
+                                &super::LinkSelfContainedComponents::all_components()
+                                    .map(|component| component.as_str().unwrap()),
+                            ),
+                        },
+                    },
+                    "required": ["components"],
+                })) {
                     if let Some(o) = o.as_object() {
                         let component_array = o.get("components")
                             .ok_or_else(|| format!("{name}: expected a \
@@ -378,7 +450,7 @@ macro_rules! key {
             } );
             ($key_name:ident = $json_name:expr, link_self_contained_backwards_compatible) => ( {
                 let name = $json_name;
-                obj.remove(name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(name, enum_schema(&["false", "true", "wasm", "musl", "mingw"])).and_then(|o| o.as_str().and_then(|s| {
                     match s.parse::<super::LinkSelfContainedDefault>() {
                         Ok(lsc_default) => base.$key_name = lsc_default,
                         _ => return Some(Err(format!("'{}' is not a valid `-Clink-self-contained` default. \
@@ -389,7 +461,14 @@ macro_rules! key {
             } );
             ($key_name:ident = $json_name:expr, link_objects) => ( {
                 let name = $json_name;
-                if let Some(val) = obj.remove(name) {
+                if let Some(val) = obj.remove(name, json!({
+                    "type": "object",
+                    "propertyNames": enum_schema(&[
+                        "dynamic-nopic-exe", "dynamic-pic-exe", "static-nopic-exe",
+                        "static-pic-exe", "dynamic-dylib", "static-dylib", "wasi-reactor-exe",
+                    ]),
+                    "additionalProperties": string_list_schema(),
+                })) {
                     let obj = val.as_object().ok_or_else(|| format!("{}: expected a \
                         JSON object with fields per CRT object kind.", name))?;
                     let mut args = super::CrtObjects::new();
@@ -417,7 +496,11 @@ macro_rules! key {
             } );
             ($key_name:ident = $json_name:expr, link_args) => ( {
                 let name = $json_name;
-                if let Some(val) = obj.remove(name) {
+                if let Some(val) = obj.remove(name, json!({
+                    "type": "object",
+                    "propertyNames": linker_flavor_schema(),
+                    "additionalProperties": string_list_schema(),
+                })) {
                     let obj = val.as_object().ok_or_else(|| format!("{}: expected a \
                         JSON object with fields per linker-flavor.", name))?;
                     let mut args = super::LinkArgsCli::new();
@@ -444,7 +527,8 @@ macro_rules! key {
             } );
             ($key_name:ident, env) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                if let Some(o) = obj.remove(&name) {
+                let schema = json!({ "type": "array", "items": { "type": "string", "pattern": "=" } });
+                if let Some(o) = obj.remove(&name, schema) {
                     if let Some(a) = o.as_array() {
                         for o in a {
                             if let Some(s) = o.as_str() {
@@ -461,7 +545,8 @@ macro_rules! key {
                 }
             } );
             ($key_name:ident, target_families) => ( {
-                if let Some(value) = obj.remove("target-family") {
+                let schema = json!({ "oneOf": [string_schema(), string_list_schema()] });
+                if let Some(value) = obj.remove("target-family", schema) {
                     if let Some(v) = value.as_array() {
                         base.$key_name = v.iter()
                             .map(|a| a.as_str().unwrap().to_string().into())
@@ -473,7 +558,7 @@ macro_rules! key {
             } );
             ($key_name:ident, Conv) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
-                obj.remove(&name).and_then(|o| o.as_str().and_then(|s| {
+                obj.remove(&name, string_schema()).and_then(|o| o.as_str().and_then(|s| {
                     match super::Conv::from_str(s) {
                         Ok(c) => {
                             base.$key_name = c;
@@ -485,7 +570,7 @@ macro_rules! key {
             } );
         }
 
-        if let Some(j) = obj.remove("target-endian") {
+        if let Some(j) = obj.remove("target-endian", enum_schema(&["little", "big"])) {
             if let Some(s) = j.as_str() {
                 base.endian = s.parse()?;
             } else {
@@ -493,7 +578,9 @@ macro_rules! key {
             }
         }
 
-        if let Some(fp) = obj.remove("frame-pointer") {
+        if let Some(fp) =
+            obj.remove("frame-pointer", enum_schema(&["always", "non-leaf", "may-omit"]))
+        {
             if let Some(s) = fp.as_str() {
                 base.frame_pointer = s
                     .parse()
@@ -614,16 +701,105 @@ macro_rules! key {
         key!(entry_abi, Conv)?;
         key!(supports_xray, bool);
 
-        base.update_from_cli();
-        base.check_consistency(TargetKind::Json)?;
+        Ok((base, incorrect_type))
+    }
+}
 
-        // Each field should have been read using `Json::remove` so any keys remaining are unused.
-        let remaining_keys = obj.keys();
-        Ok((base, TargetWarnings {
-            unused_fields: remaining_keys.cloned().collect(),
-            incorrect_type,
-        }))
+/// The fields of a target descriptor which were not parsed yet.
+///
+/// Every field is looked up together with the JSON schema of its value, so
+/// that the schema of target descriptors is built by the code parsing them.
+struct Fields {
+    obj: serde_json::Map<String, Value>,
+    properties: serde_json::Map<String, Value>,
+    required: Vec<String>,
+}
+
+impl Fields {
+    fn new(obj: serde_json::Map<String, Value>) -> Fields {
+        Fields { obj, properties: serde_json::Map::new(), required: vec![] }
+    }
+
+    fn remove(&mut self, name: &str, schema: Value) -> Option<Value> {
+        self.properties.insert(name.to_owned(), schema);
+        self.obj.remove(name)
+    }
+
+    fn remove_required(&mut self, name: &str) -> Option<Value> {
+        self.required.push(name.to_owned());
+        self.remove(name, string_schema())
     }
+
+    /// Each field should have been read using `Fields::remove`, so any fields
+    /// remaining are unknown, except for the fields starting with `$`, like
+    /// `$schema`, which are reserved for tools.
+    fn check_unknown(&self) -> Result<(), String> {
+        let unknown = self
+            .obj
+            .keys()
+            .filter(|name| !name.starts_with('$'))
+            .map(|name| match self.best_match(name) {
+                Some(known) => format!("`{name}` (did you mean `{known}`?)"),
+                None => format!("`{name}`"),
+            })
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("target json file contains unknown fields: {}", unknown.join(", ")))
+        }
+    }
+
+    /// Finds the known field with the name closest to `name`, treating `_`
+    /// as `-` since the fields are named after the `TargetOptions` fields.
+    fn best_match(&self, name: &str) -> Option<&str> {
+        let name = name.replace('_', "-");
+        let limit = std::cmp::max(name.len(), 3) / 3;
+        self.properties
+            .keys()
+            .filter_map(|known| Some((edit_distance(&name, known, limit)?, known.as_str())))
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, known)| known)
+    }
+
+    fn schema(self) -> Json {
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "rustc target specification",
+            "type": "object",
+            "properties": self.properties,
+            "required": self.required,
+            "patternProperties": { "^\\$": {} },
+            "additionalProperties": false,
+        })
+    }
+}
+
+/// A target descriptor with only the required fields.
+fn minimal_descriptor() -> serde_json::Map<String, Value> {
+    serde_json::Map::from_iter([
+        ("llvm-target".to_owned(), "".into()),
+        ("target-pointer-width".to_owned(), "64".into()),
+        ("data-layout".to_owned(), "".into()),
+        ("arch".to_owned(), "".into()),
+    ])
+}
+
+fn string_schema() -> Value {
+    json!({ "type": "string" })
+}
+
+fn string_list_schema() -> Value {
+    json!({ "type": "array", "items": string_schema() })
+}
+
+fn enum_schema(values: &[&str]) -> Value {
+    json!({ "type": "string", "enum": values })
+}
+
+fn linker_flavor_schema() -> Value {
+    let flavors = super::LinkerFlavorCli::all().iter().map(|flavor| flavor.desc());
+    enum_schema(&flavors.collect::<Vec<_>>())
 }
 
 impl ToJson for Target {
@@ -0,0 +1,64 @@
+use serde_json::{Map, Value, json};
+
+use super::*;
+
+/// Returns values matching `schema` which together use every value of the `enum`s in it.
+fn enum_samples(schema: &Value) -> Vec<Value> {
+    if let Some(values) = schema.get("enum") {
+        return values.as_array().unwrap().clone();
+    }
+    match schema["type"].as_str() {
+        Some("array") => enum_samples(&schema["items"]).into_iter().map(|v| json!([v])).collect(),
+        Some("object") => {
+            let mut samples = vec![];
+            if let Some(names) = schema.get("propertyNames") {
+                let value = sample(&schema["additionalProperties"]);
+                for name in enum_samples(names) {
+                    let name = name.as_str().unwrap().to_owned();
+                    samples.push(Value::Object(Map::from_iter([(name, value.clone())])));
+                }
+            }
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (name, property) in properties {
+                    for value in enum_samples(property) {
+                        let mut object: Map<_, _> =
+                            properties.iter().map(|(name, p)| (name.clone(), sample(p))).collect();
+                        object.insert(name.clone(), value);
+                        samples.push(Value::Object(object));
+                    }
+                }
+            }
+            samples
+        }
+        _ => vec![],
+    }
+}
+
+/// Returns a value matching `schema`.
+fn sample(schema: &Value) -> Value {
+    if let Some(value) = enum_samples(schema).into_iter().next() {
+        return value;
+    }
+    match schema["type"].as_str() {
+        Some("array") => json!([]),
+        Some("object") => json!({}),
+        Some("integer") => json!(0),
+        Some("boolean") => json!(false),
+        _ => json!(""),
+    }
+}
+
+#[test]
+fn parser_accepts_schema_enum_values() {
+    let schema = Target::json_schema();
+    for (name, property) in schema["properties"].as_object().unwrap() {
+        for value in enum_samples(property) {
+            let mut obj = minimal_descriptor();
+            obj.insert(name.clone(), value.clone());
+            let mut fields = Fields::new(obj);
+            let (_, incorrect_type) = Target::from_fields(&mut fields)
+                .unwrap_or_else(|error| panic!("`{name}: {value}` is rejected: {error}"));
+            assert!(incorrect_type.is_empty(), "`{name}: {value}` has an incorrect type");
+        }
+    }
+}
@@ -1969,26 +1969,19 @@ macro_rules! cvs {
 
 /// Warnings encountered when parsing the target `json`.
 ///
-/// Includes fields that weren't recognized and fields that don't have the expected type.
+/// Includes fields that don't have the expected type.
 #[derive(Debug, PartialEq)]
 pub struct TargetWarnings {
-    unused_fields: Vec<String>,
     incorrect_type: Vec<String>,
 }
 
 impl TargetWarnings {
     pub fn empty() -> Self {
-        Self { unused_fields: Vec::new(), incorrect_type: Vec::new() }
+        Self { incorrect_type: Vec::new() }
     }
 
     pub fn warning_messages(&self) -> Vec<String> {
         let mut warnings = vec![];
-        if !self.unused_fields.is_empty() {
-            warnings.push(format!(
-                "target json file contains unused fields: {}",
-                self.unused_fields.join(", ")
-            ));
-        }
         if !self.incorrect_type.is_empty() {
             warnings.push(format!(
                 "target json file contains fields whose value doesn't have the correct json type: {}",
@@ -3153,6 +3146,45 @@ macro_rules! check_matches {
             );
         }
 
+        // Check the sizes of C types and atomics.
+        check_matches!(
+            &*self.c_int_width,
+            "16" | "32" | "64",
+            "`c_int_width` must be 16, 32 or 64"
+        );
+        if let Some(c_enum_min_bits) = self.c_enum_min_bits {
+            check!(
+                c_enum_min_bits <= self.c_int_width.parse().unwrap(),
+                "`c_enum_min_bits` must not be larger than `c_int_width`"
+            );
+        }
+        let is_atomic_width = |width: u64| width.is_power_of_two() && (8..=128).contains(&width);
+        check!(
+            is_atomic_width(self.min_atomic_width()),
+            "`min_atomic_width` must be a power of two between 8 and 128"
+        );
+        // A `max_atomic_width` of 0 means that atomics are not supported at all.
+        if self.max_atomic_width() != 0 {
+            check!(
+                is_atomic_width(self.max_atomic_width()),
+                "`max_atomic_width` must be 0 or a power of two between 8 and 128"
+            );
+            check!(
+                self.min_atomic_width() <= self.max_atomic_width(),
+                "`min_atomic_width` must not be larger than `max_atomic_width`"
+            );
+        }
+
+        check!(
+            self.supported_split_debuginfo.contains(&self.split_debuginfo),
+            "the default `split_debuginfo` must be one of `supported_split_debuginfo`"
+        );
+        check_eq!(
+            self.is_like_android,
+            self.os == "android",
+            "`is_like_android` must be set if and only if `os` is `android`"
+        );
+
         // Check that RISC-V targets always specify which ABI they use.
         match &*self.arch {
             "riscv32" => {
@@ -1,7 +1,10 @@
-use crate::spec::Target;
+use serde_json::Value;
+
+use crate::json::ToJson;
+use crate::spec::{TARGETS, Target, TargetTuple};
 
 #[test]
-fn report_unused_fields() {
+fn report_unknown_fields() {
     let json = serde_json::from_str(
         r#"
     {
@@ -14,9 +17,82 @@ fn report_unused_fields() {
     "#,
     )
     .unwrap();
+    let error = Target::from_json(json).unwrap_err();
+    assert!(error.contains("`code-mode` (did you mean `code-model`?)"), "{error}");
+}
+
+#[test]
+fn allow_fields_reserved_for_tools() {
+    let json = serde_json::from_str(
+        r#"
+    {
+        "$schema": "target-spec.schema.json",
+        "arch": "powerpc64",
+        "data-layout": "e-m:e-i64:64-n32:64",
+        "llvm-target": "powerpc64le-elf",
+        "target-pointer-width": "64"
+    }
+    "#,
+    )
+    .unwrap();
     let warnings = Target::from_json(json).unwrap().1;
-    assert_eq!(warnings.warning_messages().len(), 1);
-    assert!(warnings.warning_messages().join("\n").contains("code-mode"));
+    assert_eq!(warnings.warning_messages().len(), 0);
+}
+
+#[test]
+fn reject_inconsistent_fields() {
+    let json = serde_json::from_str(
+        r#"
+    {
+        "arch": "powerpc64",
+        "data-layout": "e-m:e-i64:64-n32:64",
+        "llvm-target": "powerpc64le-elf",
+        "target-pointer-width": "64",
+        "min-atomic-width": 64,
+        "max-atomic-width": 32
+    }
+    "#,
+    )
+    .unwrap();
+    let error = Target::from_json(json).unwrap_err();
+    assert!(error.contains("`min_atomic_width`"), "{error}");
+}
+
+/// Checks that the strings in `value` are among the values of the `enum`s of `schema`.
+#[track_caller]
+fn assert_enum_values(field: &str, value: &Value, schema: &Value) {
+    if let Some(values) = schema.get("enum") {
+        assert!(
+            values.as_array().unwrap().contains(value),
+            "`{field}: {value}` is not in the schema"
+        );
+    } else if let Some(items) = value.as_array() {
+        for item in items {
+            assert_enum_values(field, item, &schema["items"]);
+        }
+    } else if let Some(object) = value.as_object() {
+        for (name, value) in object {
+            if let Some(names) = schema.get("propertyNames") {
+                assert_enum_values(field, &name.as_str().into(), names);
+            }
+            let property = schema.get("properties").and_then(|properties| properties.get(name));
+            assert_enum_values(field, value, property.unwrap_or(&schema["additionalProperties"]));
+        }
+    }
+}
+
+#[test]
+fn json_schema_describes_builtin_targets() {
+    let schema = Target::json_schema();
+    let properties = schema["properties"].as_object().unwrap();
+    for tuple in TARGETS {
+        let target = Target::expect_builtin(&TargetTuple::from_tuple(tuple));
+        for (field, value) in target.to_json().as_object().unwrap() {
+            let property = properties.get(field);
+            assert!(property.is_some(), "`{field}` of {tuple} is not in the schema");
+            assert_enum_values(field, value, property.unwrap());
+        }
+    }
 }
 
 #[test]
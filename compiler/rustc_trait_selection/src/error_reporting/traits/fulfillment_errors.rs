@@ -1,8 +1,10 @@
 use core::ops::ControlFlow;
 use std::borrow::Cow;
+use std::fs;
+use std::hash::{Hash, Hasher};
 
 use rustc_ast::TraitObjectSyntax;
-use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::fx::{FxHashMap, FxHasher};
 use rustc_data_structures::unord::UnordSet;
 use rustc_errors::codes::*;
 use rustc_errors::{
@@ -43,6 +45,7 @@
     AsyncClosureNotFn, ClosureFnMutLabel, ClosureFnOnceLabel, ClosureKindMismatch,
 };
 use crate::infer::{self, InferCtxt, InferCtxtExt as _};
+use crate::solve::inspect::proof_tree_to_json;
 use crate::traits::query::evaluate_obligation::InferCtxtExt as _;
 use crate::traits::{
     MismatchedProjectionTypes, NormalizeExt, Obligation, ObligationCause, ObligationCauseCode,
@@ -264,6 +267,7 @@ pub fn report_selection_error(
                             ));
                             err.note("consider using `--verbose` to print the full type name to the console");
                         }
+                        self.note_dumped_proof_tree(&mut err, root_obligation);
                         let mut suggested = false;
                         if is_try_conversion {
                             suggested = self.try_conversion_context(&obligation, main_trait_ref.skip_binder(), &mut err);
@@ -2195,6 +2199,52 @@ fn note_version_mismatch(&self, err: &mut Diag<'_>, trait_ref: ty::PolyTraitRef<
         suggested
     }
 
+    /// Writes the proof tree of the failed `root_obligation` to the directory passed to
+    /// `-Zdump-failed-proof-trees` and points to the written file from `err`.
+    fn note_dumped_proof_tree(
+        &self,
+        err: &mut Diag<'_>,
+        root_obligation: &PredicateObligation<'tcx>,
+    ) {
+        let Some(dir) = &self.tcx.sess.opts.unstable_opts.dump_failed_proof_trees else {
+            return;
+        };
+        if !self.next_trait_solver() {
+            err.note("proof trees can only be dumped with `-Znext-solver`");
+            return;
+        }
+
+        let obligation = self.resolve_vars_if_possible(root_obligation.clone());
+        let span = obligation.cause.span;
+        let proof_tree = proof_tree_to_json(self, obligation.into(), span);
+        let contents = serde_json::to_string_pretty(&proof_tree).unwrap();
+
+        // Name the file after its contents, so that the same failure reported twice
+        // or in another compilation session ends up in the same file.
+        let mut hasher = FxHasher::default();
+        contents.hash(&mut hasher);
+        let path = dir.join(format!(
+            "{}-proof-tree-{:016x}.json",
+            self.tcx.crate_name(LOCAL_CRATE),
+            hasher.finish()
+        ));
+
+        match fs::create_dir_all(dir).and_then(|()| fs::write(&path, contents)) {
+            Ok(()) => {
+                err.note(format!(
+                    "the proof tree of this goal was written to `{}`",
+                    path.display()
+                ));
+            }
+            Err(error) => {
+                err.note(format!(
+                    "failed to write the proof tree of this goal to `{}`: {error}",
+                    path.display()
+                ));
+            }
+        }
+    }
+
     /// Creates a `PredicateObligation` with `new_self_ty` replacing the existing type in the
     /// `trait_ref`.
     ///
@@ -0,0 +1,137 @@
+//! Serialization of proof trees for `-Zdump-failed-proof-trees`.
+//!
+//! The dump is meant for humans trying to understand why a goal failed,
+//! so it uses the same lossy view of the proof tree as the rest of the
+//! [`ProofTreeVisitor`] infrastructure: each goal lists the candidates
+//! which were considered for it together with their nested goals.
+
+use std::mem;
+
+use rustc_infer::infer::InferCtxt;
+use rustc_middle::traits::solve::{CandidateSource, Certainty, Goal, GoalSource, NoSolution};
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_next_trait_solver::solve::MaybeCause;
+use rustc_next_trait_solver::solve::inspect::ProbeKind;
+use rustc_span::Span;
+use serde_json::{Map, Value, json};
+
+use super::{InspectConfig, InspectGoal, ProofTreeInferCtxtExt, ProofTreeVisitor};
+
+/// Goals nested deeper than this are not expanded in the dump.
+const MAX_DUMP_DEPTH: usize = 32;
+
+/// Evaluates `goal` and returns its proof tree as a JSON object.
+///
+/// Every goal is an object with the fields `goal`, `source` and `result`,
+/// and a list of `candidates` unless it is a `cycle` on the path from the
+/// root or was `truncated` because it is nested too deeply. Every candidate
+/// has a `kind`, a `result` and its `nested_goals`.
+pub fn proof_tree_to_json<'tcx>(
+    infcx: &InferCtxt<'tcx>,
+    goal: Goal<'tcx, ty::Predicate<'tcx>>,
+    span: Span,
+) -> Value {
+    let mut dumper = ProofTreeDumper { span, stack: vec![], goals: vec![] };
+    infcx.probe(|_| infcx.visit_proof_tree(goal, &mut dumper));
+    dumper.goals.pop().unwrap()
+}
+
+struct ProofTreeDumper<'tcx> {
+    span: Span,
+    /// The goals on the path from the root to the goal which is currently
+    /// visited, used to detect cycles.
+    stack: Vec<Goal<'tcx, ty::Predicate<'tcx>>>,
+    /// The already visited nested goals of the current candidate.
+    goals: Vec<Value>,
+}
+
+impl<'tcx> ProofTreeVisitor<'tcx> for ProofTreeDumper<'tcx> {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn config(&self) -> InspectConfig {
+        // We stop at `MAX_DUMP_DEPTH` ourselves to mark the goal as truncated.
+        InspectConfig { max_depth: MAX_DUMP_DEPTH + 1 }
+    }
+
+    fn visit_goal(&mut self, goal: &InspectGoal<'_, 'tcx>) {
+        let tcx = goal.infcx().tcx;
+        let mut node = Map::new();
+        node.insert("goal".to_owned(), goal.goal().predicate.to_string().into());
+        node.insert("source".to_owned(), goal_source_str(goal.source()).into());
+        node.insert("result".to_owned(), result_str(goal.result()).into());
+
+        if self.stack.contains(&goal.goal()) {
+            node.insert("cycle".to_owned(), true.into());
+        } else if goal.depth() >= MAX_DUMP_DEPTH {
+            node.insert("truncated".to_owned(), true.into());
+        } else {
+            self.stack.push(goal.goal());
+            let mut candidates = vec![];
+            for candidate in goal.candidates() {
+                let outer_goals = mem::take(&mut self.goals);
+                candidate.visit_nested_in_probe(self);
+                let nested_goals = mem::replace(&mut self.goals, outer_goals);
+                candidates.push(json!({
+                    "kind": candidate_kind_string(tcx, goal.goal().param_env, candidate.kind()),
+                    "result": result_str(candidate.result()),
+                    "nested_goals": nested_goals,
+                }));
+            }
+            self.stack.pop();
+            node.insert("candidates".to_owned(), candidates.into());
+        }
+
+        self.goals.push(Value::Object(node));
+    }
+}
+
+fn goal_source_str(source: GoalSource) -> &'static str {
+    match source {
+        GoalSource::Misc => "misc",
+        GoalSource::ImplWhereBound => "impl where-bound",
+        GoalSource::InstantiateHigherRanked => "instantiate higher-ranked",
+        GoalSource::AliasWellFormed => "alias well-formed",
+    }
+}
+
+fn result_str(result: Result<Certainty, NoSolution>) -> &'static str {
+    match result {
+        Ok(Certainty::Yes) => "yes",
+        Ok(Certainty::Maybe(MaybeCause::Ambiguity)) => "ambiguous",
+        Ok(Certainty::Maybe(MaybeCause::Overflow { .. })) => "overflow",
+        Err(NoSolution) => "no solution",
+    }
+}
+
+fn candidate_kind_string<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    kind: ProbeKind<TyCtxt<'tcx>>,
+) -> String {
+    match kind {
+        ProbeKind::TraitCandidate { source, result: _ } => match source {
+            CandidateSource::Impl(def_id) => format!(
+                "impl `{}` at {}",
+                tcx.def_path_str(def_id),
+                tcx.sess.source_map().span_to_embeddable_string(tcx.def_span(def_id)),
+            ),
+            CandidateSource::BuiltinImpl(_) => "builtin impl".to_owned(),
+            CandidateSource::ParamEnv(index) => match param_env.caller_bounds().get(index) {
+                Some(clause) => format!("where-bound `{clause}`"),
+                None => format!("where-bound #{index}"),
+            },
+            CandidateSource::AliasBound => "alias bound".to_owned(),
+            CandidateSource::CoherenceUnknowable => "unknowable downstream impl".to_owned(),
+        },
+        ProbeKind::Root { result: _ } => "root".to_owned(),
+        ProbeKind::TryNormalizeNonRigid { result: _ } => "normalization".to_owned(),
+        ProbeKind::OpaqueTypeStorageLookup { result: _ } => "opaque type storage".to_owned(),
+        ProbeKind::RigidAlias { result: _ } => "rigid alias".to_owned(),
+        ProbeKind::NormalizedSelfTyAssembly
+        | ProbeKind::UnsizeAssembly
+        | ProbeKind::UpcastProjectionCompatibility
+        | ProbeKind::ShadowedEnvProbing => format!("{kind:?}"),
+    }
+}
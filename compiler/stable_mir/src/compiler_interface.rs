@@ -8,6 +8,7 @@
 use crate::abi::{FnAbi, Layout, LayoutShape};
 use crate::crate_def::Attribute;
 use crate::mir::alloc::{AllocId, GlobalAlloc};
+use crate::mir::dataflow::{BitSet, MaybeInitializedPlaces, Results};
 use crate::mir::mono::{Instance, InstanceDef, StaticDef};
 use crate::mir::{BinOp, Body, Place, UnOp};
 use crate::target::MachineInfo;
@@ -169,6 +170,12 @@ pub trait Context {
     /// Get the body of an Instance which is already monomorphized.
     fn instance_body(&self, instance: InstanceDef) -> Option<Body>;
 
+    /// Compute the locals that may be borrowed at each location of the body of an instance.
+    fn maybe_borrowed_locals(&self, instance: InstanceDef) -> Results<BitSet>;
+
+    /// Compute the places that may be initialized at each location of the body of an instance.
+    fn maybe_initialized_places(&self, instance: InstanceDef) -> MaybeInitializedPlaces;
+
     /// Get the instance type with generic instantiations applied and lifetimes erased.
     fn instance_ty(&self, instance: InstanceDef) -> Ty;
 
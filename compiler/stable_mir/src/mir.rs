@@ -1,5 +1,6 @@
 pub mod alloc;
 mod body;
+pub mod dataflow;
 pub mod mono;
 pub mod pretty;
 pub mod visit;
@@ -103,6 +103,54 @@ pub fn dump<W: io::Write>(&self, w: &mut W, fn_name: &str) -> io::Result<()> {
     pub fn spread_arg(&self) -> Option<Local> {
         self.spread_arg
     }
+
+    /// Add a new local of the given type to this body, and return it.
+    ///
+    /// The new local comes after all existing locals, so the existing locals keep their index.
+    pub fn new_local(&mut self, ty: Ty, span: Span, mutability: Mutability) -> Local {
+        self.locals.push(LocalDecl { ty, span, mutability });
+        self.locals.len() - 1
+    }
+
+    /// Add a new basic block to this body, and return its index.
+    ///
+    /// The new block comes after all existing blocks, so the existing blocks keep their index.
+    pub fn new_block(&mut self, block: BasicBlock) -> BasicBlockIdx {
+        self.blocks.push(block);
+        self.blocks.len() - 1
+    }
+
+    /// Insert a statement into a basic block before the statement at `index`.
+    ///
+    /// If `index` is the number of statements in the block, the statement is added right before
+    /// the block terminator.
+    pub fn insert_statement(&mut self, block: BasicBlockIdx, index: usize, statement: Statement) {
+        self.blocks[block].statements.insert(index, statement);
+    }
+
+    /// Replace the terminator of a basic block, and return the previous one.
+    pub fn replace_terminator(
+        &mut self,
+        block: BasicBlockIdx,
+        terminator: Terminator,
+    ) -> Terminator {
+        std::mem::replace(&mut self.blocks[block].terminator, terminator)
+    }
+
+    /// Split a basic block before the statement at `index`, and return the index of the new block.
+    ///
+    /// The statements starting at `index` and the terminator of `block` are moved to a new block,
+    /// and `block` jumps to the new block instead. This is useful to insert new control flow, such
+    /// as a check, in the middle of a block.
+    pub fn split_block(&mut self, block: BasicBlockIdx, index: usize) -> BasicBlockIdx {
+        let span = self.blocks[block].terminator.span;
+        let statements = self.blocks[block].statements.split_off(index);
+        let terminator = self.blocks[block].terminator.clone();
+        let new_block = self.new_block(BasicBlock { statements, terminator });
+        self.blocks[block].terminator =
+            Terminator { kind: TerminatorKind::Goto { target: new_block }, span };
+        new_block
+    }
 }
 
 type LocalDecls = Vec<LocalDecl>;
@@ -208,6 +256,34 @@ pub fn successors(&self) -> Successors {
         }
     }
 
+    /// Mutable references to the successors of this terminator, in the same order as
+    /// [`TerminatorKind::successors`], e.g. to redirect its edges to a new block.
+    pub fn successors_mut(&mut self) -> Vec<&mut BasicBlockIdx> {
+        use self::TerminatorKind::*;
+        let (target, unwind) = match self {
+            Goto { target } => (Some(target), None),
+            Call { target, unwind, .. } => (target.as_mut(), Some(unwind)),
+            Drop { target, unwind, .. } | Assert { target, unwind, .. } => {
+                (Some(target), Some(unwind))
+            }
+            InlineAsm { destination, unwind, .. } => (destination.as_mut(), Some(unwind)),
+            SwitchInt { targets, .. } => {
+                return targets
+                    .branches
+                    .iter_mut()
+                    .map(|(_, target)| target)
+                    .chain(Some(&mut targets.otherwise))
+                    .collect();
+            }
+            Return | Resume | Abort | Unreachable => (None, None),
+        };
+        let cleanup = match unwind {
+            Some(UnwindAction::Cleanup(cleanup)) => Some(cleanup),
+            _ => None,
+        };
+        target.into_iter().chain(cleanup).collect()
+    }
+
     pub fn unwind(&self) -> Option<&UnwindAction> {
         match *self {
             TerminatorKind::Goto { .. }
@@ -251,6 +327,10 @@ pub enum AssertMessage {
     ResumedAfterReturn(CoroutineKind),
     ResumedAfterPanic(CoroutineKind),
     MisalignedPointerDereference { required: Operand, found: Operand },
+    NullPointerDereference,
+    InvalidBool(Operand),
+    InvalidChar(Operand),
+    InvalidEnumDiscriminant(Operand),
 }
 
 impl AssertMessage {
@@ -306,6 +386,12 @@ pub fn description(&self) -> Result<&'static str, Error> {
             AssertMessage::MisalignedPointerDereference { .. } => {
                 Ok("misaligned pointer dereference")
             }
+            AssertMessage::NullPointerDereference => Ok("null pointer dereference occurred"),
+            AssertMessage::InvalidBool(_) => Ok("loaded an invalid `bool`"),
+            AssertMessage::InvalidChar(_) => Ok("loaded an invalid `char`"),
+            AssertMessage::InvalidEnumDiscriminant(_) => {
+                Ok("loaded an enum with an invalid discriminant")
+            }
         }
     }
 }
@@ -0,0 +1,417 @@
+//! A framework for dataflow analyses over stable MIR bodies.
+//!
+//! An [`Analysis`] describes a lattice of states and the effect of each statement and terminator
+//! on a state. [`Analysis::iterate_to_fixpoint`] computes the state at every location of a body,
+//! which can then be looked up in the returned [`Results`].
+//!
+//! Some of the analyses of the compiler are also available, for the bodies of instances:
+//! [`maybe_borrowed_locals`] and [`maybe_initialized_places`].
+
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Formatter};
+
+use serde::Serialize;
+
+use crate::mir::mono::Instance;
+use crate::mir::{BasicBlockIdx, Body, Place, Statement, Terminator, TerminatorKind};
+use crate::with;
+
+/// A lattice whose elements can be joined, i.e., that has a least upper bound for any two
+/// elements.
+pub trait JoinSemiLattice: Clone + Eq {
+    /// Computes the least upper bound of `self` and `other`, and stores it in `self`.
+    ///
+    /// Returns whether `self` changed.
+    fn join(&mut self, other: &Self) -> bool;
+}
+
+impl JoinSemiLattice for bool {
+    fn join(&mut self, other: &Self) -> bool {
+        let changed = !*self && *other;
+        *self |= *other;
+        changed
+    }
+}
+
+impl JoinSemiLattice for () {
+    fn join(&mut self, _other: &Self) -> bool {
+        false
+    }
+}
+
+/// The join of two sets is their union.
+impl JoinSemiLattice for BitSet {
+    fn join(&mut self, other: &Self) -> bool {
+        self.union(other)
+    }
+}
+
+/// A set of indices smaller than a fixed domain size, such as a set of locals.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct BitSet {
+    domain_size: usize,
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    /// Creates an empty set of indices smaller than `domain_size`.
+    pub fn new_empty(domain_size: usize) -> BitSet {
+        BitSet { domain_size, words: vec![0; domain_size.div_ceil(64)] }
+    }
+
+    /// Creates the set of all the indices smaller than `domain_size`.
+    pub fn new_filled(domain_size: usize) -> BitSet {
+        let mut set = BitSet::new_empty(domain_size);
+        for index in 0..domain_size {
+            set.insert(index);
+        }
+        set
+    }
+
+    pub fn domain_size(&self) -> usize {
+        self.domain_size
+    }
+
+    /// Inserts `index` in the set, and returns whether it was not in the set yet.
+    pub fn insert(&mut self, index: usize) -> bool {
+        assert!(index < self.domain_size, "{index} is out of the domain of the set");
+        let (word, mask) = (index / 64, 1 << (index % 64));
+        let inserted = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        inserted
+    }
+
+    /// Removes `index` from the set, and returns whether it was in the set.
+    pub fn remove(&mut self, index: usize) -> bool {
+        assert!(index < self.domain_size, "{index} is out of the domain of the set");
+        let (word, mask) = (index / 64, 1 << (index % 64));
+        let removed = self.words[word] & mask != 0;
+        self.words[word] &= !mask;
+        removed
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.domain_size && self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Returns the number of indices in the set.
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Iterates over the indices in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.domain_size).filter(|&index| self.contains(index))
+    }
+
+    /// Adds the indices of `other` to the set, and returns whether the set changed.
+    pub fn union(&mut self, other: &BitSet) -> bool {
+        assert_eq!(self.domain_size, other.domain_size);
+        let mut changed = false;
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            changed |= *other & !*word != 0;
+            *word |= *other;
+        }
+        changed
+    }
+
+    /// Removes the indices that are not in `other` from the set, and returns whether the set
+    /// changed.
+    pub fn intersect(&mut self, other: &BitSet) -> bool {
+        assert_eq!(self.domain_size, other.domain_size);
+        let mut changed = false;
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            changed |= *word & !*other != 0;
+            *word &= *other;
+        }
+        changed
+    }
+}
+
+impl Debug for BitSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// A program point of a body: the statement at `statement_index` in `block`, or the terminator
+/// of `block` if `statement_index` is the number of statements of the block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct Location {
+    pub block: BasicBlockIdx,
+    pub statement_index: usize,
+}
+
+/// The direction in which the states of an analysis flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// From the start of the body to its end.
+    Forward,
+    /// From the end of the body to its start.
+    Backward,
+}
+
+/// A dataflow analysis.
+///
+/// The effects of statements and terminators must be monotonic: applying them to a greater
+/// state must not result in a smaller state. Otherwise, [`Analysis::iterate_to_fixpoint`] may not
+/// terminate.
+pub trait Analysis {
+    /// The state of the analysis at a program point.
+    type Domain: JoinSemiLattice;
+
+    /// The direction of this analysis.
+    const DIRECTION: Direction = Direction::Forward;
+
+    /// Returns the state on entry to each basic block, before any state flowed into it.
+    fn bottom_value(&self, body: &Body) -> Self::Domain;
+
+    /// Mutates the state on entry to the start block of the body.
+    ///
+    /// This is only called for forward analyses.
+    fn initialize_start_block(&self, _body: &Body, _state: &mut Self::Domain) {}
+
+    /// Updates the state with the effect of a statement.
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut Self::Domain,
+        statement: &Statement,
+        location: Location,
+    );
+
+    /// Updates the state with the effect of a terminator.
+    ///
+    /// The effect of a successful return from a `Call` terminator should be applied by
+    /// [`Analysis::apply_call_return_effect`] instead, so that it does not affect the unwind edge
+    /// of the call.
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut Self::Domain,
+        terminator: &Terminator,
+        location: Location,
+    );
+
+    /// Updates the state with the effect of a successful return from the `Call` terminator of
+    /// `block`, which writes the returned value to `destination`.
+    fn apply_call_return_effect(
+        &mut self,
+        _state: &mut Self::Domain,
+        _block: BasicBlockIdx,
+        _destination: &Place,
+    ) {
+    }
+
+    /// Computes the state of the analysis at every location of `body`.
+    fn iterate_to_fixpoint(mut self, body: &Body) -> Results<Self::Domain>
+    where
+        Self: Sized,
+    {
+        let blocks = &body.blocks;
+        let mut entry_states = vec![self.bottom_value(body); blocks.len()];
+        if Self::DIRECTION == Direction::Forward && !blocks.is_empty() {
+            self.initialize_start_block(body, &mut entry_states[0]);
+        }
+        let mut predecessors = vec![vec![]; blocks.len()];
+        for (block, data) in blocks.iter().enumerate() {
+            for successor in data.terminator.successors() {
+                predecessors[successor].push(block);
+            }
+        }
+
+        let mut dirty: VecDeque<BasicBlockIdx> = (0..blocks.len()).collect();
+        let mut is_dirty = vec![true; blocks.len()];
+        while let Some(block) = dirty.pop_front() {
+            is_dirty[block] = false;
+            let mut state = entry_states[block].clone();
+            let mut propagate = |target: BasicBlockIdx, state: &Self::Domain| {
+                if entry_states[target].join(state) && !is_dirty[target] {
+                    is_dirty[target] = true;
+                    dirty.push_back(target);
+                }
+            };
+            match Self::DIRECTION {
+                Direction::Forward => {
+                    apply_block_effects(&mut self, body, block, &mut state, |_| {});
+                    let terminator = &blocks[block].terminator;
+                    for successor in terminator.successors() {
+                        match &terminator.kind {
+                            TerminatorKind::Call { destination, target: Some(target), .. }
+                                if *target == successor =>
+                            {
+                                let mut return_state = state.clone();
+                                self.apply_call_return_effect(
+                                    &mut return_state,
+                                    block,
+                                    destination,
+                                );
+                                propagate(successor, &return_state);
+                            }
+                            _ => propagate(successor, &state),
+                        }
+                    }
+                }
+                Direction::Backward => {
+                    apply_block_effects(&mut self, body, block, &mut state, |_| {});
+                    for &predecessor in &predecessors[block] {
+                        match &blocks[predecessor].terminator.kind {
+                            TerminatorKind::Call { destination, target: Some(target), .. }
+                                if *target == block =>
+                            {
+                                let mut return_state = state.clone();
+                                self.apply_call_return_effect(
+                                    &mut return_state,
+                                    predecessor,
+                                    destination,
+                                );
+                                propagate(predecessor, &return_state);
+                            }
+                            _ => propagate(predecessor, &state),
+                        }
+                    }
+                }
+            }
+        }
+
+        let blocks = entry_states
+            .into_iter()
+            .enumerate()
+            .map(|(block, mut state)| {
+                // The states before each effect in dataflow order, and after the last effect.
+                let mut states = vec![];
+                apply_block_effects(&mut self, body, block, &mut state, |state| {
+                    states.push(state.clone())
+                });
+                states.push(state);
+                if Self::DIRECTION == Direction::Backward {
+                    states.reverse();
+                }
+                let after = states[1..].to_vec();
+                states.pop();
+                BlockStates { before: states, after }
+            })
+            .collect();
+        Results { blocks }
+    }
+}
+
+/// Applies the effects of the statements and the terminator of `block` to `state`, in the
+/// direction of the analysis, and calls `report` with the state before each effect.
+fn apply_block_effects<A: Analysis>(
+    analysis: &mut A,
+    body: &Body,
+    block: BasicBlockIdx,
+    state: &mut A::Domain,
+    mut report: impl FnMut(&A::Domain),
+) {
+    let data = &body.blocks[block];
+    let terminator_location = Location { block, statement_index: data.statements.len() };
+    match A::DIRECTION {
+        Direction::Forward => {
+            for (statement_index, statement) in data.statements.iter().enumerate() {
+                report(state);
+                analysis.apply_statement_effect(
+                    state,
+                    statement,
+                    Location { block, statement_index },
+                );
+            }
+            report(state);
+            analysis.apply_terminator_effect(state, &data.terminator, terminator_location);
+        }
+        Direction::Backward => {
+            report(state);
+            analysis.apply_terminator_effect(state, &data.terminator, terminator_location);
+            for (statement_index, statement) in data.statements.iter().enumerate().rev() {
+                report(state);
+                analysis.apply_statement_effect(
+                    state,
+                    statement,
+                    Location { block, statement_index },
+                );
+            }
+        }
+    }
+}
+
+/// The states of an analysis at every location of a body.
+///
+/// The states are in program order, whatever the direction of the analysis: the state before a
+/// statement is the state when control reaches the statement, and the state after a statement
+/// is the state when the statement completed. The state after a terminator does not include
+/// the effect of a successful return from a call.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Results<D> {
+    /// The states of each basic block of the body.
+    pub blocks: Vec<BlockStates<D>>,
+}
+
+/// The states of an analysis at every location of a basic block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BlockStates<D> {
+    /// The state before each statement, and before the terminator.
+    pub before: Vec<D>,
+    /// The state after each statement, and after the terminator.
+    pub after: Vec<D>,
+}
+
+impl<D> Results<D> {
+    /// The state before the statement or terminator at `location` is executed.
+    pub fn state_before(&self, location: Location) -> &D {
+        &self.blocks[location.block].before[location.statement_index]
+    }
+
+    /// The state after the statement or terminator at `location` is executed.
+    pub fn state_after(&self, location: Location) -> &D {
+        &self.blocks[location.block].after[location.statement_index]
+    }
+
+    /// The state on entry to `block`.
+    pub fn block_start(&self, block: BasicBlockIdx) -> &D {
+        &self.blocks[block].before[0]
+    }
+
+    /// The state after the terminator of `block` is executed.
+    pub fn block_end(&self, block: BasicBlockIdx) -> &D {
+        self.blocks[block].after.last().unwrap()
+    }
+}
+
+/// Computes the locals that may be borrowed at each location of the body of `instance`, using the
+/// analysis of the compiler.
+///
+/// A local becomes borrowed when it is borrowed, its address is taken or it is dropped, and only
+/// stops being borrowed at its `StorageDead`, since a borrow may live as long as the local. Moving
+/// out of a local does not end its borrows.
+///
+/// # Panics
+///
+/// This function panics if the instance has no body.
+pub fn maybe_borrowed_locals(instance: &Instance) -> Results<BitSet> {
+    with(|cx| cx.maybe_borrowed_locals(instance.def))
+}
+
+/// The places that may be initialized at each location of a body.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MaybeInitializedPlaces {
+    /// The places whose initialization is tracked, which are the indices of the sets of
+    /// `results`.
+    pub move_paths: Vec<Place>,
+    pub results: Results<BitSet>,
+}
+
+/// Computes the places that may be initialized at each location of the body of `instance`,
+/// using the analysis of the compiler.
+///
+/// The places that are tracked are the locals of the body and the fields of their values that
+/// are moved out of separately. The sets of unreachable locations are empty.
+///
+/// # Panics
+///
+/// This function panics if the instance has no body.
+pub fn maybe_initialized_places(instance: &Instance) -> MaybeInitializedPlaces {
+    with(|cx| cx.maybe_initialized_places(instance.def))
+}
@@ -296,7 +296,15 @@ fn pretty_assert_message<W: Write>(writer: &mut W, msg: &AssertMessage) -> io::R
                 "\"misaligned pointer dereference: address must be a multiple of {{}} but is {{}}\",{pretty_required}, {pretty_found}"
             )
         }
-        AssertMessage::ResumedAfterReturn(_) | AssertMessage::ResumedAfterPanic(_) => {
+        AssertMessage::InvalidBool(found)
+        | AssertMessage::InvalidChar(found)
+        | AssertMessage::InvalidEnumDiscriminant(found) => {
+            let pretty_found = pretty_operand(found);
+            write!(writer, "\"{}: {{:#x}}\", {pretty_found}", msg.description().unwrap())
+        }
+        AssertMessage::ResumedAfterReturn(_)
+        | AssertMessage::ResumedAfterPanic(_)
+        | AssertMessage::NullPointerDereference => {
             write!(writer, "{}", msg.description().unwrap())
         }
     }
@@ -423,10 +423,15 @@ fn super_assert_msg(&mut self, msg: &AssertMessage, location: Location) {
             }
             AssertMessage::OverflowNeg(op)
             | AssertMessage::DivisionByZero(op)
-            | AssertMessage::RemainderByZero(op) => {
+            | AssertMessage::RemainderByZero(op)
+            | AssertMessage::InvalidBool(op)
+            | AssertMessage::InvalidChar(op)
+            | AssertMessage::InvalidEnumDiscriminant(op) => {
                 self.visit_operand(op, location);
             }
-            AssertMessage::ResumedAfterReturn(_) | AssertMessage::ResumedAfterPanic(_) => { //nothing to visit
+            AssertMessage::ResumedAfterReturn(_)
+            | AssertMessage::ResumedAfterPanic(_)
+            | AssertMessage::NullPointerDereference => { //nothing to visit
             }
             AssertMessage::MisalignedPointerDereference { required, found } => {
                 self.visit_operand(required, location);
@@ -254,7 +254,7 @@ pub struct Placeholder<T> {
     pub bound: T,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub struct Span(usize);
 
 impl Debug for Span {
@@ -530,6 +530,7 @@ pub fn fn_def(&self) -> Option<(FnDef, &GenericArgs)> {
     }
 }
 
+#[derive(Serialize)]
 pub struct TypeAndMut {
     pub ty: Ty,
     pub mutability: Mutability,
@@ -646,6 +647,7 @@ pub fn module(&self) -> ForeignModule {
     }
 }
 
+#[derive(Serialize)]
 pub struct ForeignModule {
     pub def_id: ForeignModuleDef,
     pub abi: Abi,
@@ -1430,6 +1432,7 @@ pub struct GenericParamDef {
     pub kind: GenericParamDefKind,
 }
 
+#[derive(Serialize)]
 pub struct GenericPredicates {
     pub parent: Option<TraitDef>,
     pub predicates: Vec<(PredicateKind, Span)>,
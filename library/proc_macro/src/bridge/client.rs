@@ -412,4 +412,27 @@ pub const fn bang(
     ) -> Self {
         ProcMacro::Bang { name, client: Client::expand1(expand) }
     }
+
+    /// Runs the macro on behalf of a server in another process.
+    ///
+    /// `input` is the buffer the server encoded for the macro, and `dispatch` forwards the
+    /// requests of the macro to the server and returns its replies.
+    pub fn run_remote(
+        &self,
+        input: Buffer,
+        mut dispatch: impl FnMut(Buffer) -> Buffer,
+        force_show_panics: bool,
+    ) -> Buffer {
+        let run = match self {
+            ProcMacro::CustomDerive { client, .. } => client.run,
+            ProcMacro::Attr { client, .. } => client.run,
+            ProcMacro::Bang { client, .. } => client.run,
+        };
+        run(BridgeConfig {
+            input,
+            dispatch: (&mut dispatch).into(),
+            force_show_panics,
+            _marker: marker::PhantomData,
+        })
+    }
 }
@@ -348,6 +348,22 @@ fn run_bridge_and_client(
     }
 }
 
+/// Creates the output of a client that failed without reporting a result, e.g. because it ran
+/// in another process that crashed. The server reports it like a panic of the client.
+pub fn client_failure(message: String) -> Buffer {
+    let mut buf = Buffer::new();
+    Err::<(), PanicMessage>(PanicMessage::String(message)).encode(&mut buf, &mut ());
+    buf
+}
+
+/// Creates the reply to a request of a client that the server cannot answer, e.g. because it
+/// runs in another process than the client and stopped responding. The client panics with
+/// `message` when it receives the reply.
+pub fn dispatch_failure(message: String) -> Buffer {
+    // Replies are encoded like results, so that of any request that failed is an error.
+    client_failure(message)
+}
+
 /// A message pipe used for communicating between server and client threads.
 pub trait MessagePipe<T>: Sized {
     /// Creates a new pair of endpoints for the message pipe.
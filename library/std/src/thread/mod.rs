@@ -884,6 +884,7 @@ pub fn sleep_ms(ms: u32) {
 /// assert!(now.elapsed() >= ten_millis);
 /// ```
 #[stable(feature = "thread_sleep", since = "1.4.0")]
+#[cfg_attr(not(test), rustc_diagnostic_item = "thread_sleep")]
 pub fn sleep(dur: Duration) {
     imp::Thread::sleep(dur)
 }
@@ -509,6 +509,7 @@ impl SystemTime {
     /// ```
     #[must_use]
     #[stable(feature = "time2", since = "1.8.0")]
+    #[cfg_attr(not(test), rustc_diagnostic_item = "system_time_now")]
     pub fn now() -> SystemTime {
         SystemTime(time::SystemTime::now())
     }
@@ -335,6 +335,7 @@ fn run(self, builder: &Builder<'_>) -> Self::Output {
     CollectLicenseMetadata, "src/tools/collect-license-metadata", "collect-license-metadata";
     Compiletest, "src/tools/compiletest", "compiletest";
     CoverageDump, "src/tools/coverage-dump", "coverage-dump";
+    CoverageReport, "src/tools/coverage-report", "coverage-report";
     Jsondocck, "src/tools/jsondocck", "jsondocck";
     Jsondoclint, "src/tools/jsondoclint", "jsondoclint";
     LintDocs, "src/tools/lint-docs", "lint-docs";
@@ -859,6 +859,7 @@ macro_rules! describe {
                 tool::RustdocGUITest,
                 tool::OptimizedDist,
                 tool::CoverageDump,
+                tool::CoverageReport,
                 tool::LlvmBitcodeLinker,
                 tool::RustcPerf,
             ),
@@ -873,6 +874,7 @@ macro_rules! describe {
                 clippy::CollectLicenseMetadata,
                 clippy::Compiletest,
                 clippy::CoverageDump,
+                clippy::CoverageReport,
                 clippy::Jsondocck,
                 clippy::Jsondoclint,
                 clippy::LintDocs,
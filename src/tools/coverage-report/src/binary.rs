@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use anyhow::{Context, ensure};
+use object::{Object, ObjectSection};
+
+/// The raw contents of the coverage-related sections of an instrumented
+/// binary.
+pub(crate) struct CoverageSections {
+    pub(crate) covmap: Vec<u8>,
+    pub(crate) covfun: Vec<u8>,
+    pub(crate) prf_names: Vec<u8>,
+    pub(crate) big_endian: bool,
+}
+
+// Section names differ across object file formats. In linked PE images, the
+// `$M` suffix of the COFF section names has been dropped by the linker.
+const COVMAP_SECTIONS: &[&str] = &["__llvm_covmap", ".lcovmap$M", ".lcovmap"];
+const COVFUN_SECTIONS: &[&str] = &["__llvm_covfun", ".lcovfun$M", ".lcovfun"];
+const PRF_NAMES_SECTIONS: &[&str] = &["__llvm_prf_names", ".lprfn$M", ".lprfn"];
+
+pub(crate) fn read_coverage_sections(path: &Path) -> anyhow::Result<CoverageSections> {
+    let data = std::fs::read(path).context("couldn't read binary")?;
+    let file = object::File::parse(&*data).context("couldn't parse binary")?;
+
+    let sections_named = |names: &[&str]| -> anyhow::Result<Vec<u8>> {
+        let mut contents = vec![];
+        for section in file.sections() {
+            if section.name().is_ok_and(|name| names.contains(&name)) {
+                // Records are 8-byte aligned within each section, so keep
+                // them aligned when concatenating several sections.
+                contents.resize(contents.len().next_multiple_of(8), 0);
+                contents.extend_from_slice(section.data()?);
+            }
+        }
+        Ok(contents)
+    };
+
+    let covmap = sections_named(COVMAP_SECTIONS)?;
+    let covfun = sections_named(COVFUN_SECTIONS)?;
+    let prf_names = sections_named(PRF_NAMES_SECTIONS)?;
+    ensure!(
+        !covmap.is_empty() && !covfun.is_empty(),
+        "no coverage mappings found; was the binary built with `-Cinstrument-coverage`?"
+    );
+
+    Ok(CoverageSections { covmap, covfun, prf_names, big_endian: !file.is_little_endian() })
+}
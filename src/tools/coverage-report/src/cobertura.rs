@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::path::Path;
+
+use crate::report::{BranchCounts, FileReport, Report, branch_totals, line_totals};
+
+/// Writes the report in Cobertura XML format. Each source file is a class,
+/// in a package named after its directory; file paths are made relative to
+/// `source_root` when they are inside it.
+pub(crate) fn write_cobertura(
+    report: &Report,
+    source_root: &Path,
+    timestamp: u64,
+    output: &mut String,
+) -> fmt::Result {
+    // Group the files into packages by their directory.
+    let mut packages = BTreeMap::<String, Vec<(String, &FileReport)>>::new();
+    for (path, file) in &report.files {
+        let path = path.strip_prefix(source_root).unwrap_or(path);
+        let package = match path.parent() {
+            Some(parent) if parent != Path::new("") => parent.display().to_string(),
+            _ => ".".to_owned(),
+        };
+        packages.entry(package).or_default().push((path.display().to_string(), file));
+    }
+
+    let (lines_covered, lines_valid) = report
+        .files
+        .values()
+        .fold((0, 0), |totals, file| add_totals(totals, line_totals(&file.lines)));
+    let (branches_covered, branches_valid) =
+        branch_totals(report.files.values().flat_map(|file| file.branches.values()));
+
+    writeln!(output, r#"<?xml version="1.0" ?>"#)?;
+    writeln!(
+        output,
+        r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )?;
+    writeln!(
+        output,
+        r#"<coverage lines-valid="{lines_valid}" lines-covered="{lines_covered}" line-rate="{}" branches-valid="{branches_valid}" branches-covered="{branches_covered}" branch-rate="{}" complexity="0" version="{}" timestamp="{timestamp}">"#,
+        rate(lines_covered, lines_valid),
+        rate(branches_covered, branches_valid),
+        env!("CARGO_PKG_VERSION"),
+    )?;
+    writeln!(output, "  <sources>")?;
+    writeln!(output, "    <source>{}</source>", escape(&source_root.display().to_string()))?;
+    writeln!(output, "  </sources>")?;
+    writeln!(output, "  <packages>")?;
+    for (package, files) in &packages {
+        let (lines_covered, lines_valid) = files
+            .iter()
+            .fold((0, 0), |totals, (_, file)| add_totals(totals, line_totals(&file.lines)));
+        let (branches_covered, branches_valid) = files.iter().fold((0, 0), |totals, (_, file)| {
+            add_totals(totals, branch_totals(file.branches.values()))
+        });
+        writeln!(
+            output,
+            r#"    <package name="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+            escape(package),
+            rate(lines_covered, lines_valid),
+            rate(branches_covered, branches_valid),
+        )?;
+        writeln!(output, "      <classes>")?;
+        for (filename, file) in files {
+            let name = Path::new(filename).file_name().unwrap_or_default().to_string_lossy();
+            writeln!(
+                output,
+                r#"        <class name="{}" filename="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+                escape(&name),
+                escape(filename),
+                line_rate(&file.lines),
+                branch_rate(&file.branches),
+            )?;
+            writeln!(output, "          <methods>")?;
+            for ((_, name), function) in &file.functions {
+                writeln!(
+                    output,
+                    r#"            <method name="{}" signature="" line-rate="{}" branch-rate="{}" complexity="0">"#,
+                    escape(name),
+                    line_rate(&function.lines),
+                    branch_rate(&function.branches),
+                )?;
+                write_lines(output, "              ", &function.lines, &function.branches)?;
+                writeln!(output, "            </method>")?;
+            }
+            writeln!(output, "          </methods>")?;
+            write_lines(output, "          ", &file.lines, &file.branches)?;
+            writeln!(output, "        </class>")?;
+        }
+        writeln!(output, "      </classes>")?;
+        writeln!(output, "    </package>")?;
+    }
+    writeln!(output, "  </packages>")?;
+    writeln!(output, "</coverage>")?;
+    Ok(())
+}
+
+fn write_lines(
+    output: &mut String,
+    indent: &str,
+    lines: &BTreeMap<u32, u64>,
+    branches: &BTreeMap<(u32, u32), BranchCounts>,
+) -> fmt::Result {
+    writeln!(output, "{indent}<lines>")?;
+    for (&line, count) in lines {
+        let (taken, total) =
+            branch_totals(branches.range((line, 0)..=(line, u32::MAX)).map(|(_, b)| b));
+        match (taken * 100).checked_div(total) {
+            None => writeln!(
+                output,
+                r#"{indent}  <line number="{line}" hits="{count}" branch="false"/>"#
+            )?,
+            Some(percent) => writeln!(
+                output,
+                r#"{indent}  <line number="{line}" hits="{count}" branch="true" condition-coverage="{percent}% ({taken}/{total})"/>"#,
+            )?,
+        }
+    }
+    writeln!(output, "{indent}</lines>")?;
+    Ok(())
+}
+
+fn line_rate(lines: &BTreeMap<u32, u64>) -> String {
+    let (covered, valid) = line_totals(lines);
+    rate(covered, valid)
+}
+
+fn branch_rate(branches: &BTreeMap<(u32, u32), BranchCounts>) -> String {
+    let (covered, valid) = branch_totals(branches.values());
+    rate(covered, valid)
+}
+
+fn add_totals(
+    (covered, valid): (usize, usize),
+    (more_covered, more_valid): (usize, usize),
+) -> (usize, usize) {
+    (covered + more_covered, valid + more_valid)
+}
+
+/// Formats the fraction of covered items, which is 1 if there are none.
+fn rate(covered: usize, valid: usize) -> String {
+    if valid == 0 { "1".to_owned() } else { (covered as f64 / valid as f64).to_string() }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
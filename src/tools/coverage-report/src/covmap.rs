@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, anyhow, bail, ensure};
+
+use crate::parser::{Parser, truncated_md5};
+
+/// The coverage mappings of one function, as stored in a covfun record, with
+/// its file IDs and line numbers resolved.
+#[derive(Clone)]
+pub(crate) struct FunctionMappings {
+    /// Hash of the function's mangled name.
+    pub(crate) name_hash: u64,
+    /// Hash of the function's source, which must match the hash recorded
+    /// with its counters in a profile for them to be used. This is 0 for
+    /// functions that were never codegenned.
+    pub(crate) func_hash: u64,
+    /// The file of each of the function's local file IDs.
+    pub(crate) files: Vec<PathBuf>,
+    /// The operands of each coverage-counter expression.
+    pub(crate) expressions: Vec<(CovTerm, CovTerm)>,
+    pub(crate) regions: Vec<Region>,
+}
+
+/// Enum that can hold a constant zero value, the ID of an physical coverage
+/// counter, or the ID (and operation) of a coverage-counter expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CovTerm {
+    Zero,
+    Counter(u32),
+    Expression(u32, Op),
+}
+
+/// Operator (addition or subtraction) used by an expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Op {
+    Sub,
+    Add,
+}
+
+impl CovTerm {
+    fn decode(input: u32) -> Option<Self> {
+        let (high, tag) = (input >> 2, input & 0b11);
+        match tag {
+            0b00 if high == 0 => Some(Self::Zero),
+            0b01 => Some(Self::Counter(high)),
+            0b10 => Some(Self::Expression(high, Op::Sub)),
+            0b11 => Some(Self::Expression(high, Op::Add)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RegionKind {
+    Code(CovTerm),
+    Gap(CovTerm),
+    Branch { true_term: CovTerm, false_term: CovTerm },
+}
+
+/// A mapping region, with absolute 1-based line and column numbers. The end
+/// column is exclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Region {
+    /// Index into the function's `files`.
+    pub(crate) file: usize,
+    pub(crate) kind: RegionKind,
+    pub(crate) start_line: u32,
+    pub(crate) start_column: u32,
+    pub(crate) end_line: u32,
+    pub(crate) end_column: u32,
+}
+
+/// Decodes the contents of the covmap and covfun sections of a binary into
+/// the mappings of each function it contains.
+pub(crate) fn read_function_mappings(
+    covmap: &[u8],
+    covfun: &[u8],
+    big_endian: bool,
+) -> anyhow::Result<Vec<FunctionMappings>> {
+    let filename_tables = read_filename_tables(covmap, big_endian).context("reading covmap")?;
+
+    let mut functions = vec![];
+    let mut parser = Parser::new(covfun, big_endian);
+    while !parser.only_padding_left() {
+        // A covfun record consists of four target-endian integers (packed
+        // together), followed by the encoded mapping data.
+        let name_hash = parser.read_u64()?;
+        let data_len = parser.read_u32()? as usize;
+        let func_hash = parser.read_u64()?;
+        let filenames_hash = parser.read_u64()?;
+        let data = parser.read_n_bytes(data_len)?;
+        parser.align_to(8)?;
+
+        let filenames = filename_tables
+            .get(&filenames_hash)
+            .ok_or_else(|| anyhow!("no filenames table with hash {filenames_hash:#x}"))?;
+        let function = read_mappings(data, filenames, name_hash, func_hash)
+            .with_context(|| format!("reading covfun record of function {name_hash:#x}"))?;
+        functions.push(function);
+    }
+    Ok(functions)
+}
+
+/// Decodes the filenames table of each covmap record, keyed by the hash that
+/// covfun records use to refer to it. Relative filenames are resolved against
+/// the working directory, which is always the first entry.
+fn read_filename_tables(
+    covmap: &[u8],
+    big_endian: bool,
+) -> anyhow::Result<HashMap<u64, Vec<PathBuf>>> {
+    let mut tables = HashMap::new();
+    let mut parser = Parser::new(covmap, big_endian);
+    while !parser.only_padding_left() {
+        let _num_records = parser.read_u32()?;
+        let filenames_len = parser.read_u32()? as usize;
+        let coverage_len = parser.read_u32()? as usize;
+        // The version number is zero-based, so 5 means "version 6", which is
+        // the first one to store the working directory as the first filename.
+        let version = parser.read_u32()?;
+        ensure!(version >= 5, "unsupported coverage mapping format version {}", version + 1);
+
+        let filenames_bytes = parser.read_n_bytes(filenames_len)?;
+        parser.read_n_bytes(coverage_len)?;
+        parser.align_to(8)?;
+
+        let mut filenames_parser = Parser::new(filenames_bytes, big_endian);
+        let num_filenames = filenames_parser.read_uleb128_usize()?;
+        let encoded = filenames_parser.read_maybe_compressed_bytes()?;
+        filenames_parser.ensure_empty()?;
+
+        let mut encoded_parser = Parser::new(&encoded, big_endian);
+        let mut filenames = Vec::with_capacity(num_filenames);
+        for _ in 0..num_filenames {
+            let len = encoded_parser.read_uleb128_usize()?;
+            let filename = String::from_utf8_lossy(encoded_parser.read_n_bytes(len)?);
+            filenames.push(PathBuf::from(&*filename));
+        }
+        encoded_parser.ensure_empty()?;
+
+        if let Some((working_dir, rest)) = filenames.split_first_mut() {
+            for filename in rest {
+                if filename.is_relative() {
+                    *filename = working_dir.join(&*filename);
+                }
+            }
+        }
+        tables.insert(truncated_md5(filenames_bytes), filenames);
+    }
+    Ok(tables)
+}
+
+fn read_mappings(
+    data: &[u8],
+    filenames: &[PathBuf],
+    name_hash: u64,
+    func_hash: u64,
+) -> anyhow::Result<FunctionMappings> {
+    // The mapping data doesn't contain any fixed-size integers.
+    let mut parser = Parser::new(data, false);
+
+    let num_files = parser.read_uleb128_usize()?;
+    let mut files = Vec::with_capacity(num_files);
+    for _ in 0..num_files {
+        let global_file_id = parser.read_uleb128_usize()?;
+        let filename = filenames
+            .get(global_file_id)
+            .ok_or_else(|| anyhow!("global file ID {global_file_id} is out of bounds"))?;
+        files.push(filename.clone());
+    }
+
+    let num_expressions = parser.read_uleb128_usize()?;
+    let mut expressions = Vec::with_capacity(num_expressions);
+    for _ in 0..num_expressions {
+        let lhs = parser.read_simple_term()?;
+        let rhs = parser.read_simple_term()?;
+        expressions.push((lhs, rhs));
+    }
+
+    let mut regions = vec![];
+    for file in 0..num_files {
+        let num_mappings = parser.read_uleb128_u32()?;
+        // Start lines are stored relative to the start line of the previous
+        // mapping of the same file.
+        let mut prev_start_line = 0;
+        for _ in 0..num_mappings {
+            let kind = parser.read_mapping_kind()?;
+            let start_line = prev_start_line + parser.read_uleb128_u32()?;
+            let start_column = parser.read_uleb128_u32()?;
+            let end_line = start_line + parser.read_uleb128_u32()?;
+            let mut end_column = parser.read_uleb128_u32()?;
+            prev_start_line = start_line;
+
+            let kind = match kind {
+                // The highest bit of the end column turns a code region into
+                // a gap region.
+                Some(RegionKind::Code(term)) if end_column & (1 << 31) != 0 => {
+                    end_column &= !(1 << 31);
+                    RegionKind::Gap(term)
+                }
+                Some(kind) => kind,
+                // Skipped regions, expansion regions and MC/DC decision regions
+                // don't have counts of their own.
+                None => continue,
+            };
+            regions.push(Region { file, kind, start_line, start_column, end_line, end_column });
+        }
+    }
+    parser.ensure_empty()?;
+
+    let check_term = |term: CovTerm| match term {
+        CovTerm::Expression(id, _) if id as usize >= expressions.len() => {
+            Err(anyhow!("expression ID {id} is out of bounds"))
+        }
+        _ => Ok(()),
+    };
+    for &(lhs, rhs) in &expressions {
+        check_term(lhs)?;
+        check_term(rhs)?;
+    }
+    for region in &regions {
+        match region.kind {
+            RegionKind::Code(term) | RegionKind::Gap(term) => check_term(term)?,
+            RegionKind::Branch { true_term, false_term } => {
+                check_term(true_term)?;
+                check_term(false_term)?;
+            }
+        }
+    }
+
+    Ok(FunctionMappings { name_hash, func_hash, files, expressions, regions })
+}
+
+// Extra parser methods only needed when parsing covfun payloads.
+impl<'a> Parser<'a> {
+    fn read_simple_term(&mut self) -> anyhow::Result<CovTerm> {
+        let raw_term = self.read_uleb128_u32()?;
+        CovTerm::decode(raw_term).context("decoding term")
+    }
+
+    /// Reads the kind of a mapping, along with any extra data that follows
+    /// it, and returns `None` for the kinds that don't have counts.
+    fn read_mapping_kind(&mut self) -> anyhow::Result<Option<RegionKind>> {
+        let raw_mapping_kind = self.read_uleb128_u32()?;
+        if let Some(term) = CovTerm::decode(raw_mapping_kind) {
+            return Ok(Some(RegionKind::Code(term)));
+        }
+
+        ensure!(raw_mapping_kind & 0b11 == 0, "unknown mapping kind: {raw_mapping_kind:#x}");
+        let (high, is_expansion) = (raw_mapping_kind >> 3, raw_mapping_kind & 0b100 != 0);
+        if is_expansion {
+            return Ok(None);
+        }
+        match high {
+            // Skipped region.
+            2 => Ok(None),
+            // Branch region.
+            4 => {
+                let true_term = self.read_simple_term()?;
+                let false_term = self.read_simple_term()?;
+                Ok(Some(RegionKind::Branch { true_term, false_term }))
+            }
+            // MC/DC decision region.
+            5 => {
+                let _bitmap_idx = self.read_uleb128_u32()?;
+                let _conditions_num = self.read_uleb128_u32()?;
+                Ok(None)
+            }
+            // MC/DC branch region, which is reported like a plain branch.
+            6 => {
+                let true_term = self.read_simple_term()?;
+                let false_term = self.read_simple_term()?;
+                let _condition_id = self.read_uleb128_u32()?;
+                let _true_next_id = self.read_uleb128_u32()?;
+                let _false_next_id = self.read_uleb128_u32()?;
+                Ok(Some(RegionKind::Branch { true_term, false_term }))
+            }
+            _ => bail!("unknown mapping kind: {raw_mapping_kind:#x}"),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt::{self, Write as _};
+
+use crate::report::{Report, branch_totals, line_totals};
+
+/// Writes the report as an lcov tracefile, with one record per source file.
+pub(crate) fn write_lcov(report: &Report, output: &mut String) -> fmt::Result {
+    for (path, file) in &report.files {
+        writeln!(output, "SF:{}", path.display())?;
+
+        for (line, name) in file.functions.keys() {
+            writeln!(output, "FN:{line},{name}")?;
+        }
+        for ((_, name), function) in &file.functions {
+            writeln!(output, "FNDA:{},{name}", function.count)?;
+        }
+        let functions_hit = file.functions.values().filter(|function| function.count > 0).count();
+        writeln!(output, "FNF:{}", file.functions.len())?;
+        writeln!(output, "FNH:{functions_hit}")?;
+
+        // Each branch region is a block with two branches, for its true and
+        // false arms. Blocks are numbered from 0 on each line.
+        let mut prev_line = None;
+        let mut block = 0;
+        for (&(line, _), counts) in &file.branches {
+            block = if prev_line == Some(line) { block + 1 } else { 0 };
+            prev_line = Some(line);
+
+            // Arms of conditions that were never evaluated are reported as
+            // `-` rather than 0.
+            let never_evaluated = counts.true_count == 0 && counts.false_count == 0;
+            for (branch, count) in [counts.true_count, counts.false_count].into_iter().enumerate() {
+                if never_evaluated {
+                    writeln!(output, "BRDA:{line},{block},{branch},-")?;
+                } else {
+                    writeln!(output, "BRDA:{line},{block},{branch},{count}")?;
+                }
+            }
+        }
+        let (branches_hit, branches_found) = branch_totals(file.branches.values());
+        writeln!(output, "BRF:{branches_found}")?;
+        writeln!(output, "BRH:{branches_hit}")?;
+
+        for (line, count) in &file.lines {
+            writeln!(output, "DA:{line},{count}")?;
+        }
+        let (lines_hit, lines_found) = line_totals(&file.lines);
+        writeln!(output, "LF:{lines_found}")?;
+        writeln!(output, "LH:{lines_hit}")?;
+
+        writeln!(output, "end_of_record")?;
+    }
+    Ok(())
+}
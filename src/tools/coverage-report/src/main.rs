@@ -0,0 +1,111 @@
+mod binary;
+mod cobertura;
+mod covmap;
+mod lcov;
+mod parser;
+mod prf_names;
+mod profraw;
+mod report;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, bail};
+
+use crate::profraw::Profile;
+use crate::report::Report;
+
+const USAGE: &str = "usage: coverage-report [--format lcov|cobertura] [--output PATH] \
+    [--source-root DIR] --object BINARY [--object BINARY ...] PROFRAW ...";
+
+enum Format {
+    Lcov,
+    Cobertura,
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut format = Format::Lcov;
+    let mut output_path = None;
+    let mut source_root = None;
+    let mut objects = vec![];
+    let mut profraws = vec![];
+
+    let mut args = std::env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next().with_context(|| format!("missing value for `{flag}`\n{USAGE}"))
+        };
+        match arg.to_str() {
+            Some("--format") => {
+                format = match value("--format")?.to_str() {
+                    Some("lcov") => Format::Lcov,
+                    Some("cobertura") => Format::Cobertura,
+                    _ => bail!("unknown report format; expected `lcov` or `cobertura`"),
+                }
+            }
+            Some("--output") => output_path = Some(PathBuf::from(value("--output")?)),
+            Some("--source-root") => source_root = Some(PathBuf::from(value("--source-root")?)),
+            Some("--object") => objects.push(PathBuf::from(value("--object")?)),
+            Some("-h" | "--help") => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => profraws.push(PathBuf::from(arg)),
+        }
+    }
+    if objects.is_empty() || profraws.is_empty() {
+        bail!("{USAGE}");
+    }
+
+    let mut functions = vec![];
+    let mut function_names = HashMap::new();
+    for object in &objects {
+        let mut read_object = || -> anyhow::Result<()> {
+            let sections = binary::read_coverage_sections(object)?;
+            prf_names::read_function_names(&sections.prf_names, &mut function_names)?;
+            functions.extend(covmap::read_function_mappings(
+                &sections.covmap,
+                &sections.covfun,
+                sections.big_endian,
+            )?);
+            Ok(())
+        };
+        read_object().with_context(|| format!("reading `{}`", object.display()))?;
+    }
+
+    let mut profile = Profile::default();
+    for profraw in &profraws {
+        let data = std::fs::read(profraw).context("couldn't read raw profile")?;
+        profile.merge_profraw(&data).with_context(|| format!("reading `{}`", profraw.display()))?;
+    }
+
+    let report = Report::new(&functions, &profile, &function_names);
+    let mut output = String::new();
+    match format {
+        Format::Lcov => lcov::write_lcov(&report, &mut output)?,
+        Format::Cobertura => {
+            let source_root = match source_root {
+                Some(source_root) => source_root,
+                None => std::env::current_dir()?,
+            };
+            cobertura::write_cobertura(&report, &source_root, timestamp()?, &mut output)?
+        }
+    }
+
+    match output_path {
+        Some(path) => std::fs::write(&path, output)
+            .with_context(|| format!("couldn't write `{}`", path.display()))?,
+        None => print!("{output}"),
+    }
+    Ok(())
+}
+
+/// Seconds since the Unix epoch, or the value of `SOURCE_DATE_EPOCH` if it is
+/// set, so that reports can be reproducible.
+fn timestamp() -> anyhow::Result<u64> {
+    if let Ok(source_date_epoch) = std::env::var("SOURCE_DATE_EPOCH") {
+        return source_date_epoch.parse().context("invalid `SOURCE_DATE_EPOCH`");
+    }
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
@@ -0,0 +1,110 @@
+use anyhow::{Context, anyhow, ensure};
+
+pub(crate) struct Parser<'a> {
+    input: &'a [u8],
+    rest: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Creates a parser for `input`, whose fixed-size integers have the given
+    /// endianness. (LEB128 values and byte strings don't depend on it.)
+    pub(crate) fn new(input: &'a [u8], big_endian: bool) -> Self {
+        Self { input, rest: input, big_endian }
+    }
+
+    pub(crate) fn ensure_empty(self) -> anyhow::Result<()> {
+        ensure!(self.rest.is_empty(), "unparsed bytes: 0x{:02x?}", self.rest);
+        Ok(())
+    }
+
+    /// Returns true if nothing but zero bytes remains, which is how sections
+    /// and files are padded after their last record.
+    pub(crate) fn only_padding_left(&self) -> bool {
+        self.rest.iter().all(|&b| b == 0)
+    }
+
+    /// Skips the padding bytes that align the next record to `align` bytes,
+    /// relative to the start of the input.
+    pub(crate) fn align_to(&mut self, align: usize) -> anyhow::Result<()> {
+        let position = self.input.len() - self.rest.len();
+        let padding = position.next_multiple_of(align) - position;
+        self.read_n_bytes(padding.min(self.rest.len()))?;
+        Ok(())
+    }
+
+    pub(crate) fn read_n_bytes(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        ensure!(n <= self.rest.len(), "expected {n} more bytes, found {}", self.rest.len());
+
+        let (bytes, rest) = self.rest.split_at(n);
+        self.rest = rest;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_u32(&mut self) -> anyhow::Result<u32> {
+        let bytes = self.read_n_bytes(4)?.try_into().unwrap();
+        Ok(if self.big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+    }
+
+    pub(crate) fn read_u64(&mut self) -> anyhow::Result<u64> {
+        let bytes = self.read_n_bytes(8)?.try_into().unwrap();
+        Ok(if self.big_endian { u64::from_be_bytes(bytes) } else { u64::from_le_bytes(bytes) })
+    }
+
+    pub(crate) fn read_uleb128_u32(&mut self) -> anyhow::Result<u32> {
+        self.read_uleb128_u64_and_convert()
+    }
+
+    pub(crate) fn read_uleb128_usize(&mut self) -> anyhow::Result<usize> {
+        self.read_uleb128_u64_and_convert()
+    }
+
+    fn read_uleb128_u64_and_convert<T>(&mut self) -> anyhow::Result<T>
+    where
+        T: TryFrom<u64> + 'static,
+        T::Error: std::error::Error + Send + Sync,
+    {
+        let mut temp_rest = self.rest;
+        let raw_value: u64 = leb128::read::unsigned(&mut temp_rest)?;
+        let converted_value = T::try_from(raw_value)?;
+
+        // Only update `self.rest` if the above steps succeeded, so that the
+        // parser position can be used for error reporting if desired.
+        self.rest = temp_rest;
+        Ok(converted_value)
+    }
+
+    /// Reads a byte string that is prefixed by its uncompressed length and its
+    /// compressed length (or 0 if it isn't compressed) as LEB128 values, as
+    /// used by both symbol name tables and coverage filename tables.
+    pub(crate) fn read_maybe_compressed_bytes(&mut self) -> anyhow::Result<Vec<u8>> {
+        let uncompressed_len = self.read_uleb128_usize()?;
+        let compressed_len = self.read_uleb128_usize()?;
+
+        if compressed_len == 0 {
+            return Ok(self.read_n_bytes(uncompressed_len)?.to_vec());
+        }
+
+        let compressed_bytes = self.read_n_bytes(compressed_len)?;
+        let uncompressed_bytes = miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(
+            compressed_bytes,
+            uncompressed_len,
+        )
+        .map_err(|e| anyhow!("{e:?}"))
+        .context("decompressing")?;
+        ensure!(uncompressed_bytes.len() == uncompressed_len);
+        Ok(uncompressed_bytes)
+    }
+}
+
+/// LLVM's profiler/coverage metadata often uses an MD5 hash truncated to
+/// 64 bits as a way to associate data stored in different tables/sections.
+pub(crate) fn truncated_md5(bytes: &[u8]) -> u64 {
+    use md5::{Digest, Md5};
+    let mut hasher = Md5::new();
+    hasher.update(bytes);
+    let hash: [u8; 8] = hasher.finalize().as_slice()[..8].try_into().unwrap();
+    // The truncated hash is explicitly little-endian, regardless of host
+    // or target platform. (See `MD5Result::low` in LLVM's `MD5.h`.)
+    u64::from_le_bytes(hash)
+}
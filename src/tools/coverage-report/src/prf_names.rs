@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use crate::parser::{Parser, truncated_md5};
+
+/// Decodes the contents of a `__llvm_prf_names` section, or of the names
+/// section of a `.profraw` file, and adds the (demangled) function names it
+/// contains to `names`, keyed by the hash that other tables use to refer to
+/// them.
+///
+/// The section is a sequence of chunks (typically one per compilation unit),
+/// each holding a possibly-compressed list of symbol names.
+pub(crate) fn read_function_names(
+    data: &[u8],
+    names: &mut HashMap<u64, String>,
+) -> anyhow::Result<()> {
+    let mut parser = Parser::new(data, false);
+    while !parser.only_padding_left() {
+        let symbol_names = parser.read_maybe_compressed_bytes()?;
+
+        // Symbol names in the payload are separated by `0x01` bytes.
+        for raw_name in symbol_names.split(|&b| b == 0x01) {
+            names.insert(truncated_md5(raw_name), demangle_if_able(raw_name)?);
+        }
+    }
+    Ok(())
+}
+
+fn demangle_if_able(symbol_name_bytes: &[u8]) -> anyhow::Result<String> {
+    // In practice, raw symbol names should always be ASCII.
+    let symbol_name_str = std::str::from_utf8(symbol_name_bytes)?;
+    match rustc_demangle::try_demangle(symbol_name_str) {
+        Ok(d) => Ok(format!("{d:#}")),
+        // Names that aren't Rust symbols (e.g. from C code instrumented by
+        // `clang` and linked into the same binary) are reported as they are.
+        Err(_) => Ok(symbol_name_str.to_owned()),
+    }
+}
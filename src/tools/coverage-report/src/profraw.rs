@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use anyhow::{Context, bail, ensure};
+
+use crate::parser::Parser;
+
+/// `"\xfflprofr\x81"`, read as a native-endian u64: the magic number of raw
+/// profiles written by 64-bit programs.
+const MAGIC_64: u64 = 0xff6c_7072_6f66_7281;
+/// The magic number of raw profiles written by 32-bit programs.
+const MAGIC_32: u64 = 0xff6c_7072_6f66_5281;
+
+/// Set in the version field when each counter is a single byte, which is 0
+/// if the counter was reached.
+const VARIANT_MASK_BYTE_COVERAGE: u64 = 1 << 60;
+/// Set in the version field when only function entries were counted.
+const VARIANT_MASK_FUNCTION_ENTRY_ONLY: u64 = 1 << 61;
+
+/// Size of a (64-bit) `__llvm_profile_data` record in raw profile versions 9
+/// and 10, including its trailing padding.
+const DATA_RECORD_SIZE: u64 = 64;
+
+/// The counters of each function, merged from any number of raw profiles.
+#[derive(Default)]
+pub(crate) struct Profile {
+    /// The counters of each function, keyed by the hash of its name and the
+    /// hash of its source (which must match the one in its coverage mappings).
+    pub(crate) counters: HashMap<(u64, u64), Vec<u64>>,
+}
+
+impl Profile {
+    /// Reads a `.profraw` file written by an instrumented program, and adds its
+    /// counters to the ones already in this profile.
+    pub(crate) fn merge_profraw(&mut self, data: &[u8]) -> anyhow::Result<()> {
+        ensure!(data.len() >= 8, "file is too small to be a raw profile");
+        let magic = u64::from_le_bytes(data[..8].try_into().unwrap());
+        let big_endian = match magic {
+            MAGIC_64 => false,
+            _ if magic == MAGIC_64.swap_bytes() => true,
+            _ if magic == MAGIC_32 || magic == MAGIC_32.swap_bytes() => {
+                bail!("raw profiles of 32-bit programs aren't supported")
+            }
+            _ => bail!("not a raw profile (magic number {magic:#x})"),
+        };
+
+        let mut parser = Parser::new(data, big_endian);
+        let _magic = parser.read_u64()?;
+        let version = parser.read_u64()?;
+        ensure!(
+            version & VARIANT_MASK_FUNCTION_ENTRY_ONLY == 0,
+            "raw profiles which only count function entries aren't supported"
+        );
+        let counter_size = if version & VARIANT_MASK_BYTE_COVERAGE != 0 { 1 } else { 8 };
+        // The upper 32 bits of the version field hold flags.
+        let version = version as u32;
+        ensure!(
+            version == 9 || version == 10,
+            "unsupported raw profile version {version} (expected 9 or 10)"
+        );
+
+        let binary_ids_size = parser.read_u64()?;
+        let num_data = parser.read_u64()?;
+        let padding_before_counters = parser.read_u64()?;
+        let num_counters = parser.read_u64()?;
+        let _padding_after_counters = parser.read_u64()?;
+        let _num_bitmap_bytes = parser.read_u64()?;
+        let _padding_after_bitmap_bytes = parser.read_u64()?;
+        let _names_size = parser.read_u64()?;
+        let counters_delta = parser.read_u64()?;
+        let _bitmap_delta = parser.read_u64()?;
+        let _names_delta = parser.read_u64()?;
+        if version >= 10 {
+            let _num_vtables = parser.read_u64()?;
+            let _vnames_size = parser.read_u64()?;
+        }
+        let _value_kind_last = parser.read_u64()?;
+
+        parser.read_section(binary_ids_size).context("reading binary IDs")?;
+        let data_records = parser
+            .read_section(num_data.saturating_mul(DATA_RECORD_SIZE))
+            .context("reading data records")?;
+        parser.read_section(padding_before_counters)?;
+        let counters = parser
+            .read_section(num_counters.saturating_mul(counter_size))
+            .context("reading counters")?;
+        // The bitmaps, function names, vtables and value profiling data that
+        // follow aren't needed: the binary has its own copy of the names.
+
+        // The counter pointer of each data record is relative to the address of
+        // the record itself, while `counters_delta` is the offset from the
+        // first record to the first counter.
+        let mut counters_delta = counters_delta;
+        let mut records = Parser::new(data_records, big_endian);
+        for _ in 0..num_data {
+            let record = records.read_n_bytes(DATA_RECORD_SIZE as usize)?;
+            let mut record = Parser::new(record, big_endian);
+            let name_hash = record.read_u64()?;
+            let func_hash = record.read_u64()?;
+            let counter_ptr = record.read_u64()?;
+            let _bitmap_ptr = record.read_u64()?;
+            let _function_ptr = record.read_u64()?;
+            let _values = record.read_u64()?;
+            let function_num_counters = record.read_u32()? as usize;
+
+            let offset = counter_ptr.wrapping_sub(counters_delta);
+            counters_delta = counters_delta.wrapping_sub(DATA_RECORD_SIZE);
+            ensure!(
+                offset % counter_size == 0 && offset / counter_size <= num_counters,
+                "counters of function {name_hash:#x} are out of bounds"
+            );
+
+            let first = (offset / counter_size) as usize;
+            let function_counters = counters
+                .chunks_exact(counter_size as usize)
+                .skip(first)
+                .take(function_num_counters)
+                .map(|counter| match *counter {
+                    [byte] => u64::from(byte == 0),
+                    _ => Parser::new(counter, big_endian).read_u64().unwrap(),
+                })
+                .collect::<Vec<_>>();
+            ensure!(
+                function_counters.len() == function_num_counters,
+                "counters of function {name_hash:#x} are out of bounds"
+            );
+
+            let merged = self.counters.entry((name_hash, func_hash)).or_default();
+            if merged.len() < function_counters.len() {
+                merged.resize(function_counters.len(), 0);
+            }
+            for (total, count) in merged.iter_mut().zip(function_counters) {
+                *total = total.saturating_add(count);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Extra parser methods only needed when parsing raw profiles.
+impl<'a> Parser<'a> {
+    fn read_section(&mut self, size: u64) -> anyhow::Result<&'a [u8]> {
+        self.read_n_bytes(usize::try_from(size)?)
+    }
+}
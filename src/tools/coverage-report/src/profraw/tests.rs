@@ -0,0 +1,89 @@
+use super::Profile;
+
+fn push_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend(value.to_le_bytes());
+}
+
+/// Builds a little-endian raw profile with the given data records (name hash,
+/// function hash, index of the first counter and number of counters).
+fn make_profraw(version: u64, records: &[(u64, u64, u64, u32)], counters: &[u64]) -> Vec<u8> {
+    let mut bytes = vec![];
+    let num_data = records.len() as u64;
+    let counters_delta = num_data * 64;
+    let header = [
+        0xff6c_7072_6f66_7281,
+        version,
+        0, // binary IDs size
+        num_data,
+        0, // padding before counters
+        counters.len() as u64,
+        0, // padding after counters
+        0, // number of bitmap bytes
+        0, // padding after bitmap bytes
+        0, // names size
+        counters_delta,
+        0, // bitmap delta
+        0, // names delta
+    ];
+    for value in header {
+        push_u64(&mut bytes, value);
+    }
+    if version >= 10 {
+        // Number of vtables and vtable names size.
+        push_u64(&mut bytes, 0);
+        push_u64(&mut bytes, 0);
+    }
+    // Last value profiling kind.
+    push_u64(&mut bytes, 2);
+
+    for (i, &(name_hash, func_hash, first_counter, num_counters)) in records.iter().enumerate() {
+        let counter_ptr = counters_delta + first_counter * 8 - i as u64 * 64;
+        for value in [name_hash, func_hash, counter_ptr, 0, 0, 0] {
+            push_u64(&mut bytes, value);
+        }
+        bytes.extend(num_counters.to_le_bytes());
+        // Value sites, bitmap bytes and padding.
+        bytes.resize(bytes.len() + 12, 0);
+    }
+    for &counter in counters {
+        push_u64(&mut bytes, counter);
+    }
+    bytes
+}
+
+#[test]
+fn read_counters() {
+    let profraw = make_profraw(10, &[(0xa, 0x1, 0, 2), (0xb, 0x2, 2, 1)], &[3, 1, 7]);
+    let mut profile = Profile::default();
+    profile.merge_profraw(&profraw).unwrap();
+    assert_eq!(profile.counters[&(0xa, 0x1)], [3, 1]);
+    assert_eq!(profile.counters[&(0xb, 0x2)], [7]);
+}
+
+#[test]
+fn read_version_9() {
+    let profraw = make_profraw(9, &[(0xa, 0x1, 0, 1)], &[5]);
+    let mut profile = Profile::default();
+    profile.merge_profraw(&profraw).unwrap();
+    assert_eq!(profile.counters[&(0xa, 0x1)], [5]);
+}
+
+#[test]
+fn merge_counters() {
+    let mut profile = Profile::default();
+    profile.merge_profraw(&make_profraw(10, &[(0xa, 0x1, 0, 2)], &[3, 0])).unwrap();
+    profile.merge_profraw(&make_profraw(10, &[(0xa, 0x1, 0, 2)], &[1, 4])).unwrap();
+    // Counters of a function whose source changed are kept apart.
+    profile.merge_profraw(&make_profraw(10, &[(0xa, 0x2, 0, 1)], &[9])).unwrap();
+    assert_eq!(profile.counters[&(0xa, 0x1)], [4, 4]);
+    assert_eq!(profile.counters[&(0xa, 0x2)], [9]);
+}
+
+#[test]
+fn reject_unsupported_profiles() {
+    let mut profile = Profile::default();
+    assert!(profile.merge_profraw(&make_profraw(8, &[], &[])).is_err());
+    assert!(profile.merge_profraw(b"not a profile").is_err());
+    // Counters out of bounds.
+    assert!(profile.merge_profraw(&make_profraw(10, &[(0xa, 0x1, 1, 2)], &[1])).is_err());
+}
@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use crate::covmap::{CovTerm, FunctionMappings, Op, Region, RegionKind};
+use crate::profraw::Profile;
+
+/// Coverage of each source file, combined from all functions (and all
+/// instantiations of generic functions) that have mappings in it.
+#[derive(Default)]
+pub(crate) struct Report {
+    pub(crate) files: BTreeMap<PathBuf, FileReport>,
+}
+
+#[derive(Default)]
+pub(crate) struct FileReport {
+    /// Execution count of each line that is covered by a mapping.
+    pub(crate) lines: BTreeMap<u32, u64>,
+    /// Counts of each branch, keyed by its start line and column.
+    pub(crate) branches: BTreeMap<(u32, u32), BranchCounts>,
+    /// The functions that start in this file, keyed by their start line and
+    /// (demangled) name.
+    pub(crate) functions: BTreeMap<(u32, String), FunctionReport>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct BranchCounts {
+    pub(crate) true_count: u64,
+    pub(crate) false_count: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct FunctionReport {
+    /// How many times the function was entered.
+    pub(crate) count: u64,
+    /// Execution count of each of the function's lines, in the file in which
+    /// it starts.
+    pub(crate) lines: BTreeMap<u32, u64>,
+    /// Counts of each of the function's branches, in the file in which it
+    /// starts.
+    pub(crate) branches: BTreeMap<(u32, u32), BranchCounts>,
+}
+
+impl Report {
+    pub(crate) fn new(
+        functions: &[FunctionMappings],
+        profile: &Profile,
+        function_names: &HashMap<u64, String>,
+    ) -> Self {
+        let counters_of = |function: &FunctionMappings| {
+            profile.counters.get(&(function.name_hash, function.func_hash))
+        };
+
+        // The same function can have covfun records in several compilation
+        // units (or binaries), possibly as an unused function in some of them.
+        // Only report one of them, preferring one that has counters.
+        let mut unique_functions = HashMap::new();
+        for function in functions {
+            let rank = |function: &FunctionMappings| {
+                (counters_of(function).is_some(), function.func_hash != 0)
+            };
+            match unique_functions.entry(function.name_hash) {
+                Entry::Vacant(entry) => {
+                    entry.insert(function);
+                }
+                Entry::Occupied(mut entry) => {
+                    if rank(function) > rank(entry.get()) {
+                        entry.insert(function);
+                    }
+                }
+            }
+        }
+
+        let mut report = Self::default();
+        for function in unique_functions.into_values() {
+            let name = match function_names.get(&function.name_hash) {
+                Some(name) => name.clone(),
+                None => format!("(unknown function {:#x})", function.name_hash),
+            };
+            let counters = counters_of(function).map_or(&[][..], Vec::as_slice);
+            report.add_function(function, name, counters);
+        }
+        report
+    }
+
+    fn add_function(&mut self, function: &FunctionMappings, name: String, counters: &[u64]) {
+        let count_of = |term| evaluate(term, &function.expressions, counters);
+
+        // The function is entered through its first code region.
+        let Some((entry_line, entry_term)) = function
+            .regions
+            .iter()
+            .filter(|region| region.file == 0)
+            .filter_map(|region| match region.kind {
+                RegionKind::Code(term) => Some(((region.start_line, region.start_column), term)),
+                _ => None,
+            })
+            .min_by_key(|&(start, _)| start)
+            .map(|((line, _), term)| (line, term))
+        else {
+            return;
+        };
+        let mut function_report =
+            FunctionReport { count: count_of(entry_term), ..Default::default() };
+
+        for (file, path) in function.files.iter().enumerate() {
+            let regions = function.regions.iter().filter(|region| region.file == file);
+            let mut line_regions = vec![];
+            let mut branches = BTreeMap::<_, BranchCounts>::new();
+            for region in regions {
+                match region.kind {
+                    RegionKind::Code(term) | RegionKind::Gap(term) => {
+                        line_regions.push((region, count_of(term)));
+                    }
+                    RegionKind::Branch { true_term, false_term } => {
+                        let counts =
+                            branches.entry((region.start_line, region.start_column)).or_default();
+                        counts.true_count = counts.true_count.saturating_add(count_of(true_term));
+                        counts.false_count =
+                            counts.false_count.saturating_add(count_of(false_term));
+                    }
+                }
+            }
+            let lines = line_counts(&line_regions);
+
+            let file_report = self.files.entry(path.clone()).or_default();
+            merge_lines(&mut file_report.lines, &lines);
+            merge_branches(&mut file_report.branches, &branches);
+            if file == 0 {
+                merge_lines(&mut function_report.lines, &lines);
+                merge_branches(&mut function_report.branches, &branches);
+            }
+        }
+
+        // Instantiations of a generic function can share the same name, in
+        // which case their counts are combined.
+        let Some(path) = function.files.first() else { return };
+        let functions = &mut self.files.entry(path.clone()).or_default().functions;
+        let merged = functions.entry((entry_line, name)).or_default();
+        merged.count = merged.count.saturating_add(function_report.count);
+        merge_lines(&mut merged.lines, &function_report.lines);
+        merge_branches(&mut merged.branches, &function_report.branches);
+    }
+}
+
+/// Computes the value of a counter or expression.
+fn evaluate(term: CovTerm, expressions: &[(CovTerm, CovTerm)], counters: &[u64]) -> u64 {
+    match term {
+        CovTerm::Zero => 0,
+        // Functions without counters in the profile were never run.
+        CovTerm::Counter(id) => counters.get(id as usize).copied().unwrap_or(0),
+        CovTerm::Expression(id, op) => {
+            let (lhs, rhs) = expressions[id as usize];
+            let lhs = evaluate(lhs, expressions, counters);
+            let rhs = evaluate(rhs, expressions, counters);
+            match op {
+                Op::Sub => lhs.saturating_sub(rhs),
+                Op::Add => lhs.saturating_add(rhs),
+            }
+        }
+    }
+}
+
+/// Computes the execution count of each line spanned by the given code and
+/// gap regions (of a single file), the way `llvm-cov` does in the common
+/// cases: a line's count is the highest count among the code regions that
+/// start on it and the innermost region that spans it from an earlier line.
+fn line_counts(regions: &[(&Region, u64)]) -> BTreeMap<u32, u64> {
+    let mut lines = BTreeMap::new();
+    for line in regions.iter().flat_map(|(region, _)| region.start_line..=region.end_line) {
+        if lines.contains_key(&line) {
+            continue;
+        }
+
+        let starting = regions
+            .iter()
+            .filter(|(region, _)| {
+                matches!(region.kind, RegionKind::Code(_)) && region.start_line == line
+            })
+            .map(|&(_, count)| count)
+            .max();
+        // The end column is exclusive, so a region that ends at the start of
+        // a line doesn't span it.
+        let spanning = regions
+            .iter()
+            .filter(|(region, _)| {
+                region.start_line < line
+                    && (line < region.end_line
+                        || (line == region.end_line && region.end_column > 1))
+            })
+            .max_by_key(|(region, _)| (region.start_line, region.start_column))
+            .map(|&(_, count)| count);
+        if let Some(count) = starting.max(spanning) {
+            lines.insert(line, count);
+        }
+    }
+    lines
+}
+
+fn merge_lines(total: &mut BTreeMap<u32, u64>, lines: &BTreeMap<u32, u64>) {
+    for (&line, &count) in lines {
+        let total = total.entry(line).or_default();
+        *total = total.saturating_add(count);
+    }
+}
+
+fn merge_branches(
+    total: &mut BTreeMap<(u32, u32), BranchCounts>,
+    branches: &BTreeMap<(u32, u32), BranchCounts>,
+) {
+    for (&start, counts) in branches {
+        let total = total.entry(start).or_default();
+        total.true_count = total.true_count.saturating_add(counts.true_count);
+        total.false_count = total.false_count.saturating_add(counts.false_count);
+    }
+}
+
+/// Returns the number of lines that were executed, and the number of lines.
+pub(crate) fn line_totals(lines: &BTreeMap<u32, u64>) -> (usize, usize) {
+    (lines.values().filter(|&&count| count > 0).count(), lines.len())
+}
+
+/// Returns the number of branch arms that were taken, and the number of
+/// branch arms (two per branch).
+pub(crate) fn branch_totals<'a>(
+    branches: impl IntoIterator<Item = &'a BranchCounts>,
+) -> (usize, usize) {
+    branches.into_iter().fold((0, 0), |(taken, total), counts| {
+        let taken_arms = usize::from(counts.true_count > 0) + usize::from(counts.false_count > 0);
+        (taken + taken_arms, total + 2)
+    })
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::Report;
+use crate::covmap::{CovTerm, FunctionMappings, Op, Region, RegionKind};
+use crate::profraw::Profile;
+
+const MAIN_HASH: u64 = 0x1111;
+const UNUSED_HASH: u64 = 0x2222;
+
+fn region(kind: RegionKind, start: (u32, u32), end: (u32, u32)) -> Region {
+    let (start_line, start_column) = start;
+    let (end_line, end_column) = end;
+    Region { file: 0, kind, start_line, start_column, end_line, end_column }
+}
+
+/// Builds a report for this program, where `main` was run 3 times and took
+/// the `if` branch once:
+///
+/// ```text
+///  1 fn main() {
+///  2     if x() {
+///  3         a();
+///  4     } else {
+///  5         b();
+///  6     }
+///  7 }
+///  8
+///  9 fn unused() {
+/// 10     c();
+/// 11 }
+/// ```
+fn make_report() -> Report {
+    let files = vec![PathBuf::from("/work/src/main.rs")];
+    let else_term = CovTerm::Expression(0, Op::Sub);
+    let main = FunctionMappings {
+        name_hash: MAIN_HASH,
+        func_hash: 0xabcd,
+        files: files.clone(),
+        expressions: vec![(CovTerm::Counter(0), CovTerm::Counter(1))],
+        regions: vec![
+            region(RegionKind::Code(CovTerm::Counter(0)), (1, 1), (2, 13)),
+            region(
+                RegionKind::Branch { true_term: CovTerm::Counter(1), false_term: else_term },
+                (2, 8),
+                (2, 11),
+            ),
+            region(RegionKind::Code(CovTerm::Counter(1)), (2, 12), (4, 6)),
+            region(RegionKind::Gap(else_term), (4, 6), (4, 12)),
+            region(RegionKind::Code(else_term), (4, 12), (6, 6)),
+            region(RegionKind::Code(CovTerm::Counter(0)), (7, 1), (7, 2)),
+        ],
+    };
+    // An unused copy of `main` from another compilation unit, which must not
+    // be reported.
+    let main_unused_copy = FunctionMappings { func_hash: 0, ..main.clone() };
+    let unused = FunctionMappings {
+        name_hash: UNUSED_HASH,
+        func_hash: 0,
+        files,
+        expressions: vec![],
+        regions: vec![region(RegionKind::Code(CovTerm::Counter(0)), (9, 1), (11, 2))],
+    };
+
+    let mut profile = Profile::default();
+    profile.counters.insert((MAIN_HASH, 0xabcd), vec![3, 1]);
+    let function_names = HashMap::from([
+        (MAIN_HASH, "demo::main".to_owned()),
+        (UNUSED_HASH, "demo::unused".to_owned()),
+    ]);
+    Report::new(&[main_unused_copy, main, unused], &profile, &function_names)
+}
+
+#[test]
+fn lcov() {
+    let mut output = String::new();
+    crate::lcov::write_lcov(&make_report(), &mut output).unwrap();
+    let expected = "\
+SF:/work/src/main.rs
+FN:1,demo::main
+FN:9,demo::unused
+FNDA:3,demo::main
+FNDA:0,demo::unused
+FNF:2
+FNH:1
+BRDA:2,0,0,1
+BRDA:2,0,1,2
+BRF:2
+BRH:2
+DA:1,3
+DA:2,3
+DA:3,1
+DA:4,2
+DA:5,2
+DA:6,2
+DA:7,3
+DA:9,0
+DA:10,0
+DA:11,0
+LF:10
+LH:7
+end_of_record
+";
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn cobertura() {
+    let mut output = String::new();
+    crate::cobertura::write_cobertura(&make_report(), Path::new("/work"), 0, &mut output).unwrap();
+    for expected in [
+        r#"<coverage lines-valid="10" lines-covered="7" line-rate="0.7" branches-valid="2" branches-covered="2" branch-rate="1" "#,
+        r#"<package name="src" line-rate="0.7" branch-rate="1" complexity="0">"#,
+        r#"<class name="main.rs" filename="src/main.rs" line-rate="0.7" branch-rate="1" complexity="0">"#,
+        r#"<method name="demo::unused" signature="" line-rate="0" branch-rate="1" complexity="0">"#,
+        r#"<line number="2" hits="3" branch="true" condition-coverage="100% (2/2)"/>"#,
+        r#"<line number="10" hits="0" branch="false"/>"#,
+    ] {
+        assert!(output.contains(expected), "missing `{expected}` in:\n{output}");
+    }
+}
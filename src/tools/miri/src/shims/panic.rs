@@ -242,6 +242,23 @@ fn assert_panic(
                     StackPopCleanup::Goto { ret: None, unwind },
                 )?;
             }
+            InvalidBool(found) | InvalidChar(found) | InvalidEnumDiscriminant(found) => {
+                // Forward to the lang item associated with this message.
+
+                // Only arg: found.
+                let found = this.read_immediate(&this.eval_operand(found, None)?)?;
+
+                // Call the lang item.
+                let fn_item = this.tcx.require_lang_item(msg.panic_function(), None);
+                let instance = ty::Instance::mono(this.tcx.tcx, fn_item);
+                this.call_function(
+                    instance,
+                    ExternAbi::Rust,
+                    &[found],
+                    None,
+                    StackPopCleanup::Goto { ret: None, unwind },
+                )?;
+            }
 
             _ => {
                 // Call the lang item associated with this message.
@@ -0,0 +1,28 @@
+// Check that a large match of a `&str` against string literals switches on the
+// length of the string, then on one of its bytes, before comparing it with the
+// literals.
+
+//@ compile-flags: -O
+#![crate_type = "lib"]
+
+// CHECK-LABEL: @keyword(
+#[no_mangle]
+pub fn keyword(s: &str) -> u32 {
+    // CHECK: switch i{{32|64}} %s.1, label %{{.*}} [
+    // CHECK: switch i8
+    match s {
+        "as" => 1,
+        "fn" => 2,
+        "if" => 3,
+        "in" => 4,
+        "let" => 5,
+        "mod" => 6,
+        "pub" => 7,
+        "use" => 8,
+        "impl" => 9,
+        "loop" => 10,
+        "match" => 11,
+        "while" => 12,
+        _ => 0,
+    }
+}
@@ -0,0 +1,32 @@
+// Checks that `-Zmerge-mono-items` does not merge the instances of a generic function for two
+// enums with the same layout, as their discriminants have different values.
+
+//@ compile-flags: -Copt-level=0 -Zmerge-mono-items
+#![crate_type = "lib"]
+
+#[derive(Clone, Copy)]
+pub enum Forward {
+    First = 1,
+    Second = 2,
+}
+
+#[derive(Clone, Copy)]
+pub enum Backward {
+    First = 2,
+    Second = 1,
+}
+
+// CHECK-NOT: @{{.*}}6select{{.*}} = {{.*}}alias
+
+#[inline(never)]
+fn select<T: Copy>(first: bool, x: T, y: T) -> Option<T> {
+    if first { Some(x) } else { Some(y) }
+}
+
+pub fn select_forward(first: bool) -> Option<Forward> {
+    select(first, Forward::First, Forward::Second)
+}
+
+pub fn select_backward(first: bool) -> Option<Backward> {
+    select(first, Backward::First, Backward::Second)
+}
@@ -0,0 +1,41 @@
+// Checks that `-Zmerge-mono-items` defines the instances of a generic function
+// whose arguments have the same layout as aliases of a single instance.
+
+//@ compile-flags: -Copt-level=0 -Zmerge-mono-items
+#![crate_type = "lib"]
+
+#[derive(Clone, Copy)]
+pub struct Meters(pub u32);
+
+#[derive(Clone, Copy)]
+pub struct Seconds(pub u32);
+
+// CHECK: @{{.*}}6double{{.*}} = {{.*}}alias {{.*}}@{{.*}}6double
+// CHECK-NOT: @{{.*}}8identity{{.*}} = {{.*}}alias
+
+#[inline(never)]
+fn double<T: Copy>(x: T) -> (T, T) {
+    (x, x)
+}
+
+#[inline(never)]
+fn identity<T>(x: T) -> T {
+    x
+}
+
+pub fn double_meters(x: Meters) -> (Meters, Meters) {
+    double(x)
+}
+
+pub fn double_seconds(x: Seconds) -> (Seconds, Seconds) {
+    double(x)
+}
+
+// `u32` and `u64` have different layouts.
+pub fn identity_u32(x: u32) -> u32 {
+    identity(x)
+}
+
+pub fn identity_u64(x: u64) -> u64 {
+    identity(x)
+}
@@ -0,0 +1,32 @@
+// Verifies that `-Zmerge-mono-items` does not define instances as aliases with CFI, since an
+// alias would carry the type metadata of the instance it aliases.
+//
+//@ needs-sanitizer-cfi
+//@ compile-flags: -Clto -Cno-prepopulate-passes -Ctarget-feature=-crt-static -Zsanitizer=cfi -Copt-level=0 -Zmerge-mono-items
+
+#![crate_type = "lib"]
+
+#[derive(Clone, Copy)]
+pub struct Meters(pub u32);
+
+#[derive(Clone, Copy)]
+pub struct Seconds(pub u32);
+
+// CHECK-NOT: alias
+// CHECK: define{{.*}}6double{{.*}}!type
+// CHECK-NOT: alias
+// CHECK: define{{.*}}6double{{.*}}!type
+// CHECK-NOT: alias
+
+#[inline(never)]
+fn double<T: Copy>(x: T) -> (T, T) {
+    (x, x)
+}
+
+pub fn double_meters(x: Meters) -> (Meters, Meters) {
+    double(x)
+}
+
+pub fn double_seconds(x: Seconds) -> (Seconds, Seconds) {
+    double(x)
+}
@@ -0,0 +1,46 @@
+// Check that large matches of a `&str` against string literals are lowered as
+// a decision tree on the length and the bytes of the string.
+
+// EMIT_MIR string_decision_tree.keyword.SimplifyCfg-initial.after.mir
+fn keyword(s: &str) -> u32 {
+    // CHECK-LABEL: fn keyword(
+    // CHECK: bb0: {
+    // CHECK: [[len:_[0-9]+]] = PtrMetadata(copy _1);
+    // CHECK: switchInt(move [[len]]) -> [2: [[two:bb[0-9]+]], 3: [[three:bb[0-9]+]], otherwise: [[otherwise:bb[0-9]+]]];
+    // CHECK: [[two]]: {
+    // CHECK-NOT: PtrMetadata
+    // CHECK: [[bytes:_[0-9]+]] = move {{_[0-9]+}} as *const [u8] (PtrToPtr);
+    // CHECK: switchInt(copy (*[[bytes]])[0 of 2]) -> [97: {{bb[0-9]+}}, 102: {{bb[0-9]+}}, 105: {{bb[0-9]+}}, otherwise: [[otherwise]]];
+    // CHECK: [[three]]: {
+    // CHECK: <str as PartialEq>::eq(copy _1, const "let")
+    match s {
+        "as" => 0,
+        "fn" => 1,
+        "if" => 2,
+        "in" => 3,
+        "let" => 4,
+        _ => 5,
+    }
+}
+
+// EMIT_MIR string_decision_tree.few_literals.SimplifyCfg-initial.after.mir
+fn few_literals(s: &str) -> u32 {
+    // Small matches are still lowered as a chain of comparisons.
+
+    // CHECK-LABEL: fn few_literals(
+    // CHECK-NOT: PtrMetadata
+    // CHECK: <str as PartialEq>::eq(copy _1, const "as")
+    // CHECK: <str as PartialEq>::eq(copy _1, const "fn")
+    // CHECK: <str as PartialEq>::eq(copy _1, const "let")
+    match s {
+        "as" => 0,
+        "fn" => 1,
+        "let" => 2,
+        _ => 3,
+    }
+}
+
+fn main() {
+    keyword("fn");
+    few_literals("fn");
+}
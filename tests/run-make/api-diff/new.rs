@@ -0,0 +1,42 @@
+pub struct Config {
+    pub verbose: bool,
+    pub quiet: bool,
+}
+
+impl Config {
+    pub fn new(verbose: bool) -> Config {
+        Config { verbose, quiet: false }
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+impl private::Sealed for Config {}
+
+#[non_exhaustive]
+pub enum Mode {
+    Fast,
+    Slow,
+    Balanced,
+}
+
+pub enum Level {
+    Low,
+    High,
+    Max,
+}
+
+pub trait Visitor {
+    fn visit(&mut self);
+    fn finish(&mut self) {}
+}
+
+pub fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+    input.parse()
+}
+
+pub fn added() {}
+
+pub struct Shared<T>(std::sync::Mutex<T>);
@@ -0,0 +1,32 @@
+pub struct Config {
+    pub verbose: bool,
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config { verbose: false }
+    }
+}
+
+#[non_exhaustive]
+pub enum Mode {
+    Fast,
+    Slow,
+}
+
+pub enum Level {
+    Low,
+    High,
+}
+
+pub trait Visitor {
+    fn visit(&mut self);
+}
+
+pub fn parse(input: &str) -> Option<u32> {
+    input.parse().ok()
+}
+
+pub fn removed() {}
+
+pub struct Shared<T>(Vec<T>);
@@ -0,0 +1,42 @@
+// `-Zapi-diff=OLD,NEW` loads two versions of the same crate through `--extern` and reports
+// how the public API changed between them, classified by semver severity.
+
+use run_make_support::{rust_lib_name, rustc};
+
+fn main() {
+    rustc()
+        .input("old.rs")
+        .crate_name("api")
+        .crate_type("rlib")
+        .metadata("old")
+        .output(rust_lib_name("api_old"))
+        .run();
+    rustc()
+        .input("new.rs")
+        .crate_name("api")
+        .crate_type("rlib")
+        .metadata("new")
+        .output(rust_lib_name("api_new"))
+        .run();
+
+    rustc()
+        .input("main.rs")
+        .extern_("old", rust_lib_name("api_old"))
+        .extern_("new", rust_lib_name("api_new"))
+        .arg("-Zapi-diff=old,new")
+        .run()
+        .assert_stdout_contains("major: `api::Config::quiet`: field added")
+        .assert_stdout_contains("major: `api::Config::new`: associated function changed from")
+        .assert_stdout_contains("major: `api::Level::Max`: variant added")
+        .assert_stdout_contains("major: `api::parse`: function changed from")
+        .assert_stdout_contains("major: `api::removed`: function removed")
+        .assert_stdout_contains(
+            "major: `api::Shared`: implements `Sync` where `T: core::marker::Send` \
+             instead of where `T: core::marker::Sync`",
+        )
+        .assert_stdout_contains("minor: `api::Mode::Balanced`: variant added")
+        .assert_stdout_contains("minor: `api::Visitor::finish`: associated function added")
+        .assert_stdout_contains("minor: `api::added`: function added")
+        .assert_stdout_not_contains("Sealed")
+        .assert_stdout_contains("a major version bump is required");
+}
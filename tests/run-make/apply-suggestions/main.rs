@@ -0,0 +1,6 @@
+use std::collections::HashMap;
+
+fn main() {
+    let mut x = 1;
+    println!("{x}");
+}
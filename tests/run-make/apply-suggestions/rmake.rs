@@ -0,0 +1,51 @@
+// `-Zapply-suggestions` applies machine-applicable suggestions directly to the source files,
+// optionally restricted to some lints with `-Zapply-suggestions-lints`.
+
+use run_make_support::{rfs, rustc};
+
+fn main() {
+    rfs::copy("main.rs", "fixed.rs");
+
+    rustc()
+        .input("fixed.rs")
+        .arg("-Zapply-suggestions=dry-run")
+        .run()
+        .assert_stderr_contains("would apply 2 suggestions to `fixed.rs`");
+    assert_eq!(rfs::read_to_string("fixed.rs"), rfs::read_to_string("main.rs"));
+
+    rustc()
+        .input("fixed.rs")
+        .arg("-Zapply-suggestions=diff")
+        .run()
+        .assert_stderr_contains("-    let mut x = 1;\n+    let x = 1;");
+
+    rustc()
+        .input("fixed.rs")
+        .arg("-Zapply-suggestions=diff")
+        .arg("--error-format=json")
+        .run()
+        .assert_stderr_contains(r#"{"$message_type":"suggestion_diff","file":"fixed.rs","diff":"#);
+
+    rustc()
+        .input("fixed.rs")
+        .arg("-Zapply-suggestions=write")
+        .arg("-Zapply-suggestions-lints=unused-mut")
+        .run()
+        .assert_stderr_contains("applied 1 suggestion to `fixed.rs`");
+    let fixed = rfs::read_to_string("fixed.rs");
+    assert!(fixed.contains("let x = 1;"));
+    assert!(fixed.contains("use std::collections::HashMap;"));
+
+    rustc().input("fixed.rs").arg("-Zapply-suggestions=write").run();
+    assert!(!rfs::read_to_string("fixed.rs").contains("HashMap"));
+
+    // The byte order mark and CRLF line endings survive the rewrite.
+    let crlf = format!("\u{feff}{}", rfs::read_to_string("main.rs").replace('\n', "\r\n"));
+    rfs::write("crlf.rs", &crlf);
+    rustc().input("crlf.rs").arg("-Zapply-suggestions=write").run();
+    let fixed = rfs::read_to_string("crlf.rs");
+    assert!(fixed.starts_with('\u{feff}'));
+    assert!(fixed.contains("    let x = 1;\r\n"));
+    assert!(!fixed.contains("HashMap"));
+    assert_eq!(fixed.matches('\n').count(), fixed.matches("\r\n").count());
+}
@@ -0,0 +1,4 @@
+#[cfg(not(v2))]
+pub const VERSION: u32 = 1;
+#[cfg(v2)]
+pub const VERSION: u32 = 2;
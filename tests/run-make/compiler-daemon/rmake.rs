@@ -0,0 +1,71 @@
+// Checks that compiler invocations with `RUSTC_DAEMON` set are compiled by the daemon started with
+// `-Zdaemon`, that their diagnostics and exit codes are those of the compilation, that a
+// dependency rebuilt between two requests is loaded again, and that the variables Cargo sets for
+// each crate are seen by `env!`.
+
+//@ ignore-cross-compile
+//@ only-unix
+
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use run_make_support::{cwd, env_var, run, rustc, rustc_path};
+
+fn main() {
+    // The daemon only serves requests with the same environment as its own, apart from the
+    // variables Cargo sets for each crate, so give it the library path `rustc()` sets.
+    let ld_lib_path_envvar = env_var("LD_LIB_PATH_ENVVAR");
+    let mut paths = vec![cwd(), env_var("HOST_RPATH_DIR").into()];
+    paths.extend(std::env::split_paths(&env_var(&ld_lib_path_envvar)));
+    let socket = Path::new("rustc.sock");
+    let mut daemon = Command::new(rustc_path())
+        .arg("-Zdaemon=rustc.sock")
+        .env(&ld_lib_path_envvar, std::env::join_paths(paths).unwrap())
+        .spawn()
+        .unwrap();
+    while !socket.exists() {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let through_daemon = || {
+        let mut rustc = rustc();
+        rustc.env("RUSTC_DAEMON", socket);
+        rustc
+    };
+
+    through_daemon().input("dep.rs").crate_type("lib").run();
+    through_daemon().input("main.rs").extern_("dep", "libdep.rlib").run();
+    run("main").assert_stdout_contains("version 1");
+
+    through_daemon().input("dep.rs").crate_type("lib").cfg("v2").run();
+    through_daemon().input("main.rs").extern_("dep", "libdep.rlib").run();
+    run("main").assert_stdout_contains("version 2");
+
+    through_daemon().input("error.rs").run_fail().assert_stderr_contains("mismatched types");
+
+    through_daemon().input("version.rs").env("CARGO_PKG_VERSION", "1.0.0").run();
+    run("version").assert_stdout_contains("package 1.0.0");
+    through_daemon().input("version.rs").env("CARGO_PKG_VERSION", "2.0.0").run();
+    run("version").assert_stdout_contains("package 2.0.0");
+
+    // A request with a different environment is refused and compiled by the client itself.
+    through_daemon()
+        .input("version.rs")
+        .env("CARGO_PKG_VERSION", "3.0.0")
+        .env("RUSTC_DAEMON_TEST_VAR", "1")
+        .run();
+    run("version").assert_stdout_contains("package 3.0.0");
+    // So is a request with a jobserver other than the one of the daemon.
+    through_daemon()
+        .input("version.rs")
+        .env("CARGO_PKG_VERSION", "4.0.0")
+        .env("CARGO_MAKEFLAGS", "-j2 --jobserver-fds=3,4 --jobserver-auth=3,4")
+        .run();
+    run("version").assert_stdout_contains("package 4.0.0");
+
+    // Every request above was served by the daemon, which is still running.
+    assert!(daemon.try_wait().unwrap().is_none());
+    daemon.kill().unwrap();
+}
@@ -0,0 +1,3 @@
+fn main() {
+    println!("package {}", env!("CARGO_PKG_VERSION"));
+}
@@ -0,0 +1,16 @@
+pub trait Display {}
+pub trait Render {}
+
+pub struct Wrapper<T>(T);
+pub struct Pair<A, B>(A, B);
+pub struct NotDisplay;
+
+impl Display for u32 {}
+impl<T: Display> Render for Wrapper<T> {}
+impl<A: Render, B: Render> Render for Pair<A, B> {}
+
+fn render<T: Render>(_: T) {}
+
+pub fn main() {
+    render(Pair(Wrapper(1u32), Wrapper(NotDisplay)));
+}
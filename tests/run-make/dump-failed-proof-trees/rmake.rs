@@ -0,0 +1,63 @@
+// Checks that `-Zdump-failed-proof-trees` writes the proof tree of a failed goal as JSON,
+// with the nested where-bound that failed, and points to the file from the diagnostic.
+
+use run_make_support::serde_json::Value;
+use run_make_support::{has_extension, rfs, rustc, serde_json, shallow_find_files};
+
+fn find_goal<'a>(goal: &'a Value, predicate: &str) -> Option<&'a Value> {
+    if goal["goal"] == predicate {
+        return Some(goal);
+    }
+    goal["candidates"].as_array()?.iter().find_map(|candidate| {
+        candidate["nested_goals"].as_array()?.iter().find_map(|nested| find_goal(nested, predicate))
+    })
+}
+
+fn main() {
+    let stderr = rustc()
+        .crate_type("lib")
+        .input("foo.rs")
+        .arg("-Znext-solver")
+        .arg("-Zdump-failed-proof-trees=proof-trees")
+        .run_fail()
+        .assert_stderr_contains("error[E0277]")
+        .assert_stderr_contains("the proof tree of this goal was written to `proof-trees")
+        .stderr_utf8();
+
+    let files = shallow_find_files("proof-trees", |path| has_extension(path, "json"));
+    assert_eq!(files.len(), 1, "{stderr}");
+    let file_name = files[0].file_name().unwrap().to_str().unwrap();
+    assert!(file_name.starts_with("foo-proof-tree-"));
+    assert!(stderr.contains(file_name));
+
+    let root: Value = serde_json::from_str(&rfs::read_to_string(&files[0])).unwrap();
+    assert_eq!(root["goal"], "Pair<Wrapper<u32>, Wrapper<NotDisplay>>: Render");
+    assert_eq!(root["result"], "no solution");
+
+    let candidate = &root["candidates"][0];
+    assert!(candidate["kind"].as_str().unwrap().starts_with("impl `<impl Render for Pair<A, B>>`"));
+    assert_eq!(candidate["result"], "no solution");
+
+    let holds = find_goal(&root, "Wrapper<u32>: Render").unwrap();
+    assert_eq!(holds["source"], "impl where-bound");
+    assert_eq!(holds["result"], "yes");
+
+    let fails = find_goal(&root, "Wrapper<NotDisplay>: Render").unwrap();
+    assert_eq!(fails["source"], "impl where-bound");
+    assert_eq!(fails["result"], "no solution");
+
+    // The leaf goal has no candidate at all.
+    let leaf = find_goal(fails, "NotDisplay: Display").unwrap();
+    assert_eq!(leaf["result"], "no solution");
+    assert_eq!(leaf["candidates"].as_array().unwrap().len(), 0);
+
+    // Without the new solver, nothing is written, and the error says why.
+    rfs::remove_dir_all("proof-trees");
+    rustc()
+        .crate_type("lib")
+        .input("foo.rs")
+        .arg("-Zdump-failed-proof-trees=proof-trees")
+        .run_fail()
+        .assert_stderr_contains("proof trees can only be dumped with `-Znext-solver`");
+    assert!(!std::path::Path::new("proof-trees").exists());
+}
@@ -0,0 +1,29 @@
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub enum Shape {
+    Circle(Point, u32),
+    Rectangle { corner: Point, width: u32, height: u32 },
+}
+
+pub fn area(shape: &Shape) -> u32 {
+    match shape {
+        Shape::Circle(_, radius) => 3 * radius * radius,
+        Shape::Rectangle { width, height, .. } => width * height,
+    }
+}
+
+fn double<T: Copy + std::ops::Add<Output = T>>(value: T) -> T {
+    value + value
+}
+
+pub fn total(shapes: &[Shape]) -> u32 {
+    let area: fn(&Shape) -> u32 = area;
+    shapes.iter().map(area).map(double).sum()
+}
+
+pub fn generic_area<S: AsRef<Shape>>(shape: S) -> u32 {
+    area(shape.as_ref())
+}
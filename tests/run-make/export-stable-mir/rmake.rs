@@ -0,0 +1,57 @@
+// Checks that `-Zexport-stable-mir` writes the reachable function bodies of a crate, and the
+// types and ADTs they use, as JSON and as CBOR.
+
+use run_make_support::serde_json::Value;
+use run_make_support::{rfs, rustc, serde_json};
+
+fn main() {
+    rustc().crate_type("lib").input("foo.rs").arg("-Zexport-stable-mir=json").run();
+    let export: Value = serde_json::from_str(&rfs::read_to_string("foo.smir.json")).unwrap();
+
+    assert_eq!(export["schema_version"], 1);
+    assert_eq!(export["crate_name"], "foo");
+
+    let functions = export["functions"].as_array().unwrap();
+    let function = |name: &str| {
+        functions
+            .iter()
+            .find(|function| function["name"].as_str().unwrap() == name)
+            .unwrap_or_else(|| panic!("`{name}` is not exported"))
+    };
+    // `total` and `area` are non-generic, and `double::<u32>` is only reached through the iterator
+    // adapters of the standard library.
+    for name in ["total", "area", "double::<u32>"] {
+        assert!(function(name)["body"]["blocks"].is_array(), "`{name}` has no body");
+    }
+
+    let generic_functions = export["generic_functions"].as_array().unwrap();
+    assert!(generic_functions.iter().any(|function| function["name"] == "generic_area"));
+
+    // Every type of a function is in the type table.
+    let types = export["types"].as_array().unwrap();
+    for function in functions {
+        assert!(types.iter().any(|ty| ty["id"] == function["ty"]));
+    }
+
+    let adts = export["adts"].as_array().unwrap();
+    let adt = |name: &str| adts.iter().find(|adt| adt["name"] == name).unwrap();
+    let point = adt("Point");
+    assert_eq!(point["kind"], "Struct");
+    assert_eq!(point["variants"][0]["fields"][1]["name"], "y");
+    let shape = adt("Shape");
+    assert_eq!(shape["kind"], "Enum");
+    assert_eq!(shape["variants"][1]["name"], "Rectangle");
+    for field in shape["variants"][1]["fields"].as_array().unwrap() {
+        assert!(types.iter().any(|ty| ty["id"] == field["ty"]));
+    }
+
+    assert!(!export["spans"].as_array().unwrap().is_empty());
+
+    rustc().crate_type("lib").input("foo.rs").arg("-Zexport-stable-mir=cbor").run();
+    let cbor = rfs::read("foo.smir.cbor");
+    // A map with 7 entries, whose first key is the text `schema_version` and first value is 1.
+    let mut header = vec![0xa7, 0x60 | 14];
+    header.extend_from_slice(b"schema_version");
+    header.push(0x01);
+    assert!(cbor.starts_with(&header));
+}
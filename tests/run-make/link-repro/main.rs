@@ -0,0 +1,8 @@
+#[link(name = "foo", kind = "static")]
+extern "C" {
+    fn foo(x: i32) -> i32;
+}
+
+fn main() {
+    println!("{}", unsafe { foo(21) });
+}
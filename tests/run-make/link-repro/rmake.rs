@@ -0,0 +1,61 @@
+// Checks that `-Zlink-repro` writes a reproducer of the linker invocation with copies of the
+// inputs of the link, whose replay script links the same executable once the inputs are gone,
+// and that `-Zlink-repro-on-failure` only writes the reproducers of failed links.
+
+//@ ignore-cross-compile
+//@ only-linux
+
+use std::path::Path;
+
+use run_make_support::{build_native_static_lib, cmd, rfs, rustc, static_lib_name};
+
+fn main() {
+    build_native_static_lib("foo");
+    rustc().input("main.rs").arg("-Zlink-repro=repro").run();
+    cmd("./main").run().assert_stdout_contains("42");
+
+    let repro = Path::new("repro/main");
+    assert!(repro.join("replay.sh").is_file());
+    assert!(rfs::read_to_string(repro.join("linker-output")).contains("exit status: 0"));
+    let args = rfs::read_to_string(repro.join("linker-arguments"));
+    assert!(args.contains("inputs/"));
+    assert!(args.contains("output/main"));
+    assert!(args.contains("-lfoo"));
+
+    // The rlibs of the standard library and the objects of the crate are copied.
+    let mut inputs = Vec::new();
+    rfs::read_dir_entries(repro.join("inputs"), |path| {
+        inputs.push(path.file_name().unwrap().to_string_lossy().into_owned());
+    });
+    assert!(inputs.iter().any(|name| name.starts_with("libstd-") && name.ends_with(".rlib")));
+    assert!(inputs.iter().any(|name| name.starts_with("main.") && name.ends_with(".o")));
+
+    // The native library is copied from its search path.
+    let mut found = false;
+    rfs::read_dir_entries(repro.join("search-paths"), |path| {
+        found |= path.join(static_lib_name("foo")).is_file();
+    });
+    assert!(found, "libfoo.a wasn't copied");
+
+    // The replay script doesn't need the inputs of the original link.
+    rfs::remove_file(static_lib_name("foo"));
+    rfs::remove_file("main");
+    cmd("sh").arg(repro.join("replay.sh")).run();
+    cmd(repro.join("output/main")).run().assert_stdout_contains("42");
+
+    // Successful links aren't recorded with `-Zlink-repro-on-failure`.
+    build_native_static_lib("foo");
+    rustc().input("main.rs").arg("-Zlink-repro=failures").arg("-Zlink-repro-on-failure").run();
+    assert!(!Path::new("failures").exists());
+
+    rustc()
+        .input("main.rs")
+        .arg("-Zlink-repro=failures")
+        .arg("-Zlink-repro-on-failure")
+        .link_arg("-lmissing_library")
+        .run_fail()
+        .assert_stderr_contains("a reproducer of the linker invocation was written to failures");
+    let repro = Path::new("failures/main");
+    assert!(rfs::read_to_string(repro.join("linker-output")).contains("missing_library"));
+    cmd("sh").arg(repro.join("replay.sh")).run_fail().assert_stderr_contains("missing_library");
+}
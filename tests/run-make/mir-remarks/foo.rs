@@ -0,0 +1,23 @@
+#[inline]
+pub fn small(x: u32) -> u32 {
+    x + 1
+}
+
+#[inline(never)]
+pub fn never(x: u32) -> u32 {
+    x * 2
+}
+
+pub fn caller(x: u32) -> u32 {
+    small(x) + never(x)
+}
+
+pub struct Pair {
+    a: u32,
+    b: u32,
+}
+
+pub fn sum(x: u32) -> u32 {
+    let pair = Pair { a: x, b: 2 };
+    pair.a + pair.b
+}
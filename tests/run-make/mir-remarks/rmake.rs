@@ -0,0 +1,46 @@
+// Checks that `-Zmir-remarks` writes the decisions of the MIR inliner, with the cost of the
+// callees, and the locals replaced by SROA, to a file as JSON lines instead of diagnostics.
+
+use run_make_support::serde_json::Value;
+use run_make_support::{rfs, rustc, serde_json};
+
+fn main() {
+    rustc()
+        .crate_type("lib")
+        .input("foo.rs")
+        .opt()
+        .arg("-Zmir-remarks=remarks.json")
+        .arg("-Zmir-remarks-passes=Inline ScalarReplacementOfAggregates")
+        .run()
+        .assert_stderr_not_contains("note");
+    let remarks: Vec<Value> = rfs::read_to_string("remarks.json")
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let remark = |pass: &str, kind: &str, function: &str, arg: &str, value: &str| {
+        remarks
+            .iter()
+            .find(|remark| {
+                remark["pass"] == pass
+                    && remark["kind"] == kind
+                    && remark["function"] == function
+                    && remark["args"][arg] == value
+            })
+            .unwrap_or_else(|| panic!("no {kind} remark of `{pass}` with {arg} = `{value}`"))
+    };
+
+    let inlined = remark("Inline", "passed", "caller", "callee", "small");
+    assert_eq!(inlined["args"]["caller"], "caller");
+    assert_eq!(inlined["file"], "foo.rs");
+    assert_eq!(inlined["line"], 12);
+    assert!(inlined["args"]["cost"].as_u64() <= inlined["args"]["threshold"].as_u64());
+
+    let rejected = remark("Inline", "missed", "caller", "callee", "never");
+    assert_eq!(rejected["args"]["reason"], "never inline hint");
+
+    let replaced = remark("ScalarReplacementOfAggregates", "passed", "sum", "ty", "Pair");
+    assert_eq!(replaced["args"]["fields"], 2);
+
+    // Passes whose remarks were not requested stay quiet.
+    assert!(remarks.iter().all(|remark| remark["pass"] != "GVN"));
+}
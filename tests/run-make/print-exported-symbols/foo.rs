@@ -0,0 +1,19 @@
+#[no_mangle]
+pub extern "C" fn foo_add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+#[no_mangle]
+pub static FOO_ANSWER: u32 = 42;
+
+pub fn double(x: u64) -> u64 {
+    x * 2
+}
+
+pub fn identity<T>(x: T) -> T {
+    x
+}
+
+pub fn identity_u8() -> u8 {
+    identity(1u8)
+}
@@ -0,0 +1,63 @@
+// Checks that `--print exported-symbols` and `--print exported-symbols-json` list the symbols
+// exported by each crate type with their export level and kind, and the instances they were
+// generated for.
+
+//@ ignore-cross-compile
+
+use run_make_support::serde_json::Value;
+use run_make_support::{rfs, rustc, serde_json};
+
+fn find<'a>(symbols: &'a [Value], crate_type: &str, item: &str) -> Option<&'a Value> {
+    symbols.iter().find(|symbol| symbol["crate_type"] == crate_type && symbol["item"] == item)
+}
+
+fn main() {
+    rustc()
+        .input("foo.rs")
+        .crate_type("cdylib,rlib")
+        .arg("-Zunstable-options")
+        .arg("-Zshare-generics=yes")
+        .print("exported-symbols-json=symbols.json")
+        .print("exported-symbols=symbols.txt")
+        .run();
+
+    let symbols: Value = serde_json::from_str(&rfs::read_to_string("symbols.json")).unwrap();
+    let symbols = symbols.as_array().unwrap();
+
+    let add = find(symbols, "cdylib", "foo_add").unwrap();
+    assert_eq!(add["symbol"], "foo_add");
+    assert_eq!(add["level"], "C");
+    assert_eq!(add["kind"], "text");
+    assert_eq!(add["crate"], "foo");
+
+    let answer = find(symbols, "cdylib", "FOO_ANSWER").unwrap();
+    assert_eq!(answer["kind"], "data");
+
+    // Rust-level symbols are only exported by the rlib.
+    assert!(find(symbols, "cdylib", "double").is_none());
+    let double = find(symbols, "rlib", "double").unwrap();
+    assert_eq!(double["level"], "Rust");
+    assert_ne!(double["symbol"], "double");
+
+    // Generic instantiations are shared with downstream crates.
+    let identity = find(symbols, "rlib", "identity").unwrap();
+    assert_eq!(identity["generic_args"], serde_json::json!(["u8"]));
+
+    let text = rfs::read_to_string("symbols.txt");
+    assert!(text.lines().any(|line| line == "cdylib\tC\ttext\tfoo_add\tfoo_add"), "{text}");
+    assert!(text.lines().any(|line| line == "cdylib\tC\tdata\tFOO_ANSWER\tFOO_ANSWER"), "{text}");
+    assert!(
+        text.lines().any(
+            |line| line.starts_with("rlib\tRust\ttext\t") && line.ends_with("\tidentity::<u8>")
+        ),
+        "{text}"
+    );
+
+    // The print request is unstable.
+    rustc()
+        .input("foo.rs")
+        .crate_type("cdylib")
+        .print("exported-symbols")
+        .run_fail()
+        .assert_stderr_contains("the `-Z unstable-options` flag must also be passed");
+}
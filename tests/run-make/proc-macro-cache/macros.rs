@@ -0,0 +1,25 @@
+#![feature(proc_macro_deterministic)]
+
+extern crate proc_macro;
+use proc_macro::TokenStream;
+
+#[proc_macro]
+#[proc_macro_deterministic]
+pub fn cached(input: TokenStream) -> TokenStream {
+    eprintln!("running cached!");
+    format!("pub fn cached() -> u32 {{ {input} }}").parse().unwrap()
+}
+
+#[proc_macro_derive(Answer)]
+#[proc_macro_deterministic]
+pub fn derive_answer(input: TokenStream) -> TokenStream {
+    eprintln!("running derive(Answer)");
+    let name = input.into_iter().nth(1).unwrap();
+    format!("impl {name} {{ pub fn answer() -> u32 {{ 42 }} }}").parse().unwrap()
+}
+
+#[proc_macro]
+pub fn uncached(input: TokenStream) -> TokenStream {
+    eprintln!("running uncached!");
+    format!("pub fn uncached() -> u32 {{ {input} }}").parse().unwrap()
+}
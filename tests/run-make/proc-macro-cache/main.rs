@@ -0,0 +1,13 @@
+extern crate macros;
+
+macros::cached!(1 + 1);
+macros::uncached!(2 + 2);
+
+#[derive(macros::Answer)]
+struct Foo;
+
+fn main() {
+    assert_eq!(cached(), 2);
+    assert_eq!(uncached(), 4);
+    assert_eq!(Foo::answer(), 42);
+}
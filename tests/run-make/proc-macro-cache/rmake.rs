@@ -0,0 +1,60 @@
+// Checks that with `-Zcache-proc-macros`, expansions of `#[proc_macro_deterministic]` macros
+// are replayed from the previous incremental session instead of running the macro again,
+// while other macros still run every time.
+
+//@ ignore-cross-compile
+//@ needs-dynamic-linking
+
+use run_make_support::path_helpers::read_dir_entries_recursive;
+use run_make_support::{rfs, run, rustc};
+
+fn main() {
+    rustc().input("macros.rs").crate_type("proc-macro").run();
+    let build =
+        || rustc().input("main.rs").arg("-Cincremental=incr").arg("-Zcache-proc-macros").run();
+
+    let first = build();
+    first.assert_stderr_contains("running cached!");
+    first.assert_stderr_contains("running derive(Answer)");
+    first.assert_stderr_contains("running uncached!");
+    run("main");
+
+    let second = build();
+    second.assert_stderr_not_contains("running cached!");
+    second.assert_stderr_not_contains("running derive(Answer)");
+    second.assert_stderr_contains("running uncached!");
+    run("main");
+
+    // Changing the input of a cached macro runs it again.
+    rfs::write("main.rs", rfs::read_to_string("main.rs").replace("1 + 1", "1 + 2 - 1"));
+    let third = build();
+    third.assert_stderr_contains("running cached!");
+    third.assert_stderr_not_contains("running derive(Answer)");
+    run("main");
+
+    // A truncated cache file is treated as empty.
+    read_dir_entries_recursive("incr", |path| {
+        if path.ends_with("proc-macro-cache.bin") {
+            let data = rfs::read(path);
+            rfs::remove_file(path);
+            rfs::write(path, &data[..data.len() / 2]);
+        }
+    });
+    let fourth = build();
+    fourth.assert_stderr_contains("running cached!");
+    run("main");
+
+    // So is a cache file whose entries were corrupted.
+    read_dir_entries_recursive("incr", |path| {
+        if path.ends_with("proc-macro-cache.bin") {
+            let mut data = rfs::read(path);
+            rfs::remove_file(path);
+            let middle = data.len() / 2;
+            data[middle] ^= 0xff;
+            rfs::write(path, &data);
+        }
+    });
+    let fifth = build();
+    fifth.assert_stderr_contains("running cached!");
+    run("main");
+}
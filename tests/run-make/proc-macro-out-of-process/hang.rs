@@ -0,0 +1,5 @@
+extern crate macros;
+
+macros::hang!();
+
+fn main() {}
@@ -0,0 +1,29 @@
+#![feature(proc_macro_expand)]
+
+extern crate proc_macro;
+use proc_macro::TokenStream;
+
+#[proc_macro]
+pub fn answer(_: TokenStream) -> TokenStream {
+    println!("printed from a proc macro");
+    "42".parse().unwrap()
+}
+
+#[proc_macro]
+pub fn nested(_: TokenStream) -> TokenStream {
+    // Expanding `answer!` runs it in a helper of its own while this one waits.
+    let expr: TokenStream = "answer!()".parse().unwrap();
+    expr.expand_expr().unwrap()
+}
+
+#[proc_macro]
+pub fn crash(_: TokenStream) -> TokenStream {
+    std::process::abort()
+}
+
+#[proc_macro]
+pub fn hang(_: TokenStream) -> TokenStream {
+    loop {
+        std::thread::park();
+    }
+}
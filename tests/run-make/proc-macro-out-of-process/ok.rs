@@ -0,0 +1,7 @@
+extern crate macros;
+use macros::{answer, nested};
+
+fn main() {
+    assert_eq!(answer!(), 42);
+    assert_eq!(nested!(), 42);
+}
@@ -0,0 +1,38 @@
+// Checks that with `-Zproc-macro-execution-strategy=out-of-process`, proc macros work as usual,
+// and that a macro that crashes or hangs is reported as an error instead of taking down the
+// compiler.
+
+//@ ignore-cross-compile
+//@ needs-dynamic-linking
+
+use run_make_support::{run, rustc};
+
+fn main() {
+    rustc().input("macros.rs").crate_type("proc-macro").run();
+
+    let out_of_process = || {
+        let mut rustc = rustc();
+        rustc.arg("-Zproc-macro-execution-strategy=out-of-process");
+        rustc
+    };
+
+    // Output printed by the macro must not corrupt the messages of the helper process.
+    out_of_process()
+        .input("ok.rs")
+        .run()
+        .assert_stderr_contains("printed from a proc macro")
+        .assert_stdout_not_contains("printed from a proc macro");
+    run("ok");
+
+    out_of_process()
+        .input("crash.rs")
+        .run_fail()
+        .assert_stderr_contains("proc macro panicked")
+        .assert_stderr_contains("the proc-macro helper process exited unexpectedly");
+
+    out_of_process()
+        .input("hang.rs")
+        .arg("-Zproc-macro-timeout=1")
+        .run_fail()
+        .assert_stderr_contains("the proc macro did not finish within 1 seconds");
+}
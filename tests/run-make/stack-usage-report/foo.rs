@@ -0,0 +1,28 @@
+#![crate_type = "lib"]
+
+#[inline(never)]
+fn leaf(buf: &mut [u8; 256]) -> u8 {
+    std::hint::black_box(&mut *buf);
+    buf[0] ^ buf[255]
+}
+
+#[no_mangle]
+pub extern "C" fn bounded(n: u8) -> u8 {
+    let mut buf = [n; 256];
+    leaf(&mut buf)
+}
+
+#[no_mangle]
+pub extern "C" fn recursive(n: u32) -> u32 {
+    if n == 0 { 0 } else { recursive(std::hint::black_box(n - 1)) + 1 }
+}
+
+#[no_mangle]
+pub extern "C" fn indirect(f: extern "C" fn(u8) -> u8) -> u8 {
+    f(1)
+}
+
+#[no_mangle]
+pub fn indexed(values: &[u8], index: usize) -> u8 {
+    values[index]
+}
@@ -0,0 +1,53 @@
+// Checks that `-Zstack-usage-report` combines the stack sizes of the functions along the deepest
+// call chain of each entry point, and reports why the stack usage of other entry points is not
+// bounded.
+
+//@ ignore-windows
+//@ ignore-apple
+// Reason: the stack sizes are only emitted in ELF object files.
+
+use run_make_support::serde_json::Value;
+use run_make_support::{rfs, rustc, serde_json};
+
+fn main() {
+    rustc().input("foo.rs").opt().arg("-Zstack-usage-report=stack-usage.json").run();
+    let report: Value = serde_json::from_str(&rfs::read_to_string("stack-usage.json")).unwrap();
+    let entry_points = report["entry_points"].as_array().unwrap();
+    let entry_point = |symbol: &str| {
+        entry_points.iter().find(|entry_point| entry_point["symbol"] == symbol).unwrap()
+    };
+    let reasons = |entry_point: &Value| {
+        entry_point["unbounded"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|unbounded| unbounded["reason"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    // `leaf` has its own frame, and `bounded` needs at least 256 bytes for its buffer.
+    let bounded = entry_point("bounded");
+    assert_eq!(bounded["bounded"], true, "{bounded:#}");
+    assert!(bounded["stack_size"].as_u64().unwrap() >= 256, "{bounded:#}");
+    let call_chain = bounded["call_chain"].as_array().unwrap();
+    assert_eq!(call_chain.len(), 2, "{bounded:#}");
+    assert!(call_chain[1].as_str().unwrap().ends_with("leaf"), "{bounded:#}");
+
+    let recursive = entry_point("recursive");
+    assert_eq!(recursive["bounded"], false, "{recursive:#}");
+    assert_eq!(reasons(recursive), ["recursion"], "{recursive:#}");
+
+    let indirect = entry_point("indirect");
+    assert_eq!(indirect["bounded"], false, "{indirect:#}");
+    assert_eq!(reasons(indirect), ["indirect_call"], "{indirect:#}");
+
+    // The bounds check calls the panic function of `core`, whose stack usage is not known.
+    let indexed = entry_point("indexed");
+    assert_eq!(indexed["bounded"], false, "{indexed:#}");
+    let callees = indexed["unbounded"].as_array().unwrap();
+    assert!(
+        callees.iter().any(|unbounded| unbounded["reason"] == "external_call"
+            && unbounded["callee"].as_str().unwrap().ends_with("panic_bounds_check")),
+        "{indexed:#}"
+    );
+}
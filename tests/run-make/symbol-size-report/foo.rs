@@ -0,0 +1,27 @@
+mod shapes {
+    pub trait Shape {
+        fn area(&self) -> f64;
+    }
+
+    pub struct Circle(pub f64);
+
+    impl Shape for Circle {
+        fn area(&self) -> f64 {
+            3.14 * self.0 * self.0
+        }
+    }
+
+    #[inline(never)]
+    pub fn scale<T: Copy + Into<f64>>(value: T) -> f64 {
+        value.into() * 2.0
+    }
+}
+
+static TABLE: [u8; 4096] = [1; 4096];
+
+fn main() {
+    let radius = shapes::scale(std::env::args().count() as u32) + shapes::scale(1.5f32);
+    let shapes: Vec<Box<dyn shapes::Shape>> = vec![Box::new(shapes::Circle(radius))];
+    println!("{}", shapes.iter().map(|shape| shape.area()).sum::<f64>());
+    println!("{}", TABLE[std::env::args().count()]);
+}
@@ -0,0 +1,84 @@
+// Checks that `-Zsymbol-size-report` attributes the symbols of the linked executable to the
+// crates, modules and items defining them, including instantiations of generic functions,
+// statics, vtables and the code of the standard library.
+
+//@ ignore-cross-compile
+//@ only-linux
+
+use run_make_support::serde_json::Value;
+use run_make_support::{rfs, rustc, serde_json};
+
+fn find<'a>(groups: &'a Value, name: &str) -> &'a Value {
+    groups["contents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|group| group["name"] == name)
+        .unwrap_or_else(|| panic!("no `{name}` in {groups:#}"))
+}
+
+fn main() {
+    rustc().input("foo.rs").arg("-Zsymbol-size-report=sizes.json").run();
+
+    let report: Value = serde_json::from_str(&rfs::read_to_string("sizes.json")).unwrap();
+    let artifacts = report["artifacts"].as_array().unwrap();
+    assert_eq!(artifacts.len(), 1);
+    let artifact = &artifacts[0];
+    assert!(artifact["path"].as_str().unwrap().ends_with("foo"));
+
+    let text = artifact["sections"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|section| section["name"] == ".text")
+        .unwrap();
+    assert_eq!(text["kind"], "text");
+    assert!(text["symbols_size"].as_u64().unwrap() <= text["size"].as_u64().unwrap());
+
+    // `find` expects a `contents` field, so wrap the crates of the artifact.
+    let crates = serde_json::json!({ "contents": artifact["crates"] });
+    let krate = find(&crates, "foo");
+    let sizes: Vec<u64> = crates["contents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["size"].as_u64().unwrap())
+        .collect();
+    assert!(sizes.windows(2).all(|pair| pair[0] >= pair[1]), "crates are sorted by size");
+
+    // Both instantiations of the generic function are grouped under the same item.
+    let scale = find(find(krate, "foo::shapes"), "shapes::scale");
+    let instances: Vec<&str> = scale["contents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| symbol["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(instances.len(), 2);
+    assert!(instances.contains(&"shapes::scale::<u32>"));
+    assert!(instances.contains(&"shapes::scale::<f32>"));
+
+    let table = &find(find(krate, "foo"), "TABLE")["contents"][0];
+    assert_eq!(table["kind"], "static");
+    assert_eq!(table["size"], 4096);
+
+    check_vtable(krate);
+
+    // The code of the standard library is attributed by demangling its symbols.
+    assert!(find(&crates, "std")["size"].as_u64().unwrap() > 0);
+
+    // The vtable is still attributed when incremental compilation reuses its codegen unit.
+    for _ in 0..2 {
+        rustc().input("foo.rs").incremental("incr").arg("-Zsymbol-size-report=incr.json").run();
+    }
+    let report: Value = serde_json::from_str(&rfs::read_to_string("incr.json")).unwrap();
+    let crates = serde_json::json!({ "contents": report["artifacts"][0]["crates"] });
+    check_vtable(find(&crates, "foo"));
+}
+
+fn check_vtable(krate: &Value) {
+    let vtable_name = "<foo::shapes::Circle as foo::shapes::Shape>::{vtable}";
+    let vtable = &find(find(krate, "foo::shapes"), vtable_name)["contents"][0];
+    assert_eq!(vtable["kind"], "vtable");
+    assert!(vtable["size"].as_u64().unwrap() > 0);
+}
@@ -5,7 +5,8 @@
 // using them correctly, or fails with the right error message when using them improperly.
 // See https://github.com/rust-lang/rust/pull/16156
 
-use run_make_support::{diff, rfs, rustc};
+use run_make_support::serde_json::Value;
+use run_make_support::{diff, rfs, rustc, serde_json};
 
 fn main() {
     rustc().input("foo.rs").target("my-awesome-platform.json").crate_type("lib").emit("asm").run();
@@ -20,6 +21,11 @@ fn main() {
         .target("my-incomplete-platform.json")
         .run_fail()
         .assert_stderr_contains("Field llvm-target");
+    rustc()
+        .input("foo.rs")
+        .target("my-misspelled-platform.json")
+        .run_fail()
+        .assert_stderr_contains("`panic_strategy` (did you mean `panic-strategy`?)");
     rustc()
         .env("RUST_TARGET_PATH", ".")
         .input("foo.rs")
@@ -52,6 +58,19 @@ fn main() {
         .expected_file("test-platform.json")
         .actual_text("test-platform-2", test_platform_2)
         .run();
+    // The schema describes every field of the printed target specification.
+    let schema = rustc()
+        .arg("-Zunstable-options")
+        .target("my-awesome-platform.json")
+        .print("target-spec-json-schema")
+        .run()
+        .stdout_utf8();
+    let schema: Value = serde_json::from_str(&schema).unwrap();
+    let properties = schema["properties"].as_object().unwrap();
+    let test_platform: Value = serde_json::from_str(&test_platform).unwrap();
+    for field in test_platform.as_object().unwrap().keys() {
+        assert!(properties.contains_key(field), "`{field}` is not in the schema");
+    }
     rustc()
         .input("foo.rs")
         .target("endianness-mismatch")
@@ -0,0 +1,13 @@
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub fn distance<T: Into<f64>>(a: T, b: T) -> f64 {
+    (a.into() - b.into()).abs()
+}
+
+#[inline]
+pub fn origin() -> Point {
+    Point { x: 0, y: 0 }
+}
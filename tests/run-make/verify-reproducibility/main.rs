@@ -0,0 +1,4 @@
+fn main() {
+    let numbers: Vec<u32> = (1..10).collect();
+    println!("{}", numbers.iter().sum::<u32>());
+}
@@ -0,0 +1,37 @@
+// Checks that `-Zverify-reproducibility` accepts a crate whose outputs don't depend on where it
+// is compiled, and that it reports the unremapped working directory in the metadata of an rlib
+// until it is remapped with `--remap-path-prefix`.
+
+//@ ignore-cross-compile
+//@ only-linux
+
+use std::env;
+
+use run_make_support::{cmd, rustc};
+
+fn main() {
+    rustc().input("main.rs").arg("-Zverify-reproducibility").run();
+    cmd("./main").run().assert_stdout_contains("45");
+
+    let cwd = env::current_dir().unwrap();
+    rustc()
+        .input("foo.rs")
+        .crate_type("rlib")
+        .arg("-Zverify-reproducibility")
+        .run_fail()
+        .assert_stderr_contains("libfoo.rlib` is not reproducible")
+        .assert_stderr_contains("of member `lib.rmeta`")
+        .assert_stderr_contains("in the crate metadata")
+        .assert_stderr_contains(format!(
+            "it contains the working directory `{}`, which is not remapped",
+            cwd.display()
+        ))
+        .assert_stderr_contains("--remap-path-prefix");
+
+    rustc()
+        .input("foo.rs")
+        .crate_type("rlib")
+        .arg("-Zverify-reproducibility")
+        .arg(format!("--remap-path-prefix={}=/remapped", cwd.display()))
+        .run();
+}
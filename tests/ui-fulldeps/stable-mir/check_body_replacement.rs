@@ -0,0 +1,118 @@
+//@ run-pass
+//! Test that a driver can replace the optimized MIR of a function with a modified stable body.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::{
+    BasicBlock, Body, ConstOperand, Mutability, Operand, Place, RETURN_LOCAL, Rvalue, Statement,
+    StatementKind, Terminator, TerminatorKind,
+};
+use stable_mir::ty::{MirConst, Ty, UintTy};
+use stable_mir::{CrateDef, CrateItem};
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::process::Command;
+
+const CRATE_NAME: &str = "input";
+
+/// Make `answer` return 42 by storing 42 into the return place right before it returns.
+fn replace_answer(item: CrateItem, body: &Body) -> Option<Body> {
+    if item.name() != "answer" {
+        return None;
+    }
+    let mut body = body.clone();
+    let span = body.span;
+    let u32_ty = Ty::unsigned_ty(UintTy::U32);
+
+    let tmp = body.new_local(u32_ty, span, Mutability::Not);
+    let last_block = body.blocks.len() - 1;
+    let index = body.blocks[last_block].statements.len();
+    let ret = body.split_block(last_block, index);
+    assert_eq!(body.blocks[ret].terminator.kind, TerminatorKind::Return);
+
+    let constant = ConstOperand {
+        span,
+        user_ty: None,
+        const_: MirConst::try_from_uint(42, UintTy::U32).unwrap(),
+    };
+    let store = body.new_block(BasicBlock {
+        statements: vec![Statement {
+            kind: StatementKind::Assign(
+                Place::from(RETURN_LOCAL),
+                Rvalue::Use(Operand::Move(Place::from(tmp))),
+            ),
+            span,
+        }],
+        terminator: Terminator { kind: TerminatorKind::Goto { target: ret }, span },
+    });
+    body.insert_statement(
+        store,
+        0,
+        Statement {
+            kind: StatementKind::Assign(
+                Place::from(tmp),
+                Rvalue::Use(Operand::Constant(constant)),
+            ),
+            span,
+        },
+    );
+    // Go through the new block instead of returning right away.
+    let successors = body.blocks[last_block].terminator.kind.successors_mut();
+    assert_eq!(successors.len(), 1);
+    *successors.into_iter().next().unwrap() = store;
+    Some(body)
+}
+
+/// This test will generate a dummy binary crate, compile it with the body of `answer` replaced,
+/// and check that the binary uses the new body.
+fn main() {
+    let path = "body_replacement_input.rs";
+    let output = "body_replacement_output";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=bin".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        "-o".to_string(),
+        output.to_string(),
+        path.to_string(),
+    ];
+    rustc_internal::replace_optimized_mir(replace_answer);
+    run!(args, || ControlFlow::<()>::Continue(())).unwrap();
+
+    let status = Command::new(format!("./{output}")).status().unwrap();
+    assert_eq!(status.code(), Some(42));
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        #[inline(never)]
+        fn answer() -> u32 {{
+            1
+        }}
+
+        fn main() {{
+            std::process::exit(answer() as i32);
+        }}
+        "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//@ run-pass
+//! Test that a replacement of the optimized MIR that cannot be converted back from stable MIR is
+//! reported as an error instead of crashing the compiler.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ needs-asm-support
+
+#![feature(rustc_private)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::Body;
+use stable_mir::{CrateDef, CrateItem};
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+/// Return the unmodified body of `with_asm`, which contains inline assembly.
+fn replace_with_asm(item: CrateItem, body: &Body) -> Option<Body> {
+    (item.name() == "with_asm").then(|| body.clone())
+}
+
+/// This test will generate a dummy library crate and check that compiling it with the body of
+/// `with_asm` replaced fails with an error.
+fn main() {
+    let path = "body_replacement_rejected_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        "--emit=link".to_string(),
+        path.to_string(),
+    ];
+    rustc_internal::replace_optimized_mir(replace_with_asm);
+    let result = run!(args, || ControlFlow::<()>::Continue(()));
+    assert!(result.is_err());
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        pub fn with_asm() {{
+            unsafe {{ std::arch::asm!("") }}
+        }}
+        "#
+    )?;
+    Ok(())
+}
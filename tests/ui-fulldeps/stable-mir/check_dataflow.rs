@@ -0,0 +1,202 @@
+//@ run-pass
+//! Test that users can define dataflow analyses over stable MIR bodies, and use the analyses of
+//! the compiler.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::CrateDef;
+use stable_mir::mir::dataflow::{
+    Analysis, BitSet, Direction, Location, maybe_borrowed_locals, maybe_initialized_places,
+};
+use stable_mir::mir::mono::Instance;
+use stable_mir::mir::{
+    Body, Operand, Place, RETURN_LOCAL, Rvalue, Statement, StatementKind, Terminator,
+    TerminatorKind,
+};
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+/// Whether a block can be reached from the start of the body.
+struct Reachable;
+
+impl Analysis for Reachable {
+    type Domain = bool;
+
+    fn bottom_value(&self, _body: &Body) -> bool {
+        false
+    }
+
+    fn initialize_start_block(&self, _body: &Body, state: &mut bool) {
+        *state = true;
+    }
+
+    fn apply_statement_effect(&mut self, _: &mut bool, _: &Statement, _: Location) {}
+
+    fn apply_terminator_effect(&mut self, _: &mut bool, _: &Terminator, _: Location) {}
+}
+
+/// The locals whose value may be read later, ignoring projections and borrows.
+struct LiveLocals;
+
+impl LiveLocals {
+    fn read(state: &mut BitSet, operand: &Operand) {
+        if let Operand::Copy(place) | Operand::Move(place) = operand {
+            state.insert(place.local);
+        }
+    }
+}
+
+impl Analysis for LiveLocals {
+    type Domain = BitSet;
+    const DIRECTION: Direction = Direction::Backward;
+
+    fn bottom_value(&self, body: &Body) -> BitSet {
+        BitSet::new_empty(body.local_decls().count())
+    }
+
+    fn apply_statement_effect(&mut self, state: &mut BitSet, statement: &Statement, _: Location) {
+        if let StatementKind::Assign(place, rvalue) = &statement.kind {
+            if place.projection.is_empty() {
+                state.remove(place.local);
+            }
+            match rvalue {
+                Rvalue::Use(operand) | Rvalue::Cast(_, operand, _) => Self::read(state, operand),
+                Rvalue::BinaryOp(_, lhs, rhs) | Rvalue::CheckedBinaryOp(_, lhs, rhs) => {
+                    Self::read(state, lhs);
+                    Self::read(state, rhs);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut BitSet,
+        terminator: &Terminator,
+        _: Location,
+    ) {
+        match &terminator.kind {
+            TerminatorKind::Return => {
+                state.insert(RETURN_LOCAL);
+            }
+            TerminatorKind::Call { args, .. } => args.iter().for_each(|arg| Self::read(state, arg)),
+            _ => {}
+        }
+    }
+
+    fn apply_call_return_effect(&mut self, state: &mut BitSet, _: usize, destination: &Place) {
+        if destination.projection.is_empty() {
+            state.remove(destination.local);
+        }
+    }
+}
+
+fn test_dataflow() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let item = *items.iter().find(|item| item.name() == "input").unwrap();
+    let instance = Instance::try_from(item).unwrap();
+    let body = instance.body().unwrap();
+    let start = Location { block: 0, statement_index: 0 };
+    let return_location = return_location(&body);
+
+    let reachable = Reachable.iterate_to_fixpoint(&body);
+    assert!(*reachable.block_start(0));
+    assert!(*reachable.state_before(return_location));
+
+    let live = LiveLocals.iterate_to_fixpoint(&body);
+    assert!(live.state_before(return_location).contains(RETURN_LOCAL));
+    assert!(!live.state_before(start).contains(RETURN_LOCAL));
+    assert!(!live.block_end(return_location.block).contains(RETURN_LOCAL));
+
+    // `local` is only borrowed once `&local` is evaluated.
+    let (borrow_location, borrowed) = find_borrow(&body);
+    let borrowed_locals = maybe_borrowed_locals(&instance);
+    assert!(borrowed_locals.block_start(0).is_empty());
+    assert!(!borrowed_locals.state_before(borrow_location).contains(borrowed));
+    assert!(borrowed_locals.state_after(borrow_location).contains(borrowed));
+
+    // The argument is initialized on entry, and the return place when returning.
+    let inits = maybe_initialized_places(&instance);
+    let path = |local| inits.move_paths.iter().position(|place| *place == Place::from(local));
+    let (arg, ret) = (path(1).unwrap(), path(RETURN_LOCAL).unwrap());
+    assert!(inits.results.state_before(start).contains(arg));
+    assert!(!inits.results.state_before(start).contains(ret));
+    assert!(inits.results.state_before(return_location).contains(ret));
+
+    ControlFlow::Continue(())
+}
+
+/// The location of the `Return` terminator of the body.
+fn return_location(body: &Body) -> Location {
+    let (block, data) = body
+        .blocks
+        .iter()
+        .enumerate()
+        .find(|(_, data)| data.terminator.kind == TerminatorKind::Return)
+        .unwrap();
+    Location { block, statement_index: data.statements.len() }
+}
+
+/// The location of the statement that borrows a local, and that local.
+fn find_borrow(body: &Body) -> (Location, usize) {
+    for (block, data) in body.blocks.iter().enumerate() {
+        for (statement_index, statement) in data.statements.iter().enumerate() {
+            if let StatementKind::Assign(_, Rvalue::Ref(_, _, place)) = &statement.kind {
+                if place.projection.is_empty() {
+                    return (Location { block, statement_index }, place.local);
+                }
+            }
+        }
+    }
+    panic!("no local is borrowed");
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+fn main() {
+    let path = "dataflow_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_dataflow).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        #[inline(never)]
+        pub fn observe(value: &u32) -> u32 {{
+            *value
+        }}
+
+        pub fn input(arg: u32) -> u32 {{
+            let local = arg + 1;
+            observe(&local)
+        }}
+        "#
+    )?;
+    Ok(())
+}
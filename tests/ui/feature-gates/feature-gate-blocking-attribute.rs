@@ -0,0 +1,4 @@
+#![crate_type = "lib"]
+
+#[blocking] //~ ERROR the `#[blocking]` attribute is an experimental feature
+pub fn wait() {}
@@ -0,0 +1,26 @@
+// Checks that `#[blocking]` is only accepted on functions.
+
+#![feature(blocking_attribute)]
+
+#[blocking]
+fn wait() {}
+
+extern "C" {
+    #[blocking]
+    fn foreign_wait();
+}
+
+struct Queue;
+
+impl Queue {
+    #[blocking]
+    fn pop(&self) {}
+}
+
+#[blocking] //~ ERROR attribute should be applied to a function definition
+struct NotAFunction;
+
+#[blocking] //~ ERROR attribute should be applied to a function definition
+const NOT_A_FUNCTION: () = ();
+
+fn main() {}
@@ -0,0 +1,44 @@
+//@ edition: 2021
+// Checks that calls to blocking functions are reported in async functions,
+// blocks and closures, but not in synchronous code or in closures defined
+// inside async code.
+
+#![feature(blocking_attribute)]
+#![deny(blocking_in_async)]
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[blocking]
+fn wait_for_input() {}
+
+async fn in_async_fn(m: &Mutex<u32>) {
+    std::thread::sleep(Duration::from_millis(1)); //~ ERROR call to blocking function
+    let _guard = m.lock(); //~ ERROR call to blocking function
+    wait_for_input(); //~ ERROR call to blocking function
+}
+
+fn in_async_block() -> impl std::future::Future<Output = ()> {
+    async {
+        let _ = std::fs::read_to_string("input.txt"); //~ ERROR call to blocking function
+    }
+}
+
+async fn in_async_closure() {
+    let c = async || {
+        let _ = std::fs::File::open("input.txt"); //~ ERROR call to blocking function
+    };
+    c().await;
+}
+
+async fn in_nested_closure() {
+    let c = || std::thread::sleep(Duration::from_millis(1));
+    std::thread::spawn(c);
+}
+
+fn in_sync_fn() {
+    std::thread::sleep(Duration::from_millis(1));
+    wait_for_input();
+}
+
+fn main() {}
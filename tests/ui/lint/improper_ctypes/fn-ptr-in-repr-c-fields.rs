@@ -0,0 +1,52 @@
+#![deny(improper_ctypes, improper_ctypes_definitions)]
+#![allow(dead_code)]
+
+// Checks that the function pointers reachable through the fields of `#[repr(C)]` types and
+// through type aliases are checked, and that the fields through which a type that isn't
+// FFI-safe is reached are pointed at.
+
+pub struct NotSafe(u32);
+
+#[repr(C)]
+pub struct Callbacks<T> {
+    pub user_data: *mut u8,
+    pub on_event: extern "C" fn(T),
+}
+
+#[repr(C)]
+pub struct Api {
+    pub callbacks: Callbacks<NotSafe>,
+    //~^ ERROR `extern` fn uses type `NotSafe`, which is not FFI-safe
+}
+
+pub type Callback = extern "C" fn(&str);
+//~^ ERROR `extern` fn uses type `str`, which is not FFI-safe
+
+#[repr(C)]
+pub struct Handler {
+    pub callback: Callback,
+    //~^ ERROR `extern` fn uses type `str`, which is not FFI-safe
+}
+
+pub fn register(_callbacks: Callbacks<char>) {}
+//~^ ERROR `extern` fn uses type `char`, which is not FFI-safe
+
+pub fn register_both(_callbacks: (Callbacks<u8>, Callbacks<char>)) {}
+//~^ ERROR `extern` fn uses type `char`, which is not FFI-safe
+
+#[repr(C)]
+pub struct Inner {
+    pub value: NotSafe,
+}
+
+#[repr(C)]
+pub struct Outer {
+    pub inner: Inner,
+}
+
+extern "C" {
+    fn take_outer(outer: Outer);
+    //~^ ERROR `extern` block uses type `NotSafe`, which is not FFI-safe
+}
+
+fn main() {}
@@ -0,0 +1,77 @@
+//@ run-rustfix
+#![deny(missing_must_use)]
+#![crate_type = "lib"]
+
+use std::cell::Cell;
+
+pub fn double(x: u32) -> u32 {
+    //~^ ERROR function `double` has no side effects but isn't marked `#[must_use]`
+    quadruple(x) / 2
+}
+
+fn quadruple(x: u32) -> u32 {
+    x * 4
+}
+
+pub struct Builder {
+    size: usize,
+}
+
+impl Builder {
+    pub fn size(mut self, size: usize) -> Self {
+        //~^ ERROR method `size` has no side effects but isn't marked `#[must_use]`
+        self.size = size;
+        self
+    }
+
+    // Mutates through `&mut self`.
+    pub fn grow(&mut self) -> usize {
+        self.size += 1;
+        self.size
+    }
+}
+
+// Calls a function which prints.
+pub fn noisy_double(x: u32) -> u32 {
+    log(x);
+    x * 2
+}
+
+fn log(x: u32) {
+    println!("{x}");
+}
+
+// Reaches interior mutability.
+pub fn bump(counter: &Cell<u32>) -> u32 {
+    counter.replace(counter.get() + 1)
+}
+
+static mut COUNTER: u32 = 0;
+
+// Reads a mutable static.
+pub fn counter() -> u32 {
+    unsafe { COUNTER }
+}
+
+// Calls a closure whose body isn't known.
+pub fn apply<F: Fn(u32) -> u32>(f: F) -> u32 {
+    f(1)
+}
+
+// Already returns a `#[must_use]` type.
+pub fn checked(x: u32) -> Result<u32, ()> {
+    Ok(x)
+}
+
+// Already marked.
+#[must_use]
+pub fn triple(x: u32) -> u32 {
+    x * 3
+}
+
+impl Clone for Builder {
+    // `#[must_use]` has no effect on trait impl methods.
+    fn clone(&self) -> Self {
+        Builder { size: self.size }
+    }
+}
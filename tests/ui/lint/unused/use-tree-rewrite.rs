@@ -0,0 +1,37 @@
+//@ run-rustfix
+//@ check-pass
+
+// Check that the suggestions of `unused_imports` rewrite the `use` trees minimally.
+
+#![warn(unused_imports)]
+
+pub mod nested {
+    pub struct A;
+    pub struct B;
+    pub struct C;
+    pub struct D;
+    pub mod inner {
+        pub struct E;
+    }
+}
+
+// An unused `self` doesn't prevent the braces from being removed.
+use nested::inner::{self, E};
+//~^ WARN unused import
+
+// The braces around a renamed `self` are kept.
+use nested::{self as renamed, D};
+//~^ WARN unused import
+
+// The lines of the unused imports are removed, keeping the comments of the others.
+use nested::{
+    A, // first
+    B, // second
+    C, // third
+    inner::{}, // empty
+};
+//~^^^^ WARN unused imports
+
+fn main() {
+    let _ = (E, renamed::D, A, C);
+}
@@ -0,0 +1,58 @@
+// Check that large matches of a `&str` against string literals, which are lowered as a decision
+// tree on the length and the bytes of the string, still pick the first matching arm.
+
+//@ run-pass
+
+fn classify(s: &str, guard: bool) -> u32 {
+    match s {
+        "" => 0,
+        "a" => 1,
+        "ab" if guard => 2,
+        "ab" => 3,
+        "ac" => 4,
+        "ba" => 5,
+        "é" => 6,
+        "abc" => 7,
+        "abd" => 8,
+        "xbc" => 9,
+        _ => 11,
+    }
+}
+
+fn classify_pair(pair: (&str, bool)) -> u32 {
+    match pair {
+        ("fn", _) => 0,
+        ("if", true) => 1,
+        ("in", _) => 2,
+        ("if", false) => 3,
+        ("let", _) => 4,
+        (_, true) => 5,
+        ("mod", _) => 6,
+        _ => 7,
+    }
+}
+
+fn main() {
+    assert_eq!(classify("", false), 0);
+    assert_eq!(classify("a", false), 1);
+    assert_eq!(classify("ab", true), 2);
+    assert_eq!(classify("ab", false), 3);
+    assert_eq!(classify("ac", false), 4);
+    assert_eq!(classify("ba", false), 5);
+    assert_eq!(classify("é", false), 6);
+    assert_eq!(classify("abc", false), 7);
+    assert_eq!(classify("abd", false), 8);
+    assert_eq!(classify("xbc", false), 9);
+    assert_eq!(classify("bc", false), 11);
+    assert_eq!(classify("abcd", false), 11);
+    assert_eq!(classify("e", false), 11);
+
+    assert_eq!(classify_pair(("fn", true)), 0);
+    assert_eq!(classify_pair(("if", true)), 1);
+    assert_eq!(classify_pair(("in", false)), 2);
+    assert_eq!(classify_pair(("if", false)), 3);
+    assert_eq!(classify_pair(("let", true)), 4);
+    assert_eq!(classify_pair(("mod", true)), 5);
+    assert_eq!(classify_pair(("mod", false)), 6);
+    assert_eq!(classify_pair(("pub", false)), 7);
+}
@@ -0,0 +1,11 @@
+//@ run-fail
+//@ compile-flags: -C debug-assertions
+//@ error-pattern: loaded an invalid `bool`: the byte must be 0x0 or 0x1 but is 0x3
+
+fn main() {
+    let byte = 3u8;
+    let ptr = &raw const byte as *const bool;
+    unsafe {
+        let _x = *ptr;
+    }
+}
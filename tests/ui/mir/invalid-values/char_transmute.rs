@@ -0,0 +1,8 @@
+//@ run-fail
+//@ compile-flags: -C debug-assertions
+//@ error-pattern: loaded an invalid `char`: 0xd800 is not a Unicode scalar value
+
+fn main() {
+    let surrogate = std::hint::black_box(0xD800u32);
+    let _c: char = unsafe { std::mem::transmute(surrogate) };
+}
@@ -0,0 +1,14 @@
+//@ run-fail
+//@ compile-flags: -C debug-assertions
+//@ error-pattern: loaded an enum with an invalid discriminant: the tag is 0x3
+
+// The niche of `Option<Pair>` is in the `bool` after the `u32`, so it is not at the start of the
+// transmuted value.
+
+#[repr(C)]
+struct Pair(u32, bool);
+
+fn main() {
+    let bytes = std::hint::black_box([0u8, 0, 0, 0, 3, 0, 0, 0]);
+    let _x: Option<Pair> = unsafe { std::mem::transmute(bytes) };
+}
@@ -0,0 +1,8 @@
+//@ run-fail
+//@ compile-flags: -C debug-assertions
+//@ error-pattern: loaded an enum with an invalid discriminant: the tag is 0x7
+
+fn main() {
+    let byte = std::hint::black_box(7u8);
+    let _x: Option<bool> = unsafe { std::mem::transmute(byte) };
+}
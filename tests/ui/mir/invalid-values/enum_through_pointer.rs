@@ -0,0 +1,23 @@
+//@ run-fail
+//@ compile-flags: -C debug-assertions
+//@ error-pattern: loaded an enum with an invalid discriminant: the tag is 0x2
+
+#[allow(dead_code)]
+#[repr(u8)]
+enum Sparse {
+    A = 0,
+    B = 1,
+    C = 5,
+}
+
+fn main() {
+    let byte = 2u8;
+    let ptr = &raw const byte as *const Sparse;
+    unsafe {
+        match *ptr {
+            Sparse::A => {}
+            Sparse::B => {}
+            Sparse::C => {}
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//@ run-pass
+//@ compile-flags: -C debug-assertions
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(i8)]
+enum Signed {
+    Negative = -1,
+    Positive = 1,
+}
+
+#[derive(PartialEq, Debug)]
+#[repr(C)]
+struct Pair(u32, bool);
+
+fn main() {
+    let values = [true, false];
+    let ptr = values.as_ptr();
+    unsafe {
+        assert!(*ptr);
+        assert!(!*ptr.add(1));
+    }
+
+    let c: char = unsafe { std::mem::transmute(std::hint::black_box(0x10FFFFu32)) };
+    assert_eq!(c, '\u{10FFFF}');
+
+    let s: Signed = unsafe { std::mem::transmute(std::hint::black_box(-1i8)) };
+    assert_eq!(s, Signed::Negative);
+
+    let niche: Option<bool> = unsafe { std::mem::transmute(std::hint::black_box(2u8)) };
+    assert_eq!(niche, None);
+
+    let pair: Option<Pair> =
+        unsafe { std::mem::transmute(std::hint::black_box([7u8, 7, 7, 7, 1, 0, 0, 0])) };
+    assert_eq!(pair, Some(Pair(0x0707_0707, true)));
+
+    let constant: bool = unsafe { std::mem::transmute(1u8) };
+    assert!(constant);
+}
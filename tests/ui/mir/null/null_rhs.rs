@@ -0,0 +1,10 @@
+//@ run-fail
+//@ compile-flags: -C debug-assertions
+//@ error-pattern: null pointer dereference occurred
+
+fn main() {
+    let ptr: *const u32 = std::ptr::null();
+    unsafe {
+        let _x = *ptr;
+    }
+}
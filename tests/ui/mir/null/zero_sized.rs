@@ -0,0 +1,9 @@
+//@ run-pass
+//@ compile-flags: -C debug-assertions
+
+fn main() {
+    let ptr: *const () = std::ptr::null();
+    unsafe {
+        let _x = *ptr;
+    }
+}
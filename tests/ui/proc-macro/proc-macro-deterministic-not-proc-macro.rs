@@ -0,0 +1,19 @@
+//@ force-host
+//@ no-prefer-dynamic
+
+#![crate_type = "proc-macro"]
+#![feature(proc_macro_deterministic)]
+#![allow(dead_code)]
+
+extern crate proc_macro;
+use proc_macro::TokenStream;
+
+#[proc_macro]
+#[proc_macro_deterministic]
+pub fn ok(input: TokenStream) -> TokenStream {
+    input
+}
+
+#[proc_macro_deterministic]
+//~^ ERROR `#[proc_macro_deterministic]` can only be applied to proc macro functions
+fn helper(input: TokenStream) -> TokenStream { input }
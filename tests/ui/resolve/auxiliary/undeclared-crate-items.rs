@@ -0,0 +1,11 @@
+pub mod widgets {
+    pub struct Widget;
+
+    pub fn make_widget() -> Widget {
+        Widget
+    }
+
+    pub struct Gadget(pub u8);
+
+    pub(crate) struct Internal;
+}
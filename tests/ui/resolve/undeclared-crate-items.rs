@@ -0,0 +1,23 @@
+// Names which don't resolve are looked up in the crates of the library search
+// paths which aren't declared, like the auxiliary crates which aren't passed
+// with `--extern` and the crates of the sysroot other than `std` and `core`.
+
+//@ aux-build:undeclared-crate-items.rs
+//@ edition: 2021
+
+fn take(_: Widget) {}
+//~^ ERROR cannot find type `Widget` in this scope
+
+fn stream() -> TokenStream {
+    //~^ ERROR cannot find type `TokenStream` in this scope
+    loop {}
+}
+
+fn main() {
+    make_widget();
+    //~^ ERROR cannot find function `make_widget` in this scope
+    let _ = Gadget(1);
+    //~^ ERROR cannot find function, tuple struct or tuple variant `Gadget` in this scope
+    let _ = Internal;
+    //~^ ERROR cannot find value `Internal` in this scope
+}
@@ -0,0 +1 @@
+pub const QUX: &str = "qux";
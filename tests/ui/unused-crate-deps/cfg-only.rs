@@ -0,0 +1,34 @@
+// Check that the crate dependencies which are only used under some `cfg`s are reported.
+
+//@ edition:2018
+//@ check-pass
+//@ aux-crate:bar=bar.rs
+//@ aux-crate:baz=baz.rs
+//@ aux-crate:foo=foo.rs
+//@ aux-crate:qux=qux.rs
+//@ compile-flags: --cfg=feature="extra" --check-cfg=cfg(feature,values("extra"))
+
+#![warn(cfg_only_crate_dependencies)]
+
+extern crate baz;
+//~^ WARNING extern crate `baz` is only used under `cfg(feature = "extra")`
+
+#[cfg(feature = "extra")]
+mod extra {
+    pub use qux::QUX;
+    //~^ WARNING extern crate `qux` is only used under `cfg(feature = "extra")` in crate
+
+    #[cfg(not(FALSE))]
+    pub fn get_foo() -> &'static str {
+        foo::FOO
+        //~^ WARNING extern crate `foo` is only used under `cfg(all(feature = "extra", not(FALSE)))`
+    }
+
+    pub fn get_baz() -> &'static str {
+        baz::BAZ
+    }
+}
+
+fn main() {
+    let _ = (bar::BAR, extra::QUX, extra::get_foo(), extra::get_baz());
+}